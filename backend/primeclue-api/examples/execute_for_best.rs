@@ -37,8 +37,8 @@ fn main() {
     while Instant::now().lt(&end) {
         counter += 1;
         for _ in 0..1_000 {
-            let tree = Tree::new(data.input_shape(), 30, &[], 0.5, 0.9);
-            if let Some(score) = tree.execute_for_score(&data, Class::new(0), Auc) {
+            let tree = Tree::new(data.input_shape(), 30, &[], &[], 0.5, 0.9);
+            if let Some(score) = tree.execute_for_score(&data, Class::new(0), &Auc) {
                 if score.value() > best {
                     best = score.value();
                 }
@@ -85,5 +85,5 @@ fn check_once(path: &str, seconds: usize) -> Option<f32> {
     let classifier = training.classifier().ok()?;
 
     // Get classifier's score on unseen data
-    Some(classifier.score(&test_data)?.auc)
+    Some(classifier.score(&test_data).ok()?.auc)
 }
@@ -20,68 +20,32 @@
 use primeclue::data::data_set::{DataSet, DataView};
 use primeclue::data::outcome::Class;
 use primeclue::data::{Input, Outcome, Point};
+use primeclue::exec::benchmark;
 use primeclue::exec::score::Objective;
-use primeclue::exec::training_group::TrainingGroup;
+use primeclue::exec::training_group::StopCondition;
 use rand::{thread_rng, Rng};
 use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 // A difficult-ish problem for ML. Not only test data is never seen during training, but it also
 // have values in range > 200 && <= 300. Values in this range were not observed during training.
 
 // Run with : cargo run --release --example test_training
-// Average score on unseen data: 0.66
 fn main() {
-    let mut sum = 0.0;
-    let count = 100;
-    let (training_data, verification_data, test_data) = generate_data();
-    for attempt in 1..count + 1 {
-        sum += attempt_training(
-            attempt,
-            training_data.clone(),
-            verification_data.clone(),
-            test_data.clone(),
-        );
-        println!(
-            "Average score on unseen data after {} attempts: {}",
-            attempt,
-            sum / attempt as f32
-        );
-    }
-}
-
-fn attempt_training(
-    attempt: usize,
-    training_data: DataView,
-    verification_data: DataView,
-    test_data: DataView,
-) -> f32 {
-    let mut training =
-        TrainingGroup::new(training_data, verification_data, Objective::Accuracy, 100, &[])
-            .unwrap();
-    let max_training_duration = Duration::from_secs(5 * 60);
-    let end_time = Instant::now().checked_add(max_training_duration).unwrap();
-    loop {
-        let now = Instant::now();
-        if now > end_time {
-            println!("Training failed! Unable to learn after {:?}", max_training_duration);
-            std::process::exit(1);
-        }
-        training.next_generation();
-        if let Ok(classifier) = training.classifier() {
-            if let Some(score) = classifier.score(&test_data) {
-                if let Some(stats) = training.stats() {
-                    println!(
-                        "Testing training #{}, epoch: {}, training: {:4.2}, unseen: {:4.2}, epoch time: {:?}",
-                        attempt, stats.generation, stats.training_score, score.accuracy, Instant::now().duration_since(now)
-                    );
-                    if stats.training_score >= 0.9 {
-                        return score.accuracy;
-                    }
-                }
-            }
-        }
-    }
+    let data = generate_data();
+    let budget = [
+        StopCondition::TimeLimit(Duration::from_secs(5 * 60)),
+        StopCondition::NoImprovement { patience: 100, min_delta: 0.0 },
+    ];
+    let report = benchmark::run(|| data.clone(), Objective::Accuracy, 100, &budget).unwrap();
+    println!(
+        "Score on unseen data: average {:4.2}, std dev {:4.2}, min {:4.2}, max {:4.2}",
+        report.mean(),
+        report.std_dev(),
+        report.min(),
+        report.max()
+    );
+    println!("Average generations: {}", report.average_generations());
 }
 
 fn generate_data() -> (DataView, DataView, DataView) {
@@ -106,7 +106,7 @@ fn start_training(
     let mut training = TrainingGroup::new(
         training_data,
         verification_data,
-        request.training_objective,
+        request.training_objective.clone(),
         request.size,
         &forbidden_cols,
     )?;
@@ -122,7 +122,7 @@ fn start_training(
         training.next_generation();
         if let Some(stats) = training.stats() {
             if let Ok(classifier) = training.classifier() {
-                if let Some(classifier_score) = classifier.score(&test_data) {
+                if let Ok(classifier_score) = classifier.score(&test_data) {
                     let status = TrainingStatus { stats, classifier_score };
                     status_callback(Status::Progress(
                         0.0,
@@ -216,7 +216,7 @@ impl ClassifyRequest {
         let mut data_raw = split_to_vec(&self.content, &self.separator, self.ignore_first_row);
         let numbers = parse_data(&data_raw, &self.data_columns)?;
         ClassifyRequest::validate_input_shape(&classifiers, &numbers)?;
-        let responses_list = build_responses_list(&classifiers, &numbers);
+        let responses_list = build_responses_list(&classifiers, &numbers)?;
         let mut classification = Vec::with_capacity(data_raw.len());
         classification.push(header_row.join(&self.separator));
         for r in 0..data_raw.len() {
@@ -270,13 +270,13 @@ impl ClassifyRequest {
 fn build_responses_list<'a>(
     classifiers: &'a [Classifier],
     numbers: &[Vec<f32>],
-) -> Vec<Vec<&'a str>> {
+) -> Result<Vec<Vec<&'a str>>, PrimeclueErr> {
     let mut responses_list = vec![];
     for classifier in classifiers {
-        let responses = classify_all(numbers, classifier);
+        let responses = classify_all(numbers, classifier)?;
         responses_list.push(responses);
     }
-    responses_list
+    Ok(responses_list)
 }
 
 fn split_into_sets(data: DataSet, keep_unseen: bool) -> (DataView, DataView, DataView) {
@@ -324,7 +324,10 @@ fn check_size(data: &[Vec<f32>], input_shape: &InputShape) -> Result<(), Primecl
     }
 }
 
-fn classify_all<'a>(numbers: &[Vec<f32>], classifier: &'a Classifier) -> Vec<&'a str> {
+fn classify_all<'a>(
+    numbers: &[Vec<f32>],
+    classifier: &'a Classifier,
+) -> Result<Vec<&'a str>, PrimeclueErr> {
     let mut data_set = DataSet::new(classifier.get_classes().clone());
     for row in 0..=(numbers.len() - classifier.input_shape().rows()) {
         let input_data = build_input_data(numbers, row, classifier.input_shape());
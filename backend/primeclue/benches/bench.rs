@@ -23,6 +23,8 @@ use primeclue::data::data_set::DataSet;
 use primeclue::data::outcome::Class;
 use primeclue::data::{Input, InputShape, Outcome, Point};
 use primeclue::exec::class_training::ClassTraining;
+use primeclue::exec::functions::{MATH_CONSTANTS, ONE_ARG_FUNCTIONS, TWO_ARG_FUNCTIONS};
+use primeclue::exec::node::{Node, Weighted};
 use primeclue::exec::score::Objective::{Auc, Cost};
 use primeclue::exec::training_group::TrainingGroup;
 use primeclue::exec::tree::Tree;
@@ -37,7 +39,8 @@ fn select_node(c: &mut Criterion) {
         let max_depth = rng.gen_range(2..10);
         let data_prob = rng.gen_range(0.01..0.99);
         let branch_prob = rng.gen_range(0.01..0.99);
-        let tree = Tree::new(&InputShape::new(1, 10), max_depth, &[], branch_prob, data_prob);
+        let tree =
+            Tree::new(&InputShape::new(1, 10), max_depth, &[], &[], branch_prob, data_prob);
         trees.push(tree)
     }
 
@@ -108,6 +111,21 @@ fn vec_add_fast(c: &mut Criterion) {
     });
 }
 
+/// Compare with and without `simd` feature, e.g. run `cargo bench two_arg_arithmetic --
+/// --save-baseline scalar` and then `cargo bench --features simd two_arg_arithmetic --
+/// --baseline scalar`
+fn two_arg_arithmetic_bench(c: &mut Criterion) {
+    let data = create_sample_data(100_000).into_view();
+    let col1 = data.cells().get(0, 0);
+    let col2 = data.cells().get(0, 1);
+    for name in &["add", "sub", "mul", "div"] {
+        let fun = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == *name).unwrap().fun;
+        c.bench_function(&format!("two_arg_arithmetic_{}", name), |b| {
+            b.iter(|| black_box(fun(col1.clone(), col2)))
+        });
+    }
+}
+
 fn next_generation_bench(c: &mut Criterion) {
     let (training_data, verification_data, _) =
         create_sample_data(1_000).shuffle().into_3_views_split();
@@ -128,14 +146,14 @@ fn execute_tree_bench(c: &mut Criterion) {
         let max_depth = rng.gen_range(2..10);
         let data_prob = rng.gen_range(0.01..0.99);
         let branch_prob = rng.gen_range(0.01..0.99);
-        let tree = Tree::new(data.input_shape(), max_depth, &[], branch_prob, data_prob);
+        let tree = Tree::new(data.input_shape(), max_depth, &[], &[], branch_prob, data_prob);
         trees.push(tree)
     }
 
     c.bench_function("execute_tree", |b| {
         b.iter(|| {
             for tree in &trees {
-                let _ = tree.execute_for_score(black_box(&data), Class::new(0), Auc);
+                let _ = tree.execute_for_score(black_box(&data), Class::new(0), &Auc);
             }
         })
     });
@@ -154,6 +172,7 @@ fn create_tree_bench(c: &mut Criterion) {
                     black_box(data.input_shape()),
                     max_depth,
                     &[],
+                    &[],
                     branch_prob,
                     data_prob,
                 );
@@ -162,15 +181,37 @@ fn create_tree_bench(c: &mut Criterion) {
     });
 }
 
+fn execute_constant_subtree_bench(c: &mut Criterion) {
+    let data = create_sample_data(10_000).into_view();
+    let mut constant = Weighted::from(Node::MathConstant(&MATH_CONSTANTS[0]));
+    for function in ONE_ARG_FUNCTIONS.iter().cycle().take(50) {
+        constant = Weighted::from(Node::SingleArgFunction(function, constant));
+    }
+    let tree = Tree::from_two(
+        &TWO_ARG_FUNCTIONS[0],
+        constant,
+        Weighted::from(Node::DataValue(0, 0)),
+        *data.input_shape(),
+    );
+
+    c.bench_function("execute_constant_subtree", |b| {
+        b.iter(|| {
+            let _ = tree.execute_for_score(black_box(&data), Class::new(0), &Auc);
+        })
+    });
+}
+
 criterion_group!(
     benches,
     execute_tree_bench,
+    execute_constant_subtree_bench,
     create_tree_bench,
     next_generation_bench,
     training_group_generation_bench,
     select_node,
     valid,
     vec_add_fast,
+    two_arg_arithmetic_bench,
     threshold_cost_bench
 );
 criterion_main!(benches);
@@ -17,8 +17,9 @@
    along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use rand::{RngCore, SeedableRng};
+use rand::{Error, RngCore, SeedableRng};
 use rand_xorshift::XorShiftRng;
+use std::cell::RefCell;
 use std::env;
 
 lazy_static! {
@@ -31,17 +32,85 @@ lazy_static! {
     };
 }
 
+thread_local! {
+    static SEEDED_RNG: RefCell<Option<XorShiftRng>> = const { RefCell::new(None) };
+}
+
 fn thread_rng() -> Box<dyn RngCore> {
-    Box::new(rand::thread_rng())
+    if is_seeded() {
+        Box::new(SeededRng)
+    } else {
+        Box::new(rand::thread_rng())
+    }
 }
 
 fn predictable_rng() -> Box<dyn RngCore> {
-    Box::new(XorShiftRng::seed_from_u64(42))
+    if is_seeded() {
+        Box::new(SeededRng)
+    } else {
+        Box::new(XorShiftRng::seed_from_u64(42))
+    }
+}
+
+fn is_seeded() -> bool {
+    SEEDED_RNG.with(|rng| rng.borrow().is_some())
+}
+
+/// Makes [`GET_RNG`] on the current thread draw from a single stream seeded with `seed`,
+/// until the returned guard is dropped. Code run within must not hand work over to other
+/// threads, as they won't see the seeded stream.
+pub(crate) fn seed_thread_rng(seed: u64) -> SeededRngGuard {
+    let previous =
+        SEEDED_RNG.with(|rng| rng.borrow_mut().replace(XorShiftRng::seed_from_u64(seed)));
+    SeededRngGuard { previous }
+}
+
+#[derive(Debug)]
+pub(crate) struct SeededRngGuard {
+    previous: Option<XorShiftRng>,
+}
+
+impl Drop for SeededRngGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SEEDED_RNG.with(|rng| *rng.borrow_mut() = previous);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SeededRng;
+
+impl SeededRng {
+    fn with<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+            Some(rng) => f(rng),
+            // guard dropped while the rng was still in use
+            None => f(&mut rand::thread_rng()),
+        })
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        SeededRng::with(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        SeededRng::with(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        SeededRng::with(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        SeededRng::with(|rng| rng.try_fill_bytes(dest))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::contrand::predictable_rng;
+    use crate::contrand::{predictable_rng, seed_thread_rng, GET_RNG};
     use rand::Rng;
 
     #[test]
@@ -55,4 +124,22 @@ mod test {
             assert_eq!(rng1.gen_bool(0.3), rng2.gen_bool(0.3));
         }
     }
+
+    #[test]
+    fn test_seeded_rng() {
+        let first = {
+            let _guard = seed_thread_rng(7);
+            (0..64).map(|_| GET_RNG().gen::<u64>()).collect::<Vec<_>>()
+        };
+        let second = {
+            let _guard = seed_thread_rng(7);
+            (0..64).map(|_| GET_RNG().gen::<u64>()).collect::<Vec<_>>()
+        };
+        assert_eq!(first, second);
+        let other = {
+            let _guard = seed_thread_rng(8);
+            (0..64).map(|_| GET_RNG().gen::<u64>()).collect::<Vec<_>>()
+        };
+        assert_ne!(first, other);
+    }
 }
@@ -58,6 +58,10 @@ impl<T> Data<T> {
         &self.data[row * self.input_shape.columns + column]
     }
 
+    pub(crate) fn get_mut(&mut self, row: usize, column: usize) -> &mut T {
+        &mut self.data[row * self.input_shape.columns + column]
+    }
+
     pub fn add_row(&mut self, row: Vec<T>) -> Result<usize, PrimeclueErr> {
         if !self.data.is_empty() && self.input_shape.columns() != row.len() {
             PrimeclueErr::result(format!(
@@ -18,13 +18,22 @@
 */
 
 use crate::contrand::GET_RNG;
-use crate::data::outcome::Class;
+use crate::data::disk_data_view::DiskDataView;
+use crate::data::encoding::CategoricalEncoding;
+use crate::data::normalization::Normalization;
+use crate::data::outcome::{Class, MultiLabelOutcome};
 use crate::data::{Data, Input, InputShape, Outcome};
 use crate::error::PrimeclueErr;
+use crate::math::median;
 use crate::serialization::{Deserializable, Serializable, Serializator};
+#[cfg(feature = "ndarray")]
+use ndarray::{ArrayView1, ArrayView2};
 use rand::seq::SliceRandom;
-use rand::Rng;
-use std::collections::HashMap;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use serde::Serialize;
+use std::cmp::Ordering::Equal;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::slice::Iter;
@@ -35,13 +44,21 @@ pub struct Point {
     outcome: Outcome,
 }
 
-#[derive(serde::Deserialize, Debug, Copy, Clone)]
+#[derive(serde::Deserialize, Debug, Copy, Clone, PartialEq)]
 pub struct Rewards {
     reward: f32,
     penalty: f32,
 }
 
+impl Default for Rewards {
+    fn default() -> Self {
+        Rewards { reward: 1.0, penalty: -1.0 }
+    }
+}
+
 const DATA_FILE_NAME: &str = "data.ssd";
+const FEATURE_NAMES_TOKEN: &str = "FeatureNames";
+const FEATURE_TYPES_TOKEN: &str = "FeatureTypes";
 
 impl Point {
     #[must_use]
@@ -53,6 +70,23 @@ impl Point {
     pub fn data(&self) -> (&Input, &Outcome) {
         (&self.input, &self.outcome)
     }
+
+    /// Bit patterns of all input and outcome values, the same for exactly equal points
+    fn bits(&self) -> Vec<u32> {
+        let shape = self.input.input_shape();
+        let mut bits = Vec::with_capacity(shape.rows() * shape.columns() + 6);
+        for row in 0..shape.rows() {
+            for column in 0..shape.columns() {
+                bits.push(self.input.get(row, column).to_bits());
+            }
+        }
+        let outcome = &self.outcome;
+        bits.push(u32::from(outcome.class().value()));
+        bits.push(u32::from(outcome.target().is_some()));
+        let values = [outcome.reward(), outcome.penalty(), outcome.weight()];
+        bits.extend(values.iter().chain(&outcome.target()).map(|v| v.to_bits()));
+        bits
+    }
 }
 
 impl Serializable for Point {
@@ -69,6 +103,10 @@ impl Deserializable for Point {
     }
 }
 
+/// Number of nearest points of the same class that synthetic points of
+/// [`DataView::oversample_minority`] are placed towards
+pub const SMOTE_NEIGHBORS: usize = 5;
+
 /// A structure that represents a cut through all data points within [`DataSet`] for
 /// each row/column coordinate.
 /// This is done mainly for performance (CPU cache and such) as dealing with vectors
@@ -77,8 +115,10 @@ impl Deserializable for Point {
 pub struct DataView {
     cells: Data<Vec<f32>>,
     outcomes: Vec<Outcome>,
-    class_count: HashMap<Class, usize>,
-    class_map: HashMap<Class, String>,
+    class_count: BTreeMap<Class, usize>,
+    class_map: BTreeMap<Class, String>,
+    feature_names: Vec<String>,
+    feature_types: Vec<FeatureType>,
 }
 
 impl DataView {
@@ -125,6 +165,56 @@ impl DataView {
         self.class_count.len()
     }
 
+    /// Number of points of every class present in this view, e.g. to detect a split that left
+    /// too few points of some class
+    #[must_use]
+    pub fn class_counts(&self) -> &BTreeMap<Class, usize> {
+        &self.class_count
+    }
+
+    /// Distinct classes of points of this view in ascending order. Unlike
+    /// [`DataView::class_map`], classes without any point in this view are left out.
+    #[must_use]
+    pub fn classes(&self) -> Vec<Class> {
+        self.class_count.keys().copied().collect()
+    }
+
+    /// Number of points
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// Appends `point` after all points of this view, e.g. one labeled after the view was
+    /// created. Its input must have the shape of this view and values accepted by its
+    /// feature types, and its class must be one of [`DataView::class_map`].
+    pub fn add_point(&mut self, point: Point) -> Result<(), PrimeclueErr> {
+        let (input, outcome) = point.data();
+        if input.input_shape() != self.input_shape() {
+            return Err(PrimeclueErr::ShapeMismatch {
+                expected: *self.input_shape(),
+                actual: *input.input_shape(),
+            });
+        }
+        if !self.class_map.contains_key(&outcome.class()) {
+            return PrimeclueErr::result(format!("Unknown class {}", outcome.class()));
+        }
+        validate_feature_types(input, &self.feature_types)?;
+        for row in 0..input.input_shape().rows() {
+            for column in 0..input.input_shape().columns() {
+                self.cells.get_mut(row, column).push(input.get(row, column));
+            }
+        }
+        *self.class_count.entry(outcome.class()).or_insert(0) += 1;
+        self.outcomes.push(*outcome);
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+
     pub fn input_shape(&self) -> &InputShape {
         self.cells.input_shape()
     }
@@ -137,21 +227,831 @@ impl DataView {
         &self.outcomes
     }
 
-    pub fn class_map(&self) -> &HashMap<Class, String> {
+    pub fn class_map(&self) -> &BTreeMap<Class, String> {
         &self.class_map
     }
+
+    /// Names of input columns given by [`DataSet::new_named`], empty if there are none
+    #[must_use]
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Types of input columns given by [`DataSet::with_feature_types`], empty if all columns
+    /// are continuous
+    #[must_use]
+    pub fn feature_types(&self) -> &[FeatureType] {
+        &self.feature_types
+    }
+
+    /// Sorted indices of columns whose every cell has the same value in all points, i.e.
+    /// features carrying no signal, e.g. to forbid them in training or remove them with
+    /// [`DataView::drop_features`]. Missing values are skipped, so a column with only missing
+    /// values is constant too. Every column of an empty view is constant.
+    #[must_use]
+    pub fn constant_features(&self) -> Vec<usize> {
+        let shape = self.input_shape();
+        (0..shape.columns())
+            .filter(|column| {
+                (0..shape.rows()).all(|row| {
+                    let mut values = self.cells.get(row, *column).iter().filter(|v| !v.is_nan());
+                    values.next().is_none_or(|first| values.all(|v| v == first))
+                })
+            })
+            .collect()
+    }
+
+    /// Overview of this view in a single scan, e.g. to catch constant features, features of
+    /// very different ranges or imbalanced classes before training, see [`DataSummary`]
+    #[must_use]
+    pub fn summary(&self) -> DataSummary {
+        let shape = self.input_shape();
+        let features = (0..shape.columns())
+            .map(|column| {
+                let values = (0..shape.rows())
+                    .flat_map(|row| self.cells.get(row, column).iter())
+                    .filter(|v| !v.is_nan())
+                    .map(|v| f64::from(*v))
+                    .collect::<Vec<_>>();
+                FeatureSummary::new(&values, self.len() * shape.rows() - values.len())
+            })
+            .collect();
+        DataSummary {
+            point_count: self.len(),
+            feature_count: shape.columns(),
+            features,
+            class_counts: self.class_count.clone(),
+        }
+    }
+
+    /// Returns a copy of this view without `columns`, e.g. ones given by
+    /// [`DataView::constant_features`]. Remaining columns are numbered anew in their order, so
+    /// inputs to classify must have the same columns removed. Indices out of input shape
+    /// are ignored.
+    #[must_use]
+    pub fn drop_features(&self, columns: &[usize]) -> DataView {
+        let kept = (0..self.input_shape().columns())
+            .filter(|column| !columns.contains(column))
+            .collect::<Vec<_>>();
+        let mut cells = Data::new();
+        for row in 0..self.input_shape().rows() {
+            let row_data =
+                kept.iter().map(|column| self.cells.get(row, *column).clone()).collect();
+            cells.add_row(row_data).unwrap();
+        }
+        let feature_names =
+            kept.iter().filter_map(|column| self.feature_names.get(*column).cloned()).collect();
+        let feature_types =
+            kept.iter().filter_map(|column| self.feature_types.get(*column).copied()).collect();
+        DataView {
+            cells,
+            outcomes: self.outcomes.clone(),
+            class_count: self.class_count.clone(),
+            class_map: self.class_map.clone(),
+            feature_names,
+            feature_types,
+        }
+    }
+
+    /// Drops redundant features greedily: columns are visited in order and a column is
+    /// dropped with [`DataView::drop_features`] if its absolute Pearson correlation with any
+    /// earlier column that was kept exceeds `threshold`. Correlation with already dropped
+    /// columns doesn't count, so of columns `a`, `b`, `c` where only `a` with `b` and `b` with
+    /// `c` are correlated, just `b` is dropped. Returns the view with sorted indices of dropped
+    /// columns, which must be removed from inputs to classify too. Correlation is computed
+    /// over cells of all rows of all points, skipping cells where either value is missing;
+    /// columns without variance are never correlated.
+    #[must_use]
+    pub fn drop_correlated(&self, threshold: f32) -> (DataView, Vec<usize>) {
+        let shape = self.input_shape();
+        let mut dropped = vec![];
+        for column in 0..shape.columns() {
+            let redundant = (0..column)
+                .filter(|kept| !dropped.contains(kept))
+                .any(|kept| self.correlation(kept, column).abs() > f64::from(threshold));
+            if redundant {
+                dropped.push(column);
+            }
+        }
+        (self.drop_features(&dropped), dropped)
+    }
+
+    fn correlation(&self, first: usize, second: usize) -> f64 {
+        let pairs = (0..self.input_shape().rows())
+            .flat_map(|row| {
+                self.cells.get(row, first).iter().zip(self.cells.get(row, second).iter())
+            })
+            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+            .map(|(x, y)| (f64::from(*x), f64::from(*y)))
+            .collect::<Vec<_>>();
+        let count = pairs.len() as f64;
+        let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / count;
+        let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+        for (x, y) in &pairs {
+            covariance += (x - mean_x) * (y - mean_y);
+            variance_x += (x - mean_x).powi(2);
+            variance_y += (y - mean_y).powi(2);
+        }
+        if variance_x > 0.0 && variance_y > 0.0 {
+            covariance / (variance_x * variance_y).sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    /// Features whose value alone predicts the class of points with accuracy of at least
+    /// `min_accuracy`, e.g. to catch a feature leaking the label before trusting a very good
+    /// score. Accuracy of a feature is that of the best decision stump on it: a single
+    /// threshold predicting the most frequent class of points on either side of it, the
+    /// best of all rows. Points missing the value are skipped. Returns column indices with
+    /// their accuracy, from the most accurate.
+    #[must_use]
+    pub fn leakage_report(&self, min_accuracy: f32) -> Vec<(usize, f32)> {
+        let shape = self.input_shape();
+        let mut report = (0..shape.columns())
+            .filter_map(|column| {
+                let accuracy = (0..shape.rows())
+                    .map(|row| self.stump_accuracy(row, column))
+                    .fold(0.0, f32::max);
+                (accuracy >= min_accuracy).then_some((column, accuracy))
+            })
+            .collect::<Vec<_>>();
+        report
+            .sort_by(|(c1, a1), (c2, a2)| a2.partial_cmp(a1).unwrap_or(Equal).then(c1.cmp(c2)));
+        report
+    }
+
+    fn stump_accuracy(&self, row: usize, column: usize) -> f32 {
+        let mut points = self
+            .cells
+            .get(row, column)
+            .iter()
+            .zip(&self.outcomes)
+            .filter(|(v, _)| !v.is_nan())
+            .map(|(v, outcome)| (*v, usize::from(outcome.class().value())))
+            .collect::<Vec<_>>();
+        if points.is_empty() {
+            return 0.0;
+        }
+        points.sort_by(|(v1, _), (v2, _)| v1.partial_cmp(v2).unwrap_or(Equal));
+        let classes = points.iter().map(|(_, class)| class + 1).max().unwrap_or(0);
+        let mut left = vec![0; classes];
+        let mut right = vec![0; classes];
+        points.iter().for_each(|(_, class)| right[*class] += 1);
+        let majority = |counts: &[usize]| counts.iter().copied().max().unwrap_or(0);
+        let mut best = majority(&right);
+        for (i, (value, class)) in points.iter().enumerate() {
+            left[*class] += 1;
+            right[*class] -= 1;
+            if points.get(i + 1).is_some_and(|(next, _)| next > value) {
+                best = best.max(majority(&left) + majority(&right));
+            }
+        }
+        best as f32 / points.len() as f32
+    }
+
+    /// Returns a copy of this view with every cell z-scored using its mean and standard
+    /// deviation, together with the [`Normalization`] that was applied. Use it to transform
+    /// verification / test views with [`DataView::normalized`] and unseen inputs with
+    /// [`Normalization::apply`]. Missing values are skipped and stay missing.
+    #[must_use]
+    pub fn standardized(&self) -> (DataView, Normalization) {
+        let normalization = Normalization::fit(&self.cells);
+        let view = self.transformed(&normalization);
+        (view, normalization)
+    }
+
+    /// Same as [`DataView::standardized`], but every cell is centered on its median and scaled
+    /// by its interquartile range, so that outliers don't distort scaling of other values.
+    /// Missing values are skipped.
+    #[must_use]
+    pub fn robust_scaled(&self) -> (DataView, Normalization) {
+        let normalization = Normalization::fit_robust(&self.cells);
+        let view = self.transformed(&normalization);
+        (view, normalization)
+    }
+
+    /// Returns a copy of this view transformed with previously computed [`Normalization`]
+    pub fn normalized(&self, normalization: &Normalization) -> Result<DataView, PrimeclueErr> {
+        if self.input_shape() != normalization.input_shape() {
+            PrimeclueErr::result(format!(
+                "Data shape {} doesn't match normalization shape {}",
+                self.input_shape(),
+                normalization.input_shape()
+            ))
+        } else {
+            Ok(self.transformed(normalization))
+        }
+    }
+
+    /// Sets class weight of every outcome to the weight of its class, multiplying weight of
+    /// the point itself, e.g. read from a weight column. Classes absent in `weights` are given
+    /// weight 1.0.
+    pub fn apply_class_weights(&mut self, weights: &HashMap<Class, f32>) {
+        self.outcomes.iter_mut().for_each(|outcome| {
+            outcome.set_class_weight(*weights.get(&outcome.class()).unwrap_or(&1.0));
+        });
+    }
+
+    /// Binary view for training trees of a single `class` of multi-label data, with
+    /// `labels` given for every point. Points having `class` among their labels get
+    /// `Class::from(true)`, others `Class::from(false)`. Rewards, penalties and weights
+    /// are kept.
+    pub fn label_view(
+        &self,
+        labels: &[MultiLabelOutcome],
+        class: Class,
+    ) -> Result<DataView, PrimeclueErr> {
+        if labels.len() != self.outcomes.len() {
+            return PrimeclueErr::result(format!(
+                "Labels and points counts don't match: {} vs {}",
+                labels.len(),
+                self.outcomes.len()
+            ));
+        }
+        let name = match self.class_map.get(&class) {
+            Some(name) => name,
+            None => return PrimeclueErr::result(format!("Unknown class {}", class)),
+        };
+        let mut class_map = BTreeMap::new();
+        class_map.insert(Class::from(true), name.to_owned());
+        class_map.insert(Class::from(false), format!("not {}", name));
+        let mut class_count = BTreeMap::new();
+        let outcomes = self
+            .outcomes
+            .iter()
+            .zip(labels)
+            .map(|(outcome, label)| {
+                let binary = Class::from(label.contains(class));
+                *class_count.entry(binary).or_insert(0) += 1;
+                outcome.with_class(binary)
+            })
+            .collect();
+        Ok(DataView {
+            cells: self.cells.clone(),
+            outcomes,
+            class_count,
+            class_map,
+            feature_names: self.feature_names.clone(),
+            feature_types: self.feature_types.clone(),
+        })
+    }
+
+    /// Returns a copy of this view with points permuted in an order given by `seed`. The same
+    /// seed always gives the same permutation.
+    #[must_use]
+    pub fn shuffled(&self, seed: u64) -> DataView {
+        let mut indices = (0..self.outcomes.len()).collect::<Vec<_>>();
+        indices.shuffle(&mut XorShiftRng::seed_from_u64(seed));
+        self.select(&indices)
+    }
+
+    /// Returns a view of `n` points drawn with replacement in a way given by `seed`, e.g.
+    /// to train members of a bagged [`Ensemble`](crate::exec::ensemble::Ensemble).
+    /// Sample of an empty view is empty.
+    #[must_use]
+    pub fn bootstrap_sample(&self, n: usize, seed: u64) -> DataView {
+        self.bootstrap_sample_with_oob(n, seed).0
+    }
+
+    /// Same as [`DataView::bootstrap_sample`], also returning sorted indices of points that
+    /// were never drawn, i.e. out-of-bag points usable for evaluation.
+    #[must_use]
+    pub fn bootstrap_sample_with_oob(&self, n: usize, seed: u64) -> (DataView, Vec<usize>) {
+        let len = self.outcomes.len();
+        let indices = if len == 0 {
+            Vec::new()
+        } else {
+            let mut rng = XorShiftRng::seed_from_u64(seed);
+            (0..n).map(|_| rng.gen_range(0..len)).collect::<Vec<_>>()
+        };
+        let mut drawn = vec![false; len];
+        indices.iter().for_each(|i| drawn[*i] = true);
+        let out_of_bag = (0..len).filter(|i| !drawn[*i]).collect();
+        (self.select(&indices), out_of_bag)
+    }
+
+    /// Returns a view of `fraction` of points drawn without replacement in a way given by
+    /// `seed`, in their original order. With `stratified` every class gets an equal share of
+    /// the sample, or all its points if it has fewer, so rare classes are oversampled.
+    /// At least one point of every class is kept then.
+    #[must_use]
+    pub fn subsample(&self, fraction: f64, seed: u64, stratified: bool) -> DataView {
+        let len = self.outcomes.len();
+        let size = ((len as f64 * fraction).round() as usize).max(1);
+        let mut rng = XorShiftRng::seed_from_u64(seed);
+        let mut indices = if stratified {
+            let mut by_class = BTreeMap::new();
+            for (i, outcome) in self.outcomes.iter().enumerate() {
+                by_class.entry(outcome.class()).or_insert_with(Vec::new).push(i);
+            }
+            let share = (size / by_class.len().max(1)).max(1);
+            let mut indices = Vec::with_capacity(size);
+            for class_indices in by_class.values_mut() {
+                class_indices.shuffle(&mut rng);
+                indices.extend(class_indices.iter().take(share));
+            }
+            indices
+        } else {
+            let mut indices = (0..len).collect::<Vec<_>>();
+            indices.shuffle(&mut rng);
+            indices.truncate(size);
+            indices
+        };
+        indices.sort_unstable();
+        self.select(&indices)
+    }
+
+    /// Returns a view of `count` points drawn without replacement in a way given by `seed`, in
+    /// their original order, or of all points if there are not more of them. Unlike
+    /// [`DataView::subsample`], with `stratified` every class keeps its proportion of points,
+    /// rounded so that shares of classes sum to `count`, e.g. for a small sample representative
+    /// of the whole view to prototype on.
+    #[must_use]
+    pub fn subsample_count(&self, count: usize, seed: u64, stratified: bool) -> DataView {
+        let len = self.outcomes.len();
+        let mut rng = XorShiftRng::seed_from_u64(seed);
+        let mut indices = if stratified && count < len {
+            let mut by_class = BTreeMap::new();
+            for (i, outcome) in self.outcomes.iter().enumerate() {
+                by_class.entry(outcome.class()).or_insert_with(Vec::new).push(i);
+            }
+            // largest remainder method: whole parts first, then the largest fractional parts
+            let shares = by_class.values().map(|c| (c.len() * count) as f64 / len as f64);
+            let mut quotas = shares.clone().map(|share| share as usize).collect::<Vec<_>>();
+            let mut remainders =
+                shares.enumerate().map(|(i, s)| (i, s.fract())).collect::<Vec<_>>();
+            remainders.sort_by(|(_, r1), (_, r2)| r2.partial_cmp(r1).unwrap_or(Equal));
+            let missing = count - quotas.iter().sum::<usize>();
+            remainders.iter().take(missing).for_each(|(i, _)| quotas[*i] += 1);
+            let mut indices = Vec::with_capacity(count);
+            for (class_indices, quota) in by_class.values_mut().zip(quotas) {
+                class_indices.shuffle(&mut rng);
+                indices.extend(class_indices.iter().take(quota));
+            }
+            indices
+        } else {
+            let mut indices = (0..len).collect::<Vec<_>>();
+            indices.shuffle(&mut rng);
+            indices.truncate(count);
+            indices
+        };
+        indices.sort_unstable();
+        self.select(&indices)
+    }
+
+    /// Returns a copy of this view with synthetic points added to every class that has fewer
+    /// than `target_ratio` of points of the largest class, until it has that many, drawn in a
+    /// way given by `seed` (SMOTE). A synthetic point lies at a random position between a
+    /// random point of the class and one of its [`SMOTE_NEIGHBORS`] nearest points of the same
+    /// class by euclidean distance of present values, and has its outcome. Values of
+    /// integer and boolean columns are rounded, missing values of either point stay missing.
+    /// A class of a single point gets its copies. Synthetic points follow the original ones.
+    ///
+    /// Use it for training data only, verification and testing data should keep the real
+    /// distribution of classes. Fails if `target_ratio` is not greater than 0 and at most 1.
+    pub fn oversample_minority(
+        &self,
+        target_ratio: f32,
+        seed: u64,
+    ) -> Result<DataView, PrimeclueErr> {
+        if !(target_ratio > 0.0 && target_ratio <= 1.0) {
+            return PrimeclueErr::result(format!(
+                "Oversampling ratio must be greater than 0 and at most 1, got {}",
+                target_ratio
+            ));
+        }
+        let mut view = self.clone();
+        let largest = self.class_count.values().max().copied().unwrap_or(0);
+        let target = (largest as f32 * target_ratio).ceil() as usize;
+        let shape = *self.input_shape();
+        let mut rng = XorShiftRng::seed_from_u64(seed);
+        for (class, count) in &self.class_count {
+            if *count >= target {
+                continue;
+            }
+            let points = (0..self.outcomes.len())
+                .filter(|i| self.outcomes[*i].class() == *class)
+                .collect::<Vec<_>>();
+            let distance = |a: usize, b: usize| {
+                (0..shape.rows())
+                    .flat_map(|row| (0..shape.columns()).map(move |column| (row, column)))
+                    .map(|(row, column)| {
+                        let values = self.cells.get(row, column);
+                        (values[a] - values[b]).powi(2)
+                    })
+                    .filter(|d| !d.is_nan())
+                    .sum::<f32>()
+            };
+            let neighbors = points
+                .iter()
+                .map(|a| {
+                    let mut others = points
+                        .iter()
+                        .filter(|b| *b != a)
+                        .map(|b| (distance(*a, *b), *b))
+                        .collect::<Vec<_>>();
+                    others.sort_by(|(d1, _), (d2, _)| d1.partial_cmp(d2).unwrap_or(Equal));
+                    others.into_iter().take(SMOTE_NEIGHBORS).map(|(_, b)| b).collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            for _ in *count..target {
+                let base = rng.gen_range(0..points.len());
+                let neighbor = neighbors[base].choose(&mut rng).copied().unwrap_or(points[base]);
+                let position = rng.gen::<f32>();
+                for row in 0..shape.rows() {
+                    for column in 0..shape.columns() {
+                        let values = self.cells.get(row, column);
+                        let (a, b) = (values[points[base]], values[neighbor]);
+                        let mut value = a + (b - a) * position;
+                        if self.feature_types.get(column).is_some_and(|t| t.is_discrete()) {
+                            value = value.round();
+                        }
+                        view.cells.get_mut(row, column).push(value);
+                    }
+                }
+                view.outcomes.push(self.outcomes[points[base]]);
+                *view.class_count.entry(*class).or_insert(0) += 1;
+            }
+        }
+        Ok(view)
+    }
+
+    /// View of points with column oriented `cells` and `outcomes`, without feature names
+    pub(crate) fn from_columns(
+        cells: Data<Vec<f32>>,
+        outcomes: Vec<Outcome>,
+        class_map: BTreeMap<Class, String>,
+    ) -> DataView {
+        let mut class_count = BTreeMap::new();
+        for outcome in &outcomes {
+            *class_count.entry(outcome.class()).or_insert(0) += 1;
+        }
+        DataView {
+            cells,
+            outcomes,
+            class_count,
+            class_map,
+            feature_names: Vec::new(),
+            feature_types: Vec::new(),
+        }
+    }
+
+    /// Same view with `feature_names` and `feature_types`, which must be valid for its
+    /// points, e.g. read from a file
+    pub(crate) fn with_features(
+        mut self,
+        feature_names: Vec<String>,
+        feature_types: Vec<FeatureType>,
+    ) -> DataView {
+        self.feature_names = feature_names;
+        self.feature_types = feature_types;
+        self
+    }
+
+    /// Reads a whole file written with [`DataSet::to_binary`] into a view, without creating
+    /// a [`DataSet`] first. Values are copied into the view, not memory-mapped, needing about
+    /// as much memory as the file, see [`DiskDataView::read_all`] for details and for what is
+    /// checked. See [`DiskDataView`] for the format and for reading files too large to keep in
+    /// memory.
+    pub fn read_binary(path: &Path) -> Result<DataView, PrimeclueErr> {
+        DiskDataView::open(path)?.read_all()
+    }
+
+    /// View of points at `indices`, in their order
+    fn select(&self, indices: &[usize]) -> DataView {
+        let mut cells = Data::new();
+        for row in 0..self.input_shape().rows() {
+            let row_data = (0..self.input_shape().columns())
+                .map(|column| {
+                    let values = self.cells.get(row, column);
+                    indices.iter().map(|i| values[*i]).collect()
+                })
+                .collect();
+            cells.add_row(row_data).unwrap();
+        }
+        let outcomes = indices.iter().map(|i| self.outcomes[*i]).collect::<Vec<_>>();
+        let mut class_count = BTreeMap::new();
+        for outcome in &outcomes {
+            *class_count.entry(outcome.class()).or_insert(0) += 1;
+        }
+        DataView {
+            cells,
+            outcomes,
+            class_count,
+            class_map: self.class_map.clone(),
+            feature_names: self.feature_names.clone(),
+            feature_types: self.feature_types.clone(),
+        }
+    }
+
+    fn transformed(&self, normalization: &Normalization) -> DataView {
+        let mut cells = Data::new();
+        for row in 0..self.input_shape().rows() {
+            let row_data = (0..self.input_shape().columns())
+                .map(|column| {
+                    self.cells
+                        .get(row, column)
+                        .iter()
+                        .map(|v| normalization.transform(row, column, *v))
+                        .collect()
+                })
+                .collect();
+            cells.add_row(row_data).unwrap();
+        }
+        DataView {
+            cells,
+            outcomes: self.outcomes.clone(),
+            class_count: self.class_count.clone(),
+            class_map: self.class_map.clone(),
+            feature_names: self.feature_names.clone(),
+            // scaled values of integer and boolean columns are no longer whole numbers
+            feature_types: Vec::new(),
+        }
+    }
+}
+
+/// Overview of a [`DataView`] given by [`DataView::summary`]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DataSummary {
+    /// Same as [`DataView::len`]
+    pub point_count: usize,
+    /// Number of input columns
+    pub feature_count: usize,
+    /// Statistics of every input column in order, over cells of all rows of all points
+    pub features: Vec<FeatureSummary>,
+    /// Same as [`DataView::class_counts`]
+    pub class_counts: BTreeMap<Class, usize>,
+}
+
+/// Statistics of values of an input column, see [`DataSummary`]. Missing values are
+/// skipped, all statistics are NaN if there are no other values.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct FeatureSummary {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// Sample standard deviation, 0 for a single value
+    pub std_dev: f32,
+    /// Number of missing values
+    pub missing: usize,
+}
+
+impl FeatureSummary {
+    fn new(values: &[f64], missing: usize) -> FeatureSummary {
+        if values.is_empty() {
+            let nan = f32::NAN;
+            return FeatureSummary { min: nan, max: nan, mean: nan, std_dev: nan, missing };
+        }
+        let min = values.iter().copied().fold(f64::MAX, f64::min);
+        let max = values.iter().copied().fold(f64::MIN, f64::max);
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = if values.len() > 1 {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+        } else {
+            0.0
+        };
+        FeatureSummary {
+            min: min as f32,
+            max: max as f32,
+            mean: mean as f32,
+            std_dev: variance.sqrt() as f32,
+            missing,
+        }
+    }
+}
+
+/// Kind of values of an input column:
+/// * `Continuous` - any real value
+/// * `Integer` - whole numbers only, e.g. counts
+/// * `Boolean` - 0 or 1 only, e.g. flags
+///
+/// Constants that evolved trees compare to integer or boolean columns are rounded, see
+/// [`DataSet::with_feature_types`]. Missing values are allowed in columns of every type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, serde::Deserialize)]
+pub enum FeatureType {
+    #[default]
+    Continuous,
+    Integer,
+    Boolean,
+}
+
+impl FeatureType {
+    /// Whether a present `value` is valid for a column of this type
+    #[must_use]
+    pub fn accepts(self, value: f32) -> bool {
+        match self {
+            FeatureType::Continuous => true,
+            FeatureType::Integer => value.fract() == 0.0,
+            FeatureType::Boolean => value == 0.0 || value == 1.0,
+        }
+    }
+
+    /// Whether constants compared to a column of this type are rounded
+    #[must_use]
+    pub fn is_discrete(self) -> bool {
+        self != FeatureType::Continuous
+    }
+}
+
+/// Checks that `input` has a column of every type of `feature_types`, unless there are none,
+/// and that its present values are accepted by them
+fn validate_feature_types(input: &Input, feature_types: &[FeatureType]) -> Result<(), String> {
+    if feature_types.is_empty() {
+        return Ok(());
+    }
+    let columns = input.input_shape().columns();
+    if columns != feature_types.len() {
+        return Err(format!(
+            "Input has {} columns, but there are {} feature types",
+            columns,
+            feature_types.len()
+        ));
+    }
+    for row in 0..input.input_shape().rows() {
+        for (column, feature_type) in feature_types.iter().enumerate() {
+            let value = input.get(row, column);
+            if !value.is_nan() && !feature_type.accepts(value) {
+                return Err(format!(
+                    "Value {} in row {}, column {} is not {:?}",
+                    value, row, column, feature_type
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fails if any of `names` is empty or repeated
+pub(crate) fn validate_feature_names(names: &[String]) -> Result<(), PrimeclueErr> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if name.trim().is_empty() {
+            return PrimeclueErr::result("Feature name can't be empty".to_string());
+        } else if !seen.insert(name) {
+            return PrimeclueErr::result(format!("Feature name {} is repeated", name));
+        }
+    }
+    Ok(())
 }
 
 #[derive(PartialEq, Debug, Default, Clone)]
 pub struct DataSet {
     points: Vec<Point>,
-    classes: HashMap<Class, String>,
+    classes: BTreeMap<Class, String>,
+    default_rewards: Rewards,
+    feature_names: Vec<String>,
+    feature_types: Vec<FeatureType>,
 }
 
 impl DataSet {
+    /// Empty data set of `classes`, given by any map of class names, e.g. a `HashMap`. Classes
+    /// are kept sorted, so that data sets of the same classes split and serialize the same.
+    #[must_use]
+    pub fn new(classes: impl IntoIterator<Item = (Class, String)>) -> DataSet {
+        DataSet {
+            points: vec![],
+            classes: classes.into_iter().collect(),
+            default_rewards: Rewards::default(),
+            feature_names: vec![],
+            feature_types: vec![],
+        }
+    }
+
+    /// Same as [`DataSet::new`], with a name of every input column, e.g. `revenue`. Names are
+    /// kept by views of this data set and by classifiers trained on them, which use them in
+    /// place of column indices, see [`Classifier::feature_names`]. Every point must have
+    /// as many columns as there are names. Fails if a name is empty or repeated.
+    ///
+    /// [`Classifier::feature_names`]: crate::exec::classifier::Classifier::feature_names
+    pub fn new_named(
+        classes: impl IntoIterator<Item = (Class, String)>,
+        feature_names: Vec<String>,
+    ) -> Result<DataSet, PrimeclueErr> {
+        validate_feature_names(&feature_names)?;
+        Ok(DataSet {
+            points: vec![],
+            classes: classes.into_iter().collect(),
+            default_rewards: Rewards::default(),
+            feature_names,
+            feature_types: vec![],
+        })
+    }
+
+    /// Names of input columns given by [`DataSet::new_named`], empty if there are none
+    #[must_use]
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Sets type of every input column, kept by views of this data set. When trees trained on
+    /// them compare an integer or boolean column to a constant with a
+    /// [`NodeKind::is_comparison`] function, the column gets weight 1 and the constant is
+    /// rounded, so that rules like `count == 3` or `flag > 0.5` come out, see
+    /// [`Weighted::discretize_comparisons`]. Every point must have as many columns as there
+    /// are types, and its present values must be accepted by them, see
+    /// [`FeatureType::accepts`].
+    ///
+    /// [`NodeKind::is_comparison`]: crate::exec::functions::NodeKind::is_comparison
+    /// [`Weighted::discretize_comparisons`]: crate::exec::node::Weighted::discretize_comparisons
+    pub fn with_feature_types(
+        mut self,
+        feature_types: Vec<FeatureType>,
+    ) -> Result<DataSet, PrimeclueErr> {
+        let points = std::mem::take(&mut self.points);
+        self.feature_types = feature_types;
+        for point in points {
+            self.add_data_point(point)?;
+        }
+        Ok(self)
+    }
+
+    /// Types of input columns given by [`DataSet::with_feature_types`], empty if all columns
+    /// are continuous
+    #[must_use]
+    pub fn feature_types(&self) -> &[FeatureType] {
+        &self.feature_types
+    }
+
+    /// Sets reward and penalty of points added with [`DataSet::add_labeled_point`],
+    /// 1.0 and -1.0 by default
+    #[must_use]
+    pub fn with_default_rewards(mut self, reward: f32, penalty: f32) -> Self {
+        self.default_rewards = Rewards { reward, penalty };
+        self
+    }
+
+    /// Empty [`DataSet`] of the same classes and default rewards, for parts of this one.
+    /// Points moved to it keep their whole [`Outcome`], including reward, penalty and weight.
+    fn empty_like(&self) -> DataSet {
+        DataSet {
+            points: vec![],
+            classes: self.classes.clone(),
+            default_rewards: self.default_rewards,
+            feature_names: self.feature_names.clone(),
+            feature_types: self.feature_types.clone(),
+        }
+    }
+
+    /// Adds a point of `class` with default reward and penalty, see
+    /// [`DataSet::with_default_rewards`]
+    pub fn add_labeled_point(&mut self, input: Input, class: Class) -> Result<(), String> {
+        let Rewards { reward, penalty } = self.default_rewards;
+        self.add_data_point(Point::new(input, Outcome::new(class, reward, penalty)))
+    }
+
+    #[must_use]
+    pub fn class_map(&self) -> &BTreeMap<Class, String> {
+        &self.classes
+    }
+
+    /// Removes every point that has the same input and outcome as an earlier one.
+    /// Returns number of removed points.
+    pub fn dedup(&mut self) -> usize {
+        let mut seen = HashSet::new();
+        let count = self.points.len();
+        self.points.retain(|point| seen.insert(point.bits()));
+        count - self.points.len()
+    }
+
+    /// Number of points [`DataSet::dedup`] would remove
     #[must_use]
-    pub fn new(classes: HashMap<Class, String>) -> DataSet {
-        DataSet { points: vec![], classes }
+    pub fn duplicate_count(&self) -> usize {
+        let mut seen = HashSet::new();
+        self.points.iter().filter(|point| !seen.insert(point.bits())).count()
+    }
+
+    /// Median of present values of every cell, 0.0 for cells missing in all points,
+    /// see [`Input::from_vector_with_mask`]. `None` if data set is empty.
+    #[must_use]
+    pub fn missing_defaults(&self) -> Option<Input> {
+        let input_shape = *self.points.first()?.input.input_shape();
+        let mut defaults = Input::new();
+        for row in 0..input_shape.rows() {
+            let mut values = Vec::with_capacity(input_shape.columns());
+            for column in 0..input_shape.columns() {
+                let mut present = self
+                    .points
+                    .iter()
+                    .filter(|point| !point.input.is_missing(row, column))
+                    .map(|point| point.input.get(row, column))
+                    .collect::<Vec<_>>();
+                values.push(if present.is_empty() { 0.0 } else { median(&mut present) });
+            }
+            defaults.add_row(values).unwrap();
+        }
+        Some(defaults)
+    }
+
+    /// Replaces missing cells of every point with `defaults`, see [`Input::fill_missing`].
+    /// Returns number of replaced cells.
+    pub fn fill_missing(&mut self, defaults: &Input) -> Result<usize, PrimeclueErr> {
+        let mut filled = 0;
+        for point in &mut self.points {
+            filled += point.input.fill_missing(defaults)?;
+        }
+        Ok(filled)
     }
 
     #[must_use]
@@ -173,7 +1073,7 @@ impl DataSet {
     where
         F: Fn(&Point) -> bool,
     {
-        let mut new = DataSet::new(self.classes.clone()); // TODO consider checking if all classes are present in `new`
+        let mut new = self.empty_like(); // TODO consider checking if all classes are present in `new`
         for point in self.points {
             if predicate(&point) {
                 new.add_data_point(point).unwrap();
@@ -182,11 +1082,93 @@ impl DataSet {
         new
     }
 
+    /// Joins points of `other` after points of this [`DataSet`]. Classes of both are united,
+    /// a class present in both must have the same name. Points must have the same input shape
+    /// and feature names (see [`DataSet::new_named`]), if both have any. Default rewards of
+    /// this data set are kept.
+    pub fn merge(mut self, other: DataSet) -> Result<DataSet, PrimeclueErr> {
+        for (class, name) in &other.classes {
+            match self.classes.get(class) {
+                Some(own) if own != name => {
+                    return PrimeclueErr::result(format!(
+                        "Class {} is named {} in one data set and {} in the other",
+                        class, own, name
+                    ))
+                }
+                _ => {}
+            }
+        }
+        if !self.is_empty() && !other.is_empty() && self.input_shape() != other.input_shape() {
+            return PrimeclueErr::result(format!(
+                "Unable to merge data sets of input shapes {} and {}",
+                self.input_shape(),
+                other.input_shape()
+            ));
+        }
+        if self.feature_names.is_empty() {
+            self.feature_names = other.feature_names;
+        } else if !other.feature_names.is_empty() && self.feature_names != other.feature_names {
+            return PrimeclueErr::result(format!(
+                "Unable to merge data sets of feature names {:?} and {:?}",
+                self.feature_names, other.feature_names
+            ));
+        }
+        if self.feature_types.is_empty() {
+            self.feature_types = other.feature_types;
+        } else if !other.feature_types.is_empty() && self.feature_types != other.feature_types {
+            return PrimeclueErr::result(format!(
+                "Unable to merge data sets of feature types {:?} and {:?}",
+                self.feature_types, other.feature_types
+            ));
+        }
+        self.classes.extend(other.classes);
+        for point in other.points {
+            self.add_data_point(point)?;
+        }
+        Ok(self)
+    }
+
+    /// Expands each of `columns` (0-based) into one-hot features, one per distinct value.
+    /// With `unknown_bucket` an extra feature is added for every column, used by
+    /// [`CategoricalEncoding::encode`] for values not seen here. Returned encoding must be
+    /// used to transform new inputs before classification.
+    pub fn with_categorical_columns(
+        self,
+        columns: &[usize],
+        unknown_bucket: bool,
+    ) -> Result<(DataSet, CategoricalEncoding), PrimeclueErr> {
+        if self.is_empty() {
+            return PrimeclueErr::result("Unable to encode empty data set".to_string());
+        }
+        let encoding = CategoricalEncoding::new(
+            *self.input_shape(),
+            columns,
+            unknown_bucket,
+            &self.points,
+        )?;
+        let mut data = DataSet::new(self.classes);
+        for point in self.points {
+            let input = encoding.encode(&point.input)?;
+            data.add_data_point(Point::new(input, point.outcome))?;
+        }
+        Ok((data, encoding))
+    }
+
+    /// Writes all points to a new binary file at `path` in the format described in
+    /// [`DiskDataView`], to be read again with [`DataView::read_binary`] or
+    /// [`DiskDataView::open`] much faster than parsing CSV. More points can be added later
+    /// with [`DiskDataView::append_points`].
+    pub fn to_binary(&self, path: &Path) -> Result<(), PrimeclueErr> {
+        DiskDataView::create(path, self).map(|_| ())
+    }
+
+    /// Converts points to a column oriented [`DataView`]. A data set with no points gives
+    /// an empty view of input shape 0x0.
     #[must_use]
     pub fn into_view(self) -> DataView {
-        // TODO change to Option, None if empty
         let mut cells = Data::new();
-        let input_shape = self.input_shape();
+        let input_shape =
+            self.points.first().map_or(InputShape::new(0, 0), |p| *p.input.input_shape());
         for row in 0..input_shape.rows() {
             let mut row_data = Vec::with_capacity(input_shape.columns());
             for column in 0..input_shape.columns() {
@@ -200,18 +1182,34 @@ impl DataSet {
         }
 
         let mut outcomes = Vec::with_capacity(self.len());
-        let mut class_count = HashMap::new();
+        let mut class_count = BTreeMap::new();
         for set in self.points {
             let outcome = set.outcome;
             outcomes.push(outcome);
             let count = class_count.remove(&outcome.class()).unwrap_or(0);
             class_count.insert(outcome.class(), count + 1);
         }
-        DataView { outcomes, cells, class_count, class_map: self.classes.clone() }
+        DataView {
+            outcomes,
+            cells,
+            class_count,
+            class_map: self.classes.clone(),
+            feature_names: self.feature_names,
+            feature_types: self.feature_types,
+        }
     }
 
     pub fn add_data_point(&mut self, point: Point) -> Result<(), String> {
         let (input, _) = point.data();
+        let columns = input.input_shape().columns();
+        if !self.feature_names.is_empty() && columns != self.feature_names.len() {
+            return Err(format!(
+                "Input has {} columns, but there are {} feature names",
+                columns,
+                self.feature_names.len()
+            ));
+        }
+        validate_feature_types(input, &self.feature_types)?;
         if !self.points.is_empty() {
             if input.input_shape() != self.input_shape() {
                 return Err(format!(
@@ -259,13 +1257,141 @@ impl DataSet {
         (s1.into_view(), s2.into_view(), s3.into_view())
     }
 
+    /// Same as [`DataSet::into_3_views_split`], also returning indices of points (in order
+    /// of [`DataSet::iter`]) of every view, in order of view's points, so that the split can
+    /// be recorded and reproduced. With `seed` points are permuted in an order given by it
+    /// before splitting, so equal data sets always give equal views, see
+    /// [`DataSet::into_3_views`].
+    pub fn into_3_views_split_indexed(
+        mut self,
+        seed: Option<u64>,
+    ) -> (DataView, DataView, DataView, [Vec<usize>; 3]) {
+        let mut indices = (0..self.points.len()).collect::<Vec<_>>();
+        if let Some(seed) = seed {
+            let mut numbered = indices.into_iter().zip(self.points).collect::<Vec<_>>();
+            numbered.shuffle(&mut XorShiftRng::seed_from_u64(seed));
+            (indices, self.points) = numbered.into_iter().unzip();
+        }
+        let test_indices = indices.split_off(indices.len() * 2 / 3);
+        let verification_indices = indices.split_off(indices.len() / 2);
+        let (s1, s2, s3) = self.split3();
+        (
+            s1.into_view(),
+            s2.into_view(),
+            s3.into_view(),
+            [indices, verification_indices, test_indices],
+        )
+    }
+
+    /// Splits [`DataSet`] into training, verification and testing [`DataView`] of `train`
+    /// and `verify` fractions of points, the rest goes to testing view. Points are permuted
+    /// in an order given by `seed` first, so the same seed always gives the same split.
+    /// Points within every view keep the order of the permutation, which depends only on
+    /// `seed` and the order of points in [`DataSet::iter`], so data sets built from the same
+    /// points in the same order give equal views, in point order too.
+    /// No attempt is made to ensure equal class count in each [`DataView`], see
+    /// [`DataSet::into_3_views_stratified`].
+    pub fn into_3_views(
+        mut self,
+        train: f32,
+        verify: f32,
+        seed: u64,
+    ) -> Result<(DataView, DataView, DataView), PrimeclueErr> {
+        if !(train > 0.0 && verify > 0.0 && train + verify < 1.0) {
+            return PrimeclueErr::result(format!(
+                "Invalid split fractions: training {}, verification {}",
+                train, verify
+            ));
+        }
+        self.points.shuffle(&mut XorShiftRng::seed_from_u64(seed));
+        let count = self.points.len();
+        let training = ((count as f32 * train).round() as usize).min(count);
+        let verification = ((count as f32 * verify).round() as usize).min(count - training);
+        let test_points = self.points.split_off(training + verification);
+        let verification_points = self.points.split_off(training);
+        let mut training_set = self.empty_like();
+        let mut verification_set = self.empty_like();
+        let mut testing_set = self.empty_like();
+        for point in self.points {
+            training_set.add_data_point(point)?;
+        }
+        for point in verification_points {
+            verification_set.add_data_point(point)?;
+        }
+        for point in test_points {
+            testing_set.add_data_point(point)?;
+        }
+        Ok((training_set.into_view(), verification_set.into_view(), testing_set.into_view()))
+    }
+
+    /// Splits [`DataSet`] into training, verification and testing [`DataView`] so that
+    /// each class is present in all three views in roughly the same proportion as in
+    /// the whole [`DataSet`]. Points keep their relative order within each view.
+    ///
+    /// # Arguments
+    /// * `train_frac` - fraction of each class' points that goes to training view
+    /// * `verify_frac` - fraction of each class' points that goes to verification view,
+    ///   the rest goes to testing view
+    pub fn into_3_views_stratified(
+        self,
+        train_frac: f64,
+        verify_frac: f64,
+    ) -> Result<(DataView, DataView, DataView), PrimeclueErr> {
+        if !(train_frac > 0.0 && verify_frac > 0.0 && train_frac + verify_frac < 1.0) {
+            return PrimeclueErr::result(format!(
+                "Invalid split fractions: training {}, verification {}",
+                train_frac, verify_frac
+            ));
+        }
+        let mut class_count = BTreeMap::new();
+        for point in &self.points {
+            *class_count.entry(point.outcome.class()).or_insert(0) += 1;
+        }
+        let mut quotas = HashMap::with_capacity(class_count.len());
+        for (class, count) in class_count {
+            if count < 3 {
+                return PrimeclueErr::result(format!(
+                    "Unable to stratify: class {} has only {} points, at least 3 required",
+                    self.class_name(class),
+                    count
+                ));
+            }
+            let training = ((count as f64 * train_frac).round() as usize).clamp(1, count - 2);
+            let verification =
+                ((count as f64 * verify_frac).round() as usize).clamp(1, count - training - 1);
+            quotas.insert(class, (training, training + verification));
+        }
+        let mut training_set = self.empty_like();
+        let mut verification_set = self.empty_like();
+        let mut testing_set = self.empty_like();
+        let mut seen = HashMap::new();
+        for point in self.points {
+            let class = point.outcome.class();
+            let index = seen.entry(class).or_insert(0);
+            let (training, verification) = quotas[&class];
+            if *index < training {
+                training_set.add_data_point(point)?;
+            } else if *index < verification {
+                verification_set.add_data_point(point)?;
+            } else {
+                testing_set.add_data_point(point)?;
+            }
+            *index += 1;
+        }
+        Ok((training_set.into_view(), verification_set.into_view(), testing_set.into_view()))
+    }
+
+    fn class_name(&self, class: Class) -> String {
+        self.classes.get(&class).cloned().unwrap_or_else(|| class.to_string())
+    }
+
     pub fn split_with_test_data_marker<P>(self, predicate: P) -> (DataView, DataView, DataView)
     where
         P: Fn(&Point) -> bool,
     {
-        let mut training_set = DataSet::new(self.classes.clone());
-        let mut verification_set = DataSet::new(self.classes.clone());
-        let mut testing_set = DataSet::new(self.classes.clone());
+        let mut training_set = self.empty_like();
+        let mut verification_set = self.empty_like();
+        let mut testing_set = self.empty_like();
         let mut rng = GET_RNG();
         for point in self.points {
             if predicate(&point) {
@@ -286,43 +1412,257 @@ impl DataSet {
         (s1.into_view(), s2.into_view())
     }
 
+    /// Partitions [`DataSet`] into `k` (training, testing) [`DataView`] pairs for
+    /// cross-validation. Testing views are disjoint and together contain every point
+    /// exactly once. Points of each class are spread evenly across all folds. Points keep
+    /// their whole [`Outcome`], including reward, penalty and weight.
+    pub fn into_k_folds(self, k: usize) -> Result<Vec<(DataView, DataView)>, PrimeclueErr> {
+        let folds = self.k_fold_sets(k)?;
+        Ok(folds.into_iter().map(|(tr, tst)| (tr.into_view(), tst.into_view())).collect())
+    }
+
+    pub(crate) fn k_fold_sets(
+        mut self,
+        k: usize,
+    ) -> Result<Vec<(DataSet, DataSet)>, PrimeclueErr> {
+        if k < 2 || k > self.len() {
+            return PrimeclueErr::result(format!(
+                "Invalid fold count {} for {} data points",
+                k,
+                self.len()
+            ));
+        }
+        let mut folds = vec![Vec::new(); k];
+        let mut seen = HashMap::new();
+        for point in self.points.drain(..) {
+            let index = seen.entry(point.outcome.class()).or_insert(0);
+            folds[*index % k].push(point);
+            *index += 1;
+        }
+        let mut sets = Vec::with_capacity(k);
+        for test_fold in 0..k {
+            let mut training_set = self.empty_like();
+            let mut testing_set = self.empty_like();
+            for (fold, points) in folds.iter().enumerate() {
+                let set = if fold == test_fold { &mut testing_set } else { &mut training_set };
+                for point in points {
+                    set.add_data_point(point.clone())?;
+                }
+            }
+            sets.push((training_set, testing_set));
+        }
+        Ok(sets)
+    }
+
     /// Shuffles data points within [`DataSet`]
     pub fn shuffle(mut self) -> Self {
         self.points.shuffle(&mut GET_RNG());
         self
     }
 
-    fn split3(mut self) -> (DataSet, DataSet, DataSet) {
-        let mut training_set = DataSet::new(self.classes.clone());
-        let mut verification_set = DataSet::new(self.classes.clone());
-        let mut testing_set = DataSet::new(self.classes.clone());
-        let test_points = self.points.split_off(self.points.len() * 2 / 3);
-        let verification_points = self.points.split_off(self.points.len() / 2);
-        let training_points = self.points;
-        for point in training_points {
-            training_set.add_data_point(point).unwrap();
-        }
-        for point in verification_points {
-            verification_set.add_data_point(point).unwrap();
+    fn split3(mut self) -> (DataSet, DataSet, DataSet) {
+        let mut training_set = self.empty_like();
+        let mut verification_set = self.empty_like();
+        let mut testing_set = self.empty_like();
+        let test_points = self.points.split_off(self.points.len() * 2 / 3);
+        let verification_points = self.points.split_off(self.points.len() / 2);
+        let training_points = self.points;
+        for point in training_points {
+            training_set.add_data_point(point).unwrap();
+        }
+        for point in verification_points {
+            verification_set.add_data_point(point).unwrap();
+        }
+        for point in test_points {
+            testing_set.add_data_point(point).unwrap();
+        }
+        (training_set, verification_set, testing_set)
+    }
+
+    fn split2(mut self) -> (DataSet, DataSet) {
+        let mut training_set = self.empty_like();
+        let mut verification_set = self.empty_like();
+        let verification_points = self.points.split_off(self.points.len() / 2);
+        let training_points = self.points;
+        for point in training_points {
+            training_set.add_data_point(point).unwrap();
+        }
+        for point in verification_points {
+            verification_set.add_data_point(point).unwrap();
+        }
+        (training_set, verification_set)
+    }
+
+    /// Reads comma separated values into a [`DataSet`]. Each non-empty line becomes a point
+    /// with a single-row [`Input`] made of all columns but the label and weight columns. Class
+    /// labels are numbered in order of their first appearance.
+    ///
+    /// # Arguments
+    /// * `path` - a [`Path`] to CSV file
+    /// * `label_column` - index (starting from 0) of the column holding class label
+    /// * `weight_column` - index of the column holding non-negative weight of the row, which
+    ///   becomes [`Outcome::weight`] scaling its reward and penalty and its share of every
+    ///   score. Rows of weight 0 don't affect training. All weights are 1 if `None`
+    /// * `has_header` - skip the first line of the file
+    pub fn from_csv(
+        path: &Path,
+        label_column: usize,
+        weight_column: Option<usize>,
+        has_header: bool,
+    ) -> Result<DataSet, PrimeclueErr> {
+        if weight_column == Some(label_column) {
+            return PrimeclueErr::result(format!(
+                "Column {} can't hold both label and weight",
+                label_column
+            ));
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| PrimeclueErr::from(format!("Unable to read {:?}: {}", path, e)))?;
+        let mut labels = HashMap::new();
+        let mut rows = Vec::new();
+        let mut columns = None;
+        for (line_num, line) in content.lines().enumerate().skip(has_header as usize) {
+            let line_num = line_num + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let cells = line.split(',').map(str::trim).collect::<Vec<_>>();
+            let expected = *columns.get_or_insert(cells.len());
+            if cells.len() != expected {
+                return PrimeclueErr::result(format!(
+                    "Invalid column count in line {}: found {}, expected {}",
+                    line_num,
+                    cells.len(),
+                    expected
+                ));
+            }
+            if label_column >= cells.len() {
+                return PrimeclueErr::result(format!(
+                    "No label column {} in line {}",
+                    label_column, line_num
+                ));
+            }
+            let weight = match weight_column {
+                Some(column) => {
+                    let cell = cells.get(column).ok_or_else(|| {
+                        PrimeclueErr::from(format!(
+                            "No weight column {} in line {}",
+                            column, line_num
+                        ))
+                    })?;
+                    match cell.parse::<f32>() {
+                        Ok(weight) if weight.is_finite() && weight >= 0.0 => weight,
+                        _ => {
+                            return PrimeclueErr::result(format!(
+                                "Invalid weight '{}' in line {}, must be a non-negative number",
+                                cell, line_num
+                            ))
+                        }
+                    }
+                }
+                None => 1.0,
+            };
+            let mut numbers = Vec::with_capacity(cells.len() - 1);
+            for (column, cell) in cells.iter().enumerate() {
+                if column != label_column && Some(column) != weight_column {
+                    numbers.push(cell.parse::<f32>().map_err(|_| {
+                        PrimeclueErr::from(format!(
+                            "Unable to parse '{}' to number in line {}, column {}",
+                            cell,
+                            line_num,
+                            column + 1
+                        ))
+                    })?);
+                }
+            }
+            let next_class = Class::new(labels.len() as u16);
+            let class = *labels.entry(cells[label_column].to_owned()).or_insert(next_class);
+            rows.push((numbers, class, weight));
+        }
+        let mut data = DataSet::new(labels.into_iter().map(|(label, class)| (class, label)));
+        for (numbers, class, weight) in rows {
+            let input = Input::from_vector(vec![numbers])?;
+            let mut outcome = Outcome::new(class, 1.0, -1.0);
+            outcome.set_weight(weight);
+            data.add_data_point(Point::new(input, outcome))?;
+        }
+        Ok(data)
+    }
+
+    /// Creates [`DataSet`] with one point per row of `features`, each with a single-row
+    /// [`Input`], and class taken from `labels` at the same index. Every label must be
+    /// a class from `classes`.
+    #[cfg(feature = "ndarray")]
+    pub fn from_arrays(
+        features: ArrayView2<'_, f32>,
+        labels: ArrayView1<'_, u16>,
+        classes: impl IntoIterator<Item = (Class, String)>,
+    ) -> Result<DataSet, PrimeclueErr> {
+        if features.nrows() != labels.len() {
+            return PrimeclueErr::result(format!(
+                "Feature rows and labels counts don't match: {} vs {}",
+                features.nrows(),
+                labels.len()
+            ));
         }
-        for point in test_points {
-            testing_set.add_data_point(point).unwrap();
+        let mut data = DataSet::new(classes);
+        for (row, (values, label)) in features.outer_iter().zip(labels).enumerate() {
+            let class = Class::new(*label);
+            if !data.classes.contains_key(&class) {
+                return PrimeclueErr::result(format!(
+                    "Unknown class {} in row {}",
+                    label,
+                    row + 1
+                ));
+            }
+            let input = Input::from_vector(vec![values.to_vec()])?;
+            data.add_data_point(Point::new(input, Outcome::new(class, 1.0, -1.0)))?;
         }
-        (training_set, verification_set, testing_set)
+        Ok(data)
     }
 
-    fn split2(mut self) -> (DataSet, DataSet) {
-        let mut training_set = DataSet::new(self.classes.clone());
-        let mut verification_set = DataSet::new(self.classes.clone());
-        let verification_points = self.points.split_off(self.points.len() / 2);
-        let training_points = self.points;
-        for point in training_points {
-            training_set.add_data_point(point).unwrap();
+    /// Creates [`DataSet`] from a time series, with one point per window of `window`
+    /// consecutive values, windows starting every `stride` values. Each window becomes
+    /// a single-row [`Input`] labelled with the class from `labels` at the window's index,
+    /// so there must be exactly one label per window. Class names are class numbers.
+    pub fn from_series_windows(
+        series: &[f32],
+        labels: &[Class],
+        window: usize,
+        stride: usize,
+    ) -> Result<DataSet, PrimeclueErr> {
+        if window == 0 || stride == 0 {
+            return PrimeclueErr::result(format!(
+                "Window {} and stride {} must be at least 1",
+                window, stride
+            ));
         }
-        for point in verification_points {
-            verification_set.add_data_point(point).unwrap();
+        if series.len() < window {
+            return PrimeclueErr::result(format!(
+                "Series of length {} is shorter than window {}",
+                series.len(),
+                window
+            ));
         }
-        (training_set, verification_set)
+        let windows = (series.len() - window) / stride + 1;
+        if labels.len() != windows {
+            return PrimeclueErr::result(format!(
+                "Series of length {} has {} windows of {} with stride {}, but got {} labels",
+                series.len(),
+                windows,
+                window,
+                stride,
+                labels.len()
+            ));
+        }
+        let mut data = DataSet::new(labels.iter().map(|class| (*class, class.to_string())));
+        for (i, class) in labels.iter().enumerate() {
+            let start = i * stride;
+            let input = Input::from_vector(vec![series[start..start + window].to_vec()])?;
+            data.add_data_point(Point::new(input, Outcome::new(*class, 1.0, -1.0)))?;
+        }
+        Ok(data)
     }
 
     /// Reads data in Primeclue format from disk
@@ -368,6 +1708,15 @@ impl DataSet {
             serializator.add(ds);
             callback(i)?;
         }
+        // optional, so that data saved before names and types were kept can still be read
+        if !self.feature_names.is_empty() {
+            serializator.add_str(FEATURE_NAMES_TOKEN);
+            serializator.add(&self.feature_names);
+        }
+        if !self.feature_types.is_empty() {
+            serializator.add_str(FEATURE_TYPES_TOKEN);
+            serializator.add(&self.feature_types);
+        }
         Ok(())
     }
 
@@ -387,26 +1736,379 @@ impl Serializable for DataSet {
 
 impl Deserializable for DataSet {
     fn deserialize(s: &mut Serializator) -> Result<DataSet, String> {
-        let classes = HashMap::deserialize(s)?;
+        let classes: BTreeMap<Class, String> = BTreeMap::deserialize(s)?;
         let len = usize::deserialize(s)?;
-        let mut data = DataSet::new(classes);
-        for _ in 0..len {
-            let point = Point::deserialize(s)?;
+        let points = (0..len).map(|_| Point::deserialize(s)).collect::<Result<Vec<_>, _>>()?;
+        let feature_names = if s.peek_token() == Some(FEATURE_NAMES_TOKEN) {
+            s.next_token()?;
+            Vec::deserialize(s)?
+        } else {
+            Vec::new()
+        };
+        let feature_types = if s.peek_token() == Some(FEATURE_TYPES_TOKEN) {
+            s.next_token()?;
+            Vec::deserialize(s)?
+        } else {
+            Vec::new()
+        };
+        let mut data = DataSet::new_named(classes, feature_names)
+            .and_then(|data| data.with_feature_types(feature_types))
+            .map_err(|e| e.to_string())?;
+        for point in points {
             data.add_data_point(point)?;
         }
         Ok(data)
     }
 }
 
+impl Serializable for FeatureType {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(match self {
+            FeatureType::Continuous => "Continuous",
+            FeatureType::Integer => "Integer",
+            FeatureType::Boolean => "Boolean",
+        })
+    }
+}
+
+impl Deserializable for FeatureType {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "Continuous" => Ok(FeatureType::Continuous),
+            "Integer" => Ok(FeatureType::Integer),
+            "Boolean" => Ok(FeatureType::Boolean),
+            other => Err(format!("Invalid feature type {}", other)),
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test {
-    use crate::contrand::GET_RNG;
-    use crate::data::data_set::{DataSet, Rewards};
-    use crate::data::outcome::Class;
-    use crate::data::{Input, Outcome, Point};
+    use crate::contrand::{seed_thread_rng, GET_RNG};
+    use crate::data::data_set::{DataSet, DataView, FeatureType, Rewards};
+    use crate::data::outcome::{Class, MultiLabelOutcome};
+    use crate::data::{Input, InputShape, Outcome, Point};
     use crate::serialization::serializator::test::test_serialization;
+    use crate::serialization::serializator::Serializator;
+    use crate::serialization::Deserializable;
     use rand::Rng;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+    use std::{env, fs, process};
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn from_arrays() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let features = ndarray::arr2(&[[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        let labels = ndarray::arr1(&[0, 1, 1]);
+        let data =
+            DataSet::from_arrays(features.view(), labels.view(), classes.clone()).unwrap();
+        assert_eq!(data.len(), 3);
+        assert_eq!(data.input_shape(), &InputShape::new(1, 2));
+        let (input, outcome) = data.iter().nth(1).unwrap().data();
+        assert_eq!(input.row(0), vec![3.0, 4.0]);
+        assert_eq!(outcome.class(), Class::new(1));
+
+        let labels = ndarray::arr1(&[0, 1]);
+        assert!(DataSet::from_arrays(features.view(), labels.view(), classes.clone()).is_err());
+        let labels = ndarray::arr1(&[0, 1, 2]);
+        assert!(DataSet::from_arrays(features.view(), labels.view(), classes).is_err());
+    }
+
+    #[test]
+    fn add_labeled_point() {
+        let mut data = create_simple_data(0).with_default_rewards(2.0, -3.0);
+        let input = || Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        data.add_labeled_point(input(), Class::new(1)).unwrap();
+        data.add_data_point(Point::new(input(), Outcome::new(Class::new(0), 1.0, -1.0)))
+            .unwrap();
+        let outcomes = data.iter().map(|p| *p.data().1).collect::<Vec<_>>();
+        assert_eq!(outcomes[0], Outcome::new(Class::new(1), 2.0, -3.0));
+        assert_eq!(outcomes[1], Outcome::new(Class::new(0), 1.0, -1.0));
+        assert!(data
+            .add_labeled_point(Input::from_vector(vec![vec![1.0]]).unwrap(), Class::new(0))
+            .is_err());
+    }
+
+    #[test]
+    fn class_counts() {
+        let view = create_simple_data(30).into_view();
+        assert_eq!(view.len(), 30);
+        assert!(!view.is_empty());
+        assert_eq!(view.class_counts().values().sum::<usize>(), 30);
+        for (class, count) in view.class_counts() {
+            let expected = view.outcomes().iter().filter(|o| o.class() == *class).count();
+            assert_eq!(*count, expected);
+        }
+    }
+
+    #[test]
+    fn oversample_minority() {
+        let classes = (0..3).map(|c| (Class::new(c), c.to_string())).collect::<HashMap<_, _>>();
+        let mut data = DataSet::new(classes);
+        let points =
+            (0..20).map(|x| (x as f32, 0)).chain([100.0, 102.0, 104.0, 106.0].map(|x| (x, 1)));
+        for (x, class) in points.chain([(-5.0, 2)]) {
+            let input = Input::from_vector(vec![vec![x, 2.0 * x]]).unwrap();
+            data.add_labeled_point(input, Class::new(class)).unwrap();
+        }
+        let view = data.into_view();
+        let oversampled = view.oversample_minority(0.5, 3).unwrap();
+        assert_eq!(oversampled.class_items_count(Class::new(0)), Some(20));
+        assert_eq!(oversampled.class_items_count(Class::new(1)), Some(10));
+        assert_eq!(oversampled.class_items_count(Class::new(2)), Some(10));
+        assert_eq!(oversampled.outcomes()[..25], view.outcomes()[..]);
+        let (xs, ys) = (oversampled.cells().get(0, 0), oversampled.cells().get(0, 1));
+        for point in 25..oversampled.len() {
+            let (x, y) = (xs[point], ys[point]);
+            match oversampled.outcomes()[point].class().value() {
+                1 => assert!((100.0..=106.0).contains(&x) && (y - 2.0 * x).abs() < 1e-3),
+                class => assert!(class == 2 && x == -5.0 && y == -10.0),
+            }
+        }
+        assert_eq!(view.oversample_minority(0.5, 3).unwrap().cells(), oversampled.cells());
+        assert_eq!(view.oversample_minority(0.05, 3).unwrap().len(), view.len());
+        assert!(view.oversample_minority(0.0, 3).is_err());
+        assert!(view.oversample_minority(1.5, 3).is_err());
+    }
+
+    #[test]
+    fn summary() {
+        let classes = (0..2).map(|c| (Class::new(c), c.to_string())).collect::<HashMap<_, _>>();
+        let mut data = DataSet::new(classes);
+        let rows =
+            [(0.0, Some(5.0), 0), (300.0, Some(5.0), 1), (150.0, None, 1), (50.0, Some(5.0), 1)];
+        for (x, y, class) in &rows {
+            let mask = [vec![false, y.is_none()]];
+            let input =
+                Input::from_vector_with_mask(vec![vec![*x, y.unwrap_or(0.0)]], &mask).unwrap();
+            data.add_labeled_point(input, Class::new(*class)).unwrap();
+        }
+        let summary = data.into_view().summary();
+        assert_eq!(summary.point_count, 4);
+        assert_eq!(summary.feature_count, 2);
+        assert_eq!(summary.class_counts.get(&Class::new(0)), Some(&1));
+        assert_eq!(summary.class_counts.get(&Class::new(1)), Some(&3));
+        let x = summary.features[0];
+        assert_eq!((x.min, x.max, x.mean, x.missing), (0.0, 300.0, 125.0, 0));
+        assert!((x.std_dev - 132.2876).abs() < 1e-3);
+        let y = summary.features[1];
+        assert_eq!((y.min, y.max, y.mean, y.std_dev, y.missing), (5.0, 5.0, 5.0, 0.0, 1));
+
+        let empty = DataSet::new(vec![(Class::new(0), "0".to_string())]).into_view().summary();
+        assert_eq!(empty.point_count, 0);
+        assert!(empty.features.is_empty());
+    }
+
+    #[test]
+    fn classes() {
+        let classes = (0..3).map(|c| (Class::new(c), c.to_string())).collect::<HashMap<_, _>>();
+        let mut data = DataSet::new(classes);
+        for class in &[2, 0, 2] {
+            let input = Input::from_vector(vec![vec![1.0]]).unwrap();
+            data.add_labeled_point(input, Class::new(*class)).unwrap();
+        }
+        let view = data.into_view();
+        assert_eq!(view.class_map().len(), 3);
+        assert_eq!(view.classes(), vec![Class::new(0), Class::new(2)]);
+    }
+
+    #[test]
+    fn dedup() {
+        let mut data = create_simple_data(20);
+        let points = data.iter().cloned().collect::<Vec<_>>();
+        for point in points.iter().take(5) {
+            data.add_data_point(point.clone()).unwrap();
+        }
+        let (input, outcome) = points[0].data();
+        let mut weighted = *outcome;
+        weighted.set_weight(2.0);
+        data.add_data_point(Point::new(input.clone(), weighted)).unwrap();
+        assert_eq!(data.duplicate_count(), 5);
+        assert_eq!(data.len(), 26);
+        assert_eq!(data.dedup(), 5);
+        assert_eq!(data.len(), 21);
+        assert_eq!(data.iter().take(20).cloned().collect::<Vec<_>>(), points);
+        assert_eq!(data.duplicate_count(), 0);
+    }
+
+    #[test]
+    fn missing_values() {
+        let mut data = DataSet::new(HashMap::from([(Class::new(0), "A".to_owned())]));
+        assert!(data.missing_defaults().is_none());
+        for (values, mask) in [
+            (vec![1.0, 0.0, 5.0], vec![false, true, false]),
+            (vec![0.0, 0.0, 3.0], vec![true, true, false]),
+            (vec![3.0, 0.0, 0.0], vec![false, true, true]),
+        ] {
+            let input = Input::from_vector_with_mask(vec![values], &[mask]).unwrap();
+            data.add_labeled_point(input, Class::new(0)).unwrap();
+        }
+        let defaults = data.missing_defaults().unwrap();
+        assert_eq!(defaults.row(0), vec![2.0, 0.0, 4.0]);
+        assert_eq!(data.fill_missing(&defaults).unwrap(), 5);
+        let rows = data.iter().map(|point| point.data().0.row(0)).collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec![1.0, 0.0, 5.0], vec![2.0, 0.0, 3.0], vec![3.0, 0.0, 4.0]]);
+    }
+
+    #[test]
+    fn subsample() {
+        let mut data = DataSet::new(create_simple_data(0).class_map().clone());
+        for i in 0..100 {
+            let input = Input::from_vector(vec![vec![i as f32]]).unwrap();
+            data.add_labeled_point(input, Class::new((i % 10 == 0) as u16)).unwrap();
+        }
+        let view = data.into_view();
+        let sample = view.subsample(0.2, 3, false);
+        assert_eq!(sample.len(), 20);
+        assert_eq!(sample.cells().get(0, 0), view.subsample(0.2, 3, false).cells().get(0, 0));
+        assert_ne!(sample.cells().get(0, 0), view.subsample(0.2, 4, false).cells().get(0, 0));
+        assert!(sample.cells().get(0, 0).windows(2).all(|w| w[0] < w[1]));
+
+        let sample = view.subsample(0.2, 3, true);
+        assert_eq!(sample.class_items_count(Class::new(0)), Some(10));
+        assert_eq!(sample.class_items_count(Class::new(1)), Some(10));
+        let sample = view.subsample(0.01, 3, true);
+        assert_eq!(sample.class_items_count(Class::new(0)), Some(1));
+        assert_eq!(sample.class_items_count(Class::new(1)), Some(1));
+        assert_eq!(view.subsample(1.0, 3, false).outcomes(), view.outcomes());
+    }
+
+    #[test]
+    fn subsample_count() {
+        let classes = (0..3).map(|c| (Class::new(c), c.to_string())).collect::<HashMap<_, _>>();
+        let mut data = DataSet::new(classes);
+        for i in 0..100 {
+            let input = Input::from_vector(vec![vec![i as f32]]).unwrap();
+            let class = match i % 10 {
+                0 => 2,
+                1..=3 => 1,
+                _ => 0,
+            };
+            data.add_labeled_point(input, Class::new(class)).unwrap();
+        }
+        let view = data.into_view();
+        let sample = view.subsample_count(10, 3, true);
+        assert_eq!(sample.class_items_count(Class::new(0)), Some(6));
+        assert_eq!(sample.class_items_count(Class::new(1)), Some(3));
+        assert_eq!(sample.class_items_count(Class::new(2)), Some(1));
+        assert!(sample.cells().get(0, 0).windows(2).all(|w| w[0] < w[1]));
+        let counts = |view: &DataView| view.class_counts().values().copied().collect::<Vec<_>>();
+        assert_eq!(counts(&view.subsample_count(15, 3, true)), [9, 5, 1]);
+        assert_eq!(sample.cells(), view.subsample_count(10, 3, true).cells());
+        assert_ne!(sample.cells(), view.subsample_count(10, 4, true).cells());
+        assert_eq!(view.subsample_count(10, 3, false).len(), 10);
+        assert_eq!(view.subsample_count(500, 3, true).outcomes(), view.outcomes());
+        assert!(view.subsample_count(0, 3, true).is_empty());
+    }
+
+    #[test]
+    fn split_indexed() {
+        let data = create_simple_data(30);
+        let points = data.iter().cloned().collect::<Vec<_>>();
+        let (training, verification, test, indices) =
+            data.clone().into_3_views_split_indexed(None);
+        assert_eq!(
+            indices,
+            [(0..10).collect::<Vec<_>>(), (10..20).collect(), (20..30).collect()]
+        );
+        let (t, v, _) = data.clone().into_3_views_split();
+        assert_eq!(training.outcomes(), t.outcomes());
+        assert_eq!(verification.cells().get(0, 1), v.cells().get(0, 1));
+        assert_eq!(test.len(), 10);
+
+        let (training, _, test, indices) = data.clone().into_3_views_split_indexed(Some(5));
+        assert_eq!(indices, data.clone().into_3_views_split_indexed(Some(5)).3);
+        assert_ne!(indices, data.into_3_views_split_indexed(Some(6)).3);
+        let mut all = indices.concat();
+        all.sort_unstable();
+        assert_eq!(all, (0..30).collect::<Vec<_>>());
+        for (view, indices) in [(&training, &indices[0]), (&test, &indices[2])] {
+            for (position, index) in indices.iter().enumerate() {
+                let (input, outcome) = points[*index].data();
+                assert_eq!(view.outcomes()[position], *outcome);
+                assert_eq!(view.cells().get(0, 1)[position], input.get(0, 1));
+            }
+        }
+    }
+
+    #[test]
+    fn shuffled() {
+        let view = create_simple_data(50).into_view();
+        let shuffled = view.shuffled(7);
+        assert_eq!(shuffled.outcomes(), view.shuffled(7).outcomes());
+        assert_eq!(shuffled.cells().get(0, 0), view.shuffled(7).cells().get(0, 0));
+        assert_ne!(shuffled.cells().get(0, 0), view.cells().get(0, 0));
+        assert_ne!(shuffled.cells().get(0, 0), view.shuffled(8).cells().get(0, 0));
+        for class in view.class_map().keys() {
+            assert_eq!(shuffled.class_items_count(*class), view.class_items_count(*class));
+        }
+        let mut values = shuffled.cells().get(0, 0).clone();
+        values.sort_by(|v1, v2| v1.partial_cmp(v2).unwrap());
+        let mut original = view.cells().get(0, 0).clone();
+        original.sort_by(|v1, v2| v1.partial_cmp(v2).unwrap());
+        assert_eq!(values, original);
+    }
+
+    #[test]
+    fn bootstrap_sample() {
+        let view = create_simple_data(50).into_view();
+        let (sample, out_of_bag) = view.bootstrap_sample_with_oob(80, 3);
+        assert_eq!(sample.outcomes().len(), 80);
+        assert_eq!(sample.cells().get(0, 1).len(), 80);
+        assert_eq!(sample.outcomes(), view.bootstrap_sample(80, 3).outcomes());
+        assert!(!out_of_bag.is_empty());
+        // first column holds point index
+        let drawn = sample.cells().get(0, 0);
+        for i in 0..50 {
+            assert_eq!(out_of_bag.contains(&i), !drawn.contains(&(i as f32)));
+        }
+        let count =
+            view.class_map().keys().filter_map(|c| sample.class_items_count(*c)).sum::<usize>();
+        assert_eq!(count, 80);
+    }
+
+    #[test]
+    fn from_series_windows() {
+        let series = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        let labels = [Class::new(0), Class::new(1), Class::new(0)];
+        let data = DataSet::from_series_windows(&series, &labels, 3, 2).unwrap();
+        assert_eq!(data.len(), 3);
+        assert_eq!(data.input_shape(), &InputShape::new(1, 3));
+        let rows = data.iter().map(|p| p.data().0.row(0)).collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec![1.0, 2.0, 3.0], vec![3.0, 4.0, 5.0], vec![5.0, 6.0, 7.0]]);
+        assert_eq!(data.iter().nth(1).unwrap().data().1.class(), Class::new(1));
+
+        assert!(DataSet::from_series_windows(&series, &labels[..2], 3, 2).is_err());
+        assert!(DataSet::from_series_windows(&series, &labels, 8, 2).is_err());
+        assert!(DataSet::from_series_windows(&series, &labels, 3, 0).is_err());
+        assert!(DataSet::from_series_windows(&series, &labels, 0, 2).is_err());
+    }
+
+    #[test]
+    fn label_view() {
+        let view = create_multiclass_data().into_view();
+        let count = view.outcomes().len();
+        let labels = (0..count)
+            .map(|i| {
+                MultiLabelOutcome::new(
+                    (0..3).map(Class::new).filter(|c| (i as u16 + c.value()).is_multiple_of(2)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let class = *view.class_map().keys().min().unwrap();
+        let binary = view.label_view(&labels, class).unwrap();
+        assert_eq!(binary.class_count(), 2);
+        for (label, outcome) in labels.iter().zip(binary.outcomes()) {
+            assert_eq!(outcome.class(), Class::from(label.contains(class)));
+        }
+        assert_eq!(binary.cells().get(0, 0), view.cells().get(0, 0));
+        assert!(view.label_view(&labels[1..], class).is_err());
+        assert!(view.label_view(&labels, Class::new(100)).is_err());
+    }
 
     #[test]
     fn serialize() {
@@ -414,6 +2116,29 @@ pub(crate) mod test {
         test_serialization(data);
     }
 
+    #[test]
+    fn serialize_features() {
+        let classes = HashMap::from([(Class::new(0), "a".to_string())]);
+        let names = vec!["sepal length".to_string(), "petals".to_string()];
+        let mut data = DataSet::new_named(classes.clone(), names)
+            .unwrap()
+            .with_feature_types(vec![FeatureType::Continuous, FeatureType::Integer])
+            .unwrap();
+        data.add_labeled_point(Input::from_vector(vec![vec![1.5, 3.0]]).unwrap(), Class::new(0))
+            .unwrap();
+        test_serialization(data.clone());
+
+        // data saved before feature names and types were kept
+        let mut s = Serializator::new();
+        s.add(&data.classes);
+        s.add(&data.len());
+        data.iter().for_each(|point| s.add(point));
+        let read = DataSet::deserialize(&mut s).unwrap();
+        assert!(read.feature_names().is_empty());
+        assert!(read.feature_types().is_empty());
+        assert_eq!(read.iter().collect::<Vec<_>>(), data.iter().collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_add_input_shape() {
         let mut classes = HashMap::new();
@@ -511,6 +2236,405 @@ pub(crate) mod test {
         }
     }
 
+    #[test]
+    fn test_split_by_fractions() {
+        let data = create_simple_data(100);
+        let split = |seed| data.clone().into_3_views(0.8, 0.1, seed).unwrap();
+        let (tr, vs, tst) = split(7);
+        assert_eq!(tr.outcomes.len(), 80);
+        assert_eq!(vs.outcomes.len(), 10);
+        assert_eq!(tst.outcomes.len(), 10);
+        let (tr2, vs2, tst2) = split(7);
+        assert_eq!(tr.cells, tr2.cells);
+        assert_eq!(vs.cells, vs2.cells);
+        assert_eq!(tst.cells, tst2.cells);
+        assert_ne!(split(8).0.cells, tr.cells);
+        assert!(create_simple_data(10).into_3_views(0.5, 0.5, 7).is_err());
+        assert!(create_simple_data(10).into_3_views(0.0, 0.5, 7).is_err());
+        assert!(create_simple_data(10).into_3_views(0.5, -0.1, 7).is_err());
+    }
+
+    #[test]
+    fn test_deterministic_views() {
+        let csv = (0..60)
+            .map(|i| format!("{},{},{}", i, ["a", "b", "c"][i % 3], (i * 7) % 11))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let path = write_csv("deterministic_views", &csv);
+        let views = || {
+            let data = DataSet::from_csv(&path, 1, None, false).unwrap();
+            let (tr, vs, tst) = data.clone().into_3_views(0.6, 0.2, 7).unwrap();
+            let (tr2, vs2, tst2, indices) = data.clone().into_3_views_split_indexed(Some(7));
+            let (tr3, vs3, tst3) = data.clone().into_3_views_stratified(0.6, 0.2).unwrap();
+            let folds =
+                data.into_k_folds(3).unwrap().into_iter().flat_map(|(tr, tst)| [tr, tst]);
+            let views = [tr, vs, tst, tr2, vs2, tst2, tr3, vs3, tst3].into_iter().chain(folds);
+            let contents = views
+                .map(|view| (view.cells, view.outcomes, view.class_count, view.class_map))
+                .collect::<Vec<_>>();
+            (contents, indices)
+        };
+        let first = views();
+        assert_eq!(first, views());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stratified_split() {
+        let mut data = create_big_multiclass_data();
+        let rare = Class::new(3);
+        data.classes.insert(rare, "d".to_owned());
+        for _ in 0..10 {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]).unwrap(),
+                Outcome::new(rare, 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let total = data.len();
+        let (tr, vs, tst) = data.into_3_views_stratified(0.5, 0.3).unwrap();
+        assert_eq!(tr.outcomes.len() + vs.outcomes.len() + tst.outcomes.len(), total);
+        assert_eq!(tr.class_items_count(rare), Some(5));
+        assert_eq!(vs.class_items_count(rare), Some(3));
+        assert_eq!(tst.class_items_count(rare), Some(2));
+        for view in &[tr, vs, tst] {
+            assert_eq!(view.class_count(), 4);
+        }
+    }
+
+    #[test]
+    fn test_stratified_split_too_few_points() {
+        let mut data = create_multiclass_data();
+        data.points.truncate(8);
+        assert!(data.clone().into_3_views_stratified(0.4, 0.3).is_err());
+        let data = create_multiclass_data();
+        assert!(data.clone().into_3_views_stratified(0.7, 0.3).is_err());
+        let (tr, vs, tst) = data.into_3_views_stratified(0.4, 0.3).unwrap();
+        for view in &[tr, vs, tst] {
+            assert_eq!(view.outcomes.len(), 3);
+            assert_eq!(view.class_count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_k_folds() {
+        let data = create_multiclass_data();
+        let total = data.len();
+        let folds = data.into_k_folds(3).unwrap();
+        assert_eq!(folds.len(), 3);
+        let mut tested = vec![];
+        for (training, testing) in &folds {
+            assert_eq!(training.outcomes.len() + testing.outcomes.len(), total);
+            assert_eq!(testing.class_count(), 3);
+            assert_eq!(training.class_count(), 3);
+            tested.extend(testing.cells.get(0, 0).iter().copied());
+        }
+        tested.sort_by(|v1, v2| v1.partial_cmp(v2).unwrap());
+        let expected: Vec<f32> = vec![1.0, 7.0, 10.0, 13.0, 40.0, 60.0, 100.0, 110.0, 160.0];
+        assert_eq!(tested, expected);
+    }
+
+    #[test]
+    fn test_resampling_keeps_outcomes() {
+        let mut data = DataSet::new(create_multiclass_data().class_map().clone())
+            .with_default_rewards(3.0, -4.0);
+        for i in 0..30 {
+            let input = Input::from_vector(vec![vec![i as f32]]).unwrap();
+            let mut outcome = Outcome::new(Class::new(i % 3), i as f32, -(i as f32) * 2.0);
+            outcome.set_weight(1.0 + i as f32);
+            data.add_data_point(Point::new(input, outcome)).unwrap();
+        }
+        let check = |view: &DataView| {
+            assert!(!view.is_empty());
+            for (value, outcome) in view.cells().get(0, 0).iter().zip(view.outcomes()) {
+                let i = *value as u16;
+                let mut expected = Outcome::new(Class::new(i % 3), *value, -*value * 2.0);
+                expected.set_weight(1.0 + *value);
+                assert_eq!(*outcome, expected);
+            }
+        };
+        for (training, testing) in data.clone().into_k_folds(3).unwrap() {
+            check(&training);
+            check(&testing);
+        }
+        let view = data.clone().into_view();
+        check(&view.shuffled(1));
+        check(&view.bootstrap_sample(20, 2));
+        check(&view.subsample(0.5, 3, true));
+        let (training, verification, testing) =
+            data.clone().into_3_views_stratified(0.5, 0.25).unwrap();
+        [training, verification, testing].iter().for_each(check);
+
+        let (mut training, _) = data.k_fold_sets(3).unwrap().remove(0);
+        training
+            .add_labeled_point(Input::from_vector(vec![vec![0.0]]).unwrap(), Class::new(0))
+            .unwrap();
+        let (_, added) = training.points.last().unwrap().data();
+        assert_eq!((added.reward(), added.penalty()), (3.0, -4.0));
+    }
+
+    #[test]
+    fn test_constant_features() {
+        let mut data = DataSet::new(create_multiclass_data().class_map().clone());
+        for i in 0..5 {
+            let x = i as f32;
+            let input = Input::from_vector_with_mask(
+                vec![vec![x, 7.0, 2.0, x], vec![1.0, 7.0, 0.0, 3.0]],
+                &[vec![false, false, i == 0, false], vec![false, false, false, false]],
+            )
+            .unwrap();
+            data.add_labeled_point(input, Class::new(i % 3)).unwrap();
+        }
+        let view = data.into_view();
+        assert_eq!(view.constant_features(), vec![1, 2]);
+
+        let dropped = view.drop_features(&[1, 2, 10]);
+        assert_eq!(*dropped.input_shape(), InputShape::new(2, 2));
+        assert_eq!(dropped.cells().get(0, 1), view.cells().get(0, 3));
+        assert_eq!(dropped.cells().get(1, 0), view.cells().get(1, 0));
+        assert_eq!(dropped.outcomes(), view.outcomes());
+        assert!(dropped.constant_features().is_empty());
+    }
+
+    #[test]
+    fn drop_correlated() {
+        let classes = create_multiclass_data().class_map().clone();
+        let names = ["a", "b", "c", "d"].iter().map(|n| n.to_string()).collect();
+        let mut data = DataSet::new_named(classes, names).unwrap();
+        for i in 0..10 {
+            let x = f32::from(i);
+            let noise = [3.0, -1.0, 4.0, 1.0, -5.0, 9.0, 2.0, -6.0, 5.0, 3.0][usize::from(i)];
+            let mask = [vec![false, i == 3, false, false]];
+            let values = vec![vec![x, 2.0 * x + 1.0, -x + 0.1 * noise, noise]];
+            let input = Input::from_vector_with_mask(values, &mask).unwrap();
+            data.add_labeled_point(input, Class::new(i % 3)).unwrap();
+        }
+        let view = data.into_view();
+        let (reduced, dropped) = view.drop_correlated(0.95);
+        assert_eq!(dropped, vec![1, 2]);
+        assert_eq!(reduced.feature_names(), ["a".to_string(), "d".to_string()]);
+        assert_eq!(reduced.cells().get(0, 1), view.cells().get(0, 3));
+        assert_eq!(reduced.outcomes(), view.outcomes());
+        assert_eq!(view.drop_correlated(1.0).1, Vec::<usize>::new());
+        assert_eq!(reduced.drop_correlated(0.95).1, Vec::<usize>::new());
+    }
+
+    #[test]
+    fn leakage_report() {
+        let classes = create_multiclass_data().class_map().clone();
+        let mut data = DataSet::new(classes);
+        for i in 0..20 {
+            let class = i % 2;
+            let leak = if class == 0 { -1.0 - f32::from(i) } else { 50.0 + f32::from(i) };
+            let almost = f32::from(i) + if i == 5 { 100.0 } else { 0.0 };
+            let mask = [vec![false, false, i == 3], vec![false, false, false]];
+            let values = vec![vec![7.0, almost, leak], vec![0.0, 0.0, 0.0]];
+            let input = Input::from_vector_with_mask(values, &mask).unwrap();
+            data.add_labeled_point(input, Class::new(class)).unwrap();
+        }
+        let view = data.into_view();
+        assert_eq!(view.leakage_report(0.99), vec![(2, 1.0)]);
+        let report = view.leakage_report(0.5);
+        assert_eq!(report.iter().map(|(column, _)| *column).collect::<Vec<_>>(), [2, 1, 0]);
+        assert!(report[1].1 > 0.5 && report[1].1 < 0.99);
+        assert_eq!(report[2].1, 0.5);
+        let empty = DataSet::new(create_multiclass_data().class_map().clone()).into_view();
+        assert!(empty.leakage_report(0.0).is_empty());
+    }
+
+    #[test]
+    fn test_feature_names() {
+        let classes = create_multiclass_data().class_map().clone();
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert!(DataSet::new_named(classes.clone(), names(&["a", ""])).is_err());
+        assert!(DataSet::new_named(classes.clone(), names(&["a", "a"])).is_err());
+        let mut data = DataSet::new_named(classes, names(&["a", "b", "c"])).unwrap();
+        let input = |columns: usize| Input::from_vector(vec![vec![1.0; columns]]).unwrap();
+        assert!(data.add_labeled_point(input(2), Class::new(0)).is_err());
+        for class in 0..3 {
+            data.add_labeled_point(input(3), Class::new(class)).unwrap();
+        }
+        let (training, _) = data.clone().into_2_views_split();
+        assert_eq!(training.feature_names(), names(&["a", "b", "c"]).as_slice());
+        let view = data.into_view();
+        assert_eq!(view.shuffled(1).feature_names(), view.feature_names());
+        assert_eq!(view.drop_features(&[1]).feature_names(), names(&["a", "c"]).as_slice());
+    }
+
+    #[test]
+    fn add_point() {
+        let types = vec![FeatureType::Integer, FeatureType::Continuous];
+        let data = create_simple_data(10).with_feature_types(types);
+        let mut view = data.unwrap().into_view();
+        let count = view.class_items_count(Class::new(1)).unwrap_or(0);
+        let point = |values| {
+            let input = Input::from_vector(vec![values]).unwrap();
+            Point::new(input, Outcome::new(Class::new(1), 1.0, -1.0))
+        };
+        view.add_point(point(vec![3.0, 4.0])).unwrap();
+        assert_eq!(view.len(), 11);
+        assert_eq!(view.cells().get(0, 1)[10], 4.0);
+        assert_eq!(view.class_items_count(Class::new(1)), Some(count + 1));
+        assert!(view.add_point(point(vec![3.5, 4.0])).is_err());
+        assert!(view.add_point(point(vec![3.0])).is_err());
+        assert_eq!(view.len(), 11);
+    }
+
+    #[test]
+    fn test_class_order() {
+        let data = create_multiclass_data();
+        let reversed = data.class_map().iter().rev().map(|(c, n)| (*c, n.clone()));
+        let mut other = DataSet::new(reversed.collect::<HashMap<_, _>>());
+        data.iter().for_each(|point| other.add_data_point(point.clone()).unwrap());
+        assert!(data
+            .class_map()
+            .keys()
+            .zip(data.class_map().keys().skip(1))
+            .all(|(a, b)| a < b));
+        let serialized = |data: &DataSet| {
+            let mut s = Serializator::new();
+            s.add(data);
+            s.to_bytes()
+        };
+        assert_eq!(serialized(&data), serialized(&other));
+        let guess_cost = |data: DataSet| {
+            let _seeded = seed_thread_rng(7);
+            data.into_view().random_guess_cost_once()
+        };
+        assert_eq!(guess_cost(data), guess_cost(other));
+    }
+
+    #[test]
+    fn test_feature_types() {
+        let classes = create_multiclass_data().class_map().clone();
+        let types = vec![FeatureType::Continuous, FeatureType::Integer, FeatureType::Boolean];
+        let input = |values: Vec<f32>| Input::from_vector(vec![values]).unwrap();
+        let mut data = DataSet::new(classes).with_feature_types(types.clone()).unwrap();
+        assert!(data.add_labeled_point(input(vec![0.5, 2.0]), Class::new(0)).is_err());
+        assert!(data.add_labeled_point(input(vec![0.5, 2.5, 1.0]), Class::new(0)).is_err());
+        assert!(data.add_labeled_point(input(vec![0.5, 2.0, 2.0]), Class::new(0)).is_err());
+        let missing =
+            Input::from_vector_with_mask(vec![vec![0.5, 0.0, 0.0]], &[vec![false, true, true]])
+                .unwrap();
+        data.add_labeled_point(missing, Class::new(0)).unwrap();
+        for class in 0..3 {
+            data.add_labeled_point(input(vec![0.5, -3.0, 1.0]), Class::new(class)).unwrap();
+        }
+        assert!(data.clone().with_feature_types(vec![FeatureType::Boolean; 3]).is_err());
+        let (training, _) = data.clone().into_2_views_split();
+        assert_eq!(training.feature_types(), types.as_slice());
+        let view = data.clone().into_view();
+        assert_eq!(view.shuffled(1).feature_types(), view.feature_types());
+        assert_eq!(
+            view.drop_features(&[1]).feature_types(),
+            &[FeatureType::Continuous, FeatureType::Boolean]
+        );
+        assert!(view.standardized().0.feature_types().is_empty());
+        assert!(view.robust_scaled().0.feature_types().is_empty());
+        let untyped = DataSet::new(data.class_map().clone());
+        assert_eq!(untyped.merge(data.clone()).unwrap().feature_types(), types.as_slice());
+        let other = DataSet::new(data.class_map().clone())
+            .with_feature_types(vec![FeatureType::Continuous; 3])
+            .unwrap();
+        assert!(data.merge(other).is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let classes = |names: &[(u16, &str)]| {
+            names
+                .iter()
+                .map(|(c, n)| (Class::new(*c), n.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let input = |columns: usize| Input::from_vector(vec![vec![1.0; columns]]).unwrap();
+        let mut first = DataSet::new(classes(&[(0, "a"), (1, "b")]));
+        first.add_labeled_point(input(2), Class::new(0)).unwrap();
+        let mut second = DataSet::new(classes(&[(1, "b"), (2, "c")]));
+        second.add_labeled_point(input(2), Class::new(2)).unwrap();
+        second.add_labeled_point(input(2), Class::new(1)).unwrap();
+
+        let merged = first.clone().merge(second.clone()).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.class_map(), &classes(&[(0, "a"), (1, "b"), (2, "c")]));
+        let outcomes = merged.iter().map(|p| p.data().1.class().value()).collect::<Vec<_>>();
+        assert_eq!(outcomes, vec![0, 2, 1]);
+        assert_eq!(DataSet::default().merge(second.clone()).unwrap(), second);
+
+        let renamed = DataSet::new(classes(&[(1, "not b")]));
+        assert!(first.clone().merge(renamed).is_err());
+        let mut wide = DataSet::new(classes(&[(0, "a")]));
+        wide.add_labeled_point(input(3), Class::new(0)).unwrap();
+        assert!(first.clone().merge(wide).is_err());
+        let names = vec!["x".to_string(), "y".to_string()];
+        let mut named = DataSet::new_named(classes(&[(0, "a")]), names.clone()).unwrap();
+        named.add_labeled_point(input(2), Class::new(0)).unwrap();
+        assert_eq!(first.clone().merge(named.clone()).unwrap().feature_names(), names);
+        let mut other =
+            DataSet::new_named(classes(&[(0, "a")]), vec!["x".into(), "z".into()]).unwrap();
+        other.add_labeled_point(input(2), Class::new(0)).unwrap();
+        assert!(named.merge(other).is_err());
+    }
+
+    #[test]
+    fn test_k_folds_invalid_count() {
+        assert!(create_multiclass_data().into_k_folds(1).is_err());
+        assert!(create_multiclass_data().into_k_folds(10).is_err());
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let path = write_csv("from_csv", "a,label,b\n1.0,yes,2\n\n3,no,4.5\n5,yes,6\n");
+        let data = DataSet::from_csv(&path, 1, None, true).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(data.len(), 3);
+        assert_eq!(data.classes.get(&Class::new(0)).unwrap(), "yes");
+        assert_eq!(data.classes.get(&Class::new(1)).unwrap(), "no");
+        let view = data.into_view();
+        assert_eq!(view.cells.get(0, 0), &vec![1.0, 3.0, 5.0]);
+        assert_eq!(view.cells.get(0, 1), &vec![2.0, 4.5, 6.0]);
+        assert_eq!(view.class_items_count(Class::new(0)), Some(2));
+    }
+
+    #[test]
+    fn test_from_csv_errors() {
+        let path = write_csv("csv_not_number", "1,2,true\n3,x,false\n");
+        let r = DataSet::from_csv(&path, 2, None, false);
+        fs::remove_file(&path).unwrap();
+        assert!(r.err().unwrap().to_string().contains("line 2"));
+
+        let path = write_csv("csv_columns", "h1,h2,h3\n1,2,true\n3,false\n");
+        let r = DataSet::from_csv(&path, 2, None, true);
+        fs::remove_file(&path).unwrap();
+        assert!(r.err().unwrap().to_string().contains("line 3"));
+
+        let path = write_csv("csv_weights", "1,0.5,true\n3,-1,false\n");
+        let r = DataSet::from_csv(&path, 2, Some(1), false);
+        fs::remove_file(&path).unwrap();
+        assert!(r.err().unwrap().to_string().contains("line 2"));
+        let path = write_csv("csv_weight_label", "1,0.5,true\n");
+        assert!(DataSet::from_csv(&path, 2, Some(2), false).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_from_csv_weights() {
+        let path = write_csv("csv_weighted", "a,w,label\n1,2,yes\n3,0,no\n5,0.5,yes\n");
+        let data = DataSet::from_csv(&path, 2, Some(1), true).unwrap();
+        fs::remove_file(&path).unwrap();
+        let view = data.into_view();
+        assert_eq!(view.input_shape().columns(), 1);
+        assert_eq!(view.cells.get(0, 0), &vec![1.0, 3.0, 5.0]);
+        let weights = view.outcomes().iter().map(Outcome::weight).collect::<Vec<_>>();
+        assert_eq!(weights, vec![2.0, 0.0, 0.5]);
+    }
+
+    fn write_csv(name: &str, content: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("primeclue_{}_{}.csv", name, process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
     #[test]
     fn test_shuffling() {
         let data = create_simple_data(100);
@@ -559,6 +2683,110 @@ pub(crate) mod test {
         assert_eq!(count, 0);
     }
 
+    #[test]
+    fn test_standardized() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "0".to_owned());
+        let mut data = DataSet::new(classes);
+        for (a, b) in &[(1.0, 100.0), (2.0, 200.0), (3.0, 300.0)] {
+            let input = Input::from_vector(vec![vec![*a, *b, 7.0]]).unwrap();
+            data.add_data_point(Point::new(input, Outcome::new(Class::new(0), 1.0, -1.0)))
+                .unwrap();
+        }
+        let view = data.into_view();
+        let (standardized, normalization) = view.standardized();
+        assert_eq!(standardized.cells().get(0, 0), &vec![-1.0, 0.0, 1.0]);
+        assert_eq!(standardized.cells().get(0, 1), &vec![-1.0, 0.0, 1.0]);
+        assert_eq!(standardized.cells().get(0, 2), &vec![0.0, 0.0, 0.0]);
+        assert_eq!(view.cells().get(0, 1), &vec![100.0, 200.0, 300.0]);
+        assert_eq!(standardized.outcomes(), view.outcomes());
+        assert_eq!(view.normalized(&normalization).unwrap().cells(), standardized.cells());
+
+        let mut input = Input::from_vector(vec![vec![4.0, 150.0, 8.0]]).unwrap();
+        normalization.apply(&mut input).unwrap();
+        assert_eq!(input.row(0), vec![2.0, -0.5, 1.0]);
+        let mut input = Input::from_vector(vec![vec![4.0]]).unwrap();
+        assert!(normalization.apply(&mut input).is_err());
+        test_serialization(normalization);
+
+        let empty = view.subsample_count(0, 1, false);
+        assert!(empty.is_empty());
+        let (standardized, normalization) = empty.standardized();
+        assert!(standardized.is_empty());
+        assert_eq!(normalization.input_shape(), view.input_shape());
+        let single = view.subsample_count(1, 1, false);
+        let (standardized, _) = single.standardized();
+        assert_eq!(standardized.cells().get(0, 1), &vec![0.0]);
+    }
+
+    #[test]
+    fn test_standardized_missing_values() {
+        let mut data = DataSet::new(HashMap::from([(Class::new(0), "0".to_owned())]));
+        for a in [1.0, f32::NAN, 2.0, 3.0] {
+            let input =
+                Input::from_vector_with_mask(vec![vec![a]], &[vec![a.is_nan()]]).unwrap();
+            data.add_data_point(Point::new(input, Outcome::new(Class::new(0), 1.0, -1.0)))
+                .unwrap();
+        }
+        let (standardized, normalization) = data.into_view().standardized();
+        let values = standardized.cells().get(0, 0);
+        assert_eq!([values[0], values[2], values[3]], [-1.0, 0.0, 1.0]);
+        assert!(values[1].is_nan());
+        let mut input = Input::from_vector(vec![vec![4.0]]).unwrap();
+        normalization.apply(&mut input).unwrap();
+        assert_eq!(input.row(0), vec![2.0]);
+    }
+
+    #[test]
+    fn test_robust_scaled() {
+        let mut data = DataSet::new(HashMap::from([(Class::new(0), "0".to_owned())]));
+        for (a, b) in &[(1.0, 7.0), (2.0, 7.0), (3.0, 7.0), (4.0, 7.0), (1_000.0, f32::NAN)] {
+            let missing = [vec![false, b.is_nan()]];
+            let input = Input::from_vector_with_mask(vec![vec![*a, *b]], &missing).unwrap();
+            data.add_data_point(Point::new(input, Outcome::new(Class::new(0), 1.0, -1.0)))
+                .unwrap();
+        }
+        let view = data.into_view();
+        let (scaled, normalization) = view.robust_scaled();
+        assert_eq!(scaled.cells().get(0, 0), &vec![-1.0, -0.5, 0.0, 0.5, 498.5]);
+        assert_eq!(scaled.cells().get(0, 1)[..4], [0.0; 4]);
+        assert!(scaled.cells().get(0, 1)[4].is_nan());
+        let normalized = view.normalized(&normalization).unwrap();
+        assert_eq!(normalized.cells().get(0, 0), scaled.cells().get(0, 0));
+        let mut input = Input::from_vector(vec![vec![5.0, 9.0]]).unwrap();
+        normalization.apply(&mut input).unwrap();
+        assert_eq!(input.row(0), vec![1.0, 2.0]);
+        test_serialization(normalization);
+    }
+
+    #[test]
+    fn test_categorical_columns() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "0".to_owned());
+        let mut data = DataSet::new(classes);
+        for (a, b) in &[(1.0, 0.5), (3.0, 1.5), (2.0, 2.5), (1.0, 3.5)] {
+            let input = Input::from_vector(vec![vec![*a, *b]]).unwrap();
+            data.add_data_point(Point::new(input, Outcome::new(Class::new(0), 1.0, -1.0)))
+                .unwrap();
+        }
+        assert!(data.clone().with_categorical_columns(&[2], false).is_err());
+        let (encoded, encoding) = data.clone().with_categorical_columns(&[0], false).unwrap();
+        assert_eq!(encoded.input_shape(), &InputShape::new(1, 4));
+        let view = encoded.into_view();
+        assert_eq!(view.cells().get(0, 0), &vec![1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(view.cells().get(0, 2), &vec![0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(view.cells().get(0, 3), &vec![0.5, 1.5, 2.5, 3.5]);
+
+        let input = Input::from_vector(vec![vec![3.0, 7.0]]).unwrap();
+        assert_eq!(encoding.encode(&input).unwrap().row(0), vec![0.0, 0.0, 1.0, 7.0]);
+        let unseen = Input::from_vector(vec![vec![4.0, 7.0]]).unwrap();
+        assert!(encoding.encode(&unseen).is_err());
+        test_serialization(encoding);
+
+        let (_, encoding) = data.with_categorical_columns(&[0], true).unwrap();
+        assert_eq!(encoding.encode(&unseen).unwrap().row(0), vec![0.0, 0.0, 0.0, 1.0, 7.0]);
+    }
+
     pub(crate) fn create_simple_data(count: usize) -> DataSet {
         let mut classes = HashMap::new();
         classes.insert(Class::new(0), "FALSE".to_owned());
@@ -0,0 +1,631 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::data::data_set::{DataSet, DataView, FeatureType, Point};
+use crate::data::outcome::Class;
+use crate::data::{Data, Input, InputShape, Outcome};
+use crate::error::PrimeclueErr;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const DISK_MAGIC: &[u8; 4] = b"PCDV";
+const DISK_FORMAT_VERSION: u32 = 2;
+const DISK_FORMAT_V1: u32 = 1;
+/// Number of points read from the file at once by [`DiskDataView::read_all`]
+pub const READ_BLOCK_POINTS: usize = 4096;
+
+/// Points stored in a binary file, for data too large to keep in memory. Points are read
+/// on demand in chunks, each returned as an in-memory [`DataView`], e.g. to train on them one
+/// by one with [`crate::exec::training_group::TrainingGroup::train_on_chunks`].
+/// Note that standard deviation nodes normalize values within a chunk, not within all points.
+/// Files are written with [`DataSet::to_binary`], extended with
+/// [`DiskDataView::append_points`] and can be read whole with [`DataView::read_binary`] as
+/// well.
+///
+/// File format, all numbers little-endian:
+/// * magic bytes `PCDV` and format version as `u32`, currently 2
+/// * number of rows and columns of every input as `u32`
+/// * number of classes as `u32`, then every class as `u16`, length of its name in bytes
+///   as `u32` and the name in UTF-8
+/// * number of feature names as `u32`, then length of every name in bytes as `u32` and the
+///   name in UTF-8, see [`DataSet::new_named`]
+/// * number of feature types as `u32`, then every type as `u8`: 0 continuous, 1 integer and
+///   2 boolean, see [`DataSet::with_feature_types`]
+/// * number of points as `u64`
+/// * every point: its input values as `f32`, row by row, NaN for a missing value (see
+///   [`Input::from_vector_with_mask`]), then its class as `u16`, and reward, penalty, weight
+///   and target as `f32`, with target NaN if there is none
+///
+/// Files of version 1 have no feature names and types and are read as if there were none.
+#[derive(Clone, Debug)]
+pub struct DiskDataView {
+    path: PathBuf,
+    input_shape: InputShape,
+    class_map: BTreeMap<Class, String>,
+    feature_names: Vec<String>,
+    feature_types: Vec<FeatureType>,
+    len: usize,
+    data_offset: u64,
+}
+
+impl DiskDataView {
+    /// Writes all points of `data` to a new file at `path` and opens it
+    pub fn create(path: &Path, data: &DataSet) -> Result<DiskDataView, PrimeclueErr> {
+        let mut w = BufWriter::new(File::create(path)?);
+        let shape = data.input_shape();
+        w.write_all(DISK_MAGIC)?;
+        w.write_all(&DISK_FORMAT_VERSION.to_le_bytes())?;
+        w.write_all(&(shape.rows() as u32).to_le_bytes())?;
+        w.write_all(&(shape.columns() as u32).to_le_bytes())?;
+        let classes = data.class_map();
+        w.write_all(&(classes.len() as u32).to_le_bytes())?;
+        for (class, name) in classes {
+            w.write_all(&class.value().to_le_bytes())?;
+            write_str(&mut w, name)?;
+        }
+        w.write_all(&(data.feature_names().len() as u32).to_le_bytes())?;
+        for name in data.feature_names() {
+            write_str(&mut w, name)?;
+        }
+        w.write_all(&(data.feature_types().len() as u32).to_le_bytes())?;
+        for feature_type in data.feature_types() {
+            let code: u8 = match feature_type {
+                FeatureType::Continuous => 0,
+                FeatureType::Integer => 1,
+                FeatureType::Boolean => 2,
+            };
+            w.write_all(&[code])?;
+        }
+        w.write_all(&(data.len() as u64).to_le_bytes())?;
+        for point in data.iter() {
+            write_point(&mut w, point)?;
+        }
+        w.flush()?;
+        DiskDataView::open(path)
+    }
+
+    /// Appends all points of `data` to the end of an existing file at `path` and opens it
+    /// again. Number of points in the header is updated in place, so the file is neither
+    /// rewritten nor renamed. Fails, leaving the file unchanged, if `data` has different
+    /// input shape, classes, feature names or feature types than the file.
+    pub fn append_points(path: &Path, data: &DataSet) -> Result<DiskDataView, PrimeclueErr> {
+        let disk = DiskDataView::open(path)?;
+        if data.is_empty() {
+            return Ok(disk);
+        }
+        if data.input_shape() != disk.input_shape() {
+            return PrimeclueErr::result(format!(
+                "Points have input shape {}, but data file {} has {}",
+                data.input_shape(),
+                path.display(),
+                disk.input_shape()
+            ));
+        }
+        if data.class_map() != disk.class_map()
+            || data.feature_names() != disk.feature_names()
+            || data.feature_types() != disk.feature_types()
+        {
+            return PrimeclueErr::result(format!(
+                "Points have different classes or features than data file {}",
+                path.display()
+            ));
+        }
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.seek(SeekFrom::Start(disk.data_offset + (disk.len * disk.point_bytes()) as u64))?;
+        let mut w = BufWriter::new(file);
+        for point in data.iter() {
+            write_point(&mut w, point)?;
+        }
+        let mut file = w.into_inner().map_err(|e| PrimeclueErr::from(e.into_error()))?;
+        // number of points is the last field of the header
+        file.seek(SeekFrom::Start(disk.data_offset - size_of::<u64>() as u64))?;
+        file.write_all(&((disk.len + data.len()) as u64).to_le_bytes())?;
+        DiskDataView::open(path)
+    }
+
+    /// Opens a file in the format described in [`DiskDataView`], reading only its header
+    pub fn open(path: &Path) -> Result<DiskDataView, PrimeclueErr> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut r = BufReader::new(file);
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != DISK_MAGIC {
+            return PrimeclueErr::result(format!("{} is not a data file", path.display()));
+        }
+        let version = read_u32(&mut r)?;
+        if version != DISK_FORMAT_VERSION && version != DISK_FORMAT_V1 {
+            return PrimeclueErr::result(format!(
+                "Unsupported data file format version {}, expected {}",
+                version, DISK_FORMAT_VERSION
+            ));
+        }
+        let input_shape =
+            InputShape::new(read_u32(&mut r)? as usize, read_u32(&mut r)? as usize);
+        let mut class_map = BTreeMap::new();
+        for _ in 0..read_u32(&mut r)? {
+            let class = Class::new(read_u16(&mut r)?);
+            let name = read_string(&mut r)
+                .map_err(|e| format!("Invalid name of class {}: {}", class, e))?;
+            class_map.insert(class, name);
+        }
+        let mut feature_names = Vec::new();
+        let mut feature_types = Vec::new();
+        if version == DISK_FORMAT_VERSION {
+            for _ in 0..read_u32(&mut r)? {
+                feature_names.push(read_string(&mut r)?);
+            }
+            for _ in 0..read_u32(&mut r)? {
+                let mut code = [0];
+                r.read_exact(&mut code)?;
+                feature_types.push(match code[0] {
+                    0 => FeatureType::Continuous,
+                    1 => FeatureType::Integer,
+                    2 => FeatureType::Boolean,
+                    other => {
+                        return PrimeclueErr::result(format!("Invalid feature type {}", other))
+                    }
+                });
+            }
+        }
+        let len = read_u64(&mut r)? as usize;
+        let data_offset = r.stream_position()?;
+        let view = DiskDataView {
+            path: path.to_path_buf(),
+            input_shape,
+            class_map,
+            feature_names,
+            feature_types,
+            len,
+            data_offset,
+        };
+        let expected_len = data_offset + (len * view.point_bytes()) as u64;
+        if file_len != expected_len {
+            return PrimeclueErr::result(format!(
+                "Data file {} has {} bytes, expected {} for {} points",
+                path.display(),
+                file_len,
+                expected_len,
+                len
+            ));
+        }
+        Ok(view)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[must_use]
+    pub fn input_shape(&self) -> &InputShape {
+        &self.input_shape
+    }
+
+    #[must_use]
+    pub fn class_map(&self) -> &BTreeMap<Class, String> {
+        &self.class_map
+    }
+
+    /// Names of input columns of the data set written to the file, empty if there are none
+    #[must_use]
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Types of input columns of the data set written to the file, empty if all columns are
+    /// continuous
+    #[must_use]
+    pub fn feature_types(&self) -> &[FeatureType] {
+        &self.feature_types
+    }
+
+    /// Reads `count` points starting from point `start`, or fewer if the file ends before.
+    /// Fails if any input value is infinite, is not accepted by its column's feature type or
+    /// a class is not one of the file's classes.
+    pub fn read_chunk(&self, start: usize, count: usize) -> Result<DataView, PrimeclueErr> {
+        let count = count.min(self.len.saturating_sub(start));
+        let mut r = BufReader::new(File::open(&self.path)?);
+        r.seek(SeekFrom::Start(self.data_offset + (start * self.point_bytes()) as u64))?;
+        let mut data = DataSet::new_named(self.class_map.clone(), self.feature_names.clone())?
+            .with_feature_types(self.feature_types.clone())?;
+        for _ in 0..count {
+            let mut input = Vec::with_capacity(self.input_shape.rows());
+            for _ in 0..self.input_shape.rows() {
+                let row = (0..self.input_shape.columns())
+                    .map(|_| read_f32(&mut r))
+                    .collect::<Result<Vec<_>, _>>()?;
+                input.push(row);
+            }
+            let class = Class::new(read_u16(&mut r)?);
+            let (reward, penalty) = (read_f32(&mut r)?, read_f32(&mut r)?);
+            let weight = read_f32(&mut r)?;
+            let target = Some(read_f32(&mut r)?).filter(|target| !target.is_nan());
+            let mut outcome = Outcome::new(class, reward, penalty).with_target(target);
+            outcome.set_weight(weight);
+            let missing = input
+                .iter()
+                .map(|row| row.iter().map(|v| v.is_nan()).collect())
+                .collect::<Vec<_>>();
+            let input = Input::from_vector_with_mask(input, &missing)?;
+            data.add_data_point(Point::new(input, outcome))?;
+        }
+        Ok(data.into_view())
+    }
+
+    /// Reads all points into a single view, decoding values straight into its columns while
+    /// the file is read in blocks of [`READ_BLOCK_POINTS`] points, so little memory is needed
+    /// beside the view itself. File is not memory-mapped: trees read values column by column,
+    /// while the file keeps them point by point, so they're copied into the view anyway.
+    /// Fails the same way as [`DiskDataView::read_chunk`] if any input value is infinite, is
+    /// not accepted by its column's feature type or a class is not one of the file's classes.
+    pub fn read_all(&self) -> Result<DataView, PrimeclueErr> {
+        let file = File::open(&self.path)?;
+        if file.metadata()?.len() != self.data_offset + (self.len * self.point_bytes()) as u64 {
+            return PrimeclueErr::result(format!(
+                "Data file {} changed since it was opened",
+                self.path.display()
+            ));
+        }
+        let mut r = BufReader::new(file);
+        r.seek(SeekFrom::Start(self.data_offset))?;
+        let cell_count = self.input_shape.rows() * self.input_shape.columns();
+        let mut columns = vec![Vec::with_capacity(self.len); cell_count];
+        let mut outcomes = Vec::with_capacity(self.len);
+        let mut block = vec![0; READ_BLOCK_POINTS.min(self.len) * self.point_bytes()];
+        let mut left = self.len;
+        while left > 0 {
+            let count = left.min(READ_BLOCK_POINTS);
+            left -= count;
+            let block = &mut block[..count * self.point_bytes()];
+            r.read_exact(block)?;
+            self.decode_points(block, &mut columns, &mut outcomes)?;
+        }
+        let mut columns = columns.into_iter();
+        let mut cells = Data::new();
+        for _ in 0..self.input_shape.rows() {
+            cells.add_row(columns.by_ref().take(self.input_shape.columns()).collect())?;
+        }
+        Ok(DataView::from_columns(cells, outcomes, self.class_map.clone())
+            .with_features(self.feature_names.clone(), self.feature_types.clone()))
+    }
+
+    /// Appends values of every point of `points` to `columns` and its outcome to `outcomes`
+    fn decode_points(
+        &self,
+        points: &[u8],
+        columns: &mut [Vec<f32>],
+        outcomes: &mut Vec<Outcome>,
+    ) -> Result<(), PrimeclueErr> {
+        let cell_count = columns.len();
+        for point in points.chunks_exact(self.point_bytes()) {
+            for (cell, column) in columns.iter_mut().enumerate() {
+                let v = f32_at(point, 4 * cell);
+                let (row, column_index) =
+                    (cell / self.input_shape.columns(), cell % self.input_shape.columns());
+                if v.is_infinite() {
+                    return PrimeclueErr::result(format!(
+                        "Non-finite value {} in input row {}, column {}",
+                        v,
+                        row + 1,
+                        column_index + 1
+                    ));
+                }
+                if let Some(feature_type) = self.feature_types.get(column_index) {
+                    if !v.is_nan() && !feature_type.accepts(v) {
+                        return PrimeclueErr::result(format!(
+                            "Value {} in input row {}, column {} is not {:?}",
+                            v,
+                            row + 1,
+                            column_index + 1,
+                            feature_type
+                        ));
+                    }
+                }
+                column.push(v);
+            }
+            let offset = 4 * cell_count;
+            let class = Class::new(u16::from_le_bytes([point[offset], point[offset + 1]]));
+            if !self.class_map.contains_key(&class) {
+                return PrimeclueErr::result(format!("Unknown class {}", class));
+            }
+            let outcome_value = |i: usize| f32_at(point, offset + 2 + 4 * i);
+            let target = Some(outcome_value(3)).filter(|target| !target.is_nan());
+            let mut outcome =
+                Outcome::new(class, outcome_value(0), outcome_value(1)).with_target(target);
+            outcome.set_weight(outcome_value(2));
+            outcomes.push(outcome);
+        }
+        Ok(())
+    }
+
+    /// Reads all points in consecutive chunks of `chunk_size` points, the last one may be
+    /// smaller. Only one chunk at a time is read into memory.
+    pub fn chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Result<DataView, PrimeclueErr>> + '_ {
+        let chunk_size = chunk_size.max(1);
+        (0..self.len).step_by(chunk_size).map(move |start| self.read_chunk(start, chunk_size))
+    }
+
+    fn point_bytes(&self) -> usize {
+        let values = self.input_shape.rows() * self.input_shape.columns() + 4;
+        values * size_of::<f32>() + size_of::<u16>()
+    }
+}
+
+fn read_u16(r: &mut impl Read) -> Result<u16, PrimeclueErr> {
+    let mut bytes = [0; 2];
+    r.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32, PrimeclueErr> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(r: &mut impl Read) -> Result<u64, PrimeclueErr> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_string(r: &mut impl Read) -> Result<String, PrimeclueErr> {
+    let mut bytes = vec![0; read_u32(r)? as usize];
+    r.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|e| PrimeclueErr::from(format!("Invalid string: {}", e)))
+}
+
+/// Writes input values and outcome of `point` in the format described in [`DiskDataView`]
+fn write_point(w: &mut impl Write, point: &Point) -> Result<(), PrimeclueErr> {
+    let (input, outcome) = point.data();
+    let shape = input.input_shape();
+    for row in 0..shape.rows() {
+        for column in 0..shape.columns() {
+            w.write_all(&input.get(row, column).to_le_bytes())?;
+        }
+    }
+    w.write_all(&outcome.class().value().to_le_bytes())?;
+    for value in &[
+        outcome.reward(),
+        outcome.penalty(),
+        outcome.weight(),
+        outcome.target().unwrap_or(f32::NAN),
+    ] {
+        w.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes length of `s` in bytes as `u32` and `s` in UTF-8
+fn write_str(w: &mut impl Write, s: &str) -> Result<(), PrimeclueErr> {
+    w.write_all(&(s.len() as u32).to_le_bytes())?;
+    w.write_all(s.as_bytes())?;
+    Ok(())
+}
+
+fn read_f32(r: &mut impl Read) -> Result<f32, PrimeclueErr> {
+    Ok(f32::from_bits(read_u32(r)?))
+}
+
+/// Little-endian `f32` at `offset` of `bytes`, which must have 4 bytes there
+fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::test::create_simple_data;
+    use crate::data::data_set::{DataSet, DataView, FeatureType};
+    use crate::data::disk_data_view::{DiskDataView, READ_BLOCK_POINTS};
+    use crate::data::outcome::Class;
+    use crate::data::{Input, Outcome, Point};
+    use crate::exec::score::Objective::Auc;
+    use crate::exec::training_group::TrainingGroup;
+    use std::collections::HashMap;
+    use std::{env, fs, process};
+
+    #[test]
+    fn test_binary_round_trip() {
+        let path = env::temp_dir().join(format!("primeclue_binary_{}.pcdv", process::id()));
+        let mut data = create_simple_data(100);
+        let mut outcome = Outcome::new(Class::new(1), 2.0, -3.0).with_target(Some(2.5));
+        outcome.set_weight(0.5);
+        data.add_data_point(Point::new(
+            Input::from_vector(vec![vec![1.0, 2.0]]).unwrap(),
+            outcome,
+        ))
+        .unwrap();
+        data.to_binary(&path).unwrap();
+        let read = DataView::read_binary(&path).unwrap();
+        let view = data.into_view();
+        assert_eq!(read.cells(), view.cells());
+        assert_eq!(read.outcomes(), view.outcomes());
+        assert_eq!(read.class_map(), view.class_map());
+        assert_eq!(read.class_counts(), view.class_counts());
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 3, &[], 5).unwrap();
+        for _ in 0..3 {
+            group.next_generation();
+        }
+        let classifier = group.classifier().unwrap();
+        let (read, view) = (classifier.score(&read).unwrap(), classifier.score(&view).unwrap());
+        assert_eq!(read.auc.to_bits(), view.auc.to_bits());
+        assert_eq!(read.accuracy.to_bits(), view.accuracy.to_bits());
+        assert_eq!(read.confusion_matrix(), view.confusion_matrix());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_binary_features() {
+        let path = env::temp_dir().join(format!("primeclue_features_{}.pcdv", process::id()));
+        let classes = HashMap::from([(Class::new(0), "a".to_string())]);
+        let names = vec!["width".to_string(), "flag".to_string()];
+        let types = vec![FeatureType::Continuous, FeatureType::Boolean];
+        let mut data = DataSet::new_named(classes, names.clone())
+            .unwrap()
+            .with_feature_types(types.clone())
+            .unwrap();
+        data.add_labeled_point(Input::from_vector(vec![vec![1.5, 1.0]]).unwrap(), Class::new(0))
+            .unwrap();
+        data.to_binary(&path).unwrap();
+        let disk = DiskDataView::open(&path).unwrap();
+        assert_eq!(disk.feature_names(), names.as_slice());
+        assert_eq!(disk.feature_types(), types.as_slice());
+        for read in [disk.read_all().unwrap(), disk.read_chunk(0, 1).unwrap()] {
+            assert_eq!(read.feature_names(), names.as_slice());
+            assert_eq!(read.feature_types(), types.as_slice());
+            assert_eq!(read.cells(), data.clone().into_view().cells());
+        }
+
+        // boolean value of the only point made 0.5
+        let mut bytes = fs::read(&path).unwrap();
+        let start = bytes.len() - 22;
+        bytes[start..start + 4].copy_from_slice(&0.5_f32.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        assert!(DataView::read_binary(&path).is_err());
+        assert!(disk.read_chunk(0, 1).is_err());
+        bytes[start..start + 4].copy_from_slice(&1.0_f32.to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+
+        // version 1 files have no feature names and types
+        let bytes = fs::read(&path).unwrap();
+        let header = 4 + 4 + 8 + 4 + 2 + 4 + 1;
+        let features = 4 + (4 + 5) + (4 + 4) + 4 + 2;
+        let mut v1 = bytes[..header].to_vec();
+        v1[4..8].copy_from_slice(&1_u32.to_le_bytes());
+        v1.extend_from_slice(&bytes[header + features..]);
+        fs::write(&path, v1).unwrap();
+        let read = DataView::read_binary(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(read.feature_names().is_empty());
+        assert!(read.feature_types().is_empty());
+        assert_eq!(read.cells(), data.into_view().cells());
+    }
+
+    #[test]
+    fn test_append_points() {
+        let path = env::temp_dir().join(format!("primeclue_append_{}.pcdv", process::id()));
+        let data = create_simple_data(10);
+        let more = create_simple_data(7);
+        data.to_binary(&path).unwrap();
+        let disk = DiskDataView::append_points(&path, &more).unwrap();
+        assert_eq!(disk.len(), 17);
+        let read = DataView::read_binary(&path).unwrap();
+        let mut all = data.clone();
+        for point in more.iter() {
+            all.add_data_point(point.clone()).unwrap();
+        }
+        let all = all.into_view();
+        assert_eq!(read.cells(), all.cells());
+        assert_eq!(read.outcomes(), all.outcomes());
+        assert_eq!(disk.read_chunk(10, 7).unwrap().outcomes(), more.into_view().outcomes());
+
+        let mut wide = DataSet::new(data.class_map().clone());
+        wide.add_labeled_point(
+            Input::from_vector(vec![vec![1.0, 2.0, 3.0]]).unwrap(),
+            Class::new(0),
+        )
+        .unwrap();
+        assert!(DiskDataView::append_points(&path, &wide).is_err());
+        let other_classes = HashMap::from([(Class::new(0), "other".to_string())]);
+        let mut renamed = DataSet::new(other_classes);
+        renamed
+            .add_labeled_point(Input::from_vector(vec![vec![1.0, 2.0]]).unwrap(), Class::new(0))
+            .unwrap();
+        assert!(DiskDataView::append_points(&path, &renamed).is_err());
+        assert_eq!(DiskDataView::open(&path).unwrap().len(), 17);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_all_in_blocks() {
+        let path = env::temp_dir().join(format!("primeclue_blocks_{}.pcdv", process::id()));
+        let data = create_simple_data(2 * READ_BLOCK_POINTS + 3);
+        data.to_binary(&path).unwrap();
+        let read = DataView::read_binary(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let view = data.into_view();
+        assert_eq!(read.cells(), view.cells());
+        assert_eq!(read.outcomes(), view.outcomes());
+    }
+
+    #[test]
+    fn test_disk_data_view() {
+        let path = env::temp_dir().join(format!("primeclue_data_{}.pcdv", process::id()));
+        let mut data = create_simple_data(24);
+        let mut outcome = Outcome::new(Class::new(1), 2.0, -3.0).with_target(Some(2.5));
+        outcome.set_weight(0.5);
+        data.add_data_point(Point::new(
+            Input::from_vector(vec![vec![1.0, 2.0]]).unwrap(),
+            outcome,
+        ))
+        .unwrap();
+        let disk = DiskDataView::create(&path, &data).unwrap();
+        let opened = DiskDataView::open(&path).unwrap();
+        assert_eq!(opened.len(), data.len());
+        assert_eq!(opened.input_shape(), data.input_shape());
+        assert_eq!(opened.class_map(), data.class_map());
+
+        let view = data.into_view();
+        let whole = disk.read_chunk(0, 1_000).unwrap();
+        assert_eq!(whole.outcomes(), view.outcomes());
+        assert_eq!(whole.cells().get(0, 1), view.cells().get(0, 1));
+
+        let chunks = disk.chunks(10).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(chunks.iter().map(|c| c.outcomes().len()).collect::<Vec<_>>(), [10, 10, 5]);
+        assert_eq!(chunks[1].outcomes()[..], view.outcomes()[10..20]);
+        assert_eq!(chunks[2].cells().get(0, 0)[..], view.cells().get(0, 0)[20..]);
+
+        let mut missing = DataSet::new(view.class_map().clone());
+        let outcome = Outcome::new(Class::new(0), 1.0, -1.0);
+        let input = Input::from_vector_with_mask(vec![vec![0.0, 1.0]], &[vec![true, false]]);
+        missing.add_data_point(Point::new(input.unwrap(), outcome)).unwrap();
+        missing.to_binary(&path).unwrap();
+        let read = DataView::read_binary(&path).unwrap();
+        assert!(read.cells().get(0, 0)[0].is_nan());
+        assert_eq!(read.cells().get(0, 1), &vec![1.0]);
+        let chunk = DiskDataView::open(&path).unwrap().read_chunk(0, 1).unwrap();
+        assert!(chunk.cells().get(0, 0)[0].is_nan());
+
+        // the only input value of the only point made infinite
+        let mut bytes = fs::read(&path).unwrap();
+        let start = bytes.len() - 26;
+        bytes[start..start + 4].copy_from_slice(&f32::INFINITY.to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+        assert!(DataView::read_binary(&path).is_err());
+        assert!(DiskDataView::open(&path).unwrap().read_chunk(0, 1).is_err());
+
+        fs::write(&path, b"PCDV").unwrap();
+        assert!(DiskDataView::open(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::data::{Input, InputShape, Point};
+use crate::error::PrimeclueErr;
+use crate::serialization::{Deserializable, Serializable, Serializator};
+
+/// One-hot encoding of categorical columns, created by
+/// [`DataSet::with_categorical_columns`](super::data_set::DataSet::with_categorical_columns).
+/// Each categorical column is replaced by one column per category seen during encoding,
+/// followed by an extra "unknown" column if such bucket was requested.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoricalEncoding {
+    input_shape: InputShape,
+    // sorted categories for every input column, empty for non-categorical columns
+    categories: Vec<Vec<f32>>,
+    unknown_bucket: bool,
+}
+
+impl CategoricalEncoding {
+    pub(crate) fn new(
+        input_shape: InputShape,
+        columns: &[usize],
+        unknown_bucket: bool,
+        points: &[Point],
+    ) -> Result<Self, PrimeclueErr> {
+        if let Some(column) = columns.iter().find(|c| **c >= input_shape.columns()) {
+            return PrimeclueErr::result(format!(
+                "Categorical column {} out of range, data has {} columns",
+                column,
+                input_shape.columns()
+            ));
+        }
+        let mut categories = vec![vec![]; input_shape.columns()];
+        for (input, _) in points.iter().map(Point::data) {
+            for row in 0..input_shape.rows() {
+                for column in columns {
+                    categories[*column].push(input.get(row, *column));
+                }
+            }
+        }
+        for values in &mut categories {
+            values.sort_by(f32::total_cmp);
+            values.dedup();
+        }
+        Ok(CategoricalEncoding { input_shape, categories, unknown_bucket })
+    }
+
+    /// Shape of inputs after encoding
+    #[must_use]
+    pub fn encoded_shape(&self) -> InputShape {
+        let columns = self.categories.iter().map(|c| self.encoded_width(c)).sum();
+        InputShape::new(self.input_shape.rows(), columns)
+    }
+
+    fn encoded_width(&self, categories: &[f32]) -> usize {
+        if categories.is_empty() {
+            1
+        } else {
+            categories.len() + usize::from(self.unknown_bucket)
+        }
+    }
+
+    /// Encodes `input` the same way data was encoded. Returns an error if `input` has
+    /// different shape or contains a category not seen during encoding, unless
+    /// the "unknown" bucket was requested.
+    pub fn encode(&self, input: &Input) -> Result<Input, PrimeclueErr> {
+        if input.input_shape() != &self.input_shape {
+            return PrimeclueErr::result(format!(
+                "Input shape {} doesn't match encoding shape {}",
+                input.input_shape(),
+                self.input_shape
+            ));
+        }
+        let mut rows = Vec::with_capacity(self.input_shape.rows());
+        for row in 0..self.input_shape.rows() {
+            let mut values = Vec::with_capacity(self.encoded_shape().columns());
+            for (column, categories) in self.categories.iter().enumerate() {
+                let value = input.get(row, column);
+                if categories.is_empty() {
+                    values.push(value);
+                    continue;
+                }
+                let mut one_hot = vec![0.0; self.encoded_width(categories)];
+                match categories.iter().position(|c| *c == value) {
+                    Some(index) => one_hot[index] = 1.0,
+                    None if self.unknown_bucket => one_hot[categories.len()] = 1.0,
+                    None => {
+                        return PrimeclueErr::result(format!(
+                            "Unknown category {} in row {}, column {}",
+                            value,
+                            row + 1,
+                            column + 1
+                        ))
+                    }
+                }
+                values.extend(one_hot);
+            }
+            rows.push(values);
+        }
+        Input::from_vector(rows)
+    }
+}
+
+impl Serializable for CategoricalEncoding {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_items(&[&self.input_shape, &self.categories, &self.unknown_bucket]);
+    }
+}
+
+impl Deserializable for CategoricalEncoding {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        let input_shape = InputShape::deserialize(s)?;
+        let categories = Vec::deserialize(s)?;
+        let unknown_bucket = bool::deserialize(s)?;
+        Ok(CategoricalEncoding { input_shape, categories, unknown_bucket })
+    }
+}
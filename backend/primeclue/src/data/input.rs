@@ -21,6 +21,8 @@ use crate::data::Data;
 use crate::data::InputShape;
 use crate::error::PrimeclueErr;
 use crate::serialization::{Deserializable, Serializable, Serializator};
+#[cfg(feature = "ndarray")]
+use ndarray::ArrayView2;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Input {
@@ -38,7 +40,134 @@ impl Input {
         Input { data: Data::new() }
     }
 
+    /// Creates [`Input`] from rows of values. Returns an error if any value is NaN or infinite,
+    /// see [`Input::from_vector_sanitized`] to replace them instead.
     pub fn from_vector(data: Vec<Vec<f32>>) -> Result<Input, PrimeclueErr> {
+        for (row_id, row) in data.iter().enumerate() {
+            if let Some(column) = row.iter().position(|v| !v.is_finite()) {
+                return PrimeclueErr::result(format!(
+                    "Non-finite value {} in input row {}, column {}",
+                    row[column],
+                    row_id + 1,
+                    column + 1
+                ));
+            }
+        }
+        Input::create(data)
+    }
+
+    /// Same as [`Input::from_vector`] with a single row of `len` values, all of them 0 except
+    /// for `entries` of column index and value. Fails if an index is out of range or given
+    /// more than once.
+    pub fn from_sparse(len: usize, entries: &[(usize, f32)]) -> Result<Input, PrimeclueErr> {
+        let mut row = vec![0.0; len];
+        let mut given = vec![false; len];
+        for (column, value) in entries {
+            match given.get_mut(*column) {
+                Some(true) => {
+                    return PrimeclueErr::result(format!(
+                        "Column {} of sparse input given more than once",
+                        column + 1
+                    ))
+                }
+                Some(given) => *given = true,
+                None => {
+                    return PrimeclueErr::result(format!(
+                        "Column {} out of range of sparse input of {} columns",
+                        column + 1,
+                        len
+                    ))
+                }
+            }
+            row[*column] = *value;
+        }
+        Input::from_vector(vec![row])
+    }
+
+    /// Same as [`Input::from_vector`] with rows of `array` as rows of values
+    #[cfg(feature = "ndarray")]
+    pub fn from_array2(array: ArrayView2<'_, f32>) -> Result<Input, PrimeclueErr> {
+        Input::from_vector(array.outer_iter().map(|row| row.to_vec()).collect())
+    }
+
+    /// Creates [`Input`] from rows of values, replacing every NaN or infinite value
+    /// with `fill`, which must be finite itself.
+    pub fn from_vector_sanitized(
+        mut data: Vec<Vec<f32>>,
+        fill: f32,
+    ) -> Result<Input, PrimeclueErr> {
+        if !fill.is_finite() {
+            return PrimeclueErr::result(format!("Non-finite fill value {}", fill));
+        }
+        data.iter_mut().flatten().filter(|v| !v.is_finite()).for_each(|v| *v = fill);
+        Input::create(data)
+    }
+
+    /// Creates [`Input`] from rows of values, with cells marked in `missing` having no value.
+    /// `missing` must have the same shape as `data`. Values of missing cells are ignored,
+    /// all other values must be finite. Missing cells are kept as NaN, so trees using them
+    /// give no output, see [`Input::fill_missing`] to replace them instead.
+    pub fn from_vector_with_mask(
+        mut data: Vec<Vec<f32>>,
+        missing: &[Vec<bool>],
+    ) -> Result<Input, PrimeclueErr> {
+        if data.len() != missing.len()
+            || data.iter().zip(missing).any(|(row, mask)| row.len() != mask.len())
+        {
+            return PrimeclueErr::result("Missing mask doesn't match input shape".to_string());
+        }
+        for (row_id, (row, mask)) in data.iter_mut().zip(missing).enumerate() {
+            for (column, (value, missing)) in row.iter_mut().zip(mask).enumerate() {
+                if *missing {
+                    *value = f32::NAN;
+                } else if !value.is_finite() {
+                    return PrimeclueErr::result(format!(
+                        "Non-finite value {} in input row {}, column {}",
+                        value,
+                        row_id + 1,
+                        column + 1
+                    ));
+                }
+            }
+        }
+        Input::create(data)
+    }
+
+    /// Tells if cell has no value, see [`Input::from_vector_with_mask`]
+    #[must_use]
+    pub fn is_missing(&self, row: usize, column: usize) -> bool {
+        self.get(row, column).is_nan()
+    }
+
+    /// Tells if any cell has no value
+    #[must_use]
+    pub fn has_missing(&self) -> bool {
+        (0..self.input_shape().rows()).any(|row| self.row(row).iter().any(|v| v.is_nan()))
+    }
+
+    /// Replaces every missing cell with the value of the same cell in `defaults`, e.g.
+    /// given by [`crate::data::data_set::DataSet::missing_defaults`]. Returns number of
+    /// replaced cells. Fails if `defaults` has different shape.
+    pub fn fill_missing(&mut self, defaults: &Input) -> Result<usize, PrimeclueErr> {
+        if defaults.input_shape() != self.input_shape() {
+            return Err(PrimeclueErr::ShapeMismatch {
+                expected: *self.input_shape(),
+                actual: *defaults.input_shape(),
+            });
+        }
+        let mut filled = 0;
+        for row in 0..self.input_shape().rows() {
+            for column in 0..self.input_shape().columns() {
+                if self.is_missing(row, column) {
+                    self.set(row, column, defaults.get(row, column));
+                    filled += 1;
+                }
+            }
+        }
+        Ok(filled)
+    }
+
+    fn create(data: Vec<Vec<f32>>) -> Result<Input, PrimeclueErr> {
         let mut input_data = Input::new();
 
         for (id, row) in data.into_iter().enumerate() {
@@ -54,6 +183,11 @@ impl Input {
         *self.data.get(row, column)
     }
 
+    pub(crate) fn set(&mut self, row: usize, column: usize, value: f32) {
+        *self.data.get_mut(row, column) = value;
+    }
+
+    /// Adds a row as is, non-finite values are not checked
     pub fn add_row(&mut self, row: Vec<f32>) -> Result<usize, PrimeclueErr> {
         self.data.add_row(row)
     }
@@ -68,6 +202,17 @@ impl Input {
         self.data.row(r).into_iter().copied().collect()
     }
 
+    /// Input of `point` of column oriented `cells`, the reverse of [`Input::to_view`]
+    pub(crate) fn from_cells(cells: &Data<Vec<f32>>, point: usize) -> Input {
+        let shape = cells.input_shape();
+        let mut input = Input::new();
+        for row in 0..shape.rows() {
+            let values = (0..shape.columns()).map(|col| cells.get(row, col)[point]).collect();
+            input.add_row(values).unwrap();
+        }
+        input
+    }
+
     #[must_use]
     pub fn to_view(&self) -> Data<Vec<f32>> {
         let mut data = Data::new();
@@ -108,6 +253,65 @@ impl Deserializable for Input {
 mod test {
     use crate::data::Input;
 
+    #[test]
+    fn test_non_finite_values() {
+        assert!(Input::from_vector(vec![vec![1.0, f32::NAN]]).is_err());
+        assert!(Input::from_vector(vec![vec![1.0], vec![f32::INFINITY]]).is_err());
+        let input =
+            Input::from_vector_sanitized(vec![vec![1.0, f32::NAN, f32::NEG_INFINITY]], 0.5)
+                .unwrap();
+        assert_eq!(input.row(0), vec![1.0, 0.5, 0.5]);
+        for fill in [f32::NAN, f32::INFINITY] {
+            assert!(Input::from_vector_sanitized(vec![vec![f32::NAN]], fill).is_err());
+        }
+    }
+
+    #[test]
+    fn test_from_sparse() {
+        let input = Input::from_sparse(5, &[(3, 2.5), (0, -1.0)]).unwrap();
+        assert_eq!(input, Input::from_vector(vec![vec![-1.0, 0.0, 0.0, 2.5, 0.0]]).unwrap());
+        assert_eq!(
+            Input::from_sparse(2, &[]).unwrap(),
+            Input::from_vector(vec![vec![0.0; 2]]).unwrap()
+        );
+        assert!(Input::from_sparse(3, &[(3, 1.0)]).is_err());
+        assert!(Input::from_sparse(3, &[(1, 1.0), (1, 2.0)]).is_err());
+        assert!(Input::from_sparse(3, &[(1, f32::NAN)]).is_err());
+    }
+
+    #[test]
+    fn test_missing_mask() {
+        let data = vec![vec![1.0, 2.0], vec![f32::NAN, 4.0]];
+        let mut input =
+            Input::from_vector_with_mask(data, &[vec![false, true], vec![true, false]]).unwrap();
+        assert!(input.has_missing());
+        assert!(!input.is_missing(0, 0));
+        assert!(input.is_missing(0, 1));
+        assert!(input.is_missing(1, 0));
+        let defaults = Input::from_vector(vec![vec![5.0, 6.0], vec![7.0, 8.0]]).unwrap();
+        assert_eq!(input.fill_missing(&defaults).unwrap(), 2);
+        assert!(!input.has_missing());
+        assert_eq!(input.row(0), vec![1.0, 6.0]);
+        assert_eq!(input.row(1), vec![7.0, 4.0]);
+
+        assert!(Input::from_vector_with_mask(vec![vec![f32::NAN]], &[vec![false]]).is_err());
+        assert!(Input::from_vector_with_mask(vec![vec![1.0]], &[vec![false, true]]).is_err());
+        let defaults = Input::from_vector(vec![vec![1.0]]).unwrap();
+        assert!(input.fill_missing(&defaults).is_err());
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_from_array2() {
+        let array = ndarray::arr2(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let input = Input::from_array2(array.view()).unwrap();
+        assert_eq!(
+            input,
+            Input::from_vector(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]).unwrap()
+        );
+        assert!(Input::from_array2(ndarray::arr2(&[[f32::NAN]]).view()).is_err());
+    }
+
     #[test]
     fn test_input_to_view_single_row() {
         let vec = vec![vec![1.0, 2.0, 3.0]];
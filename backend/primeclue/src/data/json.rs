@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! JSON interchange format of [`DataSet`], for sharing data with other tools. A data set is an
+//! object with class-name map sorted by class id and points in order, e.g.:
+//!
+//! ```json
+//! {
+//!   "classes": [{ "id": 0, "name": "false" }, { "id": 1, "name": "true" }],
+//!   "feature_names": ["width", "count"],
+//!   "feature_types": ["Continuous", "Integer"],
+//!   "points": [
+//!     {
+//!       "input": [[1.5, 2.0], [3.0, 4.0]],
+//!       "class": 1,
+//!       "reward": 1.0,
+//!       "penalty": -1.0,
+//!       "weight": 1.0,
+//!       "target": null
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `input` is a list of rows of finite values, or `null` for missing ones (see
+//! [`Input::from_vector_with_mask`]), all points must have the same number of rows and
+//! columns. `class` is an id from `classes`. `target` is the value to be predicted by
+//! regression or `null` for classification. `feature_names` and `feature_types` of columns,
+//! see [`DataSet::new_named`] and [`DataSet::with_feature_types`], may be left out if there
+//! are none.
+
+use crate::data::data_set::{DataSet, FeatureType};
+use crate::data::outcome::Class;
+use crate::data::{Input, Outcome, Point};
+use crate::error::PrimeclueErr;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+#[derive(Serialize, Deserialize)]
+struct JsonDataSet {
+    classes: Vec<JsonClass>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    feature_names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    feature_types: Vec<FeatureType>,
+    points: Vec<JsonPoint>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonClass {
+    id: u16,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonPoint {
+    input: Vec<Vec<Option<f32>>>,
+    class: u16,
+    reward: f32,
+    penalty: f32,
+    weight: f32,
+    target: Option<f32>,
+}
+
+impl DataSet {
+    /// Writes data set to `writer` in JSON format described in [`crate::data::json`]
+    pub fn to_json<W: Write>(&self, writer: W) -> Result<(), PrimeclueErr> {
+        let mut classes = self
+            .class_map()
+            .iter()
+            .map(|(class, name)| JsonClass { id: class.value(), name: name.clone() })
+            .collect::<Vec<_>>();
+        classes.sort_by_key(|class| class.id);
+        let points = self
+            .iter()
+            .map(|point| {
+                let (input, outcome) = point.data();
+                JsonPoint {
+                    input: (0..input.input_shape().rows())
+                        .map(|row| {
+                            input.row(row).into_iter().map(|v| Some(v).filter(|v| !v.is_nan()))
+                        })
+                        .map(Iterator::collect)
+                        .collect(),
+                    class: outcome.class().value(),
+                    reward: outcome.reward(),
+                    penalty: outcome.penalty(),
+                    weight: outcome.weight(),
+                    target: outcome.target(),
+                }
+            })
+            .collect();
+        let feature_names = self.feature_names().to_vec();
+        let feature_types = self.feature_types().to_vec();
+        let json = JsonDataSet { classes, feature_names, feature_types, points };
+        serde_json::to_writer(writer, &json)
+            .map_err(|e| PrimeclueErr::from(format!("Unable to write data set as JSON: {}", e)))
+    }
+
+    /// Reads data set written by [`DataSet::to_json`] or another tool from `reader`. Fails if
+    /// JSON is invalid, class ids repeat, a point has unknown class or different shape of input.
+    pub fn from_json<R: Read>(reader: R) -> Result<DataSet, PrimeclueErr> {
+        let json: JsonDataSet = serde_json::from_reader(reader)
+            .map_err(|e| PrimeclueErr::from(format!("Unable to read data set JSON: {}", e)))?;
+        let mut classes = HashMap::with_capacity(json.classes.len());
+        for class in json.classes {
+            if classes.insert(Class::new(class.id), class.name).is_some() {
+                return PrimeclueErr::result(format!("Class id {} is repeated", class.id));
+            }
+        }
+        let mut data_set = DataSet::new_named(classes, json.feature_names)?
+            .with_feature_types(json.feature_types)?;
+        for (id, point) in json.points.into_iter().enumerate() {
+            let class = Class::new(point.class);
+            if !data_set.class_map().contains_key(&class) {
+                return PrimeclueErr::result(format!("Unknown class {} of point {}", class, id));
+            }
+            let missing = point
+                .input
+                .iter()
+                .map(|row| row.iter().map(Option::is_none).collect())
+                .collect::<Vec<_>>();
+            let values = point
+                .input
+                .into_iter()
+                .map(|row| row.into_iter().map(|value| value.unwrap_or(f32::NAN)).collect());
+            let input = Input::from_vector_with_mask(values.collect(), &missing)?;
+            let mut outcome =
+                Outcome::new(class, point.reward, point.penalty).with_target(point.target);
+            outcome.set_weight(point.weight);
+            data_set
+                .add_data_point(Point::new(input, outcome))
+                .map_err(|e| PrimeclueErr::from(format!("Invalid point {}: {}", id, e)))?;
+        }
+        Ok(data_set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::{DataSet, FeatureType};
+    use crate::data::outcome::Class;
+    use crate::data::{Input, Outcome, Point};
+    use std::collections::HashMap;
+
+    #[test]
+    fn json_round_trip() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(3), "three".to_string());
+        classes.insert(Class::new(7), "seven \"7\"".to_string());
+        let mut data = DataSet::new(classes);
+        let mut outcome = Outcome::new(Class::new(7), 2.5, -0.5);
+        outcome.set_weight(0.25);
+        data.add_data_point(Point::new(
+            Input::from_vector(vec![vec![1.0, -2.0], vec![0.1, 1e-7]]).unwrap(),
+            outcome,
+        ))
+        .unwrap();
+        data.add_data_point(Point::new(
+            Input::from_vector(vec![vec![3.0, 4.0], vec![5.0, 6.0]]).unwrap(),
+            Outcome::regression(42.5).with_class(Class::new(3)),
+        ))
+        .unwrap();
+
+        let mut json = vec![];
+        data.to_json(&mut json).unwrap();
+        let read = DataSet::from_json(json.as_slice()).unwrap();
+        assert_eq!(read.class_map(), data.class_map());
+        let points = read.iter().map(Point::data).collect::<Vec<_>>();
+        assert_eq!(points, data.iter().map(Point::data).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn json_missing_values() {
+        let mut data = DataSet::new(HashMap::from([(Class::new(0), "a".to_string())]));
+        let input = Input::from_vector_with_mask(vec![vec![1.0, 0.0]], &[vec![false, true]]);
+        data.add_labeled_point(input.unwrap(), Class::new(0)).unwrap();
+        let mut json = vec![];
+        data.to_json(&mut json).unwrap();
+        assert!(String::from_utf8(json.clone()).unwrap().contains("[[1.0,null]]"));
+        let read = DataSet::from_json(json.as_slice()).unwrap();
+        let (input, _) = read.iter().next().unwrap().data();
+        assert_eq!(input.get(0, 0), 1.0);
+        assert!(input.is_missing(0, 1));
+    }
+
+    #[test]
+    fn json_features() {
+        let classes = HashMap::from([(Class::new(0), "a".to_string())]);
+        let names = vec!["width".to_string(), "count".to_string()];
+        let types = vec![FeatureType::Continuous, FeatureType::Integer];
+        let mut data = DataSet::new_named(classes, names.clone())
+            .unwrap()
+            .with_feature_types(types.clone())
+            .unwrap();
+        let input = Input::from_vector(vec![vec![1.5, 2.0]]).unwrap();
+        data.add_labeled_point(input, Class::new(0)).unwrap();
+        let mut json = vec![];
+        data.to_json(&mut json).unwrap();
+        let read = DataSet::from_json(json.as_slice()).unwrap();
+        assert_eq!(read.feature_names(), names.as_slice());
+        assert_eq!(read.feature_types(), types.as_slice());
+
+        let invalid = r#"{"classes": [{"id": 0, "name": "a"}], "feature_types": ["Boolean"],
+            "points": [{"input": [[0.5]], "class": 0, "reward": 1.0, "penalty": -1.0,
+            "weight": 1.0, "target": null}]}"#;
+        assert!(DataSet::from_json(invalid.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn invalid_json() {
+        let unknown_class = r#"{"classes": [{"id": 0, "name": "a"}], "points": [
+            {"input": [[1.0]], "class": 1, "reward": 1.0, "penalty": -1.0, "weight": 1.0,
+             "target": null}]}"#;
+        assert!(DataSet::from_json(unknown_class.as_bytes()).is_err());
+        let repeated_class =
+            r#"{"classes": [{"id": 0, "name": "a"}, {"id": 0, "name": "b"}], "points": []}"#;
+        assert!(DataSet::from_json(repeated_class.as_bytes()).is_err());
+        assert!(DataSet::from_json("{".as_bytes()).is_err());
+    }
+}
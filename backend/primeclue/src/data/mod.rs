@@ -25,6 +25,11 @@ pub use self::outcome::Outcome;
 
 pub mod common;
 pub mod data_set;
+pub mod disk_data_view;
+pub mod encoding;
 pub mod importer;
 pub mod input;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod normalization;
 pub mod outcome;
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::data::{Data, Input, InputShape};
+use crate::error::PrimeclueErr;
+use crate::math::quantile;
+use crate::serialization::{Deserializable, Serializable, Serializator};
+
+/// Per-cell center and scale of training data, used to transform values to
+/// `(value - center) / scale`. Obtained from
+/// [`DataView::standardized`](crate::data::data_set::DataView::standardized), with mean and
+/// standard deviation, or from
+/// [`DataView::robust_scaled`](crate::data::data_set::DataView::robust_scaled), with median
+/// and interquartile range, so that the same transformation can later be applied to unseen
+/// [`Input`]s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalization {
+    input_shape: InputShape,
+    centers: Vec<f32>,
+    scales: Vec<f32>,
+}
+
+impl Normalization {
+    /// Mean and standard deviation of present values of every cell, missing ones are skipped
+    pub(crate) fn fit(cells: &Data<Vec<f32>>) -> Normalization {
+        let input_shape = *cells.input_shape();
+        let mut means = Vec::with_capacity(input_shape.rows() * input_shape.columns());
+        let mut std_devs = Vec::with_capacity(means.capacity());
+        for row in 0..input_shape.rows() {
+            for column in 0..input_shape.columns() {
+                let values = cells
+                    .get(row, column)
+                    .iter()
+                    .copied()
+                    .filter(|v| !v.is_nan())
+                    .collect::<Vec<_>>();
+                if values.len() < 2 {
+                    // no spread to scale by, a single value is only shifted
+                    means.push(values.first().copied().unwrap_or(0.0));
+                    std_devs.push(1.0);
+                    continue;
+                }
+                let mean = values.iter().sum::<f32>() / values.len() as f32;
+                let variance = values.iter().map(|v| (v - mean).powf(2.0)).sum::<f32>()
+                    / (values.len() - 1) as f32;
+                let std_dev = variance.sqrt();
+                means.push(mean);
+                // constant columns are only shifted
+                std_devs.push(if std_dev.is_normal() { std_dev } else { 1.0 });
+            }
+        }
+        Normalization { input_shape, centers: means, scales: std_devs }
+    }
+
+    /// Same as [`Normalization::fit`] with median and interquartile range of present values,
+    /// which outliers affect much less than mean and standard deviation
+    pub(crate) fn fit_robust(cells: &Data<Vec<f32>>) -> Normalization {
+        let input_shape = *cells.input_shape();
+        let mut centers = Vec::with_capacity(input_shape.rows() * input_shape.columns());
+        let mut scales = Vec::with_capacity(centers.capacity());
+        for row in 0..input_shape.rows() {
+            for column in 0..input_shape.columns() {
+                let mut values = cells
+                    .get(row, column)
+                    .iter()
+                    .copied()
+                    .filter(|v| !v.is_nan())
+                    .collect::<Vec<_>>();
+                let (center, scale) = match [0.25, 0.5, 0.75].map(|q| quantile(&mut values, q)) {
+                    [Some(lower), Some(center), Some(upper)] => {
+                        let range = upper - lower;
+                        // columns with most values the same are only shifted
+                        (center, if range.is_normal() { range } else { 1.0 })
+                    }
+                    _ => (0.0, 1.0),
+                };
+                centers.push(center);
+                scales.push(scale);
+            }
+        }
+        Normalization { input_shape, centers, scales }
+    }
+
+    #[must_use]
+    pub fn input_shape(&self) -> &InputShape {
+        &self.input_shape
+    }
+
+    /// Centers of cells row by row
+    pub(crate) fn centers(&self) -> &[f32] {
+        &self.centers
+    }
+
+    /// Scales of cells row by row
+    pub(crate) fn scales(&self) -> &[f32] {
+        &self.scales
+    }
+
+    #[must_use]
+    pub fn transform(&self, row: usize, column: usize, value: f32) -> f32 {
+        let index = row * self.input_shape.columns() + column;
+        (value - self.centers[index]) / self.scales[index]
+    }
+
+    /// Standardizes `input` with training data statistics
+    pub fn apply(&self, input: &mut Input) -> Result<(), PrimeclueErr> {
+        if input.input_shape() != &self.input_shape {
+            return PrimeclueErr::result(format!(
+                "Input shape {} doesn't match normalization shape {}",
+                input.input_shape(),
+                self.input_shape
+            ));
+        }
+        for row in 0..self.input_shape.rows() {
+            for column in 0..self.input_shape.columns() {
+                let value = self.transform(row, column, input.get(row, column));
+                input.set(row, column, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serializable for Normalization {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_items(&[&self.input_shape, &self.centers, &self.scales]);
+    }
+}
+
+impl Deserializable for Normalization {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        let input_shape = InputShape::deserialize(s)?;
+        let centers = Vec::deserialize(s)?;
+        let scales = Vec::deserialize(s)?;
+        Ok(Normalization { input_shape, centers, scales })
+    }
+}
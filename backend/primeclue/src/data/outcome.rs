@@ -19,10 +19,13 @@
 
 use crate::serialization::{Deserializable, Serializable, Serializator};
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
-#[derive(Clone, Copy, Default, PartialEq, Debug, Eq, Hash, serde::Serialize)]
+#[derive(
+    Clone, Copy, Default, PartialEq, Debug, Eq, PartialOrd, Ord, Hash, serde::Serialize,
+)]
 pub struct Class(u16);
 
 impl Class {
@@ -30,6 +33,11 @@ impl Class {
         Class(v)
     }
 
+    #[must_use]
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+
     pub fn from(b: bool) -> Self {
         if b {
             Class::new(1)
@@ -58,17 +66,77 @@ impl Deserializable for Class {
     }
 }
 
-#[derive(Clone, Copy, Default, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Outcome {
     class: Class,
     reward: f32,
     penalty: f32,
+    target: Option<f32>,
+    weight: f32,
+    class_weight: f32,
+}
+
+const TARGET_TOKEN: &str = "Target";
+const WEIGHT_TOKEN: &str = "Weight";
+const CLASS_WEIGHT_TOKEN: &str = "ClassWeight";
+
+impl Default for Outcome {
+    fn default() -> Self {
+        Outcome::new(Class::default(), 0.0, 0.0)
+    }
 }
 
 impl Outcome {
     #[must_use]
     pub fn new(class: Class, reward: f32, penalty: f32) -> Outcome {
-        Outcome { class, reward, penalty }
+        Outcome { class, reward, penalty, target: None, weight: 1.0, class_weight: 1.0 }
+    }
+
+    /// Creates [`Outcome`] for regression: a continuous value to be predicted.
+    /// All regression outcomes share the same, default [`Class`].
+    #[must_use]
+    pub fn regression(target: f32) -> Outcome {
+        Outcome {
+            class: Class::default(),
+            reward: 1.0,
+            penalty: -1.0,
+            target: Some(target),
+            weight: 1.0,
+            class_weight: 1.0,
+        }
+    }
+
+    #[must_use]
+    pub fn target(&self) -> Option<f32> {
+        self.target
+    }
+
+    /// Scale of this outcome's contribution to a score: its own weight multiplied by the
+    /// weight of its class, both 1.0 unless set otherwise
+    #[must_use]
+    pub fn weight(&self) -> f32 {
+        self.weight * self.class_weight
+    }
+
+    /// Sets weight of this outcome itself, e.g. read from a weight column, which is kept
+    /// when class weights are applied, see [`Outcome::set_class_weight`]
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight;
+    }
+
+    /// Sets weight of this outcome's class, multiplying its own weight
+    pub(crate) fn set_class_weight(&mut self, class_weight: f32) {
+        self.class_weight = class_weight;
+    }
+
+    /// Copy of this outcome with a different target value
+    pub(crate) fn with_target(&self, target: Option<f32>) -> Outcome {
+        Outcome { target, ..*self }
+    }
+
+    /// Copy of this outcome with a different class
+    pub(crate) fn with_class(&self, class: Class) -> Outcome {
+        Outcome { class, ..*self }
     }
 
     pub fn calculate_cost(&self, guess: bool, class: Class) -> f32 {
@@ -108,6 +176,18 @@ impl Outcome {
 impl Serializable for Outcome {
     fn serialize(&self, s: &mut Serializator) {
         s.add_items(&[&self.class, &self.reward, &self.penalty]);
+        if let Some(target) = self.target {
+            s.add_str(TARGET_TOKEN);
+            s.add(&target);
+        }
+        if (self.weight - 1.0).abs() > f32::EPSILON {
+            s.add_str(WEIGHT_TOKEN);
+            s.add(&self.weight);
+        }
+        if (self.class_weight - 1.0).abs() > f32::EPSILON {
+            s.add_str(CLASS_WEIGHT_TOKEN);
+            s.add(&self.class_weight);
+        }
     }
 }
 
@@ -116,7 +196,53 @@ impl Deserializable for Outcome {
         let class = Class::deserialize(s)?;
         let reward = f32::deserialize(s)?;
         let penalty = f32::deserialize(s)?;
-        Ok(Outcome { class, reward, penalty })
+        let target = if s.peek_token() == Some(TARGET_TOKEN) {
+            s.next_token()?;
+            Some(f32::deserialize(s)?)
+        } else {
+            None
+        };
+        let weight = if s.peek_token() == Some(WEIGHT_TOKEN) {
+            s.next_token()?;
+            f32::deserialize(s)?
+        } else {
+            1.0
+        };
+        let class_weight = if s.peek_token() == Some(CLASS_WEIGHT_TOKEN) {
+            s.next_token()?;
+            f32::deserialize(s)?
+        } else {
+            1.0
+        };
+        Ok(Outcome { class, reward, penalty, target, weight, class_weight })
+    }
+}
+
+/// Classes a point of multi-label data belongs to, any number of them at once. Trees are
+/// trained separately for every class on
+/// [`DataView::label_view`](crate::data::data_set::DataView::label_view). As classes are
+/// independent, training each with [`Objective::Hamming`](crate::exec::score::Objective)
+/// minimizes its part of Hamming loss and with `Objective::F1` maximizes its part of
+/// macro F1.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiLabelOutcome {
+    classes: BTreeSet<Class>,
+}
+
+impl MultiLabelOutcome {
+    #[must_use]
+    pub fn new(classes: impl IntoIterator<Item = Class>) -> Self {
+        MultiLabelOutcome { classes: classes.into_iter().collect() }
+    }
+
+    #[must_use]
+    pub fn classes(&self) -> &BTreeSet<Class> {
+        &self.classes
+    }
+
+    #[must_use]
+    pub fn contains(&self, class: Class) -> bool {
+        self.classes.contains(&class)
     }
 }
 
@@ -134,6 +260,26 @@ pub fn sort_guesses(guesses: Vec<f32>, outcomes: &[Outcome]) -> Vec<(f32, Outcom
 mod test {
     use crate::data::outcome::Class;
     use crate::data::Outcome;
+    use crate::serialization::serializator::test::test_serialization;
+
+    #[test]
+    fn serialize_outcome() {
+        test_serialization(Outcome::new(Class::new(2), 1.5, -0.5));
+        test_serialization(Outcome::regression(-42.5));
+        test_serialization(vec![
+            Outcome::regression(1.0),
+            Outcome::new(Class::new(1), 1.0, -1.0),
+            Outcome::regression(2.0),
+        ]);
+        let mut weighted = Outcome::regression(3.0);
+        weighted.set_weight(2.5);
+        test_serialization(weighted);
+        let mut weighted = Outcome::new(Class::new(1), 1.0, -1.0);
+        weighted.set_weight(0.5);
+        test_serialization(vec![weighted, Outcome::new(Class::new(0), 1.0, -1.0)]);
+        weighted.set_class_weight(3.0);
+        test_serialization(weighted);
+    }
 
     #[test]
     fn set_reward_penalty() {
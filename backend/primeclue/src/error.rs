@@ -17,18 +17,45 @@
    along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::data::InputShape;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
 use std::error::Error;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
-use serde::Serialize;
-
-#[derive(Debug, Clone, Serialize)]
-pub struct PrimeclueErr {
-    err: String,
+/// Error of any Primeclue operation. Failures that callers may want to handle differently
+/// have their own variants, all others are described by [`PrimeclueErr::Message`].
+#[derive(Debug)]
+pub enum PrimeclueErr {
+    /// Data with no points, e.g. the verification set
+    EmptyDataSet(&'static str),
+    /// Training and verification data with different number of classes
+    ClassCountMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// Input or data of different shape than expected, e.g. by a classifier
+    ShapeMismatch {
+        expected: InputShape,
+        actual: InputShape,
+    },
+    /// Operation that didn't finish within its time budget, e.g.
+    /// [`Classifier::predict_within`](crate::exec::classifier::Classifier::predict_within)
+    Timeout(Duration),
+    Io(std::io::Error),
+    Message(String),
 }
 
-impl Error for PrimeclueErr {}
+impl Error for PrimeclueErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PrimeclueErr::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 impl PrimeclueErr {
     pub fn result<T>(err: String) -> Result<T, PrimeclueErr> {
@@ -38,20 +65,94 @@ impl PrimeclueErr {
 
 impl From<String> for PrimeclueErr {
     fn from(err: String) -> Self {
-        PrimeclueErr { err }
+        PrimeclueErr::Message(err)
     }
 }
 
-// TODO test
 impl From<std::io::Error> for PrimeclueErr {
     fn from(err: std::io::Error) -> Self {
-        PrimeclueErr::from(err.to_string())
+        PrimeclueErr::Io(err)
+    }
+}
+
+// io::Error is not Clone, so only its kind and message are kept
+impl Clone for PrimeclueErr {
+    fn clone(&self) -> Self {
+        match self {
+            PrimeclueErr::EmptyDataSet(name) => PrimeclueErr::EmptyDataSet(name),
+            PrimeclueErr::ClassCountMismatch { expected, actual } => {
+                PrimeclueErr::ClassCountMismatch { expected: *expected, actual: *actual }
+            }
+            PrimeclueErr::ShapeMismatch { expected, actual } => {
+                PrimeclueErr::ShapeMismatch { expected: *expected, actual: *actual }
+            }
+            PrimeclueErr::Timeout(budget) => PrimeclueErr::Timeout(*budget),
+            PrimeclueErr::Io(err) => {
+                PrimeclueErr::Io(std::io::Error::new(err.kind(), err.to_string()))
+            }
+            PrimeclueErr::Message(err) => PrimeclueErr::Message(err.clone()),
+        }
     }
 }
 
-// TODO test
 impl Display for PrimeclueErr {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.err)
+        match self {
+            PrimeclueErr::EmptyDataSet(name) => write!(f, "{} is empty", name),
+            PrimeclueErr::ClassCountMismatch { expected, actual } => write!(
+                f,
+                "Training and verification data differ in class count: {} vs {}",
+                expected, actual
+            ),
+            PrimeclueErr::ShapeMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Input shape ({}) doesn't match expected input shape ({})",
+                    actual, expected
+                )
+            }
+            PrimeclueErr::Timeout(budget) => write!(f, "Time budget of {:?} exceeded", budget),
+            PrimeclueErr::Io(err) => write!(f, "{}", err),
+            PrimeclueErr::Message(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Serialized as a structure with a single `err` field holding the message
+impl Serialize for PrimeclueErr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("PrimeclueErr", 1)?;
+        s.serialize_field("err", &self.to_string())?;
+        s.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::InputShape;
+    use crate::error::PrimeclueErr;
+    use std::error::Error;
+    use std::io;
+    use std::time::Duration;
+
+    #[test]
+    fn test_display() {
+        let err = PrimeclueErr::from("Some error".to_string());
+        assert_eq!(err.to_string(), "Some error");
+        let err = PrimeclueErr::ShapeMismatch {
+            expected: InputShape::new(1, 2),
+            actual: InputShape::new(1, 3),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Input shape (rows: 1, columns: 3) doesn't match \
+             expected input shape (rows: 1, columns: 2)"
+        );
+        let err: Box<dyn Error> =
+            Box::new(PrimeclueErr::from(io::Error::new(io::ErrorKind::NotFound, "missing")));
+        assert_eq!(err.to_string(), "missing");
+        assert!(err.source().is_some());
+        let err = PrimeclueErr::Timeout(Duration::from_millis(5));
+        assert_eq!(err.to_string(), "Time budget of 5ms exceeded");
     }
 }
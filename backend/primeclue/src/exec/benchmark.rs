@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::data::data_set::DataView;
+use crate::error::PrimeclueErr;
+use crate::exec::score::Objective;
+use crate::exec::training_group::{StopCondition, TrainingGroup};
+
+/// Size of a training group trained by [`run`], see [`TrainingGroup::new`]
+pub const GROUP_SIZE: usize = 100;
+
+/// Results of [`run`]: accuracy on unseen data and number of generations trained,
+/// one per attempt in order they were run
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkReport {
+    accuracies: Vec<f32>,
+    generations: Vec<u32>,
+}
+
+impl BenchmarkReport {
+    #[must_use]
+    pub fn accuracies(&self) -> &[f32] {
+        &self.accuracies
+    }
+
+    #[must_use]
+    pub fn generations(&self) -> &[u32] {
+        &self.generations
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> f32 {
+        self.accuracies.iter().sum::<f32>() / self.accuracies.len() as f32
+    }
+
+    /// Sample standard deviation of accuracy across attempts, 0 for a single attempt
+    #[must_use]
+    pub fn std_dev(&self) -> f32 {
+        if self.accuracies.len() < 2 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let squares = self.accuracies.iter().map(|a| (a - mean).powf(2.0)).sum::<f32>();
+        (squares / (self.accuracies.len() - 1) as f32).sqrt()
+    }
+
+    #[must_use]
+    pub fn min(&self) -> f32 {
+        self.accuracies.iter().copied().fold(f32::MAX, f32::min)
+    }
+
+    #[must_use]
+    pub fn max(&self) -> f32 {
+        self.accuracies.iter().copied().fold(f32::MIN, f32::max)
+    }
+
+    /// Average number of generations trained until any of budget conditions was met
+    #[must_use]
+    pub fn average_generations(&self) -> f32 {
+        self.generations.iter().map(|g| *g as f32).sum::<f32>() / self.generations.len() as f32
+    }
+}
+
+/// Makes `attempts` training attempts, each on training, verification and test data given
+/// by a new call of `data_factory`. Every attempt trains a [`TrainingGroup`] of
+/// [`GROUP_SIZE`] for `objective` until any of `budget` conditions is met (see
+/// [`TrainingGroup::next_generation_until`]) and scores its classifier on test data,
+/// which is never seen during training. Fails if `attempts` or `budget` is empty or
+/// any attempt does not give a classifier able to score test data.
+pub fn run(
+    mut data_factory: impl FnMut() -> (DataView, DataView, DataView),
+    objective: Objective,
+    attempts: usize,
+    budget: &[StopCondition],
+) -> Result<BenchmarkReport, PrimeclueErr> {
+    if attempts == 0 {
+        return PrimeclueErr::result("No benchmark attempts".to_string());
+    }
+    if budget.is_empty() {
+        return PrimeclueErr::result("Empty benchmark budget".to_string());
+    }
+    let mut report = BenchmarkReport {
+        accuracies: Vec::with_capacity(attempts),
+        generations: Vec::with_capacity(attempts),
+    };
+    for attempt in 1..=attempts {
+        let (training_data, verification_data, test_data) = data_factory();
+        let mut training = TrainingGroup::new(
+            training_data,
+            verification_data,
+            objective.clone(),
+            GROUP_SIZE,
+            &[],
+        )?;
+        training.next_generation_until(budget);
+        let score = training.classifier().and_then(|c| c.score(&test_data)).map_err(|e| {
+            PrimeclueErr::from(format!("Unable to score attempt {}: {}", attempt, e))
+        })?;
+        report.accuracies.push(score.accuracy);
+        report.generations.push(training.generation());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::test::create_simple_data;
+    use crate::exec::benchmark::run;
+    use crate::exec::score::Objective::Accuracy;
+    use crate::exec::training_group::StopCondition::{FixedGenerations, NoImprovement};
+
+    #[test]
+    fn benchmark() {
+        let budget = [FixedGenerations(5), NoImprovement { patience: 2, min_delta: 0.0 }];
+        let factory = || create_simple_data(150).into_3_views_split();
+        let mut succeeded = false;
+        for _ in 0..10 {
+            // an attempt fails if a short training gives no classifier
+            if let Ok(report) = run(factory, Accuracy, 3, &budget) {
+                assert_eq!(report.accuracies().len(), 3);
+                assert!(report.generations().iter().all(|g| (1..=5).contains(g)));
+                assert!(report.min() <= report.mean() && report.mean() <= report.max());
+                assert!((0.0..=1.0).contains(&report.min()));
+                assert!((0.0..=1.0).contains(&report.max()));
+                assert!(report.std_dev() >= 0.0);
+                assert!((1.0..=5.0).contains(&report.average_generations()));
+                succeeded = true;
+                break;
+            }
+        }
+        assert!(succeeded);
+
+        assert!(run(factory, Accuracy, 0, &budget).is_err());
+        assert!(run(factory, Accuracy, 1, &[]).is_err());
+    }
+}
@@ -17,36 +17,326 @@
    along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::contrand::GET_RNG;
+use crate::contrand::{seed_thread_rng, GET_RNG};
 use crate::data::data_set::DataView;
 use crate::data::outcome::Class;
 use crate::data::InputShape;
-use crate::exec::functions::TWO_ARG_FUNCTIONS;
+use crate::error::PrimeclueErr;
+use crate::exec::functions::{
+    allowed_two_arg, register_terminals, registered_terminals, SafeDivMode,
+};
+use crate::exec::functions::{NodeKind, Terminal};
+use crate::exec::node::DEFAULT_WEIGHT_RANGE;
+use crate::exec::pareto::{fronts, nsga2_order};
 use crate::exec::score::{Objective, Score};
 use crate::exec::scored_tree::ScoredTree;
 use crate::exec::tree::Tree;
+use crate::serialization::{Deserializable, Serializable, Serializator};
 use rand::prelude::SliceRandom;
 use rand::seq::IteratorRandom;
-use rand::Rng;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
 use rayon::iter::IntoParallelRefMutIterator;
 use rayon::iter::ParallelIterator;
-use std::cmp::Ordering::Equal;
-use std::collections::HashMap;
+use std::cmp::Ordering::{Equal, Greater};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Error, Formatter};
 use std::mem::replace;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
 
-#[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Debug)]
 struct GroupId(u64);
 
+/// Parameters of genetic operators used to evolve trees.
+/// * `mutation_rate` - probability that a bred tree has its structure mutated, otherwise
+///   only its weights are changed. Mutated trees get their weights changed as well half
+///   of the time
+/// * `mutation_schedule` - how the mutation rate changes over generations
+/// * `crossover_rate` - probability that a new group starts from two existing trees combined
+///   with `crossover` instead of a random tree
+/// * `selection` - how parents of bred and joined trees are chosen
+/// * `max_nodes` - upper limit of nodes in a tree. New random trees are made shallower and
+///   joined trees exceeding it are rejected. Mutations don't change tree size
+/// * `diversity` - how similar trees are treated when selecting ones to keep
+/// * `elitism` - number of groups with the best verification scores that are always kept
+///   unchanged for the next generation, regardless of `diversity` and population memory
+///   limit. 0 guarantees nothing, groups compete for selection only by their scores
+/// * `constant_range` - range weights of constants are drawn from in new random trees and
+///   when a constant is mutated. Should match the scale of input values, e.g. (-300, 300) for
+///   features spanning 0 to 300, so constants are not too small to matter
+/// * `terminals` - user-defined leaves of trees besides data values and constants, e.g.
+///   engineered features, see [`Terminal`] for how they are saved and loaded
+/// * `safe_div` - what division by zero gives in trees, see [`SafeDivMode`]
+/// * `replacement` - how much of the population is replaced in a generation, see
+///   [`ReplacementMode`]
+/// * `init_method` - how random trees of new groups are built, see [`InitMethod`]
+/// * `crossover` - how two existing trees are combined, see [`CrossoverMethod`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GeneticConfig {
+    pub mutation_rate: f64,
+    pub mutation_schedule: MutationSchedule,
+    pub crossover_rate: f64,
+    pub selection: SelectionMethod,
+    pub max_nodes: Option<usize>,
+    pub diversity: DiversityMode,
+    pub elitism: usize,
+    pub constant_range: (f32, f32),
+    pub terminals: &'static [Terminal],
+    pub safe_div: SafeDivMode,
+    pub replacement: ReplacementMode,
+    pub init_method: InitMethod,
+    pub crossover: CrossoverMethod,
+}
+
+/// How a random tree starting a new group is built, for the starting population as well as
+/// for groups added in every generation:
+/// * `Full` - every branch goes down to the maximum depth
+/// * `Grow` - every node below the root is a leaf with probability [`GROW_LEAF_PROB`], so
+///   branches end at different depths up to the maximum one
+/// * `RampedHalfAndHalf` - the maximum depth of every tree is drawn from 2 up to the maximum
+///   depth and half of the trees are built with `Full`, the other half with `Grow`, giving
+///   trees of more varied shapes and sizes than either of them
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum InitMethod {
+    #[default]
+    Full,
+    Grow,
+    RampedHalfAndHalf,
+}
+
+/// Probability that a node below the root is a leaf in trees built with [`InitMethod::Grow`]
+pub const GROW_LEAF_PROB: f64 = 0.3;
+
+/// How two parent trees chosen with `selection` of [`GeneticConfig`] make the tree starting
+/// a new group:
+/// * `Join` - both parents become arguments of a random two argument function, so the tree
+///   has all nodes of both of them and is one level deeper than the deeper one
+/// * `SubtreeSwap` - a random node of the first parent is replaced by a random node of the
+///   second one, together with nodes below them
+/// * `Uniform` - both parents are walked together from their roots and where both have a
+///   node with the same number of arguments, one of them is taken with equal probability.
+///   Where the number differs, the whole branch of either of them is taken. The tree is
+///   never deeper than the deeper parent
+///
+/// Trees made with `SubtreeSwap` and `Uniform` deeper than [`MAX_CROSSOVER_DEPTH`] are
+/// rejected, as trees of any kind with more than `max_nodes` nodes, and a random tree starts
+/// the group instead. All kinds are deterministic for training with a seed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum CrossoverMethod {
+    #[default]
+    Join,
+    SubtreeSwap,
+    Uniform,
+}
+
+/// Maximum depth of trees made with [`CrossoverMethod::SubtreeSwap`] and
+/// [`CrossoverMethod::Uniform`], see [`Tree::depth`]
+pub const MAX_CROSSOVER_DEPTH: usize = 17;
+
+/// How many groups of trees take part in a generation:
+/// * `Generational` - every group breeds new trees and as many new groups are added as there
+///   are kept, so the whole population can be replaced in a generation
+/// * `SteadyState` - only `count` groups picked at random breed new trees and only `count`
+///   new groups are added, so at most `count` groups are replaced. A generation takes less
+///   time and disrupts the population less, but [`Stats::generation`] still increments by
+///   one with every generation, so more of them are needed for the same number of
+///   evaluated trees, see [`Stats::evaluations_per_second`]
+///
+/// Both are deterministic for training with a seed.
+///
+/// [`Stats::generation`]: crate::exec::training_group::Stats::generation
+/// [`Stats::evaluations_per_second`]: crate::exec::training_group::Stats::evaluations_per_second
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ReplacementMode {
+    #[default]
+    Generational,
+    SteadyState {
+        count: usize,
+    },
+}
+
+/// How trees and groups to keep after a generation are selected:
+/// * `None` - the best scored ones are kept
+/// * `Crowding` - ones whose structure differs from all better ones by more than weights
+///   are kept first, then the best of the rest. Keeps population from collapsing to copies
+///   of a single tree at the cost of keeping weaker trees, see [`Stats::diversity`]
+///
+/// [`Stats::diversity`]: crate::exec::training_group::Stats::diversity
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DiversityMode {
+    #[default]
+    None,
+    Crowding,
+}
+
+/// How a parent tree is chosen from scored trees:
+/// * `Tournament` - the best of `size` randomly picked trees. 1 means parents are picked at
+///   random regardless of score, larger sizes raise selection pressure
+/// * `Roulette` - a tree is picked with probability proportional to how much better its
+///   score is than the worst score, so the worst tree is never picked unless all are equal
+/// * `Rank` - trees are ordered from the worst, n-th of them is picked with probability
+///   proportional to n. Pressure doesn't depend on how far apart scores are
+///
+/// Trees of equal score, e.g. all trees of an early generation or of degenerate data, are
+/// compared by node count, fewer is better, and then by hash of their structure. Trees of
+/// a group are ordered that way, from the best, before their children are bred, so picking
+/// them at random, as with `Tournament { size: 1 }` or `Roulette` when all scores are equal,
+/// doesn't depend on the order they were scored in.
+///
+/// Parents of joined trees are taken from all groups. `Tournament` picks every one of them
+/// from a random group first, so that each group is equally likely to be a parent however
+/// many trees it has, while `Roulette` and `Rank` choose among all trees at once. The
+/// default, `Tournament { size: 1 }`, thus joins trees of two uniformly random groups.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SelectionMethod {
+    Tournament { size: usize },
+    Roulette,
+    Rank,
+}
+
+impl Default for SelectionMethod {
+    fn default() -> Self {
+        SelectionMethod::Tournament { size: 1 }
+    }
+}
+
+/// Mutation rate used by every generation of a class training:
+/// * `Constant` - `mutation_rate` of [`GeneticConfig`] is always used
+/// * `LinearDecay` - the rate goes linearly from `from` in the first generation to `to`
+///   after `generations` generations and stays there, e.g. to explore early and refine late
+/// * `AdaptiveOnStagnation` - `mutation_rate` of [`GeneticConfig`] is used until the best
+///   training score of the class hasn't improved for `window` generations, then `raised`
+///   is used until it improves again
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum MutationSchedule {
+    #[default]
+    Constant,
+    LinearDecay {
+        from: f64,
+        to: f64,
+        generations: usize,
+    },
+    AdaptiveOnStagnation {
+        window: usize,
+        raised: f64,
+    },
+}
+
+impl Default for GeneticConfig {
+    fn default() -> Self {
+        GeneticConfig {
+            mutation_rate: 2.0 / 3.0,
+            mutation_schedule: MutationSchedule::Constant,
+            crossover_rate: 0.5,
+            selection: SelectionMethod::default(),
+            max_nodes: None,
+            diversity: DiversityMode::None,
+            elitism: 0,
+            constant_range: DEFAULT_WEIGHT_RANGE,
+            terminals: &[],
+            safe_div: SafeDivMode::default(),
+            replacement: ReplacementMode::default(),
+            init_method: InitMethod::default(),
+            crossover: CrossoverMethod::default(),
+        }
+    }
+}
+
+impl GeneticConfig {
+    pub(crate) fn validate(&self) -> Result<(), PrimeclueErr> {
+        let rates = match self.mutation_schedule {
+            MutationSchedule::Constant => vec![self.mutation_rate],
+            MutationSchedule::LinearDecay { from, to, .. } => vec![self.mutation_rate, from, to],
+            MutationSchedule::AdaptiveOnStagnation { raised, .. } => {
+                vec![self.mutation_rate, raised]
+            }
+        };
+        if let Some(rate) = rates.iter().find(|rate| !(0.0..=1.0).contains(*rate)) {
+            PrimeclueErr::result(format!("Mutation rate must be between 0 and 1, got {}", rate))
+        } else if matches!(
+            self.mutation_schedule,
+            MutationSchedule::LinearDecay { generations: 0, .. }
+        ) {
+            PrimeclueErr::result("Mutation decay must last at least 1 generation".to_string())
+        } else if matches!(
+            self.mutation_schedule,
+            MutationSchedule::AdaptiveOnStagnation { window: 0, .. }
+        ) {
+            PrimeclueErr::result("Stagnation window must be at least 1 generation".to_string())
+        } else if !(0.0..=1.0).contains(&self.crossover_rate) {
+            PrimeclueErr::result(format!(
+                "Crossover rate must be between 0 and 1, got {}",
+                self.crossover_rate
+            ))
+        } else if self.selection == (SelectionMethod::Tournament { size: 0 }) {
+            PrimeclueErr::result("Tournament size must be at least 1".to_string())
+        } else if self.replacement == (ReplacementMode::SteadyState { count: 0 }) {
+            PrimeclueErr::result("Steady state must replace at least 1 group".to_string())
+        } else if self.max_nodes == Some(0) {
+            PrimeclueErr::result("Max nodes must be at least 1".to_string())
+        } else if let Some((i, t)) = self.terminals.iter().enumerate().find(|(i, t)| {
+            t.name().is_empty()
+                || t.name().contains(char::is_whitespace)
+                || self.terminals[..*i].iter().any(|o| o.name() == t.name())
+        }) {
+            PrimeclueErr::result(format!(
+                "Terminal names must be unique, non empty, without whitespace, got {:?} at {}",
+                t.name(),
+                i
+            ))
+        } else if !self.constant_range.0.is_finite()
+            || !self.constant_range.1.is_finite()
+            || self.constant_range.0 >= self.constant_range.1
+        {
+            PrimeclueErr::result(format!(
+                "Constant range must be finite and not empty, got {:?}",
+                self.constant_range
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Mutation rate given by `mutation_schedule` for a generation after `generation`
+    /// generations, of which the last `stagnant` ones didn't improve the best training score
+    pub(crate) fn mutation_rate_at(&self, generation: usize, stagnant: usize) -> f64 {
+        match self.mutation_schedule {
+            MutationSchedule::Constant => self.mutation_rate,
+            MutationSchedule::LinearDecay { from, to, generations } => {
+                let progress = (generation as f64 / generations as f64).min(1.0);
+                from + (to - from) * progress
+            }
+            MutationSchedule::AdaptiveOnStagnation { window, raised } => {
+                if stagnant >= window {
+                    raised
+                } else {
+                    self.mutation_rate
+                }
+            }
+        }
+    }
+
+    fn fits(&self, tree: &Tree) -> bool {
+        self.max_nodes.is_none_or(|max| tree.node_count() <= max)
+    }
+}
+
 pub struct ClassTraining {
     next_id: GroupId,
     objective: Objective,
     size: usize,
     node_limit: usize,
     forbidden_cols: Vec<usize>,
+    forbidden_nodes: Vec<NodeKind>,
     best_tree: Option<ScoredTree>,
     class: Class,
-    groups: HashMap<GroupId, ClassGroup>,
+    groups: BTreeMap<GroupId, ClassGroup>,
+    rng: Option<XorShiftRng>,
+    config: GeneticConfig,
+    generation: usize,
+    stagnant: usize,
+    evaluations: usize,
 }
 
 impl Debug for ClassTraining {
@@ -63,19 +353,58 @@ impl ClassTraining {
         objective: Objective,
         class: Class,
     ) -> Self {
-        let groups = HashMap::new();
+        let groups = BTreeMap::new();
         ClassTraining {
             next_id: GroupId(1),
             size,
             forbidden_cols,
+            forbidden_nodes: NodeKind::OPT_IN.to_vec(),
             groups,
             node_limit: 5_000_000,
             best_tree: None,
             objective,
             class,
+            rng: None,
+            config: GeneticConfig::default(),
+            generation: 0,
+            stagnant: 0,
+            evaluations: 0,
         }
     }
 
+    /// Same as [`ClassTraining::new`] but all random choices are drawn from `seed`,
+    /// so that training on the same data always gives the same result.
+    #[must_use]
+    pub fn with_seed(
+        size: usize,
+        forbidden_cols: Vec<usize>,
+        objective: Objective,
+        class: Class,
+        seed: u64,
+    ) -> Self {
+        let mut training = ClassTraining::new(size, forbidden_cols, objective, class);
+        training.rng = Some(XorShiftRng::seed_from_u64(seed));
+        training
+    }
+
+    /// Sets parameters of genetic operators, registering its terminals, see
+    /// [`register_terminals`]
+    pub fn set_genetic_config(&mut self, config: GeneticConfig) {
+        register_terminals(config.terminals);
+        self.config = config;
+    }
+
+    /// Function nodes of `kinds` won't be used by new or mutated trees
+    pub fn set_forbidden_node_kinds(&mut self, kinds: Vec<NodeKind>) {
+        self.forbidden_nodes = kinds;
+    }
+
+    /// Kinds forbidden by [`ClassTraining::set_forbidden_node_kinds`] together with
+    /// divisions other than the one of `safe_div` of [`GeneticConfig`]
+    fn generated_forbidden_nodes(&self) -> Vec<NodeKind> {
+        self.config.safe_div.forbidden(&self.forbidden_nodes)
+    }
+
     pub fn class(&self) -> &Class {
         &self.class
     }
@@ -84,39 +413,344 @@ impl ClassTraining {
         self.best_tree.as_ref().map(|t| t.score().value())
     }
 
+    /// Average training score of all scored trees kept in population
+    pub fn average_training_score(&self) -> Option<f32> {
+        let (sum, count) = self
+            .groups
+            .values()
+            .flat_map(|g| &g.scored)
+            .fold((0.0, 0), |(sum, count), tree| (sum + tree.score().value(), count + 1));
+        if count == 0 {
+            None
+        } else {
+            Some(sum / count as f32)
+        }
+    }
+
     #[must_use]
     pub fn best_tree(&self) -> Option<&ScoredTree> {
         self.best_tree.as_ref()
     }
 
+    /// Forgets scores of all trees, so that they're scored again in the next generation,
+    /// e.g. after training data changed. The best tree is scored again right away on
+    /// `training_data` and `selection_data` the same way as in selection, so it stays
+    /// available, unless it gives invalid output on the new data.
+    pub fn reset_scores(&mut self, training_data: &DataView, selection_data: &DataView) {
+        for group in self.groups.values_mut() {
+            let scored = std::mem::take(&mut group.scored);
+            group.fresh.extend(scored.into_iter().map(ScoredTree::into_tree));
+        }
+        self.best_tree = self.best_tree.take().and_then(|best| {
+            let tree = best.into_tree();
+            let training = tree.execute_for_score(training_data, self.class, &self.objective)?;
+            let mut best = ScoredTree::new(tree, training);
+            let selection = best.execute_for_score(selection_data)?;
+            let training = best.score();
+            let score = Score::new(
+                training.objective().clone(),
+                training.class(),
+                (selection.value() + training.value()) / 2.0,
+                training.threshold(),
+            );
+            best.set_score(score);
+            Some(best)
+        });
+    }
+
+    /// Number of nodes of the largest tree kept in population
+    #[must_use]
+    pub fn max_node_count(&self) -> usize {
+        self.groups
+            .values()
+            .flat_map(|g| &g.scored)
+            .map(ScoredTree::node_count)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Number of nodes of all trees, including not yet scored ones and the best tree
+    #[must_use]
+    pub fn total_node_count(&self) -> usize {
+        let best = self.best_tree.as_ref().map_or(0, ScoredTree::node_count);
+        self.groups.values().map(ClassGroup::nodes_count).sum::<usize>() + best
+    }
+
+    /// Sets maximum total number of nodes, counted like [`ClassTraining::total_node_count`].
+    /// When exceeded after a generation, groups with the largest trees are removed.
+    pub(crate) fn set_node_limit(&mut self, node_limit: usize) {
+        self.node_limit = node_limit;
+    }
+
+    /// Number of groups kept after every generation, each of them keeping up to as many trees
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Changes [`ClassTraining::size`] between generations. When growing, new random groups
+    /// are added, when shrinking, groups with the best training scores and their best trees
+    /// are kept.
+    pub(crate) fn resize(&mut self, size: usize, input_shape: &InputShape) {
+        self.size = size;
+        if self.groups.len() > size {
+            let mut ids = self
+                .groups
+                .values()
+                .map(|g| (g.id, ScoredTree::best_tree(&g.scored)))
+                .collect::<Vec<_>>();
+            ids.sort_by(|(_, t1), (_, t2)| t2.partial_cmp(t1).unwrap_or(Equal));
+            let keep = ids.into_iter().take(size).map(|(id, _)| id).collect::<HashSet<_>>();
+            self.groups.retain(|id, _| keep.contains(id));
+        }
+        let diversity = self.config.diversity;
+        self.groups.values_mut().for_each(|g| g.remove_weak_trees(size, diversity));
+        let _seeded = self.next_seed().map(seed_thread_rng);
+        self.fill_up(input_shape, size);
+    }
+
+    /// Number of groups of trees kept, including ones none of whose trees were scored yet
+    #[must_use]
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Number of distinct structures of trees counted by [`ClassTraining::population_size`],
+    /// trees differing by weights only have the same structure
+    #[must_use]
+    pub fn structure_count(&self) -> usize {
+        let trees = self.groups.values().flat_map(|g| &g.scored);
+        trees.map(|tree| tree.tree().structure_hash()).collect::<HashSet<_>>().len()
+    }
+
+    /// Returns number of trees kept for the next generation and sum of their nodes
+    #[must_use]
+    pub fn population_size(&self) -> (usize, usize) {
+        self.groups.values().fold((0, 0), |(trees, nodes), group| {
+            let group_nodes = group.scored.iter().map(|t| t.node_count()).sum::<usize>();
+            (trees + group.scored.len(), nodes + group_nodes)
+        })
+    }
+
     pub fn next_generation(&mut self, training_data: &DataView, verification_data: &DataView) {
-        self.fill_up(training_data.input_shape());
-        let objective = self.objective;
+        self.next_generation_deadline(training_data, verification_data, None);
+    }
+
+    /// Same as [`ClassTraining::next_generation`], but no more trees are bred nor scored
+    /// once `deadline` passes. Unscored trees are kept to be scored in the next generation,
+    /// groups having no scored trees at all are kept after all groups with scored ones if
+    /// there is room for them. Returns false if the generation was cut short.
+    pub(crate) fn next_generation_deadline(
+        &mut self,
+        training_data: &DataView,
+        verification_data: &DataView,
+        deadline: Option<Instant>,
+    ) -> bool {
+        let breeding: Option<HashSet<GroupId>> = {
+            let _seeded = self.next_seed().map(seed_thread_rng);
+            match self.config.replacement {
+                ReplacementMode::Generational => {
+                    self.fill_up(training_data.input_shape(), self.size * 2);
+                    None
+                }
+                ReplacementMode::SteadyState { count } => {
+                    let ids = self.groups.keys().copied().collect::<Vec<_>>();
+                    let picked = ids.choose_multiple(&mut GET_RNG(), count).copied().collect();
+                    self.fill_up(training_data.input_shape(), self.size + count);
+                    Some(picked)
+                }
+            }
+        };
+        let ids = self.groups.keys().copied().collect::<Vec<_>>();
+        let seeds = ids.into_iter().map(|id| (id, self.next_seed())).collect::<HashMap<_, _>>();
+        let objective = &self.objective;
         let class = self.class;
         let length = self.size;
         let forbidden_cols = &self.forbidden_cols;
-        self.groups.par_iter_mut().for_each(|(_, group)| {
-            group.breed(forbidden_cols, length);
-            group.execute_and_score(objective, training_data, class);
-            group.remove_weak_trees(length);
+        let forbidden_nodes = &self.generated_forbidden_nodes();
+        let config = &self.config;
+        let mutation_rate = config.mutation_rate_at(self.generation, self.stagnant);
+        let feature_types = training_data.feature_types();
+        let discrete = feature_types.iter().any(|t| t.is_discrete());
+        let best_score = self.training_score();
+        let complete = AtomicBool::new(true);
+        let evaluations = AtomicUsize::new(0);
+        self.groups.par_iter_mut().for_each(|(id, group)| {
+            let _seeded = seeds[id].map(seed_thread_rng);
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                complete.store(false, Ordering::Relaxed);
+                return;
+            }
+            if breeding.as_ref().is_none_or(|breeding| breeding.contains(id)) {
+                group.breed(forbidden_cols, forbidden_nodes, length, config, mutation_rate);
+            }
+            if discrete {
+                group.fresh.iter_mut().for_each(|t| t.discretize_comparisons(feature_types));
+            }
+            let (scored_all, executed) =
+                group.execute_and_score(objective, training_data, class, config, deadline);
+            if !scored_all {
+                complete.store(false, Ordering::Relaxed);
+            }
+            evaluations.fetch_add(executed, Ordering::Relaxed);
+            group.remove_weak_trees(length, config.diversity);
         });
         self.remove_empty_groups();
-        self.select_best(verification_data);
-        self.keep_node_limit();
-        self.groups.shrink_to_fit();
+        let elite = self.select_best(verification_data);
+        self.keep_node_limit(&elite);
+        self.generation += 1;
+        self.stagnant = if self.training_score() == best_score { self.stagnant + 1 } else { 0 };
+        self.evaluations = evaluations.into_inner();
+        complete.into_inner()
+    }
+
+    /// Number of trees executed on training data during the last generation
+    pub(crate) fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// Input shape of evolved trees, if there are any
+    #[must_use]
+    pub fn input_shape(&self) -> Option<&InputShape> {
+        let group = self.groups.values().next()?;
+        match group.scored.first() {
+            Some(tree) => Some(tree.input_shape()),
+            None => group.fresh.first().map(Tree::input_shape),
+        }
+    }
+
+    /// Adds full training state to `s`. Random number generator of seeded training can't
+    /// be stored, so it's reseeded with a seed drawn from it first and the seed is stored.
+    pub(crate) fn save_state(&mut self, s: &mut Serializator) {
+        let seed = self.next_seed();
+        self.rng = seed.map(XorShiftRng::seed_from_u64);
+        s.add_items(&[
+            &self.next_id,
+            &self.objective,
+            &self.size,
+            &self.node_limit,
+            &self.forbidden_cols,
+            &self.forbidden_nodes,
+            &self.best_tree,
+            &self.class,
+            &seed,
+            &self.config,
+            &self.generation,
+            &self.stagnant,
+        ]);
+        s.add(&self.groups.len());
+        self.groups.values().for_each(|group| s.add(group));
+    }
+
+    /// Restores training state added by [`ClassTraining::save_state`]
+    pub(crate) fn load_state(s: &mut Serializator) -> Result<Self, String> {
+        let next_id = GroupId::deserialize(s)?;
+        let objective = Objective::deserialize(s)?;
+        let size = usize::deserialize(s)?;
+        let node_limit = usize::deserialize(s)?;
+        let forbidden_cols = Vec::deserialize(s)?;
+        let forbidden_nodes = Vec::deserialize(s)?;
+        let best_tree = Option::deserialize(s)?;
+        let class = Class::deserialize(s)?;
+        let seed: Option<u64> = Option::deserialize(s)?;
+        let config = GeneticConfig::deserialize(s)?;
+        let generation = usize::deserialize(s)?;
+        let stagnant = usize::deserialize(s)?;
+        let len = usize::deserialize(s)?;
+        let mut groups = BTreeMap::new();
+        for _ in 0..len {
+            let group = ClassGroup::deserialize(s)?;
+            groups.insert(group.id, group);
+        }
+        Ok(ClassTraining {
+            next_id,
+            objective,
+            size,
+            node_limit,
+            forbidden_cols,
+            forbidden_nodes,
+            best_tree,
+            class,
+            groups,
+            rng: seed.map(XorShiftRng::seed_from_u64),
+            config,
+            generation,
+            stagnant,
+            evaluations: 0,
+        })
+    }
+
+    /// Best trees of up to `count` groups, best first
+    pub(crate) fn emigrants(&self, count: usize) -> Vec<Tree> {
+        let mut trees = self
+            .groups
+            .values()
+            .filter_map(|g| ScoredTree::best_tree(&g.scored))
+            .collect::<Vec<_>>();
+        trees.sort_by(|t1, t2| t2.partial_cmp(t1).unwrap_or(Equal));
+        trees.into_iter().take(count).map(|t| t.tree().clone()).collect()
+    }
+
+    /// Best trees of up to `count` groups by score on `data`, best first, leaving out trees of
+    /// the same structure as a better one, see [`ClassTraining::structure_count`]
+    pub(crate) fn top_trees(&self, data: &DataView, count: usize) -> Vec<ScoredTree> {
+        let mut seen = HashSet::new();
+        self.sorted_by_score(data)
+            .into_iter()
+            .filter_map(|(id, _)| ScoredTree::best_tree(&self.groups[&id].scored))
+            .filter(|tree| seen.insert(tree.tree().structure_hash()))
+            .take(count)
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a new group grown from every tree in `trees`, e.g. taken from another training
+    pub(crate) fn immigrate(&mut self, trees: Vec<Tree>) {
+        let _seeded = self.next_seed().map(seed_thread_rng);
+        for tree in trees {
+            let id = self.next_id;
+            self.next_id.0 += 1;
+            let group = ClassGroup::create_from_tree(
+                self.size,
+                id,
+                tree,
+                &self.forbidden_cols,
+                &self.generated_forbidden_nodes(),
+                &self.config,
+            );
+            self.groups.insert(id, group);
+        }
     }
 
+    fn next_seed(&mut self) -> Option<u64> {
+        self.rng.as_mut().map(RngCore::next_u64)
+    }
+
+    /// Removes groups none of whose trees got a score, keeping ones left unscored
     fn remove_empty_groups(&mut self) {
-        self.groups.retain(|_, p| !p.scored.is_empty());
+        self.groups.retain(|_, p| !p.scored.is_empty() || !p.fresh.is_empty());
     }
 
-    fn keep_node_limit(&mut self) {
-        let mut sizes =
-            self.groups.values().map(|p| (p.id, p.nodes_count())).collect::<Vec<_>>();
-        let sum = sizes.iter().map(|(_, s)| s).sum::<usize>();
+    /// Removes groups with the largest trees until the node limit is met, except `elite` ones.
+    /// Nodes of the best tree count towards the limit, but it's never removed.
+    fn keep_node_limit(&mut self, elite: &[GroupId]) {
+        let mut sizes = self
+            .groups
+            .values()
+            .filter(|p| !elite.contains(&p.id))
+            .map(|p| (p.id, p.nodes_count()))
+            .collect::<Vec<_>>();
+        let best = self.best_tree.as_ref().map_or(0, ScoredTree::node_count);
+        let elite_sum = best
+            + elite
+                .iter()
+                .filter_map(|id| self.groups.get(id))
+                .map(ClassGroup::nodes_count)
+                .sum::<usize>();
+        let sum = elite_sum + sizes.iter().map(|(_, s)| s).sum::<usize>();
         if sum > self.node_limit {
             sizes.sort_by(|(_, s1), (_, s2)| s1.cmp(s2));
-            let mut so_far = 0;
+            let mut so_far = elite_sum;
             for (id, size) in sizes {
                 if so_far + size > self.node_limit {
                     self.groups.remove(&id);
@@ -127,31 +761,91 @@ impl ClassTraining {
         }
     }
 
-    fn fill_up(&mut self, input_shape: &InputShape) {
-        while self.groups.len() < self.size * 2 {
+    fn fill_up(&mut self, input_shape: &InputShape, count: usize) {
+        while self.groups.len() < count {
             let id = self.next_id;
             self.next_id.0 += 1;
-            let group = generate_group(self, input_shape, id, &self.forbidden_cols, 3);
+            let group = generate_group(self, input_shape, id, 3);
             self.groups.insert(group.id, group);
         }
     }
 
-    fn select_best(&mut self, data: &DataView) {
+    /// Keeps groups with the best verification scores, returns ids of elite ones
+    fn select_best(&mut self, data: &DataView) -> Vec<GroupId> {
         let mut sorted_scores = self.sorted_by_score(data);
         self.assign_best_tree(&sorted_scores);
-        self.remove_bad_groups(&mut sorted_scores);
+        if let Objective::Pareto(_) = self.objective {
+            self.pareto_sort(&mut sorted_scores, data);
+        }
+        let elitism = self.config.elitism.min(sorted_scores.len());
+        let elite = sorted_scores[..elitism].iter().map(|(id, _)| *id).collect();
+        if self.config.diversity == DiversityMode::Crowding {
+            let groups = &self.groups;
+            let mut rest = sorted_scores.split_off(elitism);
+            crowd(&mut rest, |(id, _)| {
+                ScoredTree::best_tree(&groups[id].scored).unwrap().tree().structure_hash()
+            });
+            sorted_scores.extend(rest);
+        }
+        self.remove_bad_groups(&sorted_scores);
+        elite
+    }
+
+    /// Reorders groups the way NSGA-II selects them, by Pareto values of their best trees
+    fn pareto_sort(&self, sorted_scores: &mut Vec<(GroupId, Score)>, data: &DataView) {
+        let trees = sorted_scores
+            .iter()
+            .map(|(id, _)| ScoredTree::best_tree(&self.groups[id].scored).unwrap())
+            .collect::<Vec<_>>();
+        let order = nsga2_order(&self.pareto_values(&trees, data));
+        let mut scores = std::mem::take(sorted_scores).into_iter().map(Some).collect::<Vec<_>>();
+        *sorted_scores = order.into_iter().filter_map(|i| scores[i].take()).collect();
+    }
+
+    /// Values of `trees` on `data` compared by [`Objective::Pareto`], NaN if a tree gives
+    /// invalid output
+    fn pareto_values(&self, trees: &[&ScoredTree], data: &DataView) -> Vec<Vec<f32>> {
+        trees
+            .iter()
+            .map(|tree| {
+                tree.tree()
+                    .execute_for_pareto(data, self.class, &self.objective)
+                    .unwrap_or_else(|| vec![f32::NAN])
+            })
+            .collect()
     }
 
-    fn remove_bad_groups(&mut self, sorted_scores: &mut Vec<(GroupId, Score)>) {
+    /// Best trees of groups that are not dominated by best trees of other groups in values
+    /// compared by [`Objective::Pareto`] on `data`, by ascending node count
+    pub(crate) fn pareto_front(&self, data: &DataView) -> Vec<ScoredTree> {
+        let trees = self
+            .groups
+            .values()
+            .filter_map(|g| ScoredTree::best_tree(&g.scored))
+            .collect::<Vec<_>>();
+        let first = fronts(&self.pareto_values(&trees, data)).into_iter().next();
+        let mut front =
+            first.unwrap_or_default().into_iter().map(|i| trees[i].clone()).collect::<Vec<_>>();
+        front.sort_by_key(ScoredTree::node_count);
+        front
+    }
+
+    /// Keeps [`ClassTraining::size`] groups, the best of `sorted_scores` first, followed by
+    /// unscored ones
+    fn remove_bad_groups(&mut self, sorted_scores: &[(GroupId, Score)]) {
         if self.groups.len() <= self.size {
             return;
         }
-        let mut new_group_map = HashMap::with_capacity(self.size);
-        for _ in 0..self.size {
-            if !sorted_scores.is_empty() {
-                let (first, _) = sorted_scores.remove(0);
-                new_group_map.insert(first, self.groups.remove(&first).unwrap());
-            }
+        let unscored = self.groups.values().filter(|g| g.scored.is_empty()).map(|g| g.id);
+        let kept = sorted_scores
+            .iter()
+            .map(|(id, _)| *id)
+            .chain(unscored.collect::<Vec<_>>())
+            .take(self.size)
+            .collect::<Vec<_>>();
+        let mut new_group_map = BTreeMap::new();
+        for id in kept {
+            new_group_map.insert(id, self.groups.remove(&id).unwrap());
         }
         self.groups = new_group_map;
     }
@@ -164,7 +858,7 @@ impl ClassTraining {
                     .clone();
             let score_value = (sorted_scores[0].1.value() + best_now.score().value()) / 2.0;
             let score = Score::new(
-                best_now.score().objective(),
+                best_now.score().objective().clone(),
                 best_now.score().class(),
                 score_value,
                 best_now.score().threshold(),
@@ -205,20 +899,38 @@ impl Debug for ClassGroup {
 impl ClassGroup {
     fn create_joined(
         group_size: usize,
-        existing: &HashMap<GroupId, ClassGroup>,
+        existing: &BTreeMap<GroupId, ClassGroup>,
         id: GroupId,
         forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        config: &GeneticConfig,
     ) -> Option<Self> {
         let mut rng = GET_RNG();
-        let tree1 = existing.values().choose(&mut rng)?.scored.iter().choose(&mut rng)?.tree();
-        let tree2 = existing.values().choose(&mut rng)?.scored.iter().choose(&mut rng)?.tree();
-        let tree = Tree::from_two(
-            TWO_ARG_FUNCTIONS.choose(&mut rng).unwrap(),
-            tree1.get_start_node().clone(),
-            tree2.get_start_node().clone(),
-            *tree1.input_shape(),
-        );
-        Some(ClassGroup::create_from_tree(group_size, id, tree, forbidden_cols))
+        let tree1 = config.selection.select_from_groups(existing)?.tree();
+        let tree2 = config.selection.select_from_groups(existing)?.tree();
+        let tree = match config.crossover {
+            CrossoverMethod::Join => Tree::from_two(
+                allowed_two_arg(forbidden_nodes).choose(&mut rng)?,
+                tree1.get_start_node().clone(),
+                tree2.get_start_node().clone(),
+                *tree1.input_shape(),
+            ),
+            CrossoverMethod::SubtreeSwap => tree1.subtree_swap(tree2),
+            CrossoverMethod::Uniform => tree1.uniform_crossover(tree2),
+        };
+        let too_deep =
+            config.crossover != CrossoverMethod::Join && tree.depth() > MAX_CROSSOVER_DEPTH;
+        if too_deep || !config.fits(&tree) {
+            return None;
+        }
+        Some(ClassGroup::create_from_tree(
+            group_size,
+            id,
+            tree,
+            forbidden_cols,
+            forbidden_nodes,
+            config,
+        ))
     }
 
     fn create_random(
@@ -227,12 +939,47 @@ impl ClassGroup {
         id: GroupId,
         max_depth: usize,
         forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        config: &GeneticConfig,
     ) -> Self {
         let mut rng = GET_RNG();
         let data_prob = rng.gen_range(0.01..0.99);
         let branch_prob = rng.gen_range(0.01..0.99);
-        let tree = Tree::new(input_shape, max_depth, forbidden_cols, branch_prob, data_prob);
-        ClassGroup::create_from_tree(group_size, id, tree, forbidden_cols)
+        let (mut depth, leaf_prob) = match config.init_method {
+            InitMethod::Full => (max_depth, 0.0),
+            InitMethod::Grow => (max_depth, GROW_LEAF_PROB),
+            InitMethod::RampedHalfAndHalf => {
+                let depth = rng.gen_range(max_depth.min(2)..=max_depth);
+                (depth, if rng.gen_bool(0.5) { 0.0 } else { GROW_LEAF_PROB })
+            }
+        };
+        let new_tree = |depth| {
+            Tree::generate(
+                input_shape,
+                depth,
+                forbidden_cols,
+                forbidden_nodes,
+                config.terminals,
+                branch_prob,
+                data_prob,
+                leaf_prob,
+            )
+        };
+        let mut tree = new_tree(depth);
+        // tree of depth 1 is a single node so it always fits
+        while !config.fits(&tree) && depth > 1 {
+            depth -= 1;
+            tree = new_tree(depth);
+        }
+        tree.generate_constant_weights(config.constant_range);
+        ClassGroup::create_from_tree(
+            group_size,
+            id,
+            tree,
+            forbidden_cols,
+            forbidden_nodes,
+            config,
+        )
     }
 
     fn create_from_tree(
@@ -240,53 +987,90 @@ impl ClassGroup {
         id: GroupId,
         tree: Tree,
         forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        config: &GeneticConfig,
     ) -> ClassGroup {
         let mut trees = Vec::with_capacity(group_size);
         trees.push(tree);
         while trees.len() < group_size {
             let mut t = trees[0].clone();
             t.change_weights();
-            t.mutate(forbidden_cols);
+            t.mutate(forbidden_cols, forbidden_nodes, config.terminals, config.constant_range);
             trees.push(t);
         }
         ClassGroup { id, fresh: trees, scored: Vec::new() }
     }
 
-    fn breed(&mut self, forbidden_cols: &[usize], count: usize) {
+    fn breed(
+        &mut self,
+        forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        count: usize,
+        config: &GeneticConfig,
+        mutation_rate: f64,
+    ) {
         let mut rng = GET_RNG();
+        let mut candidates = self.scored.iter().collect::<Vec<_>>();
+        candidates.sort_by(|t1, t2| t2.cmp_fitness(t1));
         while self.fresh.len() < count {
-            if let Some(tree) = self.scored.choose(&mut rng).map(|t| t.tree()) {
-                let mut child = tree.clone();
-                child.mutate(forbidden_cols);
-                self.fresh.push(child);
-
-                let mut child = tree.clone();
-                child.change_weights();
-                self.fresh.push(child);
-
+            let parent = config.selection.select(&candidates);
+            if let Some(tree) = parent.map(|t| t.tree()) {
                 let mut child = tree.clone();
-                child.mutate(forbidden_cols);
-                child.change_weights();
+                if rng.gen_bool(mutation_rate) {
+                    let terminals = config.terminals;
+                    child.mutate(
+                        forbidden_cols,
+                        forbidden_nodes,
+                        terminals,
+                        config.constant_range,
+                    );
+                    if rng.gen_bool(0.5) {
+                        child.change_weights();
+                    }
+                } else {
+                    child.change_weights();
+                }
                 self.fresh.push(child);
+            } else {
+                return;
             }
         }
     }
 
-    fn remove_weak_trees(&mut self, length: usize) {
+    fn remove_weak_trees(&mut self, length: usize, diversity: DiversityMode) {
         if self.scored.len() > length {
-            self.scored.sort_unstable_by(|t1, t2| t1.partial_cmp(t2).unwrap_or(Equal).reverse());
+            self.scored.sort_by(|t1, t2| t2.cmp_fitness(t1));
+            if diversity == DiversityMode::Crowding {
+                crowd(&mut self.scored, |tree| tree.tree().structure_hash());
+            }
             self.scored.truncate(length);
         }
     }
 
-    fn execute_and_score(&mut self, objective: Objective, data: &DataView, class: Class) {
+    /// Scores fresh trees until `deadline`, returns false if some were left unscored,
+    /// together with the number of executed trees
+    fn execute_and_score(
+        &mut self,
+        objective: &Objective,
+        data: &DataView,
+        class: Class,
+        config: &GeneticConfig,
+        deadline: Option<Instant>,
+    ) -> (bool, usize) {
         let len = self.fresh.len();
-        let trees = replace(&mut self.fresh, Vec::with_capacity(len));
-        for tree in trees {
+        let mut trees = replace(&mut self.fresh, Vec::with_capacity(len)).into_iter();
+        let mut executed = 0;
+        for tree in trees.by_ref().filter(|t| config.fits(t)) {
+            executed += 1;
             if let Some(score) = tree.execute_for_score(data, class, objective) {
                 self.scored.push(ScoredTree::new(tree, score))
             }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
         }
+        self.fresh.extend(trees);
+        (self.fresh.is_empty(), executed)
     }
 
     #[must_use]
@@ -300,16 +1084,335 @@ fn generate_group(
     training: &ClassTraining,
     input_shape: &InputShape,
     id: GroupId,
-    forbidden_cols: &[usize],
     max_depth: usize,
 ) -> ClassGroup {
     let mut rng = GET_RNG();
-    if !training.groups.is_empty() && rng.gen_bool(0.5) {
-        if let Some(group) =
-            ClassGroup::create_joined(training.size, &training.groups, id, forbidden_cols)
-        {
+    if !training.groups.is_empty() && rng.gen_bool(training.config.crossover_rate) {
+        if let Some(group) = ClassGroup::create_joined(
+            training.size,
+            &training.groups,
+            id,
+            &training.forbidden_cols,
+            &training.generated_forbidden_nodes(),
+            &training.config,
+        ) {
             return group;
         }
     }
-    ClassGroup::create_random(training.size, input_shape, id, max_depth, forbidden_cols)
+    ClassGroup::create_random(
+        training.size,
+        input_shape,
+        id,
+        max_depth,
+        &training.forbidden_cols,
+        &training.generated_forbidden_nodes(),
+        &training.config,
+    )
+}
+
+/// Moves items of the same `structure` as any earlier item to the end, keeping order otherwise
+fn crowd<T>(items: &mut Vec<T>, structure: impl Fn(&T) -> u64) {
+    let mut seen = HashSet::new();
+    let (distinct, similar): (Vec<_>, Vec<_>) =
+        items.drain(..).partition(|item| seen.insert(structure(item)));
+    *items = distinct;
+    items.extend(similar);
+}
+
+impl SelectionMethod {
+    /// Chooses a parent from scored trees of all `groups`, a tournament picks each tree from
+    /// a random group, see [`SelectionMethod`]
+    fn select_from_groups<'a>(
+        &self,
+        groups: &'a BTreeMap<GroupId, ClassGroup>,
+    ) -> Option<&'a ScoredTree> {
+        match self {
+            SelectionMethod::Tournament { size } => {
+                let mut rng = GET_RNG();
+                tournament(*size, || {
+                    groups.values().choose(&mut rng)?.scored.iter().choose(&mut rng)
+                })
+            }
+            _ => {
+                let candidates =
+                    groups.values().flat_map(|g| g.scored.iter()).collect::<Vec<_>>();
+                self.select(&candidates)
+            }
+        }
+    }
+
+    /// Chooses a parent from `candidates`, returns `None` if there are none
+    pub(crate) fn select<'a>(&self, candidates: &[&'a ScoredTree]) -> Option<&'a ScoredTree> {
+        let mut rng = GET_RNG();
+        match self {
+            SelectionMethod::Tournament { size } => {
+                tournament(*size, || candidates.choose(&mut rng).copied())
+            }
+            SelectionMethod::Roulette => {
+                let values = candidates.iter().map(|t| t.score().value());
+                let (min, max) =
+                    values.fold((f32::MAX, f32::MIN), |(min, max), v| (min.min(v), max.max(v)));
+                let fitness = |tree: &&ScoredTree| {
+                    let value = tree.score().value();
+                    let fitness = if tree.score().objective().is_regression() {
+                        max - value
+                    } else {
+                        value - min
+                    };
+                    if fitness.is_finite() {
+                        fitness
+                    } else {
+                        0.0
+                    }
+                };
+                match candidates.choose_weighted(&mut rng, fitness) {
+                    Ok(tree) => Some(*tree),
+                    // all trees are equally good
+                    Err(_) => candidates.choose(&mut rng).copied(),
+                }
+            }
+            SelectionMethod::Rank => {
+                let mut ranked = candidates.to_vec();
+                ranked.sort_by(|t1, t2| t1.cmp_fitness(t2));
+                let ranks = (1..=ranked.len()).collect::<Vec<_>>();
+                let rank = ranks.choose_weighted(&mut rng, |rank| *rank).ok()?;
+                Some(ranked[rank - 1])
+            }
+        }
+    }
+}
+
+/// Returns the best of `size` trees given by `pick`, see [`ScoredTree::cmp_fitness`]
+fn tournament<'a>(
+    size: usize,
+    mut pick: impl FnMut() -> Option<&'a ScoredTree>,
+) -> Option<&'a ScoredTree> {
+    let mut best = pick()?;
+    for _ in 1..size {
+        if let Some(candidate) = pick() {
+            if candidate.cmp_fitness(best) == Greater {
+                best = candidate;
+            }
+        }
+    }
+    Some(best)
+}
+
+impl Serializable for GroupId {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add(&self.0);
+    }
+}
+
+impl Deserializable for GroupId {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        Ok(GroupId(u64::deserialize(s)?))
+    }
+}
+
+impl Serializable for GeneticConfig {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_items(&[
+            &self.mutation_rate,
+            &self.mutation_schedule,
+            &self.crossover_rate,
+            &self.selection,
+            &self.max_nodes,
+            &self.diversity,
+            &self.elitism,
+            &self.constant_range,
+            &self.terminals.iter().map(|t| t.name().to_string()).collect::<Vec<_>>(),
+            &self.safe_div,
+            &self.replacement,
+            &self.init_method,
+            &self.crossover,
+        ]);
+    }
+}
+
+impl Serializable for CrossoverMethod {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(match self {
+            CrossoverMethod::Join => "Join",
+            CrossoverMethod::SubtreeSwap => "SubtreeSwap",
+            CrossoverMethod::Uniform => "Uniform",
+        })
+    }
+}
+
+impl Serializable for InitMethod {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(match self {
+            InitMethod::Full => "Full",
+            InitMethod::Grow => "Grow",
+            InitMethod::RampedHalfAndHalf => "RampedHalfAndHalf",
+        })
+    }
+}
+
+impl Serializable for ReplacementMode {
+    fn serialize(&self, s: &mut Serializator) {
+        match self {
+            ReplacementMode::Generational => s.add_str("Generational"),
+            ReplacementMode::SteadyState { count } => {
+                s.add_items(&[&"SteadyState".to_owned(), count])
+            }
+        }
+    }
+}
+
+impl Serializable for DiversityMode {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(match self {
+            DiversityMode::None => "None",
+            DiversityMode::Crowding => "Crowding",
+        })
+    }
+}
+
+impl Serializable for MutationSchedule {
+    fn serialize(&self, s: &mut Serializator) {
+        match self {
+            MutationSchedule::Constant => s.add_str("Constant"),
+            MutationSchedule::LinearDecay { from, to, generations } => {
+                s.add_items(&[&"LinearDecay".to_owned(), from, to, generations])
+            }
+            MutationSchedule::AdaptiveOnStagnation { window, raised } => {
+                s.add_items(&[&"AdaptiveOnStagnation".to_owned(), window, raised])
+            }
+        }
+    }
+}
+
+impl Serializable for SelectionMethod {
+    fn serialize(&self, s: &mut Serializator) {
+        match self {
+            SelectionMethod::Tournament { size } => {
+                s.add_items(&[&"Tournament".to_owned(), size])
+            }
+            SelectionMethod::Roulette => s.add_str("Roulette"),
+            SelectionMethod::Rank => s.add_str("Rank"),
+        }
+    }
+}
+
+impl Deserializable for SelectionMethod {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "Tournament" => Ok(SelectionMethod::Tournament { size: usize::deserialize(s)? }),
+            "Roulette" => Ok(SelectionMethod::Roulette),
+            "Rank" => Ok(SelectionMethod::Rank),
+            other => Err(format!("Invalid selection method {}", other)),
+        }
+    }
+}
+
+impl Deserializable for MutationSchedule {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "Constant" => Ok(MutationSchedule::Constant),
+            "LinearDecay" => Ok(MutationSchedule::LinearDecay {
+                from: f64::deserialize(s)?,
+                to: f64::deserialize(s)?,
+                generations: usize::deserialize(s)?,
+            }),
+            "AdaptiveOnStagnation" => Ok(MutationSchedule::AdaptiveOnStagnation {
+                window: usize::deserialize(s)?,
+                raised: f64::deserialize(s)?,
+            }),
+            other => Err(format!("Invalid mutation schedule {}", other)),
+        }
+    }
+}
+
+impl Deserializable for InitMethod {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "Full" => Ok(InitMethod::Full),
+            "Grow" => Ok(InitMethod::Grow),
+            "RampedHalfAndHalf" => Ok(InitMethod::RampedHalfAndHalf),
+            other => Err(format!("Invalid init method {}", other)),
+        }
+    }
+}
+
+impl Deserializable for CrossoverMethod {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "Join" => Ok(CrossoverMethod::Join),
+            "SubtreeSwap" => Ok(CrossoverMethod::SubtreeSwap),
+            "Uniform" => Ok(CrossoverMethod::Uniform),
+            other => Err(format!("Invalid crossover method {}", other)),
+        }
+    }
+}
+
+impl Deserializable for ReplacementMode {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "Generational" => Ok(ReplacementMode::Generational),
+            "SteadyState" => Ok(ReplacementMode::SteadyState { count: usize::deserialize(s)? }),
+            other => Err(format!("Invalid replacement mode {}", other)),
+        }
+    }
+}
+
+impl Deserializable for DiversityMode {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "None" => Ok(DiversityMode::None),
+            "Crowding" => Ok(DiversityMode::Crowding),
+            other => Err(format!("Invalid diversity mode {}", other)),
+        }
+    }
+}
+
+impl Deserializable for GeneticConfig {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        let mutation_rate = f64::deserialize(s)?;
+        let mutation_schedule = MutationSchedule::deserialize(s)?;
+        let crossover_rate = f64::deserialize(s)?;
+        let selection = SelectionMethod::deserialize(s)?;
+        let max_nodes = Option::deserialize(s)?;
+        let diversity = DiversityMode::deserialize(s)?;
+        let elitism = usize::deserialize(s)?;
+        let constant_range = <(f32, f32)>::deserialize(s)?;
+        let names = Vec::<String>::deserialize(s)?;
+        let terminals = registered_terminals(&names)
+            .ok_or_else(|| format!("Terminals {:?} are not registered", names))?;
+        let safe_div = SafeDivMode::deserialize(s)?;
+        let replacement = ReplacementMode::deserialize(s)?;
+        let init_method = InitMethod::deserialize(s)?;
+        let crossover = CrossoverMethod::deserialize(s)?;
+        Ok(GeneticConfig {
+            mutation_rate,
+            mutation_schedule,
+            crossover_rate,
+            selection,
+            max_nodes,
+            diversity,
+            elitism,
+            constant_range,
+            terminals,
+            safe_div,
+            replacement,
+            init_method,
+            crossover,
+        })
+    }
+}
+
+impl Serializable for ClassGroup {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_items(&[&self.id, &self.fresh, &self.scored]);
+    }
+}
+
+impl Deserializable for ClassGroup {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        let id = GroupId::deserialize(s)?;
+        let fresh = Vec::deserialize(s)?;
+        let scored = Vec::deserialize(s)?;
+        Ok(ClassGroup { id, fresh, scored })
+    }
 }
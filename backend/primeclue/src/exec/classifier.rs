@@ -17,39 +17,427 @@
    along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use crate::data::data_set::DataView;
-use crate::data::outcome::Class;
-use crate::data::InputShape;
+use crate::data::data_set::{validate_feature_names, DataView};
+use crate::data::disk_data_view::DiskDataView;
+use crate::data::normalization::Normalization;
+use crate::data::outcome::{Class, MultiLabelOutcome, Outcome};
+use crate::data::{Data, Input, InputShape};
 use crate::error::PrimeclueErr;
-use crate::exec::score::calculate_auc;
+use crate::exec::functions::NodeKind;
+use crate::exec::node::{escape_dot, rust_f32};
+use crate::exec::onnx::OnnxGraph;
+use crate::exec::score::{calculate_auc, calculate_auc_with_ties, f1, reward_multipliers};
+use crate::exec::score::{Objective, Score};
 use crate::exec::scored_tree::ScoredTree;
+use crate::exec::tree::MAX_OUTPUT;
 use crate::serialization::{Deserializable, Serializable, Serializator};
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use rayon::prelude::*;
 use serde::Serialize;
-use std::cmp::Ordering::Equal;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
 use std::path::Path;
+use std::time::{Duration, Instant};
+
+const CLASSIFIER_MAGIC: &str = "PrimeclueClassifier";
+/// Version 1 had no writer version after the format version. Since version 2 the crate
+/// version that wrote the file always follows the format version, so that even files of
+/// a future format can name the Primeclue release needed to read them.
+const CLASSIFIER_FORMAT_VERSION: usize = 2;
+const CLASSIFIER_FORMAT_V1: usize = 1;
+const TEMPERATURE_TOKEN: &str = "Temperature";
+const AGGREGATION_TOKEN: &str = "Aggregation";
+const NORMALIZATION_TOKEN: &str = "Normalization";
+const FEATURE_RANGES_TOKEN: &str = "FeatureRanges";
+const FEATURE_NAMES_TOKEN: &str = "FeatureNames";
+const BEST_THRESHOLD_CANDIDATES: usize = 256;
+/// Inputs executed together by [`Classifier::predict_batch`]
+const PREDICT_BATCH_SIZE: usize = 1024;
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct ClassifierScore {
     pub auc: f32,
+    macro_auc: f32,
     pub accuracy: f32,
     pub cost: f32,
+    pub f1: f32,
+    /// Only present if all outcomes have target values, i.e. for regression
+    pub mean_squared_error: Option<f32>,
+    /// Only present if all outcomes have target values, i.e. for regression
+    pub mean_absolute_error: Option<f32>,
     pub label_count_map: HashMap<String, usize>,
     pub label_accuracy_map: HashMap<String, f32>,
+    confusion_matrix: Vec<Vec<usize>>,
+    /// Sums of rewards and penalties making `cost`, of points of every class
+    #[serde(skip)]
+    class_costs: Vec<(f32, f32)>,
+    #[serde(skip)]
+    classes: BTreeMap<Class, String>,
+}
+
+impl ClassifierScore {
+    /// Computes metrics of `predictions` made for points of `data`. AUCs need raw outputs
+    /// so they have to be given, regression errors are left empty. Classes are counted at
+    /// their position in `classes`, so their ids don't have to be consecutive.
+    pub(crate) fn from_predictions(
+        classes: &BTreeMap<Class, String>,
+        predictions: &[Option<Class>],
+        data: &DataView,
+        auc: f32,
+        macro_auc: f32,
+    ) -> Option<ClassifierScore> {
+        let mut confusion_matrix = vec![vec![0; classes.len()]; classes.len()];
+        let mut label_count_map = HashMap::new();
+        let mut label_accuracy_map = HashMap::new();
+        let mut expected_count_map = HashMap::new();
+        let mut correct = 0;
+        let mut total = 0;
+        let mut class_costs = vec![(0.0, 0.0); classes.len()];
+        let index = |class: Class| classes.keys().position(|c| *c == class);
+        for (prediction, outcome) in predictions.iter().zip(data.outcomes()) {
+            let prediction = match prediction {
+                Some(class) => *class,
+                None => continue,
+            };
+            let expected_index = index(outcome.class())?;
+            *confusion_matrix.get_mut(expected_index)?.get_mut(index(prediction)?)? += 1;
+            let label = classes.get(&prediction)?.to_owned();
+
+            let class_count = label_count_map.remove(&label).unwrap_or(0) + 1;
+            label_count_map.insert(label.clone(), class_count);
+
+            if classes.get(&outcome.class()).unwrap() == &label {
+                let accurate = label_accuracy_map.remove(&label).unwrap_or(0.0);
+                label_accuracy_map.insert(label, accurate + 1.0);
+            }
+
+            total += 1;
+            let expected = data.class_map().get(&outcome.class())?;
+            *expected_count_map.entry(expected.as_str()).or_insert(0) += 1;
+            let (reward, penalty) = class_costs.get_mut(expected_index)?;
+            if prediction == outcome.class() {
+                correct += 1;
+                *reward += outcome.reward();
+            } else {
+                *penalty += outcome.penalty();
+            }
+        }
+        let f1 = macro_f1(&label_count_map, &label_accuracy_map, &expected_count_map);
+        for (label, correct) in label_accuracy_map.iter_mut() {
+            let count = label_count_map.get(label).unwrap();
+            *correct /= *count as f32;
+        }
+        let accuracy = correct as f32 / total as f32;
+        let cost = class_costs.iter().map(|(reward, penalty)| reward + penalty).sum();
+        Some(ClassifierScore {
+            auc,
+            macro_auc,
+            accuracy,
+            cost,
+            f1,
+            mean_squared_error: None,
+            mean_absolute_error: None,
+            label_count_map,
+            label_accuracy_map,
+            confusion_matrix,
+            class_costs,
+            classes: classes.clone(),
+        })
+    }
+
+    /// Name of `class` in data the score was computed on
+    #[must_use]
+    pub fn class_name(&self, class: Class) -> Option<&str> {
+        self.classes.get(&class).map(String::as_str)
+    }
+
+    /// Value of `objective` measured by this score. NaN for custom and Pareto objectives and
+    /// for regression objectives if outcomes had no target values.
+    #[must_use]
+    pub fn value(&self, objective: &Objective) -> f32 {
+        match objective {
+            Objective::Auc => self.auc,
+            Objective::MacroAuc => self.macro_auc,
+            Objective::Accuracy => self.accuracy,
+            Objective::Cost => self.cost,
+            Objective::WeightedReward(multipliers) => self.weighted_cost(multipliers),
+            Objective::F1 => self.f1,
+            Objective::BalancedAccuracy => self.balanced_accuracy(),
+            Objective::Hamming => self.hamming(),
+            Objective::MeanSquaredError => self.mean_squared_error.unwrap_or(f32::NAN),
+            Objective::MeanAbsoluteError => self.mean_absolute_error.unwrap_or(f32::NAN),
+            Objective::Custom(_) | Objective::Pareto(_) => f32::NAN,
+        }
+    }
+
+    /// Same as `cost` with rewards and penalties of points of every class multiplied by
+    /// its `multipliers`, see [`Objective::WeightedReward`]
+    #[must_use]
+    pub fn weighted_cost(&self, multipliers: &[(f32, f32)]) -> f32 {
+        self.class_costs
+            .iter()
+            .zip(self.classes.keys())
+            .map(|((reward, penalty), class)| {
+                let (reward_multiplier, penalty_multiplier) =
+                    reward_multipliers(multipliers, *class);
+                reward * reward_multiplier + penalty * penalty_multiplier
+            })
+            .sum()
+    }
+
+    /// Average of one-vs-rest AUC of every class, computed from raw tree outputs across all
+    /// points. Points with equal output are counted as half-correct pairs, so it doesn't
+    /// depend on their order.
+    #[must_use]
+    pub fn macro_auc(&self) -> f32 {
+        self.macro_auc
+    }
+
+    /// Counts of points indexed by expected and then predicted [`Class`], at the position of
+    /// the class among all classes in ascending order. Points for which no class was
+    /// predicted are not counted.
+    #[must_use]
+    pub fn confusion_matrix(&self) -> &Vec<Vec<usize>> {
+        &self.confusion_matrix
+    }
+
+    /// Fraction of points predicted as `class` that really belong to it.
+    /// `None` if `class` was never predicted.
+    #[must_use]
+    pub fn precision(&self, class: Class) -> Option<f32> {
+        let index = self.class_index(class)?;
+        let predicted =
+            self.confusion_matrix.iter().map(|row| row.get(index)).sum::<Option<usize>>()?;
+        if predicted == 0 {
+            None
+        } else {
+            Some(self.confusion_matrix[index][index] as f32 / predicted as f32)
+        }
+    }
+
+    /// Fraction of points belonging to `class` that were predicted as `class`.
+    /// `None` if there were no points of `class`.
+    #[must_use]
+    pub fn recall(&self, class: Class) -> Option<f32> {
+        let index = self.class_index(class)?;
+        let expected = self.confusion_matrix.get(index)?.iter().sum::<usize>();
+        if expected == 0 {
+            None
+        } else {
+            Some(self.confusion_matrix[index][index] as f32 / expected as f32)
+        }
+    }
+
+    /// Mean of [`ClassifierScore::recall`] of every class that has any points, so every class
+    /// counts the same regardless of its size. NaN if there were no points.
+    #[must_use]
+    pub fn balanced_accuracy(&self) -> f32 {
+        let recalls =
+            self.classes.keys().filter_map(|class| self.recall(*class)).collect::<Vec<_>>();
+        recalls.iter().sum::<f32>() / recalls.len() as f32
+    }
+
+    /// One minus Hamming loss, i.e. fraction of point and class pairs whose membership is
+    /// decided correctly. A wrong prediction misses membership of two classes, so for two
+    /// classes it's equal to `accuracy`.
+    #[must_use]
+    pub fn hamming(&self) -> f32 {
+        1.0 - 2.0 * (1.0 - self.accuracy) / self.classes.len().max(2) as f32
+    }
+
+    /// Position of `class` in [`ClassifierScore::confusion_matrix`]
+    fn class_index(&self, class: Class) -> Option<usize> {
+        self.classes.keys().position(|c| *c == class)
+    }
+
+    /// Matthews correlation coefficient of the confusion matrix, in range from -1 to 1.
+    /// For more than two classes it's the multi-class generalization (Gorodkin's R_K).
+    /// Returns 0.0 if it's undefined, e.g. when only one class was predicted.
+    #[must_use]
+    pub fn mcc(&self) -> f32 {
+        let (correct, total, predicted, expected) = self.matrix_sums();
+        let predicted_expected =
+            predicted.iter().zip(&expected).map(|(p, e)| p * e).sum::<f64>();
+        let predicted_squares = predicted.iter().map(|p| p * p).sum::<f64>();
+        let expected_squares = expected.iter().map(|e| e * e).sum::<f64>();
+        let numerator = correct * total - predicted_expected;
+        let denominator =
+            ((total * total - predicted_squares) * (total * total - expected_squares)).sqrt();
+        if denominator > 0.0 {
+            (numerator / denominator) as f32
+        } else {
+            0.0
+        }
+    }
+
+    /// Cohen's kappa: agreement of predictions with expected classes corrected for agreement
+    /// expected by chance given class frequencies, for any number of classes. 1 means perfect
+    /// agreement, 0 no better than chance and negative values worse than chance. Commonly
+    /// (Landis & Koch) values up to 0.2 are read as slight, up to 0.4 fair, up to 0.6
+    /// moderate, up to 0.8 substantial and above as almost perfect agreement.
+    /// Returns 0.0 if chance agreement is already perfect, i.e. kappa is undefined.
+    #[must_use]
+    pub fn cohens_kappa(&self) -> f32 {
+        let (correct, total, predicted, expected) = self.matrix_sums();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let observed = correct / total;
+        let chance =
+            predicted.iter().zip(&expected).map(|(p, e)| p * e).sum::<f64>() / (total * total);
+        if chance < 1.0 {
+            ((observed - chance) / (1.0 - chance)) as f32
+        } else {
+            0.0
+        }
+    }
+
+    /// Count of correct predictions, all predictions, and predictions and points per class
+    fn matrix_sums(&self) -> (f64, f64, Vec<f64>, Vec<f64>) {
+        let size = self.confusion_matrix.len();
+        let mut correct = 0.0;
+        let mut total = 0.0;
+        let mut predicted = vec![0.0; size];
+        let mut expected = vec![0.0; size];
+        for (expected_index, row) in self.confusion_matrix.iter().enumerate() {
+            for (predicted_index, count) in row.iter().enumerate() {
+                let count = *count as f64;
+                total += count;
+                predicted[predicted_index] += count;
+                expected[expected_index] += count;
+                if predicted_index == expected_index {
+                    correct += count;
+                }
+            }
+        }
+        (correct, total, predicted, expected)
+    }
+}
+
+/// Full evaluation of a classifier on data, see [`Classifier::evaluate`]
+#[derive(Clone, Debug, Serialize)]
+pub struct EvaluationReport {
+    pub score: ClassifierScore,
+    /// Precision of every class label that was predicted at least once
+    pub precision: HashMap<String, f32>,
+    /// Recall of every class label present in data
+    pub recall: HashMap<String, f32>,
+    pub mcc: f32,
+    pub cohens_kappa: f32,
+}
+
+/// Explanation of a single prediction, see [`Classifier::explain`]
+#[derive(Clone, Debug, Serialize)]
+pub struct PredictionExplanation {
+    /// The same as [`Classifier::predict`] gives: predicted class and confidence
+    pub prediction: Option<(Class, f32)>,
+    /// Output of the best tree of every class and the threshold it has to reach
+    pub outputs: HashMap<Class, ClassOutput>,
+    /// Every input value the winning tree refers to, ordered by row and column. Empty
+    /// if no class was predicted.
+    pub features: Vec<FeatureContribution>,
+    /// [`Tree::depth`] of the winning tree, `None` if no class was predicted
+    pub tree_depth: Option<usize>,
+    /// [`Tree::node_count`] of the winning tree, `None` if no class was predicted
+    pub tree_node_count: Option<usize>,
+}
+
+/// Raw output of a class's tree for an input, see [`PredictionExplanation`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct ClassOutput {
+    pub output: f32,
+    pub threshold: f32,
+}
+
+/// Input value referred to by the winning tree, see [`PredictionExplanation`]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FeatureContribution {
+    pub row: usize,
+    pub column: usize,
+    /// Name of the column, see [`Classifier::feature_names`]
+    pub name: Option<String>,
+    pub value: f32,
+    /// Output of the winning tree less its output with this value replaced by 0
+    pub contribution: f32,
+}
+
+/// Prediction together with input values it extrapolates from, see
+/// [`Classifier::predict_checked`]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CheckedPrediction {
+    /// The same as [`Classifier::predict`] gives: predicted class and confidence
+    pub prediction: Option<(Class, f32)>,
+    /// Every input value outside of the range seen in its cell by
+    /// [`Classifier::fit_feature_ranges`], ordered by row and column. Empty if the prediction
+    /// is no extrapolation.
+    pub extrapolated: Vec<ExtrapolatedFeature>,
+}
+
+/// Input value outside of the fitted range of its cell, see [`CheckedPrediction`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct ExtrapolatedFeature {
+    pub row: usize,
+    pub column: usize,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Metrics of multi-label prediction, see [`Classifier::score_multi_label`]
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct MultiLabelScore {
+    /// Share of wrong (point, class) decisions, the lower the better
+    pub hamming_loss: f32,
+    /// Average over classes of F1 score of deciding that class. A class that is never
+    /// expected nor predicted has F1 of 0
+    pub macro_f1: f32,
+}
+
+/// How [`Classifier`] compares outputs of trees of different classes to pick a winner:
+/// * `Priority` - the best scored tree that reaches its threshold wins, regardless of
+///   how far above the threshold its output is
+/// * `Raw` - of trees that reach their thresholds the one with the highest output less
+///   threshold wins
+/// * `MinMaxNormalized` - same as `Raw`, with output less threshold divided by the range
+///   of the tree's outputs, see [`Classifier::fit_output_ranges`]. Suits trees whose
+///   outputs have different scales.
+/// * `Softmax` - class with the highest [`Classifier::predict_proba`] probability wins,
+///   so a class is predicted even if no tree reaches its threshold
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ClassScoreAggregation {
+    #[default]
+    Priority,
+    Raw,
+    MinMaxNormalized,
+    Softmax,
 }
 
 /// A structure containing a classifier trained via [`TrainingGroup`]
 #[derive(Clone, Debug, PartialEq)]
 pub struct Classifier {
-    classes: HashMap<Class, String>,
+    classes: BTreeMap<Class, String>,
     trees: Vec<ScoredTree>,
+    temperature: f32,
+    aggregation: ClassScoreAggregation,
+    output_ranges: HashMap<Class, f32>,
+    normalization: Option<Normalization>,
+    feature_ranges: Vec<(f32, f32)>,
+    feature_names: Vec<String>,
 }
 
 impl Classifier {
+    /// Classifier of `classes`, given by any map of class names, e.g. a `HashMap`, with one
+    /// tree per class. Classes are kept sorted.
     pub fn new(
-        classes: HashMap<Class, String>,
+        classes: impl IntoIterator<Item = (Class, String)>,
         trees: Vec<ScoredTree>,
     ) -> Result<Self, PrimeclueErr> {
+        let classes: BTreeMap<Class, String> = classes.into_iter().collect();
         if classes.is_empty() {
             PrimeclueErr::result("Class map is empty".to_string())
         } else if trees.is_empty() {
@@ -61,14 +449,205 @@ impl Classifier {
                 trees.len()
             ))
         } else {
-            Ok(Classifier { classes, trees })
+            Ok(Classifier {
+                classes,
+                trees,
+                temperature: 1.0,
+                aggregation: ClassScoreAggregation::Priority,
+                output_ranges: HashMap::new(),
+                normalization: None,
+                feature_ranges: vec![],
+                feature_names: vec![],
+            })
+        }
+    }
+
+    /// How outputs of trees of different classes are compared,
+    /// [`ClassScoreAggregation::Priority`] by default
+    #[must_use]
+    pub fn aggregation(&self) -> ClassScoreAggregation {
+        self.aggregation
+    }
+
+    /// Sets how outputs of trees of different classes are compared by [`Classifier::predict`],
+    /// [`Classifier::classify`] and [`Classifier::score`]. Fails for
+    /// [`ClassScoreAggregation::MinMaxNormalized`] if output ranges were not fitted with
+    /// [`Classifier::fit_output_ranges`].
+    pub fn set_aggregation(
+        &mut self,
+        aggregation: ClassScoreAggregation,
+    ) -> Result<(), PrimeclueErr> {
+        if aggregation == ClassScoreAggregation::MinMaxNormalized
+            && self.output_ranges.is_empty()
+        {
+            return PrimeclueErr::result(
+                "Output ranges must be fitted for min-max normalization".to_string(),
+            );
+        }
+        self.aggregation = aggregation;
+        Ok(())
+    }
+
+    /// [`Normalization`] applied to every input and data before executing trees, `None`
+    /// by default
+    #[must_use]
+    pub fn normalization(&self) -> Option<&Normalization> {
+        self.normalization.as_ref()
+    }
+
+    /// Sets [`Normalization`] that was applied to training data, so that predictions and
+    /// scores of this classifier take raw, not normalized, inputs. It is saved together with
+    /// the classifier. Fails if `normalization` has different input shape than training data.
+    pub fn set_normalization(
+        &mut self,
+        normalization: Option<Normalization>,
+    ) -> Result<(), PrimeclueErr> {
+        if let Some(normalization) = &normalization {
+            self.check_shape(normalization.input_shape())?;
+        }
+        self.normalization = normalization;
+        Ok(())
+    }
+
+    /// Names of input columns, empty by default. Classifiers made by
+    /// [`TrainingGroup`](crate::exec::training_group::TrainingGroup) get names of training
+    /// data, see [`DataSet::new_named`](crate::data::data_set::DataSet::new_named). Used in
+    /// place of column indices by [`Classifier::to_expression_string`],
+    /// [`Classifier::to_dot`], [`Classifier::named_feature_usage`] and
+    /// [`Classifier::explain`].
+    #[must_use]
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+
+    /// Sets [`Classifier::feature_names`], saved together with the classifier. Fails if
+    /// `names` are not empty and their number differs from the number of input columns, or
+    /// a name is empty or repeated.
+    pub fn set_feature_names(&mut self, names: Vec<String>) -> Result<(), PrimeclueErr> {
+        let columns = self.input_shape().columns();
+        if !names.is_empty() && names.len() != columns {
+            return PrimeclueErr::result(format!(
+                "Got {} feature names for {} input columns",
+                names.len(),
+                columns
+            ));
+        }
+        validate_feature_names(&names)?;
+        self.feature_names = names;
+        Ok(())
+    }
+
+    fn feature_name_refs(&self) -> Vec<&str> {
+        self.feature_names.iter().map(String::as_str).collect()
+    }
+
+    /// `input` transformed with [`Classifier::normalization`], if any. Inputs of different
+    /// shape are left as they are, callers check the shape.
+    fn normalized_input<'a>(&self, input: &'a Input) -> Cow<'a, Input> {
+        match &self.normalization {
+            Some(normalization) => {
+                let mut input = input.clone();
+                let _ = normalization.apply(&mut input);
+                Cow::Owned(input)
+            }
+            None => Cow::Borrowed(input),
+        }
+    }
+
+    /// Same as [`Classifier::normalized_input`] for every point of `data`
+    fn normalized_view<'a>(&self, data: &'a DataView) -> Cow<'a, DataView> {
+        match &self.normalization {
+            Some(normalization) => {
+                data.normalized(normalization).map_or(Cow::Borrowed(data), Cow::Owned)
+            }
+            None => Cow::Borrowed(data),
         }
     }
 
-    pub fn get_classes(&self) -> &HashMap<Class, String> {
+    /// Remembers difference between the highest and the lowest finite output of every tree
+    /// on `data`, used by [`ClassScoreAggregation::MinMaxNormalized`]. Fails if `data` has
+    /// different input shape than training data or a tree gives the same output for all points.
+    pub fn fit_output_ranges(&mut self, data: &DataView) -> Result<(), PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        let data = self.normalized_view(data);
+        let mut ranges = HashMap::with_capacity(self.trees.len());
+        for tree in &self.trees {
+            let (min, max) = tree
+                .execute(&data)
+                .into_iter()
+                .filter(|v| v.is_finite())
+                .fold((f32::MAX, f32::MIN), |(min, max), v| (min.min(v), max.max(v)));
+            let range = max - min;
+            if !(range.is_finite() && range > 0.0) {
+                return PrimeclueErr::result(format!(
+                    "Tree of class {} has no output range on data",
+                    tree.score().class()
+                ));
+            }
+            ranges.insert(tree.score().class(), range);
+        }
+        self.output_ranges = ranges;
+        Ok(())
+    }
+
+    /// Remembers the lowest and the highest value of every input cell on `data`, usually
+    /// training data, for [`Classifier::predict_checked`]. Values must be raw, not normalized
+    /// with [`Classifier::normalization`], the same as inputs to predict. Missing values are
+    /// skipped. Fails if `data` is empty or has different input shape than training data.
+    pub fn fit_feature_ranges(&mut self, data: &DataView) -> Result<(), PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        if data.is_empty() {
+            return Err(PrimeclueErr::EmptyDataSet("Feature range data"));
+        }
+        let shape = data.input_shape();
+        let mut ranges = Vec::with_capacity(shape.rows() * shape.columns());
+        for row in 0..shape.rows() {
+            for column in 0..shape.columns() {
+                let range = data
+                    .cells()
+                    .get(row, column)
+                    .iter()
+                    .filter(|v| !v.is_nan())
+                    .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+                        (min.min(*v), max.max(*v))
+                    });
+                ranges.push(range);
+            }
+        }
+        self.feature_ranges = ranges;
+        Ok(())
+    }
+
+    /// Temperature of softmax used by [`Classifier::predict_proba`], 1.0 by default
+    #[must_use]
+    pub fn temperature(&self) -> f32 {
+        self.temperature
+    }
+
+    /// Sets temperature of softmax used by [`Classifier::predict_proba`], e.g. calibrated
+    /// on a validation set. Values above 1.0 make probabilities more even, below sharper.
+    pub fn set_temperature(&mut self, temperature: f32) -> Result<(), PrimeclueErr> {
+        if !temperature.is_finite() || temperature <= 0.0 {
+            return PrimeclueErr::result(format!(
+                "Invalid temperature {}, must be positive",
+                temperature
+            ));
+        }
+        self.temperature = temperature;
+        Ok(())
+    }
+
+    /// Names of classes in training data, kept when the classifier is saved and loaded
+    pub fn get_classes(&self) -> &BTreeMap<Class, String> {
         &self.classes
     }
 
+    /// Name of `class` in training data, e.g. for a class returned by [`Classifier::predict`]
+    #[must_use]
+    pub fn class_name(&self, class: Class) -> Option<&str> {
+        self.classes.get(&class).map(String::as_str)
+    }
+
     pub fn input_shape(&self) -> &InputShape {
         self.trees[0].input_shape()
     }
@@ -81,144 +660,2699 @@ impl Classifier {
         Some(val / self.trees.len() as f32)
     }
 
-    pub fn save(&self, path: &Path, name: &str) -> Result<usize, PrimeclueErr> {
+    /// Saves [`Classifier`] to a file, preceded by a format marker, format version
+    /// and the Primeclue version writing it, so that [`Classifier::load`] can reject
+    /// files it does not understand.
+    /// [`Classifier::normalization`], if set, is saved too.
+    pub fn save(&self, path: &Path) -> Result<(), PrimeclueErr> {
         let mut ser = Serializator::new();
-        self.serialize(&mut ser);
-        ser.save(path, format!("{}.ssd", name).as_str())
+        ser.add_str(CLASSIFIER_MAGIC);
+        ser.add(&CLASSIFIER_FORMAT_VERSION);
+        ser.add_str(env!("CARGO_PKG_VERSION"));
+        ser.add(self);
+        fs::write(path, ser.to_bytes())
+            .map_err(|e| PrimeclueErr::from(format!("Unable to write {:?}: {}", path, e)))
+    }
+
+    /// Loads [`Classifier`] previously saved with [`Classifier::save`], including files
+    /// of older format versions. Files of a newer format version are rejected with an error
+    /// naming the Primeclue version that wrote them.
+    pub fn load(path: &Path) -> Result<Classifier, PrimeclueErr> {
+        let mut ser = Serializator::load(path)?;
+        let magic = String::deserialize(&mut ser)?;
+        if magic != CLASSIFIER_MAGIC {
+            return PrimeclueErr::result(format!(
+                "{:?} is not a Primeclue classifier file",
+                path
+            ));
+        }
+        let version = usize::deserialize(&mut ser)?;
+        match version {
+            // classifier itself is serialized the same way, only writer version is missing
+            CLASSIFIER_FORMAT_V1 => {}
+            CLASSIFIER_FORMAT_VERSION => {
+                String::deserialize(&mut ser)?;
+            }
+            v if v > CLASSIFIER_FORMAT_VERSION => {
+                let writer = String::deserialize(&mut ser)?;
+                return PrimeclueErr::result(format!(
+                    "Classifier format version {} in {:?} is newer than supported {}, \
+                     file was saved by Primeclue {} and this is {}",
+                    version,
+                    path,
+                    CLASSIFIER_FORMAT_VERSION,
+                    writer,
+                    env!("CARGO_PKG_VERSION")
+                ));
+            }
+            _ => {
+                return PrimeclueErr::result(format!(
+                    "Unsupported classifier format version {} in {:?}",
+                    version, path
+                ))
+            }
+        }
+        Classifier::deserialize(&mut ser)
+            .map_err(|e| PrimeclueErr::from(format!("Unable to deserialize classifier: {}", e)))
     }
 
     pub fn node_count(&self) -> usize {
         self.trees.iter().map(|t| t.node_count()).sum()
     }
 
+    /// Number of terminal nodes referencing each input column, summed over trees of all
+    /// classes. Every column of input shape is present, so unused ones have count 0.
+    #[must_use]
+    pub fn feature_usage(&self) -> HashMap<usize, usize> {
+        let mut usage = (0..self.input_shape().columns()).map(|c| (c, 0)).collect();
+        for tree in &self.trees {
+            tree.tree().get_start_node().add_column_usage(&mut usage);
+        }
+        usage
+    }
+
+    /// Same as [`Classifier::feature_usage`], but columns are keyed by their names, see
+    /// [`Classifier::feature_names`], or `col{index}` if there are none
+    #[must_use]
+    pub fn named_feature_usage(&self) -> HashMap<String, usize> {
+        self.feature_usage()
+            .into_iter()
+            .map(|(column, count)| {
+                let name = self.feature_names.get(column).cloned();
+                (name.unwrap_or_else(|| format!("col{}", column)), count)
+            })
+            .collect()
+    }
+
+    /// Same classifier with every tree simplified (see
+    /// [`crate::exec::node::Weighted::simplified`]), giving the same predictions with fewer
+    /// nodes. Trees scored equally to another tree are kept as they are, because such trees
+    /// are ordered by node count (see [`Classifier::sorted_trees`]).
+    #[must_use]
+    pub fn simplified(&self) -> Classifier {
+        let trees = self
+            .trees
+            .iter()
+            .map(|tree| {
+                let tied = self.trees.iter().filter(|other| {
+                    !matches!(tree.score().partial_cmp(other.score()), Some(Less | Greater))
+                });
+                if tied.count() > 1 {
+                    tree.clone()
+                } else {
+                    ScoredTree::new(tree.tree().simplified(), tree.score().clone())
+                }
+            })
+            .collect();
+        Classifier { trees, ..self.clone() }
+    }
+
+    /// Kinds of all function nodes used by trees of this classifier
+    #[must_use]
+    pub fn used_node_kinds(&self) -> HashSet<NodeKind> {
+        self.trees.iter().flat_map(|t| t.tree().get_used_node_kinds()).collect()
+    }
+
+    /// Identifier of everything this classifier saves, e.g. to tell saved models apart.
+    /// Classifiers with the same classes, the same trees, including their weights and scores,
+    /// in the same order and the same settings, e.g. temperature or normalization, always have
+    /// the same fingerprint, also across runs and platforms.
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        let mut s = Serializator::new();
+        s.add(self);
+        fnv1a(s.as_serialized().as_bytes())
+    }
+
+    /// Trees in the order they were given in, one per class, e.g. for walking their nodes
+    /// with [`crate::exec::node::Weighted::node`]
+    #[must_use]
+    pub fn trees(&self) -> &[ScoredTree] {
+        &self.trees
+    }
+
+    /// Trees from worst to best scored. Equally scored trees are ordered by descending
+    /// [`Class`], so that when they all guess true the lowest class wins regardless of the
+    /// order trees were given in.
     pub fn sorted_trees(&self) -> Vec<&ScoredTree> {
         let mut scores = self.trees.iter().collect::<Vec<_>>();
-        scores.sort_unstable_by(|&t1, &t2| t1.partial_cmp(t2).unwrap_or(Equal));
+        scores.sort_by(|&t1, &t2| {
+            t1.partial_cmp(t2)
+                .unwrap_or(Equal)
+                .then_with(|| t2.score().class().cmp(&t1.score().class()))
+        });
         scores
     }
 
-    pub fn classify(&self, data: &DataView) -> Vec<&str> {
+    /// Names of classes predicted for points of `data`, empty for points with no prediction.
+    /// Fails if `data` has different input shape than training data.
+    pub fn classify(&self, data: &DataView) -> Result<Vec<&str>, PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        Ok(self
+            .classify_classes(data)
+            .into_iter()
+            .map(|class| match class {
+                Some(class) => self.classes.get(&class).unwrap().as_str(),
+                None => "",
+            })
+            .collect())
+    }
+
+    /// Same as [`Classifier::classify`] for points read from disk in chunks of `chunk_size`
+    /// points, so that no more than one chunk is kept in memory. Fails before reading any
+    /// chunk if `data` has different input shape than training data.
+    pub fn classify_disk(
+        &self,
+        data: &DiskDataView,
+        chunk_size: usize,
+    ) -> Result<Vec<&str>, PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        let mut classified = Vec::with_capacity(data.len());
+        for chunk in data.chunks(chunk_size) {
+            classified.extend(self.classify(&chunk?)?);
+        }
+        Ok(classified)
+    }
+
+    fn classify_classes(&self, data: &DataView) -> Vec<Option<Class>> {
+        let data = self.normalized_view(data);
         let trees = self.sorted_trees();
-        let mut responses = vec![""; data.cells().get(0, 0).len()];
-        for tree in &trees {
-            let values = tree.execute(data);
-            let class_string = self.classes.get(&tree.score().class()).unwrap();
-            for (value, response) in values.iter().zip(responses.iter_mut()) {
-                if let Some(guess) = tree.guess(*value) {
-                    if guess {
-                        *response = class_string;
+        let values = trees.iter().map(|tree| tree.execute(&data)).collect::<Vec<_>>();
+        (0..data.cells().get(0, 0).len())
+            .map(|point| {
+                let outputs = values.iter().map(|v| v[point]).collect::<Vec<_>>();
+                self.winner(&trees, &outputs)
+            })
+            .collect()
+    }
+
+    /// Fraction of points of `data` whose predicted class (or lack of it) stays the same in
+    /// every one of `trials` copies of `data` with Gaussian noise of `noise_std` standard
+    /// deviation added to every present cell, e.g. to report robustness beyond accuracy.
+    /// Noise is added before [`Classifier::normalization`], i.e. in units of the features.
+    /// The same `seed` gives the same result. Fails if `data` is empty or has different input
+    /// shape than training data, or if `noise_std` is negative or not finite.
+    pub fn perturbation_stability(
+        &self,
+        data: &DataView,
+        noise_std: f32,
+        trials: usize,
+        seed: u64,
+    ) -> Result<f32, PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        if data.is_empty() {
+            return PrimeclueErr::result("No points to perturb".to_string());
+        }
+        if !noise_std.is_finite() || noise_std < 0.0 {
+            return PrimeclueErr::result(format!("Invalid noise deviation {}", noise_std));
+        }
+        let expected = self.classify_classes(data);
+        let mut stable = vec![true; expected.len()];
+        let mut rng = XorShiftRng::seed_from_u64(seed);
+        let shape = *data.input_shape();
+        for _ in 0..trials {
+            let mut cells = data.cells().clone();
+            for row in 0..shape.rows() {
+                for column in 0..shape.columns() {
+                    for value in cells.get_mut(row, column).iter_mut().filter(|v| !v.is_nan()) {
+                        *value += noise_std * gaussian(&mut rng);
                     }
                 }
             }
+            let noisy = DataView::from_columns(cells, data.outcomes().clone(), BTreeMap::new());
+            for (point, class) in self.classify_classes(&noisy).into_iter().enumerate() {
+                stable[point] &= class == expected[point];
+            }
         }
-        responses
+        Ok(stable.iter().filter(|s| **s).count() as f32 / stable.len() as f32)
     }
 
-    fn execute_for_auc(&self, data: &DataView) -> Option<f32> {
-        let mut sum_score = 0.0;
-        for tree in &self.trees {
-            sum_score += Classifier::calc_tree_auc(tree, data)?;
+    /// Class winning for `outputs` of `trees` according to [`Classifier::aggregation`].
+    /// Later (i.e. better scored) trees win ties. `trees` and `outputs` must be in the order
+    /// of [`Classifier::sorted_trees`].
+    fn winner(&self, trees: &[&ScoredTree], outputs: &[f32]) -> Option<Class> {
+        let mut response = None;
+        let mut best = f32::MIN;
+        for (tree, value) in trees.iter().zip(outputs) {
+            let strength = match self.aggregation {
+                ClassScoreAggregation::Softmax => self.logit(tree, *value),
+                _ if tree.guess(*value) != Some(true) => continue,
+                ClassScoreAggregation::Priority => f32::MIN,
+                ClassScoreAggregation::Raw => value - tree.score().threshold().value(),
+                ClassScoreAggregation::MinMaxNormalized => {
+                    let range = self.output_ranges.get(&tree.score().class()).unwrap_or(&1.0);
+                    (value - tree.score().threshold().value()) / range
+                }
+            };
+            if strength >= best {
+                best = strength;
+                response = Some(tree.score().class());
+            }
         }
-        Some(sum_score / self.trees.len() as f32)
+        response
     }
 
-    pub fn score(&self, data: &DataView) -> Option<ClassifierScore> {
-        let auc = self.execute_for_auc(data)?;
-        let predictions = self.classify(data);
-        let mut label_count_map = HashMap::new();
-        let mut label_accuracy_map = HashMap::new();
-        let mut correct = 0;
-        let mut total = 0;
-        let mut reward = 0.0;
-        let mut penalty = 0.0;
-        for (prediction, outcome) in predictions.iter().zip(data.outcomes()) {
-            if prediction.is_empty() {
-                continue;
-            }
-            let label = (*prediction).to_owned();
+    /// Predicts [`Class`] for a single, unlabeled [`Input`] the same way [`Classifier::score`]
+    /// does for [`DataView`]. Also returns confidence in range from 0 to 1: the share the
+    /// winning tree has in the sum of distances above threshold of all trees that guessed true.
+    /// Returns `None` if no class was predicted. Trees using a missing cell of `input` (see
+    /// [`Input::from_vector_with_mask`]) give no output and are skipped. Fails if `input` has
+    /// different shape than training data.
+    pub fn predict(&self, input: &Input) -> Result<Option<(Class, f32)>, PrimeclueErr> {
+        self.check_shape(input.input_shape())?;
+        let input = self.normalized_input(input);
+        let trees = self.sorted_trees();
+        let outputs = trees.iter().map(|t| t.tree().execute_input(&input)).collect::<Vec<_>>();
+        Ok(self.confident_winner(&trees, &outputs))
+    }
 
-            let class_count = label_count_map.remove(&label).unwrap_or(0) + 1;
-            label_count_map.insert(label.clone(), class_count);
+    /// Same as [`Classifier::predict`], but fails with [`PrimeclueErr::Timeout`] if it takes
+    /// longer than `budget`, e.g. to bound latency of a service even for very large trees.
+    /// The budget is checked before the execution of every node of every tree starts, so it
+    /// can be exceeded by the time of a single node (see [`Weighted`]), which for a single
+    /// input is usually well below a microsecond, or a user-defined terminal
+    /// (see [`Terminal`]). Also fails if `input` has different shape than training data.
+    ///
+    /// [`Weighted`]: crate::exec::node::Weighted
+    /// [`Terminal`]: crate::exec::functions::Terminal
+    pub fn predict_within(
+        &self,
+        input: &Input,
+        budget: Duration,
+    ) -> Result<Option<(Class, f32)>, PrimeclueErr> {
+        let deadline = Instant::now() + budget;
+        self.check_shape(input.input_shape())?;
+        let input = self.normalized_input(input);
+        let trees = self.sorted_trees();
+        let outputs = trees
+            .iter()
+            .map(|t| t.tree().execute_input_until(&input, deadline))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(PrimeclueErr::Timeout(budget))?;
+        Ok(self.confident_winner(&trees, &outputs))
+    }
 
-            if self.classes.get(&outcome.class()).unwrap() == &label {
-                let accurate = label_accuracy_map.remove(&label).unwrap_or(0.0);
-                label_accuracy_map.insert(label, accurate + 1.0);
-            }
+    /// Same as [`Classifier::predict`], but returns `None` instead of a guess when the best
+    /// tree outputs of the two leading classes are closer than `margin`. Outputs are compared
+    /// normalized: output less threshold, divided by the range of the tree's outputs fitted
+    /// with [`Classifier::fit_output_ranges`], or by 1 if it wasn't. The absolute difference
+    /// of the highest and the second highest normalized output is compared to `margin`,
+    /// regardless of [`Classifier::aggregation`]. Classes whose trees give no output are
+    /// left out, so a single class with output is never abstained from. Fails if `input` has
+    /// different shape than training data or `margin` is negative or not finite.
+    pub fn predict_or_abstain(
+        &self,
+        input: &Input,
+        margin: f32,
+    ) -> Result<Option<(Class, f32)>, PrimeclueErr> {
+        if !margin.is_finite() || margin < 0.0 {
+            return PrimeclueErr::result(format!(
+                "Abstention margin must be non-negative, got {}",
+                margin
+            ));
+        }
+        self.check_shape(input.input_shape())?;
+        let input = self.normalized_input(input);
+        let trees = self.sorted_trees();
+        let outputs = trees.iter().map(|t| t.tree().execute_input(&input)).collect::<Vec<_>>();
+        // trees are sorted, so the best tree of a class is inserted last
+        let normalized = trees
+            .iter()
+            .zip(&outputs)
+            .map(|(tree, output)| {
+                let class = tree.score().class();
+                let range = self.output_ranges.get(&class).unwrap_or(&1.0);
+                (class, (output - tree.score().threshold().value()) / range)
+            })
+            .collect::<HashMap<_, _>>();
+        let mut values = normalized.into_values().filter(|v| !v.is_nan()).collect::<Vec<_>>();
+        values.sort_unstable_by(|v1, v2| v2.partial_cmp(v1).unwrap_or(Equal));
+        if values.len() >= 2 && (values[0] - values[1]).abs() < margin {
+            return Ok(None);
+        }
+        Ok(self.confident_winner(&trees, &outputs))
+    }
 
-            total += 1;
-            let expected = data.class_map().get(&outcome.class())?;
-            if prediction == expected {
-                correct += 1;
-                reward += outcome.reward();
-            } else {
-                penalty += outcome.penalty();
+    /// Same as [`Classifier::predict`] for every input of `inputs`, in the same order. Inputs
+    /// are split into chunks predicted in parallel, every tree is executed once per chunk.
+    /// Fails if any input has different shape than training data.
+    pub fn predict_batch(
+        &self,
+        inputs: &[Input],
+    ) -> Result<Vec<Option<(Class, f32)>>, PrimeclueErr> {
+        for input in inputs {
+            self.check_shape(input.input_shape())?;
+        }
+        let trees = self.sorted_trees();
+        let chunks = inputs
+            .par_chunks(PREDICT_BATCH_SIZE)
+            .map(|chunk| {
+                let cells = self.batch_cells(chunk);
+                let values = trees
+                    .iter()
+                    .map(|tree| tree.tree().execute_cells(&cells))
+                    .collect::<Vec<_>>();
+                (0..chunk.len())
+                    .map(|point| {
+                        let outputs = values.iter().map(|v| v[point]).collect::<Vec<_>>();
+                        self.confident_winner(&trees, &outputs)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        Ok(chunks.into_iter().flatten().collect())
+    }
+
+    /// Same as [`Classifier::predict`] for every point of `data`, in the same order, e.g. for
+    /// a batch whose labels are unknown or not trusted: outcomes of `data` are ignored.
+    /// Every tree is executed once. Fails if `data` has different input shape than training
+    /// data.
+    pub fn predict_view(
+        &self,
+        data: &DataView,
+    ) -> Result<Vec<Option<(Class, f32)>>, PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        let data = self.normalized_view(data);
+        let trees = self.sorted_trees();
+        let values = trees.par_iter().map(|tree| tree.execute(&data)).collect::<Vec<_>>();
+        Ok((0..data.len())
+            .map(|point| {
+                let outputs = values.iter().map(|v| v[point]).collect::<Vec<_>>();
+                self.confident_winner(&trees, &outputs)
+            })
+            .collect())
+    }
+
+    /// [`Classifier::predict_batch`] over a grid of `steps` by `steps` inputs of a single row,
+    /// made of `fixed` values with `x_feature` and `y_feature` columns replaced with values
+    /// evenly spaced from the start to the end of `x_range` and `y_range`, both inclusive.
+    /// Rows of the grid follow `y_feature`, columns follow `x_feature`, e.g. to plot how
+    /// the classifier partitions a plane of two features. Cells where no class was predicted
+    /// are `None`. Fails if `fixed` doesn't have a value for every column, features are
+    /// the same or out of range, ranges are not finite or `steps` is less than 2.
+    pub fn decision_grid(
+        &self,
+        x_feature: usize,
+        y_feature: usize,
+        fixed: &[f32],
+        x_range: (f32, f32),
+        y_range: (f32, f32),
+        steps: usize,
+    ) -> Result<Vec<Vec<Option<Class>>>, PrimeclueErr> {
+        if fixed.len() != self.input_shape().columns() {
+            return PrimeclueErr::result(format!(
+                "Expected {} fixed values, got {}",
+                self.input_shape().columns(),
+                fixed.len()
+            ));
+        }
+        if x_feature == y_feature || x_feature >= fixed.len() || y_feature >= fixed.len() {
+            return PrimeclueErr::result(format!(
+                "Invalid grid features {} and {}",
+                x_feature, y_feature
+            ));
+        }
+        if steps < 2
+            || [x_range.0, x_range.1, y_range.0, y_range.1].iter().any(|v| !v.is_finite())
+        {
+            return PrimeclueErr::result(format!(
+                "Invalid grid of {} steps over {:?} and {:?}",
+                steps, x_range, y_range
+            ));
+        }
+        let step = |(start, end): (f32, f32), i: usize| {
+            start + (end - start) * i as f32 / (steps - 1) as f32
+        };
+        let mut inputs = Vec::with_capacity(steps * steps);
+        for y in 0..steps {
+            for x in 0..steps {
+                let mut row = fixed.to_vec();
+                row[x_feature] = step(x_range, x);
+                row[y_feature] = step(y_range, y);
+                inputs.push(Input::from_vector(vec![row])?);
             }
         }
-        for (label, correct) in label_accuracy_map.iter_mut() {
-            let count = label_count_map.get(label).unwrap();
-            *correct /= *count as f32;
+        let predictions = self.predict_batch(&inputs)?;
+        Ok(predictions
+            .chunks(steps)
+            .map(|row| row.iter().map(|p| p.map(|(class, _)| class)).collect())
+            .collect())
+    }
+
+    /// Column oriented cells of normalized `inputs`, all of [`Classifier::input_shape`]
+    fn batch_cells(&self, inputs: &[Input]) -> Data<Vec<f32>> {
+        let inputs = inputs.iter().map(|input| self.normalized_input(input)).collect::<Vec<_>>();
+        let mut cells = Data::new();
+        for row in 0..self.input_shape().rows() {
+            let row_data = (0..self.input_shape().columns())
+                .map(|column| inputs.iter().map(|input| input.get(row, column)).collect())
+                .collect();
+            cells.add_row(row_data).unwrap();
         }
-        let accuracy = correct as f32 / total as f32;
-        let cost = reward + penalty;
-        Some(ClassifierScore { auc, accuracy, cost, label_count_map, label_accuracy_map })
+        cells
     }
 
-    fn calc_tree_auc(tree: &ScoredTree, data: &DataView) -> Option<f32> {
-        let values = tree.execute(data);
-        if values.iter().any(|v| !v.is_finite()) {
-            None
-        } else {
-            let mut outcomes =
-                values.into_iter().zip(data.outcomes().iter().copied()).collect::<Vec<_>>();
-            outcomes.sort_unstable_by(|(v1, _), (v2, _)| v1.partial_cmp(v2).unwrap());
-            Some(calculate_auc(&outcomes, tree.score().class()))
+    /// Same as [`Classifier::predict`], but also flags every value of `input` outside of
+    /// the range fitted with [`Classifier::fit_feature_ranges`], i.e. a prediction made
+    /// by extrapolation. Missing values are not flagged. Fails if `input` has different shape
+    /// than training data or feature ranges were not fitted.
+    pub fn predict_checked(&self, input: &Input) -> Result<CheckedPrediction, PrimeclueErr> {
+        let prediction = self.predict(input)?;
+        if self.feature_ranges.is_empty() {
+            return PrimeclueErr::result(
+                "Feature ranges are not fitted, see Classifier::fit_feature_ranges".to_string(),
+            );
         }
+        let columns = self.input_shape().columns();
+        let extrapolated = self
+            .feature_ranges
+            .iter()
+            .enumerate()
+            .filter_map(|(cell, (min, max))| {
+                let (row, column) = (cell / columns, cell % columns);
+                let value = input.get(row, column);
+                (value < *min || value > *max).then_some(ExtrapolatedFeature {
+                    row,
+                    column,
+                    value,
+                    min: *min,
+                    max: *max,
+                })
+            })
+            .collect();
+        Ok(CheckedPrediction { prediction, extrapolated })
     }
-}
 
-impl Serializable for Classifier {
-    fn serialize(&self, s: &mut Serializator) {
-        s.add(&self.classes);
-        s.add(&self.trees)
+    /// Fails if `shape` is not [`Classifier::input_shape`]
+    pub(crate) fn check_shape(&self, shape: &InputShape) -> Result<(), PrimeclueErr> {
+        if shape != self.input_shape() {
+            return Err(PrimeclueErr::ShapeMismatch {
+                expected: *self.input_shape(),
+                actual: *shape,
+            });
+        }
+        Ok(())
     }
-}
 
-impl Deserializable for Classifier {
-    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
-        let classes = HashMap::deserialize(s)?;
-        let trees = Vec::deserialize(s)?;
-        Ok(Classifier { classes, trees })
+    /// Probability of every class for a single, unlabeled [`Input`]: softmax of outputs
+    /// of the best tree of each class, less its threshold, divided by
+    /// [`Classifier::temperature`]. Probabilities sum to 1.0. Fails if `input` has different
+    /// shape than training data.
+    pub fn predict_proba(&self, input: &Input) -> Result<HashMap<Class, f32>, PrimeclueErr> {
+        self.check_shape(input.input_shape())?;
+        let input = self.normalized_input(input);
+        let trees = self.sorted_trees();
+        let outputs = trees.iter().map(|t| t.tree().execute_input(&input)).collect::<Vec<_>>();
+        Ok(self.probabilities(&trees, &outputs))
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::data::data_set::test::create_simple_data;
-    use crate::data::outcome::Class;
-    use crate::data::InputShape;
-    use crate::exec::classifier::Classifier;
-    use crate::exec::score::Objective::Auc;
-    use crate::exec::score::{Score, Threshold};
-    use crate::exec::scored_tree::ScoredTree;
-    use crate::exec::training_group::TrainingGroup;
-    use crate::exec::tree::Tree;
-    use crate::serialization::serializator::test::test_serialization;
-    use std::collections::HashMap;
+    /// Output of the tree of every class for a single, unlabeled [`Input`], ordered by
+    /// [`Class`], without comparing them to thresholds or to each other. Outputs of trees using
+    /// a missing cell are NaN. Fails if `input` has different shape than training data.
+    pub fn raw_outputs(&self, input: &Input) -> Result<Vec<(Class, f32)>, PrimeclueErr> {
+        self.check_shape(input.input_shape())?;
+        let input = self.normalized_input(input);
+        let mut outputs = self
+            .trees
+            .iter()
+            .map(|t| (t.score().class(), t.tree().execute_input(&input)))
+            .collect::<Vec<_>>();
+        outputs.sort_unstable_by_key(|(class, _)| *class);
+        Ok(outputs)
+    }
 
-    #[test]
-    fn serialize_classifier() {
-        for _ in 0..10 {
-            let (d1, d2, _) = create_simple_data(100).shuffle().into_3_views_split();
-            let mut training_group: TrainingGroup =
-                TrainingGroup::new(d1, d2, Auc, 5, &[]).unwrap();
-            loop {
-                training_group.next_generation();
-                if let Ok(classifier) = training_group.classifier() {
-                    test_serialization(classifier);
-                    break;
-                }
+    /// [`Classifier::raw_outputs`] of every point of `data`, without classes, e.g. to use them
+    /// as features of another [`DataSet`] stacking classifiers. Fails if `data` has different
+    /// input shape than training data.
+    ///
+    /// [`DataSet`]: crate::data::data_set::DataSet
+    pub fn transform(&self, data: &DataView) -> Result<Vec<Vec<f32>>, PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        let data = self.normalized_view(data);
+        let mut trees = self.trees.iter().collect::<Vec<_>>();
+        trees.sort_by_key(|t| t.score().class());
+        let values = trees.par_iter().map(|tree| tree.execute(&data)).collect::<Vec<_>>();
+        Ok((0..data.outcomes().len())
+            .map(|point| values.iter().map(|v| v[point]).collect())
+            .collect())
+    }
+
+    /// Output of the tree of `class` for every point of `data`, the same as the `class` entry
+    /// of [`Classifier::raw_outputs`], e.g. to see if a single tree saturates at
+    /// [`MAX_OUTPUT`](crate::exec::tree::MAX_OUTPUT). Fails if there is no tree of `class`
+    /// or `data` has different input shape than training data.
+    pub fn class_outputs(
+        &self,
+        data: &DataView,
+        class: Class,
+    ) -> Result<Vec<f32>, PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        match self.trees.iter().find(|t| t.score().class() == class) {
+            Some(tree) => Ok(tree.execute(&self.normalized_view(data))),
+            None => PrimeclueErr::result(format!("No tree of class {:?}", class)),
+        }
+    }
+
+    /// Explains [`Classifier::predict`] for `input`: gives outputs of trees of all classes and
+    /// every input value the winning tree uses, with the change of the tree's output that the
+    /// value causes compared to 0, before [`Classifier::normalization`] if there is one.
+    /// Fails if `input` has different shape than training data.
+    pub fn explain(&self, input: &Input) -> Result<PredictionExplanation, PrimeclueErr> {
+        self.check_shape(input.input_shape())?;
+        let normalized = self.normalized_input(input);
+        let trees = self.sorted_trees();
+        let outputs =
+            trees.iter().map(|t| t.tree().execute_input(&normalized)).collect::<Vec<_>>();
+        let prediction = self.confident_winner(&trees, &outputs);
+        // trees are sorted, so the best tree of a class is inserted last
+        let class_outputs = trees
+            .iter()
+            .zip(&outputs)
+            .map(|(tree, output)| {
+                let threshold = tree.score().threshold().value();
+                (tree.score().class(), ClassOutput { output: *output, threshold })
+            })
+            .collect();
+        let winning_tree = prediction.and_then(|(class, _)| {
+            trees
+                .iter()
+                .zip(&outputs)
+                .rev()
+                .find(|(tree, _)| tree.score().class() == class)
+                .map(|(tree, output)| (tree.tree(), *output))
+        });
+        let mut features = vec![];
+        if let Some((tree, output)) = winning_tree {
+            for (row, column) in tree.get_start_node().get_used_cells() {
+                let value = input.get(row, column);
+                let mut occluded = input.clone();
+                occluded.set(row, column, 0.0);
+                let contribution =
+                    output - tree.execute_input(&self.normalized_input(&occluded));
+                let name = self.feature_names.get(column).cloned();
+                features.push(FeatureContribution { row, column, name, value, contribution });
             }
         }
+        Ok(PredictionExplanation {
+            prediction,
+            outputs: class_outputs,
+            features,
+            tree_depth: winning_tree.map(|(tree, _)| tree.depth()),
+            tree_node_count: winning_tree.map(|(tree, _)| tree.node_count()),
+        })
+    }
+
+    /// `trees` and `outputs` must be in the order of [`Classifier::sorted_trees`].
+    fn probabilities(&self, trees: &[&ScoredTree], outputs: &[f32]) -> HashMap<Class, f32> {
+        let logits = self.logits(trees, outputs);
+        let max = logits.values().copied().fold(f32::MIN, f32::max);
+        let exps = logits.into_iter().map(|(c, l)| (c, (l - max).exp())).collect::<Vec<_>>();
+        let sum = exps.iter().map(|(_, e)| e).sum::<f32>();
+        exps.into_iter().map(|(c, e)| (c, e / sum)).collect()
+    }
+
+    /// Output of the best tree of each class less its threshold, divided by temperature
+    fn logits(&self, trees: &[&ScoredTree], outputs: &[f32]) -> HashMap<Class, f32> {
+        // trees are sorted, so the best tree of a class is inserted last
+        trees
+            .iter()
+            .zip(outputs)
+            .map(|(tree, output)| (tree.score().class(), self.logit(tree, *output)))
+            .collect()
+    }
+
+    fn logit(&self, tree: &ScoredTree, output: f32) -> f32 {
+        let margin = output - tree.score().threshold().value();
+        let logit = margin / self.temperature;
+        if logit.is_nan() {
+            f32::MIN
+        } else {
+            logit.clamp(f32::MIN, f32::MAX)
+        }
+    }
+
+    /// Combines binary classifiers, each trained on [`DataView::label_view`] of its class,
+    /// into a multi-label classifier with the best `Class::from(true)` tree of each.
+    /// Every class of `classes` must have exactly one classifier.
+    pub fn from_label_classifiers(
+        classes: impl IntoIterator<Item = (Class, String)>,
+        label_classifiers: Vec<(Class, Classifier)>,
+    ) -> Result<Classifier, PrimeclueErr> {
+        let classes: BTreeMap<Class, String> = classes.into_iter().collect();
+        let feature_names =
+            label_classifiers.first().map(|(_, c)| c.feature_names.clone()).unwrap_or_default();
+        let mut trees = Vec::with_capacity(label_classifiers.len());
+        for (class, classifier) in label_classifiers {
+            if !classes.contains_key(&class) {
+                return PrimeclueErr::result(format!("Unknown class {}", class));
+            }
+            if trees.iter().any(|t: &ScoredTree| t.score().class() == class) {
+                return PrimeclueErr::result(format!(
+                    "More than one classifier for class {}",
+                    class
+                ));
+            }
+            let mut tree = match classifier
+                .sorted_trees()
+                .into_iter()
+                .rev()
+                .find(|t| t.score().class() == Class::from(true))
+            {
+                Some(tree) => tree.clone(),
+                None => {
+                    return PrimeclueErr::result(format!(
+                        "Classifier for class {} is not binary",
+                        class
+                    ))
+                }
+            };
+            let score = tree.score();
+            let score =
+                Score::new(score.objective().clone(), class, score.value(), score.threshold());
+            tree.set_score(score);
+            trees.push(tree);
+        }
+        let mut classifier = Classifier::new(classes, trees)?;
+        classifier.set_feature_names(feature_names)?;
+        Ok(classifier)
+    }
+
+    /// Independent probability of every class for multi-label prediction: sigmoid of output
+    /// of the best tree of each class less its threshold, divided by
+    /// [`Classifier::temperature`]. Fails if `input` has different shape than training data.
+    pub fn predict_label_probabilities(
+        &self,
+        input: &Input,
+    ) -> Result<HashMap<Class, f32>, PrimeclueErr> {
+        self.check_shape(input.input_shape())?;
+        let input = self.normalized_input(input);
+        let trees = self.sorted_trees();
+        let outputs = trees.iter().map(|t| t.tree().execute_input(&input)).collect::<Vec<_>>();
+        Ok(self.label_probabilities(&trees, &outputs))
+    }
+
+    /// Classes with [`Classifier::predict_label_probabilities`] above `threshold`.
+    /// Fails if `input` has different shape than training data.
+    pub fn predict_labels(
+        &self,
+        input: &Input,
+        threshold: f32,
+    ) -> Result<HashSet<Class>, PrimeclueErr> {
+        Ok(self
+            .predict_label_probabilities(input)?
+            .into_iter()
+            .filter(|(_, p)| *p > threshold)
+            .map(|(class, _)| class)
+            .collect())
+    }
+
+    /// Scores [`Classifier::predict_labels`] against `labels` of every point of `data`.
+    /// Fails if `data` has different shape than training data or `labels` count doesn't
+    /// match points count.
+    pub fn score_multi_label(
+        &self,
+        data: &DataView,
+        labels: &[MultiLabelOutcome],
+        threshold: f32,
+    ) -> Result<MultiLabelScore, PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        if labels.len() != data.outcomes().len() {
+            return PrimeclueErr::result(format!(
+                "Got {} labels for {} points",
+                labels.len(),
+                data.outcomes().len()
+            ));
+        }
+        let data = self.normalized_view(data);
+        let trees = self.sorted_trees();
+        let values = trees.iter().map(|tree| tree.execute(&data)).collect::<Vec<_>>();
+        // true positives, false positives and false negatives of every class
+        let mut counts =
+            self.classes.keys().map(|c| (*c, (0.0, 0.0, 0.0))).collect::<BTreeMap<_, _>>();
+        for (point, label) in labels.iter().enumerate() {
+            let outputs = values.iter().map(|v| v[point]).collect::<Vec<_>>();
+            for (class, probability) in self.label_probabilities(&trees, &outputs) {
+                let count = counts.entry(class).or_default();
+                match (probability > threshold, label.contains(class)) {
+                    (true, true) => count.0 += 1.0,
+                    (true, false) => count.1 += 1.0,
+                    (false, true) => count.2 += 1.0,
+                    (false, false) => {}
+                }
+            }
+        }
+        let errors = counts.values().map(|(_, fp, fn_)| fp + fn_).sum::<f32>();
+        let hamming_loss = errors / (labels.len() * counts.len()) as f32;
+        let macro_f1 = counts.values().map(|(tp, fp, fn_)| f1(*tp, *fp, *fn_)).sum::<f32>()
+            / counts.len() as f32;
+        Ok(MultiLabelScore { hamming_loss, macro_f1 })
+    }
+
+    fn label_probabilities(
+        &self,
+        trees: &[&ScoredTree],
+        outputs: &[f32],
+    ) -> HashMap<Class, f32> {
+        self.logits(trees, outputs)
+            .into_iter()
+            .map(|(c, l)| (c, 1.0 / (1.0 + (-l).exp())))
+            .collect()
+    }
+
+    /// [`Classifier::predict_proba`] for every point of `data`
+    pub(crate) fn predict_proba_view(&self, data: &DataView) -> Vec<HashMap<Class, f32>> {
+        let data = self.normalized_view(data);
+        let trees = self.sorted_trees();
+        let values = trees.iter().map(|tree| tree.execute(&data)).collect::<Vec<_>>();
+        (0..data.cells().get(0, 0).len())
+            .map(|point| {
+                let outputs = values.iter().map(|v| v[point]).collect::<Vec<_>>();
+                self.probabilities(&trees, &outputs)
+            })
+            .collect()
+    }
+
+    fn confident_winner(&self, trees: &[&ScoredTree], outputs: &[f32]) -> Option<(Class, f32)> {
+        let class = self.winner(trees, outputs)?;
+        if self.aggregation == ClassScoreAggregation::Softmax {
+            return Some((class, self.probabilities(trees, outputs)[&class]));
+        }
+        let mut winning = 0.0;
+        let mut total = 0.0;
+        let mut fired = 0;
+        for (tree, output) in trees.iter().zip(outputs) {
+            if let Some(true) = tree.guess(*output) {
+                let margin = output - tree.score().threshold().value();
+                total += margin;
+                fired += 1;
+                if tree.score().class() == class {
+                    winning = margin;
+                }
+            }
+        }
+        let confidence = if total > 0.0 { winning / total } else { 1.0 / fired as f32 };
+        Some((class, confidence))
+    }
+
+    /// Renders every class's tree as an infix formula, one class per line, together with
+    /// its threshold: `label: formula >= threshold`. A class is predicted when its formula
+    /// reaches the threshold; if several do, the one with the best training score wins.
+    /// Columns are named with [`Classifier::feature_names`] if there are any.
+    #[must_use]
+    pub fn to_expression_string(&self) -> String {
+        let feature_names = self.feature_name_refs();
+        let mut lines = Vec::with_capacity(self.trees.len());
+        for tree in &self.trees {
+            let score = tree.score();
+            let label = self.classes.get(&score.class()).map_or("", String::as_str);
+            lines.push(format!(
+                "{}: {} >= {}",
+                label,
+                tree.get_start_node().to_named_expression(&feature_names),
+                score.threshold().value()
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// Renders trees as a Graphviz DOT digraph, with one cluster per class. Nodes are
+    /// operators or terminals, edges lead to operands, columns are labeled with
+    /// [`Classifier::feature_names`], or by index if there are none.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with_feature_names(&self.feature_name_refs())
+    }
+
+    /// Same as [`Classifier::to_dot`] but columns are labeled with `feature_names`,
+    /// if there's a name for the column.
+    #[must_use]
+    pub fn to_dot_with_feature_names(&self, feature_names: &[&str]) -> String {
+        let mut lines = vec!["digraph classifier {".to_string()];
+        for (index, tree) in self.trees.iter().enumerate() {
+            let score = tree.score();
+            let label = self.classes.get(&score.class()).map_or("", String::as_str);
+            let label = format!("{} >= {}", label, score.threshold().value());
+            lines.push(format!("subgraph cluster_{} {{", index));
+            lines.push(format!("label=\"{}\";", escape_dot(&label)));
+            let prefix = format!("c{}n", index);
+            tree.get_start_node().add_dot_nodes(&prefix, &mut 0, feature_names, &mut lines);
+            lines.push("}".to_string());
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    /// Renders classifier as a standalone Rust function `fn_name(features: &[f32]) -> u16`
+    /// that depends on `std` only. `features` are values of an [`Input`] row by row. The
+    /// function returns id of the class [`Classifier::predict`] predicts for the same input,
+    /// or `u16::MAX` if it predicts none. It panics if there are fewer features than
+    /// [`Classifier::input_shape`] requires. Raw features are expected, the function applies
+    /// [`Classifier::normalization`] itself, if there is one. Tree outputs are bounded by
+    /// [`MAX_OUTPUT`] the same way as in prediction. Fails if [`Classifier::aggregation`] is
+    /// not [`ClassScoreAggregation::Priority`] or any tree uses a
+    /// [`Terminal`](crate::exec::functions::Terminal).
+    pub fn to_rust_source(&self, fn_name: &str) -> Result<String, PrimeclueErr> {
+        if self.aggregation != ClassScoreAggregation::Priority {
+            return PrimeclueErr::result(format!(
+                "Rust source export of {:?} aggregation is not supported",
+                self.aggregation
+            ));
+        }
+        if self.sorted_trees().iter().any(|t| t.get_start_node().uses_terminals()) {
+            return PrimeclueErr::result(
+                "Rust source export of terminals is not supported".to_string(),
+            );
+        }
+        let columns = self.input_shape().columns();
+        let mut lines = vec![
+            "#[allow(clippy::all, unused_imports)]".to_string(),
+            format!("pub fn {}(features: &[f32]) -> u16 {{", fn_name),
+            "    use std::cmp::Ordering;".to_string(),
+            "    use std::f32::consts::{E, PI};".to_string(),
+        ];
+        let kinds = self.used_node_kinds().into_iter().collect::<BTreeSet<_>>();
+        for kind in kinds {
+            lines.push(format!("    {}", kind.scalar_source().code));
+        }
+        if let Some(normalization) = &self.normalization {
+            let values = |values: &[f32]| {
+                values.iter().map(|v| rust_f32(*v)).collect::<Vec<_>>().join(", ")
+            };
+            lines.push(format!("    let centers = [{}];", values(normalization.centers())));
+            lines.push(format!("    let scales = [{}];", values(normalization.scales())));
+            lines.push("    let features = features[..centers.len()]".to_string());
+            lines.push("        .iter()".to_string());
+            lines.push("        .zip(centers.iter().zip(scales.iter()))".to_string());
+            lines.push("        .map(|(v, (c, s))| (v - c) / s)".to_string());
+            lines.push("        .collect::<Vec<f32>>();".to_string());
+        }
+        lines.push(format!("    let max_output = {};", rust_f32(MAX_OUTPUT)));
+        lines.push("    let mut class = u16::MAX;".to_string());
+        // the last, i.e. best scored, tree that guesses true wins, as in `Classifier::winner`
+        for tree in self.sorted_trees() {
+            let score = tree.score();
+            lines.push(format!(
+                "    let value = ({}).clamp(-max_output, max_output);",
+                tree.get_start_node().to_rust_expression(columns)
+            ));
+            lines.push(format!(
+                "    if !value.is_nan() && value >= {} {{",
+                rust_f32(score.threshold().value())
+            ));
+            lines.push(format!("        class = {};", score.class().value()));
+            lines.push("    }".to_string());
+        }
+        lines.push("    class".to_string());
+        lines.push("}".to_string());
+        Ok(lines.join("\n"))
+    }
+
+    /// Saves classifier as an ONNX model that takes float tensor `features` of shape
+    /// `[N, rows * columns]`, with values of every [`Input`] row by row, and gives int64 tensor
+    /// `class` of shape `[N]`: id of the class [`Classifier::predict`] predicts for the point,
+    /// or -1 if it predicts none. Exponent, power, logarithm and trigonometric functions may
+    /// be computed by the runtime with different rounding than by `std`. The model applies
+    /// [`Classifier::normalization`] itself, if there is one. Tree outputs are bounded by
+    /// [`MAX_OUTPUT`] the same way as in prediction. Fails if [`Classifier::aggregation`] is not
+    /// [`ClassScoreAggregation::Priority`] or any tree uses a
+    /// [`Terminal`](crate::exec::functions::Terminal).
+    pub fn to_onnx(&self, path: &Path) -> Result<(), PrimeclueErr> {
+        if self.aggregation != ClassScoreAggregation::Priority {
+            return PrimeclueErr::result(format!(
+                "ONNX export of {:?} aggregation is not supported",
+                self.aggregation
+            ));
+        }
+        if self.sorted_trees().iter().any(|t| t.get_start_node().uses_terminals()) {
+            return PrimeclueErr::result(
+                "ONNX export of terminals is not supported".to_string(),
+            );
+        }
+        let columns = self.input_shape().columns();
+        let mut graph = OnnxGraph::default();
+        if let Some(normalization) = &self.normalization {
+            graph.normalize(normalization.centers(), normalization.scales());
+        }
+        let trees = self
+            .sorted_trees()
+            .iter()
+            .map(|tree| {
+                let score = tree.score();
+                let output = tree.get_start_node().add_onnx_nodes(columns, &mut graph);
+                (output, score.threshold().value(), score.class().value())
+            })
+            .collect::<Vec<_>>();
+        let model = graph.into_model(self.input_shape().rows() * columns, &trees);
+        fs::write(path, model)
+            .map_err(|e| PrimeclueErr::from(format!("Unable to write {:?}: {}", path, e)))
+    }
+
+    /// Returns raw output for `input`. Meant for classifiers trained with a regression
+    /// [`Objective`], which consist of a single tree. Fails if `input` has different shape
+    /// than training data.
+    pub fn predict_value(&self, input: &Input) -> Result<f32, PrimeclueErr> {
+        self.check_shape(input.input_shape())?;
+        Ok(self.trees[0].tree().execute_input(&self.normalized_input(input)))
+    }
+
+    fn regression_error(&self, data: &DataView, objective: Objective) -> Option<f32> {
+        if data.outcomes().iter().any(|o| o.target().is_none()) {
+            return None;
+        }
+        let tree = &self.trees[0];
+        let outcomes = tree
+            .execute(&self.normalized_view(data))
+            .into_iter()
+            .zip(data.outcomes().iter().copied())
+            .collect::<Vec<_>>();
+        let score = objective.score(tree.score().threshold(), &outcomes, tree.score().class());
+        Some(score.value())
+    }
+
+    fn execute_for_auc(
+        &self,
+        data: &DataView,
+        auc: fn(&[(f32, Outcome)], Class) -> f32,
+    ) -> Option<f32> {
+        let data = self.normalized_view(data);
+        let mut sum_score = 0.0;
+        for tree in &self.trees {
+            sum_score += Classifier::calc_tree_auc(tree, &data, auc)?;
+        }
+        Some(sum_score / self.trees.len() as f32)
+    }
+
+    /// Scores this classifier on `data`, executing every tree once. Fails if `data` has
+    /// different input shape or classes than training data or any tree gives a non-finite
+    /// output. Trees and points are processed in parallel, but the score is the same
+    /// regardless of number of threads.
+    pub fn score(&self, data: &DataView) -> Result<ClassifierScore, PrimeclueErr> {
+        self.check_shape(data.input_shape())?;
+        let data = self.normalized_view(data);
+        let trees = self.sorted_trees();
+        let values = trees.par_iter().map(|tree| tree.execute(&data)).collect::<Vec<_>>();
+        let aucs = trees
+            .par_iter()
+            .zip(&values)
+            .map(|(tree, values)| {
+                if values.iter().any(|v| !v.is_finite()) {
+                    return PrimeclueErr::result(format!(
+                        "Tree of class {} gives non-finite output",
+                        tree.score().class()
+                    ));
+                }
+                let mut outcomes = values
+                    .iter()
+                    .copied()
+                    .zip(data.outcomes().iter().copied())
+                    .collect::<Vec<_>>();
+                outcomes.sort_unstable_by(|(v1, _), (v2, _)| v1.partial_cmp(v2).unwrap());
+                let class = tree.score().class();
+                Ok((calculate_auc(&outcomes, class), calculate_auc_with_ties(&outcomes, class)))
+            })
+            .collect::<Vec<_>>();
+        // summed in tree order so the result doesn't depend on number of threads
+        let mut sum_auc = 0.0;
+        let mut sum_macro_auc = 0.0;
+        for aucs in aucs {
+            let (auc, macro_auc) = aucs?;
+            sum_auc += auc;
+            sum_macro_auc += macro_auc;
+        }
+        let predictions = (0..data.cells().get(0, 0).len())
+            .into_par_iter()
+            .map(|point| {
+                let outputs = values.iter().map(|v| v[point]).collect::<Vec<_>>();
+                self.winner(&trees, &outputs)
+            })
+            .collect::<Vec<_>>();
+        let mut score = ClassifierScore::from_predictions(
+            &self.classes,
+            &predictions,
+            &data,
+            sum_auc / trees.len() as f32,
+            sum_macro_auc / trees.len() as f32,
+        )
+        .ok_or_else(|| "Data classes don't match classifier classes".to_string())?;
+        if data.outcomes().iter().all(|o| o.target().is_some()) {
+            let (tree, values) = trees
+                .iter()
+                .zip(&values)
+                .find(|(tree, _)| std::ptr::eq(**tree, &self.trees[0]))
+                .unwrap();
+            let outcomes =
+                values.iter().copied().zip(data.outcomes().iter().copied()).collect::<Vec<_>>();
+            let (class, threshold) = (tree.score().class(), tree.score().threshold());
+            score.mean_squared_error =
+                Some(Objective::MeanSquaredError.score(threshold, &outcomes, class).value());
+            score.mean_absolute_error =
+                Some(Objective::MeanAbsoluteError.score(threshold, &outcomes, class).value());
+        }
+        Ok(score)
+    }
+
+    /// Same as [`Classifier::score`], together with metrics derived from it: per-class
+    /// precision and recall, Matthews correlation coefficient and Cohen's kappa
+    pub fn evaluate(&self, data: &DataView) -> Result<EvaluationReport, PrimeclueErr> {
+        let score = self.score(data)?;
+        let per_class = |metric: fn(&ClassifierScore, Class) -> Option<f32>| {
+            self.classes
+                .iter()
+                .filter_map(|(class, label)| Some((label.clone(), metric(&score, *class)?)))
+                .collect::<HashMap<_, _>>()
+        };
+        let precision = per_class(ClassifierScore::precision);
+        let recall = per_class(ClassifierScore::recall);
+        let mcc = score.mcc();
+        let cohens_kappa = score.cohens_kappa();
+        Ok(EvaluationReport { score, precision, recall, mcc, cohens_kappa })
+    }
+
+    /// Scores binary classifier labelling a point `positive` when its
+    /// [`Classifier::predict_proba`] probability of `positive` class exceeds `threshold`,
+    /// instead of choosing the winning tree. Allows to trade precision for recall.
+    /// Returns `None` if there aren't exactly two classes, `positive` is not one of them or
+    /// `data` has different input shape than training data.
+    pub fn score_with_threshold(
+        &self,
+        data: &DataView,
+        positive: Class,
+        threshold: f32,
+    ) -> Option<ClassifierScore> {
+        self.check_shape(data.input_shape()).ok()?;
+        let negative = self.negative_class(positive)?;
+        let probabilities = self.positive_probabilities(data, positive);
+        let predictions = threshold_predictions(&probabilities, positive, negative, threshold);
+        let auc = self.execute_for_auc(data, calculate_auc)?;
+        let macro_auc = self.execute_for_auc(data, calculate_auc_with_ties)?;
+        let mut score = ClassifierScore::from_predictions(
+            &self.classes,
+            &predictions,
+            data,
+            auc,
+            macro_auc,
+        )?;
+        score.mean_squared_error = self.regression_error(data, Objective::MeanSquaredError);
+        score.mean_absolute_error = self.regression_error(data, Objective::MeanAbsoluteError);
+        Some(score)
+    }
+
+    /// Threshold for [`Classifier::score_with_threshold`] maximizing `objective` on `data`.
+    /// Probabilities of points are tried as thresholds, at most 256 of them evenly spread.
+    /// Returns `None` if [`Classifier::score_with_threshold`] would, or `objective` doesn't
+    /// depend on threshold, i.e. for AUCs, regression errors and Pareto objectives.
+    pub fn best_threshold(
+        &self,
+        data: &DataView,
+        positive: Class,
+        objective: &Objective,
+    ) -> Option<f32> {
+        self.check_shape(data.input_shape()).ok()?;
+        let negative = self.negative_class(positive)?;
+        let probabilities = self.positive_probabilities(data, positive);
+        let mut candidates = probabilities.clone();
+        candidates.push(0.0);
+        candidates.sort_by(|p1, p2| p1.partial_cmp(p2).unwrap_or(Equal));
+        candidates.dedup();
+        let step = (candidates.len() as f32 / BEST_THRESHOLD_CANDIDATES as f32).max(1.0);
+        let mut best: Option<(f32, f32)> = None;
+        for i in 0..candidates.len().min(BEST_THRESHOLD_CANDIDATES) {
+            let threshold = candidates[(i as f32 * step) as usize];
+            let predictions =
+                threshold_predictions(&probabilities, positive, negative, threshold);
+            let value = match objective {
+                Objective::Accuracy
+                | Objective::Cost
+                | Objective::WeightedReward(_)
+                | Objective::F1
+                | Objective::BalancedAccuracy
+                | Objective::Hamming => {
+                    let score = ClassifierScore::from_predictions(
+                        &self.classes,
+                        &predictions,
+                        data,
+                        0.0,
+                        0.0,
+                    )?;
+                    match objective {
+                        Objective::Accuracy => score.accuracy,
+                        Objective::Cost => score.cost,
+                        Objective::WeightedReward(multipliers) => {
+                            score.weighted_cost(multipliers)
+                        }
+                        Objective::BalancedAccuracy => score.balanced_accuracy(),
+                        Objective::Hamming => score.hamming(),
+                        _ => score.f1,
+                    }
+                }
+                Objective::Custom(fun) => {
+                    let pairs = data
+                        .outcomes()
+                        .iter()
+                        .zip(&predictions)
+                        .map(|(o, p)| {
+                            (
+                                Class::from(o.class() == positive),
+                                Class::from(*p == Some(positive)),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    fun(&pairs)
+                }
+                Objective::Auc
+                | Objective::MacroAuc
+                | Objective::MeanSquaredError
+                | Objective::MeanAbsoluteError
+                | Objective::Pareto(_) => return None,
+            };
+            if best.is_none_or(|(_, best_value)| value > best_value) {
+                best = Some((threshold, value));
+            }
+        }
+        best.map(|(threshold, _)| threshold)
+    }
+
+    fn negative_class(&self, positive: Class) -> Option<Class> {
+        if self.classes.len() != 2 || !self.classes.contains_key(&positive) {
+            return None;
+        }
+        self.classes.keys().copied().find(|class| *class != positive)
+    }
+
+    fn positive_probabilities(&self, data: &DataView, positive: Class) -> Vec<f32> {
+        let data = self.normalized_view(data);
+        let trees = self.sorted_trees();
+        let values = trees.iter().map(|tree| tree.execute(&data)).collect::<Vec<_>>();
+        (0..data.cells().get(0, 0).len())
+            .map(|point| {
+                let outputs = values.iter().map(|v| v[point]).collect::<Vec<_>>();
+                self.probabilities(&trees, &outputs).get(&positive).copied().unwrap_or(0.0)
+            })
+            .collect()
+    }
+
+    fn calc_tree_auc(
+        tree: &ScoredTree,
+        data: &DataView,
+        auc: fn(&[(f32, Outcome)], Class) -> f32,
+    ) -> Option<f32> {
+        let values = tree.execute(data);
+        if values.iter().any(|v| !v.is_finite()) {
+            None
+        } else {
+            let mut outcomes =
+                values.into_iter().zip(data.outcomes().iter().copied()).collect::<Vec<_>>();
+            outcomes.sort_unstable_by(|(v1, _), (v2, _)| v1.partial_cmp(v2).unwrap());
+            Some(auc(&outcomes, tree.score().class()))
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash, which unlike [`std::collections::hash_map::DefaultHasher`] is
+/// guaranteed not to change between Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME))
+}
+
+/// Sample of the standard normal distribution, with Box-Muller transform
+fn gaussian(rng: &mut XorShiftRng) -> f32 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    ((-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()) as f32
+}
+
+fn threshold_predictions(
+    probabilities: &[f32],
+    positive: Class,
+    negative: Class,
+    threshold: f32,
+) -> Vec<Option<Class>> {
+    probabilities
+        .iter()
+        .map(|p| Some(if *p > threshold { positive } else { negative }))
+        .collect()
+}
+
+fn macro_f1(
+    predicted_count: &HashMap<String, usize>,
+    correct_count: &HashMap<String, f32>,
+    expected_count: &HashMap<&str, usize>,
+) -> f32 {
+    let mut sum = 0.0;
+    for (label, expected) in expected_count {
+        let predicted = predicted_count.get(*label).copied().unwrap_or(0);
+        let correct = correct_count.get(*label).copied().unwrap_or(0.0);
+        sum += 2.0 * correct / (predicted + expected) as f32;
+    }
+    // labels that were predicted but never expected have F1 of 0
+    let never_expected =
+        predicted_count.keys().filter(|l| !expected_count.contains_key(l.as_str())).count();
+    sum / (expected_count.len() + never_expected) as f32
+}
+
+impl Serializable for Classifier {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add(&self.classes);
+        s.add(&self.trees);
+        if (self.temperature - 1.0).abs() > f32::EPSILON {
+            s.add_str(TEMPERATURE_TOKEN);
+            s.add(&self.temperature);
+        }
+        if self.aggregation != ClassScoreAggregation::Priority {
+            s.add_str(AGGREGATION_TOKEN);
+            s.add(&self.aggregation);
+            s.add(&self.output_ranges);
+        }
+        if let Some(normalization) = &self.normalization {
+            s.add_str(NORMALIZATION_TOKEN);
+            s.add(normalization);
+        }
+        if !self.feature_ranges.is_empty() {
+            s.add_str(FEATURE_RANGES_TOKEN);
+            s.add(&self.feature_ranges);
+        }
+        if !self.feature_names.is_empty() {
+            s.add_str(FEATURE_NAMES_TOKEN);
+            s.add(&self.feature_names);
+        }
+    }
+}
+
+impl Deserializable for Classifier {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        let classes = BTreeMap::deserialize(s)?;
+        let trees = Vec::deserialize(s)?;
+        let temperature = if s.peek_token() == Some(TEMPERATURE_TOKEN) {
+            s.next_token()?;
+            f32::deserialize(s)?
+        } else {
+            1.0
+        };
+        let (aggregation, output_ranges) = if s.peek_token() == Some(AGGREGATION_TOKEN) {
+            s.next_token()?;
+            (ClassScoreAggregation::deserialize(s)?, HashMap::deserialize(s)?)
+        } else {
+            (ClassScoreAggregation::Priority, HashMap::new())
+        };
+        let normalization = if s.peek_token() == Some(NORMALIZATION_TOKEN) {
+            s.next_token()?;
+            Some(Normalization::deserialize(s)?)
+        } else {
+            None
+        };
+        let feature_ranges = if s.peek_token() == Some(FEATURE_RANGES_TOKEN) {
+            s.next_token()?;
+            Vec::deserialize(s)?
+        } else {
+            vec![]
+        };
+        let feature_names = if s.peek_token() == Some(FEATURE_NAMES_TOKEN) {
+            s.next_token()?;
+            Vec::deserialize(s)?
+        } else {
+            vec![]
+        };
+        Ok(Classifier {
+            classes,
+            trees,
+            temperature,
+            aggregation,
+            output_ranges,
+            normalization,
+            feature_ranges,
+            feature_names,
+        })
+    }
+}
+
+impl Serializable for ClassScoreAggregation {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(match self {
+            ClassScoreAggregation::Priority => "Priority",
+            ClassScoreAggregation::Raw => "Raw",
+            ClassScoreAggregation::MinMaxNormalized => "MinMaxNormalized",
+            ClassScoreAggregation::Softmax => "Softmax",
+        })
+    }
+}
+
+impl Deserializable for ClassScoreAggregation {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "Priority" => Ok(ClassScoreAggregation::Priority),
+            "Raw" => Ok(ClassScoreAggregation::Raw),
+            "MinMaxNormalized" => Ok(ClassScoreAggregation::MinMaxNormalized),
+            "Softmax" => Ok(ClassScoreAggregation::Softmax),
+            other => Err(format!("Invalid class score aggregation {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::test::create_simple_data;
+    use crate::data::data_set::DataSet;
+    use crate::data::disk_data_view::DiskDataView;
+    use crate::data::outcome::Class;
+    use crate::data::outcome::MultiLabelOutcome;
+    use crate::data::{Input, InputShape, Outcome, Point};
+    use crate::error::PrimeclueErr;
+    use crate::exec::classifier::{
+        fnv1a, ClassOutput, ClassScoreAggregation, Classifier, ClassifierScore,
+        ExtrapolatedFeature, FeatureContribution, CLASSIFIER_FORMAT_VERSION, CLASSIFIER_MAGIC,
+    };
+    use crate::exec::functions::{MATH_CONSTANTS, TWO_ARG_FUNCTIONS};
+    use crate::exec::node::{rust_f32, Node, Weighted};
+    use crate::exec::onnx::test::{decode, Field};
+    use crate::exec::score::Objective::{Accuracy, Auc, BalancedAccuracy, Hamming};
+    use crate::exec::score::{Score, Threshold};
+    use crate::exec::scored_tree::ScoredTree;
+    use crate::exec::training_group::TrainingGroup;
+    use crate::exec::tree::test::create_short_tree;
+    use crate::exec::tree::Tree;
+    use crate::serialization::serializator::test::test_serialization;
+    use crate::serialization::{Deserializable, Serializator};
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::Path;
+    use std::process::Command;
+    use std::time::Duration;
+    use std::{env, fs, process};
+
+    #[test]
+    fn serialize_classifier() {
+        for _ in 0..10 {
+            let (d1, d2, _) = create_simple_data(100).shuffle().into_3_views_split();
+            let mut training_group: TrainingGroup =
+                TrainingGroup::new(d1, d2, Auc, 5, &[]).unwrap();
+            loop {
+                training_group.next_generation();
+                if let Ok(classifier) = training_group.classifier() {
+                    test_serialization(classifier);
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn save_load_classifier() {
+        let path = env::temp_dir().join(format!("primeclue_classifier_{}.ssd", process::id()));
+        for _ in 0..10 {
+            let (d1, d2, d3) = create_simple_data(300).shuffle().into_3_views_split();
+            let mut training_group = TrainingGroup::new(d1, d2, Auc, 5, &[]).unwrap();
+            let mut classifier = None;
+            for _ in 0..20 {
+                training_group.next_generation();
+                classifier = training_group.classifier().ok();
+                if classifier.is_some() {
+                    break;
+                }
+            }
+            let scored = classifier.and_then(|c| c.score(&d3).ok().map(|score| (c, score)));
+            if let Some((classifier, score)) = scored {
+                classifier.save(&path).unwrap();
+                let loaded = Classifier::load(&path).unwrap();
+                fs::remove_file(&path).unwrap();
+                assert_eq!(loaded, classifier);
+                let loaded_score = loaded.score(&d3).unwrap();
+                assert!((loaded_score.auc - score.auc).abs() < f32::EPSILON);
+                assert!((loaded_score.accuracy - score.accuracy).abs() < f32::EPSILON);
+                assert!((loaded_score.cost - score.cost).abs() < f32::EPSILON);
+                assert_eq!(loaded_score.label_count_map, score.label_count_map);
+                return;
+            }
+        }
+        panic!("Unable to score classifier")
+    }
+
+    #[test]
+    fn parallel_score_is_deterministic() {
+        for _ in 0..10 {
+            let (d1, d2, d3) = create_simple_data(300).shuffle().into_3_views_split();
+            let mut training_group = TrainingGroup::new(d1, d2, Auc, 5, &[]).unwrap();
+            let mut classifier = None;
+            for _ in 0..20 {
+                training_group.next_generation();
+                classifier = training_group.classifier().ok();
+                if classifier.is_some() {
+                    break;
+                }
+            }
+            let classifier = match classifier {
+                Some(classifier) => classifier,
+                None => continue,
+            };
+            let score_with_threads = |threads| {
+                let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+                pool.install(|| classifier.score(&d3)).ok()
+            };
+            let serial = match score_with_threads(1) {
+                Some(score) => score,
+                None => continue,
+            };
+            for threads in &[2, 4, 7] {
+                let parallel = score_with_threads(*threads).unwrap();
+                assert_eq!(parallel.auc.to_bits(), serial.auc.to_bits());
+                assert_eq!(parallel.macro_auc.to_bits(), serial.macro_auc.to_bits());
+                assert_eq!(parallel.accuracy.to_bits(), serial.accuracy.to_bits());
+                assert_eq!(parallel.cost.to_bits(), serial.cost.to_bits());
+                assert_eq!(parallel.f1.to_bits(), serial.f1.to_bits());
+                assert_eq!(parallel.confusion_matrix, serial.confusion_matrix);
+                assert_eq!(parallel.label_count_map, serial.label_count_map);
+                assert_eq!(parallel.label_accuracy_map, serial.label_accuracy_map);
+            }
+            return;
+        }
+        panic!("Unable to score classifier")
+    }
+
+    #[test]
+    fn save_load_normalized_classifier() {
+        let path = env::temp_dir().join(format!("primeclue_normalized_{}.ssd", process::id()));
+        for _ in 0..10 {
+            let data = create_simple_data(300).shuffle();
+            let input = data.iter().next().unwrap().data().0.clone();
+            let (d1, d2, d3) = data.into_3_views_split();
+            let (d1, normalization) = d1.standardized();
+            let d2 = d2.normalized(&normalization).unwrap();
+            let normalized_d3 = d3.normalized(&normalization).unwrap();
+            let mut training_group = TrainingGroup::new(d1, d2, Auc, 5, &[]).unwrap();
+            let mut classifier = None;
+            for _ in 0..20 {
+                training_group.next_generation();
+                classifier = training_group.classifier().ok();
+                if classifier.is_some() {
+                    break;
+                }
+            }
+            let scored =
+                classifier.and_then(|c| c.score(&normalized_d3).ok().map(|score| (c, score)));
+            if let Some((mut classifier, score)) = scored {
+                let mut normalized_input = input.clone();
+                normalization.apply(&mut normalized_input).unwrap();
+                let prediction = classifier.predict(&normalized_input).unwrap();
+
+                assert!(classifier
+                    .set_normalization(Some(create_simple_data(10).into_view().standardized().1))
+                    .is_ok());
+                let wrong_shape = DataSet::new(HashMap::new());
+                assert!(classifier
+                    .set_normalization(Some(wrong_shape.into_view().standardized().1))
+                    .is_err());
+                classifier.set_normalization(Some(normalization.clone())).unwrap();
+                classifier.save(&path).unwrap();
+                let loaded = Classifier::load(&path).unwrap();
+                fs::remove_file(&path).unwrap();
+                assert_eq!(loaded, classifier);
+                assert_eq!(loaded.normalization(), Some(&normalization));
+                let loaded_score = loaded.score(&d3).unwrap();
+                assert!((loaded_score.auc - score.auc).abs() < f32::EPSILON);
+                assert!((loaded_score.accuracy - score.accuracy).abs() < f32::EPSILON);
+                assert_eq!(loaded_score.label_count_map, score.label_count_map);
+                assert_eq!(loaded.predict(&input).unwrap(), prediction);
+                return;
+            }
+        }
+        panic!("Unable to score classifier")
+    }
+
+    #[test]
+    fn load_invalid_classifier_file() {
+        let path =
+            env::temp_dir().join(format!("primeclue_not_classifier_{}.ssd", process::id()));
+        let mut s = Serializator::new();
+        s.add(&create_simple_data(10));
+        fs::write(&path, s.to_bytes()).unwrap();
+        let r = Classifier::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn load_older_format_classifier() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/classifier_v1.ssd");
+        let loaded = Classifier::load(&path).unwrap();
+        assert_eq!(loaded, create_threshold_classifier());
+    }
+
+    #[test]
+    fn load_newer_format_classifier() {
+        let path =
+            env::temp_dir().join(format!("primeclue_newer_classifier_{}.ssd", process::id()));
+        let mut s = Serializator::new();
+        s.add_str(CLASSIFIER_MAGIC);
+        s.add(&(CLASSIFIER_FORMAT_VERSION + 1));
+        s.add_str("99.0.0");
+        s.add_str("SomethingNew");
+        fs::write(&path, s.to_bytes()).unwrap();
+        let r = Classifier::load(&path);
+        fs::remove_file(&path).unwrap();
+        let err = r.unwrap_err().to_string();
+        assert!(err.contains("newer"));
+        assert!(err.contains("99.0.0"));
+
+        let path =
+            env::temp_dir().join(format!("primeclue_classifier_v2_{}.ssd", process::id()));
+        let classifier = create_threshold_classifier();
+        classifier.save(&path).unwrap();
+        let mut s = Serializator::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(s.next_token().unwrap(), CLASSIFIER_MAGIC);
+        assert_eq!(usize::deserialize(&mut s).unwrap(), CLASSIFIER_FORMAT_VERSION);
+        assert_eq!(s.next_token().unwrap(), env!("CARGO_PKG_VERSION"));
+        assert_eq!(Classifier::deserialize(&mut s).unwrap(), classifier);
+    }
+
+    fn create_threshold_classifier() -> Classifier {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        // class 0 is always guessed, but overridden by class 1 for values >= 2.5
+        let trees = vec![
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(0), 0.5, Threshold::new(-100.0)),
+            ),
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(1), 0.9, Threshold::new(2.5)),
+            ),
+        ];
+        Classifier::new(classes, trees).unwrap()
+    }
+
+    #[test]
+    fn fingerprint() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        let classifier = create_threshold_classifier();
+        let mut s = Serializator::new();
+        s.add(&classifier);
+        let loaded = Classifier::deserialize(&mut s).unwrap();
+        assert_eq!(loaded.fingerprint(), classifier.fingerprint());
+        assert_eq!(create_threshold_classifier().fingerprint(), classifier.fingerprint());
+        let mut trees = classifier.trees().to_vec();
+        trees[1] = ScoredTree::new(
+            create_short_tree(),
+            Score::new(Auc, Class::new(1), 0.9, Threshold::new(3.5)),
+        );
+        let changed = Classifier::new(classifier.get_classes().clone(), trees).unwrap();
+        assert_ne!(changed.fingerprint(), classifier.fingerprint());
+        let mut tempered = create_threshold_classifier();
+        tempered.set_temperature(2.0).unwrap();
+        assert_ne!(tempered.fingerprint(), classifier.fingerprint());
+        let mut named = create_threshold_classifier();
+        named.set_feature_names(vec!["x".to_string()]).unwrap();
+        assert_ne!(named.fingerprint(), classifier.fingerprint());
+    }
+
+    #[test]
+    fn trees() {
+        let classifier = create_threshold_classifier();
+        let classes = classifier.trees().iter().map(|t| t.score().class()).collect::<Vec<_>>();
+        assert_eq!(classes, vec![Class::new(0), Class::new(1)]);
+        for tree in classifier.trees() {
+            assert_eq!(tree.get_start_node(), create_short_tree().get_start_node());
+        }
+    }
+
+    #[test]
+    fn simplified() {
+        let data = create_simple_data(100).into_view();
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        for _ in 0..200 {
+            let trees = (0..2)
+                .map(|class| {
+                    let tree = Tree::new(data.input_shape(), 6, &[], &[], 0.7, 0.3);
+                    let threshold = Threshold::new(tree.execute(&data)[0]);
+                    let score = 0.5 + class as f32 / 10.0;
+                    ScoredTree::new(tree, Score::new(Auc, Class::new(class), score, threshold))
+                })
+                .collect();
+            let classifier = Classifier::new(classes.clone(), trees).unwrap();
+            let simplified = classifier.simplified();
+            for (tree, simple) in classifier.trees().iter().zip(simplified.trees()) {
+                assert!(simple.node_count() <= tree.node_count());
+                for (v1, v2) in tree.execute(&data).iter().zip(simple.execute(&data)) {
+                    assert!(*v1 == v2 || (v1.is_nan() && v2.is_nan()), "{} vs {}", v1, v2);
+                }
+            }
+            match (classifier.score(&data), simplified.score(&data)) {
+                (Ok(score), Ok(simple)) => {
+                    assert_eq!(score.accuracy.to_bits(), simple.accuracy.to_bits());
+                    assert_eq!(score.auc.to_bits(), simple.auc.to_bits());
+                    assert_eq!(score.cost.to_bits(), simple.cost.to_bits());
+                    assert_eq!(score.confusion_matrix, simple.confusion_matrix);
+                }
+                (Err(_), Err(_)) => {}
+                _ => panic!("Only one of classifiers has a score"),
+            }
+        }
+        let mut tree = Tree::new(data.input_shape(), 6, &[], &[], 0.7, 0.3);
+        while tree.simplified() == tree {
+            tree = Tree::new(data.input_shape(), 6, &[], &[], 0.7, 0.3);
+        }
+        let trees = (0..2)
+            .map(|class| {
+                let score = Score::new(Auc, Class::new(class), 0.5, Threshold::new(0.0));
+                ScoredTree::new(tree.clone(), score)
+            })
+            .collect();
+        let classifier = Classifier::new(classes, trees).unwrap();
+        assert_eq!(classifier.simplified(), classifier);
+    }
+
+    #[test]
+    fn classify_disk() {
+        let classifier = create_threshold_classifier();
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        for x in 0..7 {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![x as f32]]).unwrap(),
+                Outcome::new(Class::new(0), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let path = env::temp_dir().join(format!("primeclue_classify_{}.pcdv", process::id()));
+        let disk = DiskDataView::create(&path, &data).unwrap();
+        let classified = classifier.classify_disk(&disk, 3).unwrap();
+        assert_eq!(classified, classifier.classify(&data.into_view()).unwrap());
+        assert_eq!(classified, ["a", "a", "a", "b", "b", "b", "b"]);
+
+        let mut wide = DataSet::new(classifier.get_classes().clone());
+        let input = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        wide.add_data_point(Point::new(input, Outcome::new(Class::new(0), 1.0, -1.0))).unwrap();
+        let disk = DiskDataView::create(&path, &wide).unwrap();
+        assert!(classifier.classify_disk(&disk, 3).is_err());
+        assert!(classifier.classify(&wide.into_view()).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn perturbation_stability() {
+        let classifier = create_threshold_classifier();
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        for x in &[0.0, 1.0, 2.0, 2.49, 3.0, 5.0] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(0), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let view = data.into_view();
+        assert_eq!(classifier.perturbation_stability(&view, 0.0, 5, 1).unwrap(), 1.0);
+        let small = classifier.perturbation_stability(&view, 0.1, 50, 1).unwrap();
+        assert!((small - 5.0 / 6.0).abs() < 1e-6);
+        let large = classifier.perturbation_stability(&view, 10.0, 50, 1).unwrap();
+        assert!(large < small);
+        assert_eq!(classifier.perturbation_stability(&view, 10.0, 50, 1).unwrap(), large);
+        assert!(classifier.perturbation_stability(&view, -1.0, 5, 1).is_err());
+        assert!(classifier.perturbation_stability(&view, f32::NAN, 5, 1).is_err());
+        let empty = DataSet::new(classifier.get_classes().clone()).into_view();
+        assert!(classifier.perturbation_stability(&empty, 0.1, 5, 1).is_err());
+    }
+
+    #[test]
+    fn class_names() {
+        let classifier = create_threshold_classifier();
+        assert_eq!(classifier.class_name(Class::new(1)), Some("b"));
+        assert_eq!(classifier.class_name(Class::new(2)), None);
+        let mut s = Serializator::new();
+        s.add(&classifier);
+        let mut s = Serializator::from_bytes(s.to_bytes()).unwrap();
+        let loaded = Classifier::deserialize(&mut s).unwrap();
+        assert_eq!(loaded.get_classes(), classifier.get_classes());
+
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        for x in &[1.0, 3.0] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(0), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let score = classifier.score(&data.into_view()).unwrap();
+        assert_eq!(score.class_name(Class::new(0)), Some("a"));
+    }
+
+    #[test]
+    fn explain() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let shape = InputShape::new(1, 3);
+        let col = |c| Weighted::from(Node::DataValue(0, c));
+        let add = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "add").unwrap();
+        let trees = vec![
+            ScoredTree::new(
+                Tree::from_two(add, col(0), col(0), shape),
+                Score::new(Auc, Class::new(0), 0.5, Threshold::new(-100.0)),
+            ),
+            ScoredTree::new(
+                Tree::from_two(add, col(2), col(0), shape),
+                Score::new(Auc, Class::new(1), 0.9, Threshold::new(2.5)),
+            ),
+        ];
+        let classifier = Classifier::new(classes, trees).unwrap();
+
+        let input = Input::from_vector(vec![vec![2.0, 5.0, 1.0]]).unwrap();
+        let explanation = classifier.explain(&input).unwrap();
+        assert_eq!(explanation.prediction, classifier.predict(&input).unwrap());
+        assert_eq!(explanation.prediction.unwrap().0, Class::new(1));
+        let output = explanation.outputs[&Class::new(0)];
+        assert_eq!(output, ClassOutput { output: 4.0, threshold: -100.0 });
+        assert_eq!(
+            explanation.features,
+            [
+                FeatureContribution {
+                    row: 0,
+                    column: 0,
+                    name: None,
+                    value: 2.0,
+                    contribution: 2.0
+                },
+                FeatureContribution {
+                    row: 0,
+                    column: 2,
+                    name: None,
+                    value: 1.0,
+                    contribution: 1.0
+                },
+            ]
+        );
+        assert_eq!(explanation.tree_depth, Some(2));
+        assert_eq!(explanation.tree_node_count, Some(3));
+
+        let input = Input::from_vector(vec![vec![-200.0, 5.0, 1.0]]).unwrap();
+        let explanation = classifier.explain(&input).unwrap();
+        assert!(explanation.prediction.is_none());
+        assert!(explanation.features.is_empty());
+        assert_eq!(explanation.tree_depth, None);
+        assert_eq!(explanation.tree_node_count, None);
+        assert!(classifier.explain(&Input::from_vector(vec![vec![1.0]]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn to_expression_string() {
+        let classifier = create_threshold_classifier();
+        assert_eq!(classifier.to_expression_string(), "a: col0 >= -100\nb: col0 >= 2.5");
+    }
+
+    #[test]
+    fn feature_names() {
+        let mut classifier = create_threshold_classifier();
+        assert!(classifier.feature_names().is_empty());
+        assert!(classifier.set_feature_names(vec!["a".to_string(), "b".to_string()]).is_err());
+        assert!(classifier.set_feature_names(vec![String::new()]).is_err());
+        classifier.set_feature_names(vec!["length cm".to_string()]).unwrap();
+        assert_eq!(
+            classifier.to_expression_string(),
+            "a: length cm >= -100\nb: length cm >= 2.5"
+        );
+        assert!(classifier.to_dot().contains("c1n0 [label=\"length cm\"];"));
+        assert_eq!(classifier.named_feature_usage()["length cm"], 2);
+        let explanation = classifier.explain(&Input::from_vector(vec![vec![3.0]]).unwrap());
+        assert_eq!(explanation.unwrap().features[0].name.as_deref(), Some("length cm"));
+        test_serialization(classifier.clone());
+        classifier.set_feature_names(vec![]).unwrap();
+        assert_eq!(classifier.named_feature_usage()["col0"], 2);
+    }
+
+    #[test]
+    fn to_rust_source() {
+        let classifier = create_threshold_classifier();
+        let source = classifier.to_rust_source("classify").unwrap();
+        assert!(source.starts_with("#[allow(clippy::all, unused_imports)]\npub fn classify("));
+        assert!(source.ends_with("    class\n}"));
+        let value = "let value = (features[0]).clamp(-max_output, max_output);";
+        assert_eq!(source.matches(value).count(), 2);
+        let lowest = source.find("value >= -100.0_f32 {\n        class = 0;").unwrap();
+        let best = source.find("value >= 2.5_f32 {\n        class = 1;").unwrap();
+        assert!(lowest < best);
+    }
+
+    /// Compiles [`Classifier::to_rust_source`] of `classifier` to a binary called `name` and
+    /// classifies every one of `values` as a single feature with it
+    fn run_rust_source(classifier: &Classifier, name: &str, values: &[f32]) -> Vec<u16> {
+        let dir = env::temp_dir();
+        let source = dir.join(format!("primeclue_{}_{}.rs", name, process::id()));
+        let binary = dir.join(format!("primeclue_{}_{}", name, process::id()));
+        let values = values.iter().map(|v| rust_f32(*v)).collect::<Vec<_>>().join(", ");
+        let main = format!(
+            concat!(
+                "{}\nfn main() {{\n    for v in [{}] {{\n",
+                "        println!(\"{{}}\", classify(&[v]));\n    }}\n}}\n"
+            ),
+            classifier.to_rust_source("classify").unwrap(),
+            values
+        );
+        fs::write(&source, main).unwrap();
+        let compiled = Command::new("rustc")
+            .args(["--edition", "2021", "-o"])
+            .arg(&binary)
+            .arg(&source)
+            .status()
+            .unwrap();
+        fs::remove_file(&source).unwrap();
+        assert!(compiled.success());
+        let output = Command::new(&binary).output().unwrap();
+        fs::remove_file(&binary).unwrap();
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| line.parse::<u16>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn rust_source_applies_normalization() {
+        let mut classifier = create_threshold_classifier();
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        for x in 0..7 {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![x as f32]]).unwrap(),
+                Outcome::new(Class::new(0), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let (_, normalization) = data.into_view().standardized();
+        classifier.set_normalization(Some(normalization)).unwrap();
+        // 2.5 standard deviations above the mean is only reached by large raw values
+        let values = [0.0, 3.0, 5.0, 8.0, 9.0, 20.0];
+        let predicted = values
+            .iter()
+            .map(|v| {
+                let input = Input::from_vector(vec![vec![*v]]).unwrap();
+                classifier.predict(&input).unwrap().map_or(u16::MAX, |(class, _)| class.value())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(predicted, vec![0, 0, 0, 0, 1, 1]);
+        assert_eq!(run_rust_source(&classifier, "normalized", &values), predicted);
+
+        classifier.set_aggregation(ClassScoreAggregation::Softmax).unwrap();
+        assert!(classifier.to_rust_source("classify").is_err());
+    }
+
+    #[test]
+    fn exports_bound_outputs_as_predict() {
+        let mul = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "mul").unwrap();
+        let column = || Weighted::from(Node::DataValue(0, 0));
+        let square = Tree::from_two(mul, column(), column(), InputShape::new(1, 1));
+        let classes =
+            HashMap::from([(Class::new(0), "a".to_string()), (Class::new(1), "b".to_string())]);
+        let trees = vec![
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(0), 0.5, Threshold::new(-100.0)),
+            ),
+            ScoredTree::new(square, Score::new(Auc, Class::new(1), 0.9, Threshold::new(2.5))),
+        ];
+        let classifier = Classifier::new(classes, trees).unwrap();
+        // squares of the last two overflow, but are bounded, so class 1 is predicted
+        let values = [0.5, 2.0, 1e20, -1e20];
+        let predicted = values
+            .iter()
+            .map(|v| {
+                let input = Input::from_vector(vec![vec![*v]]).unwrap();
+                classifier.predict(&input).unwrap().map_or(u16::MAX, |(class, _)| class.value())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(predicted, vec![0, 1, 1, 1]);
+
+        assert_eq!(run_rust_source(&classifier, "bounded", &values), predicted);
+
+        let dir = env::temp_dir();
+
+        let path = dir.join(format!("primeclue_export_{}.onnx", process::id()));
+        classifier.to_onnx(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let contains = |op: &[u8]| bytes.windows(op.len()).any(|window| window == op);
+        assert!(contains(b"Clip"));
+        assert!(!contains(b"IsInf"));
+    }
+
+    #[test]
+    fn to_onnx() {
+        let classifier = create_threshold_classifier();
+        let path = env::temp_dir().join(format!("primeclue_classifier_{}.onnx", process::id()));
+        classifier.to_onnx(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let model = decode(&bytes).unwrap();
+        assert_eq!(model[0], (1, Field::Varint(7)));
+        let graphs = model.iter().filter(|(field, _)| *field == 7).collect::<Vec<_>>();
+        assert_eq!(graphs.len(), 1);
+        let graph = match &graphs[0].1 {
+            Field::Bytes(graph) => decode(graph).unwrap(),
+            Field::Varint(_) => panic!("Graph is not a message"),
+        };
+        let op_types = graph
+            .iter()
+            .filter(|(field, _)| *field == 1)
+            .filter_map(|(_, node)| match node {
+                Field::Bytes(node) => decode(node),
+                Field::Varint(_) => None,
+            })
+            .flat_map(|node| node.into_iter().filter(|(field, _)| *field == 4))
+            .collect::<Vec<_>>();
+        assert!(op_types.contains(&(4, Field::Bytes(b"ArgMax".to_vec()))));
+        assert_eq!(op_types.last(), Some(&(4, Field::Bytes(b"Where".to_vec()))));
+    }
+
+    #[test]
+    fn onnx_applies_normalization() {
+        let mut classifier = create_threshold_classifier();
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        for x in 0..7 {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![x as f32]]).unwrap(),
+                Outcome::new(Class::new(0), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let (_, normalization) = data.into_view().standardized();
+        let centers: Vec<u8> =
+            normalization.centers().iter().flat_map(|v| v.to_le_bytes()).collect();
+        classifier.set_normalization(Some(normalization)).unwrap();
+        let path = env::temp_dir().join(format!("primeclue_normalized_{}.onnx", process::id()));
+        classifier.to_onnx(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let (_, graph) = decode(&bytes).unwrap().into_iter().find(|(f, _)| *f == 7).unwrap();
+        let graph = match graph {
+            Field::Bytes(graph) => decode(&graph).unwrap(),
+            Field::Varint(_) => panic!("Graph is not a message"),
+        };
+        let messages = |field| {
+            graph
+                .iter()
+                .filter(|(f, _)| *f == field)
+                .filter_map(|(_, message)| match message {
+                    Field::Bytes(message) => decode(message),
+                    Field::Varint(_) => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        // raw features are only centered and measured, every tree reads normalized ones
+        let readers = messages(1)
+            .into_iter()
+            .filter(|node| node.contains(&(1, Field::Bytes(b"features".to_vec()))))
+            .map(|node| node.into_iter().find(|(f, _)| *f == 4).unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            readers,
+            vec![Field::Bytes(b"Sub".to_vec()), Field::Bytes(b"Shape".to_vec())]
+        );
+        assert!(messages(5)
+            .iter()
+            .any(|tensor| tensor.contains(&(9, Field::Bytes(centers.clone())))));
+    }
+
+    #[test]
+    fn to_dot() {
+        let classifier = create_threshold_classifier();
+        let dot = classifier.to_dot();
+        assert!(dot.starts_with("digraph classifier {"));
+        assert_eq!(dot.matches("digraph").count(), 1);
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        assert_eq!(dot.matches("subgraph").count(), 2);
+        assert!(dot.contains("label=\"b >= 2.5\";"));
+        assert!(dot.contains("c1n0 [label=\"col0\"];"));
+        let dot = classifier.to_dot_with_feature_names(&["length \"cm\""]);
+        assert!(dot.contains("c1n0 [label=\"length \\\"cm\\\"\"];"));
+    }
+
+    #[test]
+    fn predict() {
+        let classifier = create_threshold_classifier();
+        let (class, low_confidence) =
+            classifier.predict(&Input::from_vector(vec![vec![3.0]]).unwrap()).unwrap().unwrap();
+        assert_eq!(class, Class::new(1));
+        let (class, high_confidence) =
+            classifier.predict(&Input::from_vector(vec![vec![1.0]]).unwrap()).unwrap().unwrap();
+        assert_eq!(class, Class::new(0));
+        assert!((low_confidence - 0.5 / 103.5).abs() < f32::EPSILON);
+        assert!((high_confidence - 1.0).abs() < f32::EPSILON);
+        let wide = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        match classifier.predict(&wide) {
+            Err(PrimeclueErr::ShapeMismatch { expected, actual }) => {
+                assert_eq!(&expected, classifier.input_shape());
+                assert_eq!(&actual, wide.input_shape());
+            }
+            other => panic!("Expected shape mismatch, got {:?}", other),
+        }
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        data.add_data_point(Point::new(wide, Outcome::new(Class::new(0), 1.0, -1.0))).unwrap();
+        assert!(classifier.score(&data.into_view()).is_err());
+    }
+
+    #[test]
+    fn predict_within() {
+        let classifier = create_threshold_classifier();
+        let budget = Duration::from_secs(60);
+        for x in &[-1.0, 1.0, 3.0] {
+            let input = Input::from_vector(vec![vec![*x]]).unwrap();
+            let prediction = classifier.predict_within(&input, budget).unwrap();
+            assert_eq!(prediction, classifier.predict(&input).unwrap());
+            match classifier.predict_within(&input, Duration::ZERO) {
+                Err(PrimeclueErr::Timeout(budget)) => assert_eq!(budget, Duration::ZERO),
+                other => panic!("Expected timeout, got {:?}", other),
+            }
+        }
+        let wide = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        assert!(classifier.predict_within(&wide, budget).is_err());
+    }
+
+    #[test]
+    fn predict_batch() {
+        let classifier = create_threshold_classifier();
+        let inputs = (0..2500)
+            .map(|i| Input::from_vector(vec![vec![i as f32 / 500.0 - 1.0]]).unwrap())
+            .collect::<Vec<_>>();
+        let predictions = classifier.predict_batch(&inputs).unwrap();
+        assert_eq!(predictions.len(), inputs.len());
+        for (input, prediction) in inputs.iter().zip(predictions) {
+            assert_eq!(prediction, classifier.predict(input).unwrap());
+        }
+        assert!(classifier.predict_batch(&[]).unwrap().is_empty());
+        let wrong = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        assert!(classifier.predict_batch(&[inputs[0].clone(), wrong]).is_err());
+    }
+
+    #[test]
+    fn predict_view() {
+        let classifier = create_sum_classifier();
+        let inputs = [-3.0, -0.5, 0.5, 2.0, 60.0]
+            .iter()
+            .map(|x| Input::from_vector(vec![vec![*x]]).unwrap())
+            .collect::<Vec<_>>();
+        let view = |class| {
+            let mut data = DataSet::new(classifier.get_classes().clone());
+            for input in &inputs {
+                data.add_labeled_point(input.clone(), Class::new(class)).unwrap();
+            }
+            data.into_view()
+        };
+        let predictions = classifier.predict_view(&view(0)).unwrap();
+        assert_eq!(predictions, classifier.predict_batch(&inputs).unwrap());
+        assert_eq!(predictions, classifier.predict_view(&view(1)).unwrap());
+        assert!(predictions.iter().any(Option::is_some));
+        let mut wide = DataSet::new(classifier.get_classes().clone());
+        let input = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        wide.add_labeled_point(input, Class::new(0)).unwrap();
+        assert!(classifier.predict_view(&wide.into_view()).is_err());
+    }
+
+    fn create_sum_classifier() -> Classifier {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let add = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "add").unwrap();
+        let col = || Weighted::from(Node::DataValue(0, 0));
+        let trees = vec![
+            ScoredTree::new(
+                Tree::from_two(add, col(), col(), InputShape::new(1, 1)),
+                Score::new(Auc, Class::new(1), 0.9, Threshold::new(0.0)),
+            ),
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(0), 0.5, Threshold::new(100.0)),
+            ),
+        ];
+        Classifier::new(classes, trees).unwrap()
+    }
+
+    #[test]
+    fn raw_outputs() {
+        let classifier = create_sum_classifier();
+        let input = Input::from_vector(vec![vec![3.0]]).unwrap();
+        let outputs = classifier.raw_outputs(&input).unwrap();
+        assert_eq!(outputs, vec![(Class::new(0), 3.0), (Class::new(1), 6.0)]);
+        // the tree of class 1 is given first
+        assert_eq!(classifier.predict_value(&input).unwrap(), 6.0);
+        let wide = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        assert!(classifier.raw_outputs(&wide).is_err());
+        assert!(classifier.predict_value(&wide).is_err());
+    }
+
+    #[test]
+    fn decision_grid() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let add = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "add").unwrap();
+        let col = |c| Weighted::from(Node::DataValue(0, c));
+        let trees = vec![
+            ScoredTree::new(
+                Tree::from_two(add, col(0), col(2), InputShape::new(1, 3)),
+                Score::new(Auc, Class::new(1), 0.9, Threshold::new(0.5)),
+            ),
+            ScoredTree::new(
+                Tree::from_two(add, col(1), col(1), InputShape::new(1, 3)),
+                Score::new(Auc, Class::new(0), 0.5, Threshold::new(100.0)),
+            ),
+        ];
+        let classifier = Classifier::new(classes, trees).unwrap();
+        let grid = classifier.decision_grid(0, 2, &[9.0, 0.0, 9.0], (-1.0, 1.0), (-1.0, 1.0), 3);
+        let (none, one) = (None, Some(Class::new(1)));
+        assert_eq!(
+            grid.unwrap(),
+            vec![vec![none, none, none], vec![none, none, one], vec![none, one, one]]
+        );
+        let grid = classifier.decision_grid(1, 2, &[1.0, 0.0, 0.0], (0.0, 1.0), (0.0, 1.0), 2);
+        assert_eq!(grid.unwrap(), vec![vec![one, one], vec![one, one]]);
+        assert!(classifier.decision_grid(0, 2, &[0.0, 0.0], (0.0, 1.0), (0.0, 1.0), 2).is_err());
+        assert!(classifier.decision_grid(0, 0, &[0.0; 3], (0.0, 1.0), (0.0, 1.0), 2).is_err());
+        assert!(classifier.decision_grid(0, 3, &[0.0; 3], (0.0, 1.0), (0.0, 1.0), 2).is_err());
+        assert!(classifier.decision_grid(0, 2, &[0.0; 3], (0.0, 1.0), (0.0, 1.0), 1).is_err());
+        let infinite = (0.0, f32::INFINITY);
+        assert!(classifier.decision_grid(0, 2, &[0.0; 3], infinite, (0.0, 1.0), 2).is_err());
+    }
+
+    #[test]
+    fn transform() {
+        let classifier = create_sum_classifier();
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        let mut wide = DataSet::new(classifier.get_classes().clone());
+        for x in &[1.0, 3.0, -2.0] {
+            let outcome = Outcome::new(Class::new(0), 1.0, -1.0);
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                outcome,
+            ))
+            .unwrap();
+            let input = Input::from_vector(vec![vec![*x, *x]]).unwrap();
+            wide.add_data_point(Point::new(input, outcome)).unwrap();
+        }
+        let outputs = classifier.transform(&data.into_view()).unwrap();
+        assert_eq!(outputs, vec![vec![1.0, 2.0], vec![3.0, 6.0], vec![-2.0, -4.0]]);
+        assert!(classifier.transform(&wide.into_view()).is_err());
+    }
+
+    #[test]
+    fn class_outputs() {
+        let classifier = create_sum_classifier();
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        for x in &[1.0, 3.0, -2.0] {
+            let input = Input::from_vector(vec![vec![*x]]).unwrap();
+            data.add_data_point(Point::new(input, Outcome::new(Class::new(0), 1.0, -1.0)))
+                .unwrap();
+        }
+        let view = data.into_view();
+        assert_eq!(
+            classifier.class_outputs(&view, Class::new(0)).unwrap(),
+            vec![1.0, 3.0, -2.0]
+        );
+        assert_eq!(
+            classifier.class_outputs(&view, Class::new(1)).unwrap(),
+            vec![2.0, 6.0, -4.0]
+        );
+        assert!(classifier.class_outputs(&view, Class::new(2)).is_err());
+    }
+
+    #[test]
+    fn predict_proba() {
+        let mut classifier = create_threshold_classifier();
+        let input = Input::from_vector(vec![vec![1.0]]).unwrap();
+        let probabilities = classifier.predict_proba(&input).unwrap();
+        assert!((probabilities.values().sum::<f32>() - 1.0).abs() < f32::EPSILON);
+        assert!(probabilities[&Class::new(0)] > 0.999);
+
+        // margins are 101 and -1.5
+        classifier.set_temperature(100.0).unwrap();
+        let probabilities = classifier.predict_proba(&input).unwrap();
+        let expected = 1.0 / (1.0 + (-1.025_f32).exp());
+        assert!((probabilities[&Class::new(0)] - expected).abs() < 0.000_001);
+        assert!((probabilities[&Class::new(1)] - (1.0 - expected)).abs() < 0.000_001);
+        test_serialization(classifier.clone());
+
+        assert!(classifier.set_temperature(0.0).is_err());
+        assert!(classifier
+            .predict_proba(&Input::from_vector(vec![vec![1.0, 2.0]]).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn score_with_threshold() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let mut negated = create_short_tree();
+        negated.select_node_mut(0).change_weight(-1.0);
+        // probability of class 1 is sigmoid(2x): 0.881, 0.982, 0.998, 0.999
+        let classifier = Classifier::new(
+            classes,
+            vec![
+                ScoredTree::new(
+                    negated,
+                    Score::new(Auc, Class::new(0), 0.5, Threshold::new(0.0)),
+                ),
+                ScoredTree::new(
+                    create_short_tree(),
+                    Score::new(Auc, Class::new(1), 0.5, Threshold::new(0.0)),
+                ),
+            ],
+        )
+        .unwrap();
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        for (x, class) in &[(1.0, 0), (2.0, 1), (3.0, 1), (4.0, 1)] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(*class), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let data = data.into_view();
+        let positive = Class::new(1);
+        let score = classifier.score_with_threshold(&data, positive, 0.5).unwrap();
+        assert!((score.accuracy - 0.75).abs() < f32::EPSILON);
+        let score = classifier.score_with_threshold(&data, positive, 0.9).unwrap();
+        assert!((score.accuracy - 1.0).abs() < f32::EPSILON);
+        assert!(classifier.score_with_threshold(&data, Class::new(2), 0.5).is_none());
+
+        let threshold = classifier.best_threshold(&data, positive, &Accuracy).unwrap();
+        let score = classifier.score_with_threshold(&data, positive, threshold).unwrap();
+        assert!((score.accuracy - 1.0).abs() < f32::EPSILON);
+        assert_eq!(classifier.best_threshold(&data, positive, &Auc), None);
+
+        let mut wide = DataSet::new(classifier.get_classes().clone());
+        let input = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        wide.add_data_point(Point::new(input, Outcome::new(positive, 1.0, -1.0))).unwrap();
+        let wide = wide.into_view();
+        assert!(classifier.score_with_threshold(&wide, positive, 0.5).is_none());
+        assert_eq!(classifier.best_threshold(&wide, positive, &Accuracy), None);
+    }
+
+    #[test]
+    fn multi_label() {
+        let column = |c| {
+            let add = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "add").unwrap();
+            let zero = Weighted::from(Node::MathConstant(&MATH_CONSTANTS[0]));
+            Tree::from_two(
+                add,
+                Weighted::from(Node::DataValue(0, c)),
+                zero,
+                InputShape::new(1, 2),
+            )
+        };
+        // class 0 when column 0 is above 0, class 1 when column 1 is
+        let label_classifier = |c| {
+            let mut classes = HashMap::new();
+            classes.insert(Class::from(false), "no".to_string());
+            classes.insert(Class::from(true), "yes".to_string());
+            let mut negated = column(c);
+            negated.select_node_mut(0).change_weight(-1.0);
+            let trees = vec![
+                ScoredTree::new(
+                    negated,
+                    Score::new(Auc, Class::from(false), 0.9, Threshold::new(0.0)),
+                ),
+                ScoredTree::new(
+                    column(c),
+                    Score::new(Auc, Class::from(true), 0.9, Threshold::new(0.0)),
+                ),
+            ];
+            (Class::new(c as u16), Classifier::new(classes, trees).unwrap())
+        };
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let classifier = Classifier::from_label_classifiers(
+            classes.clone(),
+            vec![label_classifier(0), label_classifier(1)],
+        )
+        .unwrap();
+        let predict = |a, b| {
+            classifier
+                .predict_labels(&Input::from_vector(vec![vec![a, b]]).unwrap(), 0.5)
+                .unwrap()
+        };
+        assert_eq!(predict(1.0, 1.0), vec![Class::new(0), Class::new(1)].into_iter().collect());
+        assert_eq!(predict(1.0, -1.0), vec![Class::new(0)].into_iter().collect());
+        assert!(predict(-1.0, -1.0).is_empty());
+        let wide = Input::from_vector(vec![vec![1.0, 1.0, 1.0]]).unwrap();
+        assert!(classifier.predict_label_probabilities(&wide).is_err());
+        assert!(classifier.predict_labels(&wide, 0.5).is_err());
+
+        let mut data = DataSet::new(classes.clone());
+        let mut labels = vec![];
+        for (a, b, expected) in
+            &[(1.0, 1.0, vec![0, 1]), (1.0, -1.0, vec![0, 1]), (-1.0, 1.0, vec![1])]
+        {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*a, *b]]).unwrap(),
+                Outcome::new(Class::new(0), 1.0, -1.0),
+            ))
+            .unwrap();
+            labels.push(MultiLabelOutcome::new(expected.iter().map(|c| Class::new(*c))));
+        }
+        let data = data.into_view();
+        assert!(classifier.score_multi_label(&data, &labels[1..], 0.5).is_err());
+        let score = classifier.score_multi_label(&data, &labels, 0.5).unwrap();
+        // one wrong decision out of 6, F1 of class 0 is 1 and of class 1 is 0.8
+        assert!((score.hamming_loss - 1.0 / 6.0).abs() < f32::EPSILON);
+        assert!((score.macro_f1 - 0.9).abs() < f32::EPSILON);
+
+        assert!(Classifier::from_label_classifiers(
+            classes.clone(),
+            vec![label_classifier(0), label_classifier(0)]
+        )
+        .is_err());
+        assert!(Classifier::from_label_classifiers(
+            classes,
+            vec![(Class::new(0), create_threshold_classifier())]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn feature_usage() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let shape = InputShape::new(1, 3);
+        let col = |c| Weighted::from(Node::DataValue(0, c));
+        let add = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "add").unwrap();
+        let trees = vec![
+            ScoredTree::new(
+                Tree::from_two(add, col(0), col(2), shape),
+                Score::new(Auc, Class::new(0), 0.5, Threshold::new(0.0)),
+            ),
+            ScoredTree::new(
+                Tree::from_two(add, col(0), Weighted::from(Node::StdDev(0, 0)), shape),
+                Score::new(Auc, Class::new(1), 0.5, Threshold::new(0.0)),
+            ),
+        ];
+        let usage = Classifier::new(classes, trees).unwrap().feature_usage();
+        assert_eq!(usage.len(), 3);
+        assert_eq!(usage[&0], 3);
+        assert_eq!(usage[&1], 0);
+        assert_eq!(usage[&2], 1);
+    }
+
+    #[test]
+    fn predict_or_abstain() {
+        let mut classifier = create_threshold_classifier();
+        let input = Input::from_vector(vec![vec![3.0]]).unwrap();
+        // normalized outputs are 103 for class 0 and 0.5 for class 1
+        let prediction = classifier.predict(&input).unwrap();
+        assert_eq!(classifier.predict_or_abstain(&input, 0.0).unwrap(), prediction);
+        assert_eq!(classifier.predict_or_abstain(&input, 102.0).unwrap(), prediction);
+        assert_eq!(classifier.predict_or_abstain(&input, 103.0).unwrap(), None);
+        assert!(classifier.predict_or_abstain(&input, -1.0).is_err());
+        assert!(classifier.predict_or_abstain(&input, f32::NAN).is_err());
+        let wrong = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        assert!(classifier.predict_or_abstain(&wrong, 1.0).is_err());
+
+        classifier.output_ranges.insert(Class::new(0), 1000.0);
+        assert_eq!(classifier.predict_or_abstain(&input, 0.6).unwrap(), None);
+        assert_eq!(classifier.predict_or_abstain(&input, 0.3).unwrap(), prediction);
+    }
+
+    #[test]
+    fn predict_checked() {
+        let mut classifier = create_threshold_classifier();
+        let input =
+            |x: f32| Input::from_vector_with_mask(vec![vec![x]], &[vec![x.is_nan()]]).unwrap();
+        assert!(classifier.predict_checked(&input(1.0)).is_err());
+        let mut data = DataSet::new(classifier.classes.clone());
+        for x in [1.0, 3.0, f32::NAN] {
+            data.add_data_point(Point::new(input(x), Outcome::new(Class::new(0), 1.0, -1.0)))
+                .unwrap();
+        }
+        classifier.fit_feature_ranges(&data.into_view()).unwrap();
+
+        let checked = classifier.predict_checked(&input(2.0)).unwrap();
+        assert_eq!(checked.prediction, classifier.predict(&input(2.0)).unwrap());
+        assert!(checked.extrapolated.is_empty());
+        let checked = classifier.predict_checked(&input(5.0)).unwrap();
+        assert_eq!(checked.prediction, classifier.predict(&input(5.0)).unwrap());
+        assert_eq!(
+            checked.extrapolated,
+            vec![ExtrapolatedFeature { row: 0, column: 0, value: 5.0, min: 1.0, max: 3.0 }]
+        );
+        assert!(classifier.predict_checked(&input(f32::NAN)).unwrap().extrapolated.is_empty());
+        test_serialization(classifier.clone());
+
+        let wrong = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        assert!(classifier.predict_checked(&wrong).is_err());
+        let empty = DataSet::new(classifier.classes.clone()).into_view();
+        assert!(classifier.fit_feature_ranges(&empty).is_err());
+    }
+
+    #[test]
+    fn aggregation() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let add = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "add").unwrap();
+        let col = || Weighted::from(Node::DataValue(0, 0));
+        let double = || Weighted::from(Node::two_arg_node(add, col(), col()));
+        // class 0 tree outputs 4x, class 1 tree outputs x
+        let trees = vec![
+            ScoredTree::new(
+                Tree::from_two(add, double(), double(), InputShape::new(1, 1)),
+                Score::new(Auc, Class::new(0), 0.9, Threshold::new(0.0)),
+            ),
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(1), 0.5, Threshold::new(-1.0)),
+            ),
+        ];
+        let mut classifier = Classifier::new(classes.clone(), trees).unwrap();
+        let predict = |classifier: &Classifier, x: f32| {
+            let input = Input::from_vector(vec![vec![x]]).unwrap();
+            classifier.predict(&input).unwrap().map(|(class, _)| class)
+        };
+        assert_eq!(classifier.aggregation(), ClassScoreAggregation::Priority);
+        assert_eq!(predict(&classifier, 2.0), Some(Class::new(0)));
+        assert_eq!(predict(&classifier, -2.0), None);
+
+        // margins for 2.0 are 8 and 3, for -2.0 are -8 and -1
+        classifier.set_aggregation(ClassScoreAggregation::Raw).unwrap();
+        assert_eq!(predict(&classifier, 2.0), Some(Class::new(0)));
+        assert_eq!(predict(&classifier, -2.0), None);
+        classifier.set_aggregation(ClassScoreAggregation::Softmax).unwrap();
+        assert_eq!(predict(&classifier, 2.0), Some(Class::new(0)));
+        let input = Input::from_vector(vec![vec![-2.0]]).unwrap();
+        let (class, confidence) = classifier.predict(&input).unwrap().unwrap();
+        assert_eq!(class, Class::new(1));
+        assert!(
+            (confidence - classifier.predict_proba(&input).unwrap()[&class]).abs()
+                < f32::EPSILON
+        );
+        assert!(classifier.to_onnx(&env::temp_dir().join("aggregation.onnx")).is_err());
+
+        // output ranges on 0..=10 are 40 and 10, so normalized margins for 2.0 are 0.2 and 0.3
+        assert!(classifier.set_aggregation(ClassScoreAggregation::MinMaxNormalized).is_err());
+        let mut data = DataSet::new(classes.clone());
+        for x in 0..=10 {
+            let input = Input::from_vector(vec![vec![x as f32]]).unwrap();
+            data.add_data_point(Point::new(input, Outcome::new(Class::new(0), 1.0, -1.0)))
+                .unwrap();
+        }
+        let data = data.into_view();
+        classifier.fit_output_ranges(&data).unwrap();
+        classifier.set_aggregation(ClassScoreAggregation::MinMaxNormalized).unwrap();
+        assert_eq!(predict(&classifier, 2.0), Some(Class::new(1)));
+        assert_eq!(classifier.classify(&data).unwrap()[2], "b");
+        test_serialization(classifier.clone());
+
+        let mut constant = DataSet::new(classes);
+        let input = Input::from_vector(vec![vec![1.0]]).unwrap();
+        constant
+            .add_data_point(Point::new(input, Outcome::new(Class::new(0), 1.0, -1.0)))
+            .unwrap();
+        assert!(classifier.fit_output_ranges(&constant.into_view()).is_err());
+    }
+
+    #[test]
+    fn tie_prefers_lowest_class() {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        let tree = |class| {
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(class), 0.7, Threshold::new(0.0)),
+            )
+        };
+        let input = Input::from_vector(vec![vec![1.0]]).unwrap();
+        for order in &[[0, 1], [1, 0]] {
+            let classifier =
+                Classifier::new(classes.clone(), order.iter().map(|&c| tree(c)).collect())
+                    .unwrap();
+            for _ in 0..10 {
+                assert_eq!(classifier.predict(&input).unwrap().unwrap().0, Class::new(0));
+            }
+            let mut data = DataSet::new(classes.clone());
+            data.add_data_point(Point::new(
+                input.clone(),
+                Outcome::new(Class::new(0), 1.0, -1.0),
+            ))
+            .unwrap();
+            let score = classifier.score(&data.into_view()).unwrap();
+            assert_eq!(score.confusion_matrix(), &vec![vec![1, 0], vec![0, 0]]);
+        }
+    }
+
+    #[test]
+    fn confusion_matrix() {
+        let mut data = DataSet::new(create_threshold_classifier().get_classes().clone());
+        for (x, class) in &[(1.0, 0), (2.0, 1), (3.0, 1), (4.0, 1)] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(*class), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let score = create_threshold_classifier().score(&data.into_view()).unwrap();
+        assert_eq!(score.confusion_matrix(), &vec![vec![1, 0], vec![1, 2]]);
+        assert!((score.precision(Class::new(0)).unwrap() - 0.5).abs() < f32::EPSILON);
+        assert!((score.recall(Class::new(0)).unwrap() - 1.0).abs() < f32::EPSILON);
+        assert!((score.precision(Class::new(1)).unwrap() - 1.0).abs() < f32::EPSILON);
+        assert!((score.recall(Class::new(1)).unwrap() - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert!((score.accuracy - 0.75).abs() < f32::EPSILON);
+        assert!((score.balanced_accuracy() - 5.0 / 6.0).abs() < f32::EPSILON);
+        assert!(
+            (score.value(&BalancedAccuracy) - score.balanced_accuracy()).abs() < f32::EPSILON
+        );
+        // with two classes a wrong prediction is the only wrong membership decision
+        assert!((score.hamming() - score.accuracy).abs() < f32::EPSILON);
+        assert!((score.value(&Hamming) - 0.75).abs() < f32::EPSILON);
+        assert_eq!(score.precision(Class::new(2)), None);
+        assert_eq!(score.recall(Class::new(2)), None);
+    }
+
+    #[test]
+    fn score_with_class_gaps() {
+        let classes = HashMap::from([
+            (Class::new(2), "low".to_string()),
+            (Class::new(5), "high".to_string()),
+        ]);
+        let trees = vec![
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(2), 0.5, Threshold::new(-100.0)),
+            ),
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(5), 0.9, Threshold::new(2.5)),
+            ),
+        ];
+        let classifier = Classifier::new(classes.clone(), trees).unwrap();
+        let mut data = DataSet::new(classes);
+        for (x, class) in &[(1.0, 2), (2.0, 5), (3.0, 5), (4.0, 5)] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(*class), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let score = classifier.score(&data.into_view()).unwrap();
+        assert_eq!(score.confusion_matrix(), &vec![vec![1, 0], vec![1, 2]]);
+        assert!((score.precision(Class::new(2)).unwrap() - 0.5).abs() < f32::EPSILON);
+        assert!((score.recall(Class::new(5)).unwrap() - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert!((score.balanced_accuracy() - 5.0 / 6.0).abs() < f32::EPSILON);
+        assert_eq!(score.precision(Class::new(0)), None);
+    }
+
+    #[test]
+    fn evaluate() {
+        let classifier = create_threshold_classifier();
+        let mut data = DataSet::new(classifier.get_classes().clone());
+        for (x, class) in &[(1.0, 0), (2.0, 1), (3.0, 1), (4.0, 1)] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(*class), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        let data = data.into_view();
+        let report = classifier.evaluate(&data).unwrap();
+        let score = classifier.score(&data).unwrap();
+        assert_eq!(report.score.confusion_matrix(), score.confusion_matrix());
+        assert!((report.score.auc - score.auc).abs() < f32::EPSILON);
+        assert!((report.precision["a"] - 0.5).abs() < f32::EPSILON);
+        assert!((report.recall["b"] - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert!((report.mcc - score.mcc()).abs() < f32::EPSILON);
+        assert!((report.cohens_kappa - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn mcc() {
+        let mut data = DataSet::new(create_threshold_classifier().get_classes().clone());
+        for (x, class) in &[(1.0, 0), (2.0, 1), (3.0, 1), (4.0, 1)] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(*class), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        // tp = 2, tn = 1, fp = 0, fn = 1
+        let score = create_threshold_classifier().score(&data.into_view()).unwrap();
+        assert!((score.mcc() - 2.0 / 12.0_f32.sqrt()).abs() < f32::EPSILON);
+
+        let mut data = DataSet::new(create_threshold_classifier().get_classes().clone());
+        for (x, class) in &[(1.0, 0), (2.0, 1)] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(*class), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        // only class 0 is predicted
+        let score = create_threshold_classifier().score(&data.into_view()).unwrap();
+        assert!(score.mcc().abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn cohens_kappa() {
+        let mut classes = BTreeMap::new();
+        classes.insert(Class::new(0), "a".to_string());
+        classes.insert(Class::new(1), "b".to_string());
+        classes.insert(Class::new(2), "c".to_string());
+        let score = ClassifierScore {
+            auc: 0.0,
+            macro_auc: 0.0,
+            accuracy: 0.0,
+            cost: 0.0,
+            f1: 0.0,
+            mean_squared_error: None,
+            mean_absolute_error: None,
+            label_count_map: HashMap::new(),
+            label_accuracy_map: HashMap::new(),
+            confusion_matrix: vec![vec![4, 1, 0], vec![1, 3, 1], vec![0, 0, 5]],
+            class_costs: vec![],
+            classes,
+        };
+        // observed 12 / 15, chance (5 * 5 + 4 * 5 + 6 * 5) / 15^2 = 1 / 3
+        assert!((score.cohens_kappa() - 0.7).abs() < 0.000_001);
+        let perfect_chance = ClassifierScore {
+            confusion_matrix: vec![vec![3, 0, 0], vec![0, 0, 0], vec![0, 0, 0]],
+            ..score
+        };
+        assert!(perfect_chance.cohens_kappa().abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn macro_auc() {
+        let mut data = DataSet::new(create_threshold_classifier().get_classes().clone());
+        for (x, class) in &[(1.0, 0), (1.0, 1), (3.0, 1), (4.0, 1)] {
+            data.add_data_point(Point::new(
+                Input::from_vector(vec![vec![*x]]).unwrap(),
+                Outcome::new(Class::new(*class), 1.0, -1.0),
+            ))
+            .unwrap();
+        }
+        // class 0: tie with one of 3 negatives, class 1: tie with 1 of 3 positives
+        let score = create_threshold_classifier().score(&data.into_view()).unwrap();
+        assert!((score.macro_auc() - (0.5 / 3.0 + 2.5 / 3.0) / 2.0).abs() < f32::EPSILON);
     }
 
     #[test]
@@ -233,7 +3367,7 @@ mod test {
         let r = Classifier::new(classes, vec![]);
         assert!(r.is_err());
 
-        let t = Tree::new(&InputShape::new(1, 1), 3, &[], 0.5, 0.5);
+        let t = Tree::new(&InputShape::new(1, 1), 3, &[], &[], 0.5, 0.5);
         let classes = HashMap::new();
         let r = Classifier::new(
             classes,
@@ -246,7 +3380,7 @@ mod test {
     fn invalid_class_count() {
         let mut trees = vec![];
         for i in 0..3 {
-            let t = Tree::new(&InputShape::new(1, 1), 3, &[], 0.5, 0.5);
+            let t = Tree::new(&InputShape::new(1, 1), 3, &[], &[], 0.5, 0.5);
             trees.push(ScoredTree::new(
                 t,
                 Score::new(Auc, Class::new(i), 0.9, Threshold::new(0.0)),
@@ -260,7 +3394,7 @@ mod test {
 
         let mut trees = vec![];
         for i in 0..2 {
-            let t = Tree::new(&InputShape::new(1, 1), 3, &[], 0.5, 0.5);
+            let t = Tree::new(&InputShape::new(1, 1), 3, &[], &[], 0.5, 0.5);
             trees.push(ScoredTree::new(
                 t,
                 Score::new(Auc, Class::new(i), 0.9, Threshold::new(0.0)),
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::data::data_set::DataSet;
+use crate::error::PrimeclueErr;
+use crate::exec::classifier::ClassifierScore;
+use crate::exec::score::Objective;
+use crate::exec::training_group::TrainingGroup;
+
+/// Runs k-fold cross-validation: for each fold a [`TrainingGroup`] is trained for a fixed
+/// number of generations and the resulting classifier is scored on the fold's test data.
+#[derive(Debug, Clone)]
+pub struct CrossValidation {
+    objective: Objective,
+    size: usize,
+    generations: u32,
+    forbidden_cols: Vec<usize>,
+}
+
+/// Per-fold results of [`CrossValidation`]
+#[derive(Debug, Clone)]
+pub struct CrossValidationResult {
+    objective: Objective,
+    scores: Vec<ClassifierScore>,
+}
+
+impl CrossValidation {
+    /// # Arguments
+    /// * `objective` - [`Objective`] to train for and to aggregate
+    /// * `size` - size of a training group, see [`TrainingGroup::new`]
+    /// * `generations` - number of generations to train each fold for
+    /// * `forbidden_cols` - indexes of data columns that should not be used as input
+    #[must_use]
+    pub fn new(
+        objective: Objective,
+        size: usize,
+        generations: u32,
+        forbidden_cols: &[usize],
+    ) -> Self {
+        CrossValidation { objective, size, generations, forbidden_cols: forbidden_cols.to_vec() }
+    }
+
+    /// Splits `data` into `k` folds and trains a classifier for each of them.
+    /// Training part of each fold is further split in half into training and verification data.
+    pub fn run(&self, data: DataSet, k: usize) -> Result<CrossValidationResult, PrimeclueErr> {
+        let mut scores = Vec::with_capacity(k);
+        for (fold, (training_set, test_set)) in data.k_fold_sets(k)?.into_iter().enumerate() {
+            let (training_data, verification_data) = training_set.k_fold_sets(2)?.remove(0);
+            let mut training = TrainingGroup::new(
+                training_data.into_view(),
+                verification_data.into_view(),
+                self.objective.clone(),
+                self.size,
+                &self.forbidden_cols,
+            )?;
+            for _ in 0..self.generations {
+                training.next_generation();
+            }
+            let score = training.classifier()?.score(&test_set.into_view()).map_err(|e| {
+                PrimeclueErr::from(format!(
+                    "Unable to score classifier for fold {}: {}",
+                    fold + 1,
+                    e
+                ))
+            })?;
+            scores.push(score);
+        }
+        Ok(CrossValidationResult { objective: self.objective.clone(), scores })
+    }
+}
+
+impl CrossValidationResult {
+    #[must_use]
+    pub fn scores(&self) -> &[ClassifierScore] {
+        &self.scores
+    }
+
+    /// Values of the trained objective, one per fold, see [`ClassifierScore::value`]
+    #[must_use]
+    pub fn values(&self) -> Vec<f32> {
+        self.scores.iter().map(|s| s.value(&self.objective)).collect()
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> f32 {
+        let values = self.values();
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+
+    /// Sample standard deviation of objective values across folds
+    #[must_use]
+    pub fn std_dev(&self) -> f32 {
+        let values = self.values();
+        let mean = self.mean();
+        (values.iter().map(|v| (v - mean).powf(2.0)).sum::<f32>() / (values.len() - 1) as f32)
+            .sqrt()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::test::create_simple_data;
+    use crate::exec::cross_validation::CrossValidation;
+    use crate::exec::score::Objective::Accuracy;
+
+    #[test]
+    fn cross_validate() {
+        let cv = CrossValidation::new(Accuracy, 5, 20, &[]);
+        for _ in 0..10 {
+            let data = create_simple_data(300).filter(|p| p.data().0.get(0, 0) >= 1.0);
+            if let Ok(result) = cv.run(data, 3) {
+                assert_eq!(result.scores().len(), 3);
+                assert_eq!(result.values().len(), 3);
+                assert!(result.mean() > 0.0 && result.mean() <= 1.0);
+                assert!(result.std_dev().is_finite());
+                return;
+            }
+        }
+        panic!("Cross validation failed")
+    }
+}
@@ -0,0 +1,605 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::data::data_set::DataView;
+use crate::data::outcome::Class;
+use crate::data::{Input, Outcome};
+use crate::error::PrimeclueErr;
+use crate::exec::classifier::{Classifier, ClassifierScore};
+use crate::exec::score::{calculate_auc, calculate_auc_with_ties};
+use crate::serialization::{Deserializable, Serializable, Serializator};
+use std::cmp::Ordering::Equal;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+const ENSEMBLE_MAGIC: &str = "PrimeclueEnsemble";
+const ENSEMBLE_FORMAT_VERSION: usize = 2;
+const ENSEMBLE_FORMAT_V1: usize = 1;
+
+/// How predictions of [`Ensemble`]'s classifiers are combined:
+/// * `MajorityVote` - every classifier votes for the class it predicts, see
+///   [`Ensemble::predict`]
+/// * `WeightedSoftVote` - [`Classifier::predict_proba`] of every classifier is multiplied by
+///   its weight and summed per class, the class with the highest sum wins
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum CombineMode {
+    #[default]
+    MajorityVote,
+    WeightedSoftVote,
+}
+
+/// Several [`Classifier`]s, e.g. trained separately on the same data, that predict
+/// together as set by [`CombineMode`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ensemble {
+    classifiers: Vec<Classifier>,
+    weights: Vec<f32>,
+    combine_mode: CombineMode,
+}
+
+impl Ensemble {
+    /// Creates [`Ensemble`] from `classifiers`, each with weight 1.0, combined by
+    /// [`CombineMode::MajorityVote`]. All of them must have the same classes and input shape.
+    pub fn from_classifiers(classifiers: Vec<Classifier>) -> Result<Self, PrimeclueErr> {
+        let weights = vec![1.0; classifiers.len()];
+        Ensemble::new(classifiers, weights, CombineMode::MajorityVote)
+    }
+
+    /// Creates [`Ensemble`] from classifiers weighted by their scores, e.g. accuracy on
+    /// verification data, combined by [`CombineMode::WeightedSoftVote`]. Scores must be
+    /// finite and non-negative, at least one of them positive. All classifiers must have
+    /// the same classes and input shape.
+    pub fn from_scored(members: Vec<(Classifier, f32)>) -> Result<Self, PrimeclueErr> {
+        let (classifiers, weights) = members.into_iter().unzip();
+        Ensemble::new(classifiers, weights, CombineMode::WeightedSoftVote)
+    }
+
+    fn new(
+        classifiers: Vec<Classifier>,
+        weights: Vec<f32>,
+        combine_mode: CombineMode,
+    ) -> Result<Self, PrimeclueErr> {
+        let first = match classifiers.first() {
+            Some(first) => first,
+            None => return PrimeclueErr::result("Ensemble needs a classifier".to_string()),
+        };
+        for (index, classifier) in classifiers.iter().enumerate().skip(1) {
+            if classifier.get_classes() != first.get_classes() {
+                return PrimeclueErr::result(format!(
+                    "Classifier {} has different classes than the first one",
+                    index
+                ));
+            } else if classifier.input_shape() != first.input_shape() {
+                return PrimeclueErr::result(format!(
+                    "Classifier {} has input shape {}, expected {}",
+                    index,
+                    classifier.input_shape(),
+                    first.input_shape()
+                ));
+            }
+        }
+        if weights.len() != classifiers.len() {
+            return PrimeclueErr::result(format!(
+                "Got {} weights for {} classifiers",
+                weights.len(),
+                classifiers.len()
+            ));
+        }
+        if let Some(index) = weights.iter().position(|w| !w.is_finite() || *w < 0.0) {
+            return PrimeclueErr::result(format!(
+                "Classifier {} has invalid weight {}",
+                index, weights[index]
+            ));
+        }
+        if weights.iter().all(|w| *w == 0.0) {
+            return PrimeclueErr::result("Ensemble needs a positive weight".to_string());
+        }
+        Ok(Ensemble { classifiers, weights, combine_mode })
+    }
+
+    /// Same ensemble with predictions combined by `combine_mode`
+    #[must_use]
+    pub fn with_combine_mode(mut self, combine_mode: CombineMode) -> Self {
+        self.combine_mode = combine_mode;
+        self
+    }
+
+    /// Same ensemble without classifiers whose weight is lower than `floor`.
+    /// Fails if no classifier with positive weight is left.
+    pub fn with_score_floor(self, floor: f32) -> Result<Self, PrimeclueErr> {
+        let (classifiers, weights) = self
+            .classifiers
+            .into_iter()
+            .zip(self.weights)
+            .filter(|(_, weight)| *weight >= floor)
+            .unzip();
+        Ensemble::new(classifiers, weights, self.combine_mode)
+    }
+
+    #[must_use]
+    pub fn classifiers(&self) -> &[Classifier] {
+        &self.classifiers
+    }
+
+    /// Weight of every classifier, used by [`CombineMode::WeightedSoftVote`]
+    #[must_use]
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    #[must_use]
+    pub fn combine_mode(&self) -> CombineMode {
+        self.combine_mode
+    }
+
+    /// With [`CombineMode::MajorityVote`] predicts the [`Class`] that most classifiers predict
+    /// for `input`. Ties are broken by summed confidence of [`Classifier::predict`]. Also
+    /// returns the share of classifiers that voted for the class. Returns `None` if no
+    /// classifier predicted anything. Weights are not used.
+    ///
+    /// With [`CombineMode::WeightedSoftVote`] predicts the class with the highest sum of
+    /// weighted probabilities, together with the sum divided by the sum of weights.
+    ///
+    /// Fails if `input` has different shape than training data.
+    pub fn predict(&self, input: &Input) -> Result<Option<(Class, f32)>, PrimeclueErr> {
+        if self.combine_mode == CombineMode::WeightedSoftVote {
+            let probabilities = self
+                .classifiers
+                .iter()
+                .map(|c| c.predict_proba(input))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(
+                self.soft_vote(&self.weighted_sums(&probabilities.iter().collect::<Vec<_>>()))
+            );
+        }
+        let predictions =
+            self.classifiers.iter().map(|c| c.predict(input)).collect::<Result<Vec<_>, _>>()?;
+        Ok(vote(predictions.into_iter(), self.classifiers.len()))
+    }
+
+    /// Scores combined predictions for `data`. AUCs are computed with summed confidence
+    /// of votes, or summed weighted probabilities, for each class; regression errors are
+    /// not computed. Fails if `data` has different input shape or classes than training data.
+    pub fn score(&self, data: &DataView) -> Result<ClassifierScore, PrimeclueErr> {
+        self.classifiers[0].check_shape(data.input_shape())?;
+        let mismatch =
+            || PrimeclueErr::from("Data classes don't match ensemble classes".to_string());
+        let classes = self.classifiers[0].get_classes();
+        if self.combine_mode == CombineMode::WeightedSoftVote {
+            return self.score_soft_vote(data).ok_or_else(mismatch);
+        }
+        let votes = self
+            .classifiers
+            .iter()
+            .map(|c| c.predict_view(data))
+            .collect::<Result<Vec<_>, _>>()?;
+        let points = data.outcomes().len();
+        let mut predictions = Vec::with_capacity(points);
+        // summed confidence of votes, indexed by position of class and point
+        let mut class_values = vec![vec![0.0; points]; classes.len()];
+        for point in 0..points {
+            for (class, confidence) in votes.iter().filter_map(|v| v[point]) {
+                *classes
+                    .keys()
+                    .position(|c| *c == class)
+                    .and_then(|index| class_values[index].get_mut(point))
+                    .ok_or_else(mismatch)? += confidence;
+            }
+            predictions.push(vote(votes.iter().map(|v| v[point]), votes.len()).map(|p| p.0));
+        }
+        let auc = class_auc(&class_values, classes, data.outcomes(), calculate_auc);
+        let macro_auc =
+            class_auc(&class_values, classes, data.outcomes(), calculate_auc_with_ties);
+        ClassifierScore::from_predictions(classes, &predictions, data, auc, macro_auc)
+            .ok_or_else(mismatch)
+    }
+
+    fn score_soft_vote(&self, data: &DataView) -> Option<ClassifierScore> {
+        let classes = self.classifiers[0].get_classes();
+        let probabilities =
+            self.classifiers.iter().map(|c| c.predict_proba_view(data)).collect::<Vec<_>>();
+        let points = data.outcomes().len();
+        let mut predictions = Vec::with_capacity(points);
+        // summed weighted probabilities, indexed by position of class and point
+        let mut class_values = vec![vec![0.0; points]; classes.len()];
+        for point in 0..points {
+            let point_probabilities =
+                probabilities.iter().map(|p| &p[point]).collect::<Vec<_>>();
+            let sums = self.weighted_sums(&point_probabilities);
+            predictions.push(self.soft_vote(&sums).map(|p| p.0));
+            for (class, sum) in sums {
+                let index = classes.keys().position(|c| *c == class)?;
+                *class_values[index].get_mut(point)? = sum;
+            }
+        }
+        let auc = class_auc(&class_values, classes, data.outcomes(), calculate_auc);
+        let macro_auc =
+            class_auc(&class_values, classes, data.outcomes(), calculate_auc_with_ties);
+        ClassifierScore::from_predictions(classes, &predictions, data, auc, macro_auc)
+    }
+
+    /// Class with the highest of weighted probability `sums` and the sum divided by the sum
+    /// of weights, the lower class wins a tie
+    fn soft_vote(&self, sums: &HashMap<Class, f32>) -> Option<(Class, f32)> {
+        let total = self.weights.iter().sum::<f32>();
+        sums.iter()
+            .max_by(|(class1, sum1), (class2, sum2)| {
+                sum1.partial_cmp(sum2).unwrap_or(Equal).then(class2.value().cmp(&class1.value()))
+            })
+            .map(|(class, sum)| (*class, sum / total))
+    }
+
+    /// Probabilities of every class multiplied by weights of classifiers and summed
+    fn weighted_sums(&self, probabilities: &[&HashMap<Class, f32>]) -> HashMap<Class, f32> {
+        let mut sums = HashMap::new();
+        for (class_probabilities, weight) in probabilities.iter().zip(&self.weights) {
+            for (class, probability) in class_probabilities.iter() {
+                *sums.entry(*class).or_insert(0.0) += weight * probability;
+            }
+        }
+        sums
+    }
+
+    /// Saves all classifiers of [`Ensemble`] to a single file, the same way as
+    /// [`Classifier::save`] does
+    pub fn save(&self, path: &Path) -> Result<(), PrimeclueErr> {
+        let mut ser = Serializator::new();
+        ser.add_str(ENSEMBLE_MAGIC);
+        ser.add(&ENSEMBLE_FORMAT_VERSION);
+        ser.add(self);
+        fs::write(path, ser.to_bytes())
+            .map_err(|e| PrimeclueErr::from(format!("Unable to write {:?}: {}", path, e)))
+    }
+
+    /// Loads [`Ensemble`] previously saved with [`Ensemble::save`]. Files of the first
+    /// format version, which had no weights, are loaded with weight 1.0 of every classifier
+    /// and [`CombineMode::MajorityVote`].
+    pub fn load(path: &Path) -> Result<Ensemble, PrimeclueErr> {
+        let mut ser = Serializator::load(path)?;
+        let magic = String::deserialize(&mut ser)?;
+        if magic != ENSEMBLE_MAGIC {
+            return PrimeclueErr::result(format!("{:?} is not a Primeclue ensemble file", path));
+        }
+        let version = usize::deserialize(&mut ser)?;
+        let ensemble = match version {
+            ENSEMBLE_FORMAT_V1 => Vec::deserialize(&mut ser).map(|classifiers: Vec<_>| {
+                let weights = vec![1.0; classifiers.len()];
+                Ensemble { classifiers, weights, combine_mode: CombineMode::MajorityVote }
+            }),
+            ENSEMBLE_FORMAT_VERSION => Ensemble::deserialize(&mut ser),
+            _ => {
+                return PrimeclueErr::result(format!(
+                    "Unsupported ensemble format version {} in {:?}, expected {}",
+                    version, path, ENSEMBLE_FORMAT_VERSION
+                ))
+            }
+        }
+        .map_err(|e| PrimeclueErr::from(format!("Unable to deserialize ensemble: {}", e)))?;
+        Ensemble::new(ensemble.classifiers, ensemble.weights, ensemble.combine_mode)
+    }
+}
+
+/// Class with most votes and the share of `count` classifiers that voted for it
+fn vote(
+    predictions: impl Iterator<Item = Option<(Class, f32)>>,
+    count: usize,
+) -> Option<(Class, f32)> {
+    let mut tally: HashMap<Class, (usize, f32)> = HashMap::new();
+    for (class, confidence) in predictions.flatten() {
+        let (votes, confidence_sum) = tally.entry(class).or_insert((0, 0.0));
+        *votes += 1;
+        *confidence_sum += confidence;
+    }
+    tally
+        .into_iter()
+        .max_by(|(class1, (votes1, sum1)), (class2, (votes2, sum2))| {
+            // lower class wins a complete tie, so that result doesn't depend on map order
+            votes1
+                .cmp(votes2)
+                .then(sum1.partial_cmp(sum2).unwrap_or(Equal))
+                .then(class2.value().cmp(&class1.value()))
+        })
+        .map(|(class, (votes, _))| (class, votes as f32 / count as f32))
+}
+
+/// Average one-vs-rest `auc` of `classes`, with `class_values` in the same order as outputs
+fn class_auc(
+    class_values: &[Vec<f32>],
+    classes: &BTreeMap<Class, String>,
+    outcomes: &[Outcome],
+    auc: fn(&[(f32, Outcome)], Class) -> f32,
+) -> f32 {
+    let mut sum = 0.0;
+    for (values, class) in class_values.iter().zip(classes.keys()) {
+        let mut sorted =
+            values.iter().copied().zip(outcomes.iter().copied()).collect::<Vec<_>>();
+        sorted.sort_by(|(v1, _), (v2, _)| v1.partial_cmp(v2).unwrap_or(Equal));
+        sum += auc(&sorted, *class);
+    }
+    sum / class_values.len() as f32
+}
+
+impl Serializable for Ensemble {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_items(&[&self.classifiers, &self.weights, &self.combine_mode]);
+    }
+}
+
+impl Deserializable for Ensemble {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        let classifiers = Vec::deserialize(s)?;
+        let weights = Vec::deserialize(s)?;
+        let combine_mode = CombineMode::deserialize(s)?;
+        Ok(Ensemble { classifiers, weights, combine_mode })
+    }
+}
+
+impl Serializable for CombineMode {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(match self {
+            CombineMode::MajorityVote => "MajorityVote",
+            CombineMode::WeightedSoftVote => "WeightedSoftVote",
+        })
+    }
+}
+
+impl Deserializable for CombineMode {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "MajorityVote" => Ok(CombineMode::MajorityVote),
+            "WeightedSoftVote" => Ok(CombineMode::WeightedSoftVote),
+            other => Err(format!("Invalid combine mode {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::DataSet;
+    use crate::data::outcome::Class;
+    use crate::data::{Input, Outcome, Point};
+    use crate::exec::classifier::Classifier;
+    use crate::exec::ensemble::{CombineMode, Ensemble, ENSEMBLE_FORMAT_V1, ENSEMBLE_MAGIC};
+    use crate::exec::score::Objective::Auc;
+    use crate::exec::score::{Score, Threshold};
+    use crate::exec::scored_tree::ScoredTree;
+    use crate::exec::tree::test::create_short_tree;
+    use crate::serialization::Serializator;
+    use std::collections::HashMap;
+    use std::{env, process};
+
+    /// Predicts class 1 for values >= `threshold`, class 0 otherwise
+    fn create_classifier(threshold: f32) -> Classifier {
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "low".to_string());
+        classes.insert(Class::new(1), "high".to_string());
+        let trees = vec![
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(0), 0.5, Threshold::new(-100.0)),
+            ),
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(1), 0.9, Threshold::new(threshold)),
+            ),
+        ];
+        Classifier::new(classes, trees).unwrap()
+    }
+
+    /// Class trees output the input value, so thresholds alone decide probabilities
+    fn create_soft_classifier(threshold0: f32, threshold1: f32) -> Classifier {
+        let classifier = create_classifier(threshold1);
+        let trees = vec![
+            ScoredTree::new(
+                create_short_tree(),
+                Score::new(Auc, Class::new(0), 0.5, Threshold::new(threshold0)),
+            ),
+            classifier.sorted_trees()[1].clone(),
+        ];
+        Classifier::new(classifier.get_classes().clone(), trees).unwrap()
+    }
+
+    fn create_ensemble() -> Ensemble {
+        let classifiers =
+            vec![create_classifier(1.5), create_classifier(2.5), create_classifier(3.5)];
+        Ensemble::from_classifiers(classifiers).unwrap()
+    }
+
+    fn input(x: f32) -> Input {
+        Input::from_vector(vec![vec![x]]).unwrap()
+    }
+
+    #[test]
+    fn predict() {
+        let ensemble = create_ensemble();
+        let (class, share) = ensemble.predict(&input(3.0)).unwrap().unwrap();
+        assert_eq!(class, Class::new(1));
+        assert!((share - 2.0 / 3.0).abs() < f32::EPSILON);
+        let (class, share) = ensemble.predict(&input(2.0)).unwrap().unwrap();
+        assert_eq!(class, Class::new(0));
+        assert!((share - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert!(ensemble.predict(&Input::from_vector(vec![vec![1.0, 2.0]]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn tie_broken_by_confidence() {
+        let classifiers = vec![create_classifier(1.5), create_classifier(3.5)];
+        let ensemble = Ensemble::from_classifiers(classifiers).unwrap();
+        // first classifier barely predicts class 1, second is sure about class 0
+        let (class, share) = ensemble.predict(&input(2.0)).unwrap().unwrap();
+        assert_eq!(class, Class::new(0));
+        assert!((share - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn score() {
+        let ensemble = create_ensemble();
+        let mut data = DataSet::new(ensemble.classifiers()[0].get_classes().clone());
+        for (x, class) in &[(1.0, 0), (2.0, 0), (3.0, 1), (4.0, 1)] {
+            let outcome = Outcome::new(Class::new(*class), 1.0, -1.0);
+            data.add_data_point(Point::new(input(*x), outcome)).unwrap();
+        }
+        let data = data.into_view();
+        let score = ensemble.score(&data).unwrap();
+        assert!((score.accuracy - 1.0).abs() < f32::EPSILON);
+        assert!((score.macro_auc() - 1.0).abs() < f32::EPSILON);
+        // the outer classifiers make one mistake each, outvoted by the others
+        for index in &[0, 2] {
+            let accuracy = ensemble.classifiers()[*index].score(&data).unwrap().accuracy;
+            assert!((accuracy - 0.75).abs() < f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn weighted_soft_vote() {
+        let low = create_soft_classifier(0.0, 1.0);
+        let high = create_soft_classifier(1.0, 0.0);
+        let ensemble =
+            Ensemble::from_scored(vec![(low.clone(), 0.9), (high.clone(), 0.6)]).unwrap();
+        assert_eq!(ensemble.combine_mode(), CombineMode::WeightedSoftVote);
+        let (class, confidence) = ensemble.predict(&input(5.0)).unwrap().unwrap();
+        assert_eq!(class, Class::new(0));
+        let expected = (0.9 * low.predict_proba(&input(5.0)).unwrap()[&Class::new(0)]
+            + 0.6 * high.predict_proba(&input(5.0)).unwrap()[&Class::new(0)])
+            / 1.5;
+        assert!((confidence - expected).abs() < 1e-6);
+        let ensemble =
+            Ensemble::from_scored(vec![(low.clone(), 0.6), (high.clone(), 0.9)]).unwrap();
+        assert_eq!(ensemble.predict(&input(5.0)).unwrap().unwrap().0, Class::new(1));
+        assert!(ensemble.predict(&Input::from_vector(vec![vec![1.0, 2.0]]).unwrap()).is_err());
+
+        let mut data = DataSet::new(low.get_classes().clone());
+        for (x, class) in &[(1.0, 0), (2.0, 0), (3.0, 1), (4.0, 1)] {
+            let outcome = Outcome::new(Class::new(*class), 1.0, -1.0);
+            data.add_data_point(Point::new(input(*x), outcome)).unwrap();
+        }
+        let score = ensemble.score(&data.into_view()).unwrap();
+        assert!((score.accuracy - 0.5).abs() < f32::EPSILON);
+
+        let majority = ensemble.clone().with_combine_mode(CombineMode::MajorityVote);
+        assert_eq!(majority.combine_mode(), CombineMode::MajorityVote);
+        assert_eq!(majority.weights(), &[0.6, 0.9]);
+    }
+
+    #[test]
+    fn score_floor() {
+        let members = vec![
+            (create_soft_classifier(0.0, 1.0), 0.9),
+            (create_soft_classifier(1.0, 0.0), 0.6),
+            (create_soft_classifier(1.0, 0.0), 0.5),
+        ];
+        let ensemble = Ensemble::from_scored(members).unwrap();
+        let strong = ensemble.clone().with_score_floor(0.6).unwrap();
+        assert_eq!(strong.weights(), &[0.9, 0.6]);
+        assert_eq!(strong.classifiers(), &ensemble.classifiers()[..2]);
+        assert!(ensemble.with_score_floor(1.0).is_err());
+        let invalid = vec![(create_classifier(1.0), 0.5), (create_classifier(2.0), -0.5)];
+        assert!(Ensemble::from_scored(invalid).is_err());
+        assert!(Ensemble::from_scored(vec![(create_classifier(1.0), 0.0)]).is_err());
+        assert!(Ensemble::from_scored(vec![(create_classifier(1.0), f32::NAN)]).is_err());
+    }
+
+    #[test]
+    fn invalid_ensemble() {
+        assert!(Ensemble::from_classifiers(vec![]).is_err());
+        let mut other = create_classifier(1.0);
+        let mut classes = other.get_classes().clone();
+        classes.insert(Class::new(1), "other".to_string());
+        other = Classifier::new(classes, other.sorted_trees().into_iter().cloned().collect())
+            .unwrap();
+        assert!(Ensemble::from_classifiers(vec![create_classifier(1.0), other]).is_err());
+    }
+
+    #[test]
+    fn save_load_ensemble() {
+        let path = env::temp_dir().join(format!("primeclue_ensemble_{}.ssd", process::id()));
+        let ensemble = create_ensemble();
+        ensemble.save(&path).unwrap();
+        let loaded = Ensemble::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, ensemble);
+        assert!(Classifier::load(&path).is_err());
+        let members = vec![(create_classifier(1.0), 0.75), (create_classifier(2.0), 0.5)];
+        let ensemble = Ensemble::from_scored(members).unwrap();
+        ensemble.save(&path).unwrap();
+        let loaded = Ensemble::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, ensemble);
+
+        // the first format version had classifiers only
+        let mut s = Serializator::new();
+        s.add_str(ENSEMBLE_MAGIC);
+        s.add(&ENSEMBLE_FORMAT_V1);
+        s.add(&create_ensemble().classifiers().to_vec());
+        std::fs::write(&path, s.to_bytes()).unwrap();
+        let loaded = Ensemble::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, create_ensemble());
+        assert_eq!(loaded.weights(), &[1.0, 1.0, 1.0]);
+        assert_eq!(loaded.combine_mode(), CombineMode::MajorityVote);
+    }
+
+    #[test]
+    fn score_with_class_gaps() {
+        let gapped = |threshold| {
+            let classes = HashMap::from([
+                (Class::new(2), "low".to_string()),
+                (Class::new(5), "high".to_string()),
+            ]);
+            let trees = vec![
+                ScoredTree::new(
+                    create_short_tree(),
+                    Score::new(Auc, Class::new(2), 0.5, Threshold::new(-100.0)),
+                ),
+                ScoredTree::new(
+                    create_short_tree(),
+                    Score::new(Auc, Class::new(5), 0.9, Threshold::new(threshold)),
+                ),
+            ];
+            Classifier::new(classes, trees).unwrap()
+        };
+        let data = |low, high, classes| {
+            let mut data = DataSet::new(classes);
+            for (x, class) in &[(1.0, low), (2.0, low), (3.0, high), (4.0, high)] {
+                let outcome = Outcome::new(Class::new(*class), 1.0, -1.0);
+                data.add_data_point(Point::new(input(*x), outcome)).unwrap();
+            }
+            data.into_view()
+        };
+        let gapped_data = data(2, 5, gapped(0.0).get_classes().clone());
+        let consecutive_data = data(0, 1, create_classifier(0.0).get_classes().clone());
+        let thresholds = [1.5, 2.5, 3.5];
+        let gapped =
+            Ensemble::from_scored(thresholds.iter().map(|t| (gapped(*t), 1.0)).collect());
+        let consecutive = Ensemble::from_scored(
+            thresholds.iter().map(|t| (create_classifier(*t), 1.0)).collect(),
+        );
+        for mode in [CombineMode::MajorityVote, CombineMode::WeightedSoftVote] {
+            let gapped = gapped.clone().unwrap().with_combine_mode(mode);
+            let consecutive = consecutive.clone().unwrap().with_combine_mode(mode);
+            let score = gapped.score(&gapped_data).unwrap();
+            let expected = consecutive.score(&consecutive_data).unwrap();
+            assert_eq!(score.accuracy.to_bits(), expected.accuracy.to_bits());
+            assert_eq!(score.auc.to_bits(), expected.auc.to_bits());
+            assert_eq!(score.macro_auc().to_bits(), expected.macro_auc().to_bits());
+            assert_eq!(score.confusion_matrix(), expected.confusion_matrix());
+        }
+    }
+}
@@ -17,10 +17,39 @@
    along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::data::Input;
 use crate::serialization::{Deserializable, Serializable, Serializator};
 use std::cmp::Ordering;
 use std::f32::consts::*;
 use std::fmt::{Debug, Error, Formatter};
+use std::sync::RwLock;
+#[cfg(feature = "simd")]
+use wide::{f32x8, CmpEq};
+
+/// Defines a function on single values together with constant `$source` holding its name and
+/// code, so that code exported by [`crate::exec::classifier::Classifier::to_rust_source`] is
+/// exactly the code that is executed during training.
+macro_rules! with_source {
+    (
+        $source:ident,
+        $(#[$attr:meta])* fn $name:ident($($arg:ident: f32),+) -> f32 $body:block
+    ) => {
+        $(#[$attr])*
+        fn $name($($arg: f32),+) -> f32 $body
+
+        const $source: ScalarSource = ScalarSource {
+            name: stringify!($name),
+            code: stringify!(fn $name($($arg: f32),+) -> f32 $body),
+        };
+    };
+}
+
+/// Name and Rust code of a function on single values
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ScalarSource {
+    pub(crate) name: &'static str,
+    pub(crate) code: &'static str,
+}
 
 pub static MATH_CONSTANTS: [MathConst; 6] = [
     MathConst { name: "0", value: 0.0 },
@@ -31,6 +60,298 @@ pub static MATH_CONSTANTS: [MathConst; 6] = [
     MathConst { name: "2pi", value: 2.0 * PI },
 ];
 
+/// Kind of a function node trees are built of, used to restrict which functions training
+/// may use. Data values, standard deviations and constants are always allowed. Kinds of
+/// [`NodeKind::OPT_IN`] are used only if allowed explicitly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NodeKind {
+    Abs,
+    Ceil,
+    Cosine,
+    Dec,
+    Exp,
+    Floor,
+    Inc,
+    Log,
+    Neg,
+    Normalize,
+    Reciprocal,
+    Relu,
+    Round,
+    Sine,
+    Sqrt,
+    Square,
+    TauSigmoid,
+    TangHyper,
+    AbsHigher,
+    AbsLower,
+    Add,
+    And,
+    Diff,
+    Div,
+    DivOrOne,
+    DivOrNumerator,
+    Equal,
+    FirstIsHigher,
+    Higher,
+    Lower,
+    Mid,
+    Mul,
+    Or,
+    Sub,
+    SumOfSquares,
+    Xor,
+    RoundEqual,
+    GreaterThan,
+    LessThan,
+    IfThenElse,
+}
+
+impl NodeKind {
+    pub const ALL: [NodeKind; 40] = [
+        NodeKind::Abs,
+        NodeKind::Ceil,
+        NodeKind::Cosine,
+        NodeKind::Dec,
+        NodeKind::Exp,
+        NodeKind::Floor,
+        NodeKind::Inc,
+        NodeKind::Log,
+        NodeKind::Neg,
+        NodeKind::Normalize,
+        NodeKind::Reciprocal,
+        NodeKind::Relu,
+        NodeKind::Round,
+        NodeKind::Sine,
+        NodeKind::Sqrt,
+        NodeKind::Square,
+        NodeKind::TauSigmoid,
+        NodeKind::TangHyper,
+        NodeKind::AbsHigher,
+        NodeKind::AbsLower,
+        NodeKind::Add,
+        NodeKind::And,
+        NodeKind::Diff,
+        NodeKind::Div,
+        NodeKind::DivOrOne,
+        NodeKind::DivOrNumerator,
+        NodeKind::Equal,
+        NodeKind::FirstIsHigher,
+        NodeKind::Higher,
+        NodeKind::Lower,
+        NodeKind::Mid,
+        NodeKind::Mul,
+        NodeKind::Or,
+        NodeKind::Sub,
+        NodeKind::SumOfSquares,
+        NodeKind::Xor,
+        NodeKind::RoundEqual,
+        NodeKind::GreaterThan,
+        NodeKind::LessThan,
+        NodeKind::IfThenElse,
+    ];
+
+    /// Decision-rule kinds, forbidden unless allowed by
+    /// [`TrainingGroup::with_allowed_node_kinds`], so that trees stay arithmetic by default
+    ///
+    /// [`TrainingGroup::with_allowed_node_kinds`]:
+    /// crate::exec::training_group::TrainingGroup::with_allowed_node_kinds
+    pub const OPT_IN: [NodeKind; 3] =
+        [NodeKind::GreaterThan, NodeKind::LessThan, NodeKind::IfThenElse];
+
+    /// Whether the function compares its arguments, giving 1 or 0. Constants compared to
+    /// integer and boolean columns are whole numbers, see [`DataSet::with_feature_types`].
+    ///
+    /// [`DataSet::with_feature_types`]: crate::data::data_set::DataSet::with_feature_types
+    #[must_use]
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            NodeKind::Equal
+                | NodeKind::FirstIsHigher
+                | NodeKind::RoundEqual
+                | NodeKind::GreaterThan
+                | NodeKind::LessThan
+        )
+    }
+
+    /// Whether the function compares its arguments by order rather than equality
+    #[must_use]
+    pub fn is_ordering(&self) -> bool {
+        matches!(self, NodeKind::FirstIsHigher | NodeKind::GreaterThan | NodeKind::LessThan)
+    }
+
+    /// Name of the function, as used in serialized trees
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        ONE_ARG_FUNCTIONS
+            .iter()
+            .map(|f| (f.kind, f.name))
+            .chain(TWO_ARG_FUNCTIONS.iter().map(|f| (f.kind, f.name)))
+            .chain(THREE_ARG_FUNCTIONS.iter().map(|f| (f.kind, f.name)))
+            .find(|(kind, _)| kind == self)
+            .map(|(_, name)| name)
+            .unwrap()
+    }
+
+    /// Number of arguments of the function, from 1 to 3
+    #[must_use]
+    pub fn arity(&self) -> usize {
+        if ONE_ARG_FUNCTIONS.iter().any(|f| f.kind == *self) {
+            1
+        } else if THREE_ARG_FUNCTIONS.iter().any(|f| f.kind == *self) {
+            3
+        } else {
+            2
+        }
+    }
+
+    /// Name and Rust code of the function computing a single value of this kind. The code
+    /// refers to `E`, `PI` and `Ordering` from `std`.
+    pub(crate) fn scalar_source(self) -> ScalarSource {
+        match self {
+            NodeKind::Abs => ABS_SOURCE,
+            NodeKind::Ceil => CEIL_SOURCE,
+            NodeKind::Cosine => COSINE_SOURCE,
+            NodeKind::Dec => DEC_SOURCE,
+            NodeKind::Exp => EXP_SOURCE,
+            NodeKind::Floor => FLOOR_SOURCE,
+            NodeKind::Inc => INC_SOURCE,
+            NodeKind::Log => LOG_SOURCE,
+            NodeKind::Neg => NEG_SOURCE,
+            NodeKind::Normalize => TO_ONE_SOURCE,
+            NodeKind::Reciprocal => RECIPROCAL_SOURCE,
+            NodeKind::Relu => RELU_SOURCE,
+            NodeKind::Round => ROUND_SOURCE,
+            NodeKind::Sine => SINE_SOURCE,
+            NodeKind::Sqrt => SQRT_SOURCE,
+            NodeKind::Square => SQUARE_SOURCE,
+            NodeKind::TauSigmoid => TAU_SIGMOID_SOURCE,
+            NodeKind::TangHyper => TANG_HYPER_SOURCE,
+            NodeKind::AbsHigher => ABS_HIGHER_SOURCE,
+            NodeKind::AbsLower => ABS_LOWER_SOURCE,
+            NodeKind::Add => ADD_SOURCE,
+            NodeKind::And => AND_SOURCE,
+            NodeKind::Diff => DIFF_SOURCE,
+            NodeKind::Div => DIV_SOURCE,
+            NodeKind::DivOrOne => DIV_OR_ONE_SOURCE,
+            NodeKind::DivOrNumerator => DIV_OR_NUMERATOR_SOURCE,
+            NodeKind::Equal => EQUAL_SOURCE,
+            NodeKind::FirstIsHigher => FIRST_IS_HIGHER_SOURCE,
+            NodeKind::Higher => HIGHER_SOURCE,
+            NodeKind::Lower => LOWER_SOURCE,
+            NodeKind::Mid => MID_SOURCE,
+            NodeKind::Mul => MUL_SOURCE,
+            NodeKind::Or => OR_SOURCE,
+            NodeKind::Sub => SUB_SOURCE,
+            NodeKind::SumOfSquares => SUM_OF_SQUARES_SOURCE,
+            NodeKind::Xor => XOR_SOURCE,
+            NodeKind::RoundEqual => ROUND_EQUAL_SOURCE,
+            NodeKind::GreaterThan => GREATER_THAN_SOURCE,
+            NodeKind::LessThan => LESS_THAN_SOURCE,
+            NodeKind::IfThenElse => IF_THEN_ELSE_SOURCE,
+        }
+    }
+}
+
+impl Serializable for NodeKind {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(self.name())
+    }
+}
+
+impl Deserializable for NodeKind {
+    fn deserialize(s: &mut Serializator) -> Result<NodeKind, String> {
+        let v = s.next_token()?;
+        NodeKind::ALL
+            .iter()
+            .find(|kind| kind.name() == v)
+            .copied()
+            .ok_or_else(|| format!("NodeKind {} not found", v))
+    }
+}
+
+/// What [`NodeKind::Div`] gives when dividing by zero, instead of infinity or NaN:
+/// * `ReturnZero` - 0, which is how division always worked
+/// * `ReturnOne` - 1
+/// * `ReturnNumerator` - the numerator, as if divided by 1
+///
+/// Every mode is a function of its own kind, so trees of a saved classifier keep dividing
+/// the way they did in training. Node kind restrictions refer to division as
+/// [`NodeKind::Div`] whatever the mode, kinds of other modes are never used.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SafeDivMode {
+    #[default]
+    ReturnZero,
+    ReturnOne,
+    ReturnNumerator,
+}
+
+impl SafeDivMode {
+    const DIVISIONS: [NodeKind; 3] =
+        [NodeKind::Div, NodeKind::DivOrOne, NodeKind::DivOrNumerator];
+
+    /// Kind of division function trees are built with in this mode
+    #[must_use]
+    pub fn kind(self) -> NodeKind {
+        match self {
+            SafeDivMode::ReturnZero => NodeKind::Div,
+            SafeDivMode::ReturnOne => NodeKind::DivOrOne,
+            SafeDivMode::ReturnNumerator => NodeKind::DivOrNumerator,
+        }
+    }
+
+    /// `forbidden` kinds with divisions of other modes added, and all divisions if
+    /// [`NodeKind::Div`] is forbidden
+    pub(crate) fn forbidden(self, forbidden: &[NodeKind]) -> Vec<NodeKind> {
+        let division_forbidden = forbidden.contains(&NodeKind::Div);
+        let mut kinds = forbidden
+            .iter()
+            .copied()
+            .filter(|kind| !SafeDivMode::DIVISIONS.contains(kind))
+            .collect::<Vec<_>>();
+        kinds.extend(
+            SafeDivMode::DIVISIONS
+                .iter()
+                .filter(|kind| division_forbidden || **kind != self.kind()),
+        );
+        kinds
+    }
+}
+
+impl Serializable for SafeDivMode {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(match self {
+            SafeDivMode::ReturnZero => "ReturnZero",
+            SafeDivMode::ReturnOne => "ReturnOne",
+            SafeDivMode::ReturnNumerator => "ReturnNumerator",
+        })
+    }
+}
+
+impl Deserializable for SafeDivMode {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        match s.next_token()?.as_str() {
+            "ReturnZero" => Ok(SafeDivMode::ReturnZero),
+            "ReturnOne" => Ok(SafeDivMode::ReturnOne),
+            "ReturnNumerator" => Ok(SafeDivMode::ReturnNumerator),
+            other => Err(format!("Invalid safe division mode {}", other)),
+        }
+    }
+}
+
+pub(crate) fn allowed_one_arg(forbidden: &[NodeKind]) -> Vec<&'static SingleArgFunction> {
+    ONE_ARG_FUNCTIONS.iter().filter(|f| !forbidden.contains(&f.kind)).collect()
+}
+
+pub(crate) fn allowed_two_arg(forbidden: &[NodeKind]) -> Vec<&'static DoubleArgFunction> {
+    TWO_ARG_FUNCTIONS.iter().filter(|f| !forbidden.contains(&f.kind)).collect()
+}
+
+pub(crate) fn allowed_three_arg(forbidden: &[NodeKind]) -> Vec<&'static TripleArgFunction> {
+    THREE_ARG_FUNCTIONS.iter().filter(|f| !forbidden.contains(&f.kind)).collect()
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct MathConst {
     name: &'static str,
@@ -41,6 +362,10 @@ impl MathConst {
     pub fn value(&self) -> f32 {
         self.value
     }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
 }
 
 impl Serializable for MathConst {
@@ -61,10 +386,143 @@ impl Deserializable for &MathConst {
     }
 }
 
-fn single_array_fun(f: fn(f32) -> f32, mut v: Vec<f32>) -> Vec<f32> {
-    for value in &mut v {
+/// Function computing a value of a user-defined terminal from the whole input of a point
+pub type TerminalFun = dyn Fn(&Input) -> f32 + Sync;
+
+/// User-defined terminal, a leaf of trees computing a value from the whole input of a point,
+/// e.g. a column modulo 7 or a ratio of two columns, so that such values don't have to be
+/// added to data as columns. Trees get terminals from
+/// [`GeneticConfig::terminals`](crate::exec::class_training::GeneticConfig::terminals)
+/// and refer to them, so terminals must live as long as trees and classifiers made of them,
+/// e.g. in a `static` or leaked with [`Box::leak`].
+///
+/// Only names of terminals are serialized, trees, classifiers and training state using them
+/// can be deserialized only after the same terminals are given to [`register_terminals`] in
+/// the process. Terminals prevent [`Classifier::to_rust_source`] and [`Classifier::to_onnx`].
+///
+/// [`Classifier::to_rust_source`]: crate::exec::classifier::Classifier::to_rust_source
+/// [`Classifier::to_onnx`]: crate::exec::classifier::Classifier::to_onnx
+#[derive(Copy, Clone)]
+pub struct Terminal {
+    name: &'static str,
+    fun: &'static TerminalFun,
+}
+
+impl Terminal {
+    /// Creates terminal named `name`, which must be unique among registered terminals and
+    /// have no whitespace, so that it can be serialized. Training fails if terminals of its
+    /// [`GeneticConfig`](crate::exec::class_training::GeneticConfig) don't meet that.
+    #[must_use]
+    pub const fn new(name: &'static str, fun: &'static TerminalFun) -> Terminal {
+        Terminal { name, fun }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    #[must_use]
+    pub fn value(&self, input: &Input) -> f32 {
+        (self.fun)(input)
+    }
+}
+
+impl Debug for Terminal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_struct("Terminal").field("name", &self.name).finish_non_exhaustive()
+    }
+}
+
+/// Terminals are equal if their names are, functions can't be compared
+impl PartialEq for Terminal {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+lazy_static! {
+    static ref TERMINALS: RwLock<Vec<&'static [Terminal]>> = RwLock::new(Vec::new());
+}
+
+/// Makes `terminals` known to deserialization of trees referring to them by name, see
+/// [`Terminal`]. Terminals of later calls replace earlier ones of the same names. Training
+/// registers terminals of its
+/// [`GeneticConfig`](crate::exec::class_training::GeneticConfig) itself.
+pub fn register_terminals(terminals: &'static [Terminal]) {
+    let mut registered = TERMINALS.write().unwrap();
+    if !registered.iter().any(|r| std::ptr::eq(*r, terminals)) {
+        registered.push(terminals);
+    }
+}
+
+/// Latest registered terminal named `name`
+fn registered_terminal(name: &str) -> Option<&'static Terminal> {
+    let registered = TERMINALS.read().unwrap();
+    registered.iter().rev().flat_map(|terminals| terminals.iter()).find(|t| t.name == name)
+}
+
+/// Latest registered slice of terminals named as `names`, in order
+pub(crate) fn registered_terminals(names: &[String]) -> Option<&'static [Terminal]> {
+    if names.is_empty() {
+        return Some(&[]);
+    }
+    let registered = TERMINALS.read().unwrap();
+    registered.iter().rev().copied().find(|terminals| {
+        terminals.len() == names.len() && terminals.iter().zip(names).all(|(t, n)| t.name == n)
+    })
+}
+
+impl Serializable for Terminal {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(self.name)
+    }
+}
+
+impl Deserializable for &Terminal {
+    fn deserialize(s: &mut Serializator) -> Result<&'static Terminal, String> {
+        let name = String::deserialize(s)?;
+        registered_terminal(&name).ok_or_else(|| format!("Terminal {} is not registered", name))
+    }
+}
+
+/// Applies `f` to every value in place. Generic instead of taking a function pointer, so that
+/// every function gets its own loop the compiler can inline and vectorize.
+pub(crate) fn map_values(f: impl Fn(f32) -> f32, values: &mut [f32]) {
+    for value in values {
         *value = f(*value);
     }
+}
+
+/// Sets every value in `values1` to `f` of it and the corresponding value in `values2`
+fn zip_values(f: impl Fn(f32, f32) -> f32, values1: &mut [f32], values2: &[f32]) {
+    for (value1, value2) in values1.iter_mut().zip(values2) {
+        *value1 = f(*value1, *value2);
+    }
+}
+
+/// Same as [`zip_values`], computing 8 values at once with `simd` and the values left over
+/// with `scalar`, which must give the same results
+#[cfg(feature = "simd")]
+fn zip_values_simd(
+    simd: impl Fn(f32x8, f32x8) -> f32x8,
+    scalar: impl Fn(f32, f32) -> f32,
+    values1: &mut [f32],
+    values2: &[f32],
+) {
+    let len = values1.len().min(values2.len());
+    let mut chunks1 = values1[..len].chunks_exact_mut(8);
+    let mut chunks2 = values2[..len].chunks_exact(8);
+    for (chunk1, chunk2) in chunks1.by_ref().zip(chunks2.by_ref()) {
+        let v1 = f32x8::from(<[f32; 8]>::try_from(&*chunk1).unwrap());
+        let v2 = f32x8::from(<[f32; 8]>::try_from(chunk2).unwrap());
+        chunk1.copy_from_slice(&simd(v1, v2).to_array());
+    }
+    zip_values(scalar, chunks1.into_remainder(), chunks2.remainder());
+}
+
+fn single_array_fun(f: impl Fn(f32) -> f32, mut v: Vec<f32>) -> Vec<f32> {
+    map_values(f, &mut v);
     v
 }
 
@@ -72,42 +530,93 @@ fn sqrt_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(sqrt, v)
 }
 
-fn sqrt(v: f32) -> f32 {
-    v.sqrt()
-}
+// negative values give 0 instead of NaN
+with_source!(
+    SQRT_SOURCE,
+    fn sqrt(v: f32) -> f32 {
+        if v < 0.0 {
+            0.0
+        } else {
+            v.sqrt()
+        }
+    }
+);
 
 fn square_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(square, v)
 }
 
-fn square(v: f32) -> f32 {
-    v * v
-}
+with_source!(
+    SQUARE_SOURCE,
+    fn square(v: f32) -> f32 {
+        v * v
+    }
+);
 
 fn log_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(log, v)
 }
 
-fn log(v: f32) -> f32 {
-    v.log(E)
+// non-positive values give 0 instead of NaN or infinity
+with_source!(
+    LOG_SOURCE,
+    fn log(v: f32) -> f32 {
+        if v <= 0.0 {
+            0.0
+        } else {
+            v.log(E)
+        }
+    }
+);
+
+fn exp_array(v: Vec<f32>) -> Vec<f32> {
+    single_array_fun(exp, v)
 }
 
+with_source!(
+    EXP_SOURCE,
+    fn exp(v: f32) -> f32 {
+        v.exp()
+    }
+);
+
 fn reciprocal_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(reciprocal, v)
 }
 
-fn reciprocal(v: f32) -> f32 {
-    1.0 / v
-}
+with_source!(
+    RECIPROCAL_SOURCE,
+    fn reciprocal(v: f32) -> f32 {
+        if v == 0.0 {
+            0.0
+        } else {
+            1.0 / v
+        }
+    }
+);
 
 fn sine_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(sine, v)
 }
 
-fn sine(v: f32) -> f32 {
-    f32::sin(v)
+with_source!(
+    SINE_SOURCE,
+    fn sine(v: f32) -> f32 {
+        f32::sin(v)
+    }
+);
+
+fn cosine_array(v: Vec<f32>) -> Vec<f32> {
+    single_array_fun(cosine, v)
 }
 
+with_source!(
+    COSINE_SOURCE,
+    fn cosine(v: f32) -> f32 {
+        f32::cos(v)
+    }
+);
+
 fn abs_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(abs, v)
 }
@@ -116,21 +625,30 @@ fn ceil_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(ceil, v)
 }
 
-fn ceil(v: f32) -> f32 {
-    v.ceil()
-}
+with_source!(
+    CEIL_SOURCE,
+    fn ceil(v: f32) -> f32 {
+        v.ceil()
+    }
+);
 
-fn abs(v: f32) -> f32 {
-    v.abs()
-}
+with_source!(
+    ABS_SOURCE,
+    fn abs(v: f32) -> f32 {
+        v.abs()
+    }
+);
 
 fn inc_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(inc, v)
 }
 
-fn inc(v: f32) -> f32 {
-    v + 1.0
-}
+with_source!(
+    INC_SOURCE,
+    fn inc(v: f32) -> f32 {
+        v + 1.0
+    }
+);
 
 fn dec_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(dec, v)
@@ -140,51 +658,69 @@ fn floor_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(floor, v)
 }
 
-fn dec(v: f32) -> f32 {
-    v - 1.0
-}
+with_source!(
+    DEC_SOURCE,
+    fn dec(v: f32) -> f32 {
+        v - 1.0
+    }
+);
 
-fn floor(v: f32) -> f32 {
-    v.floor()
-}
+with_source!(
+    FLOOR_SOURCE,
+    fn floor(v: f32) -> f32 {
+        v.floor()
+    }
+);
 
 fn neg_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(neg, v)
 }
 
-fn neg(v: f32) -> f32 {
-    -v
-}
+with_source!(
+    NEG_SOURCE,
+    fn neg(v: f32) -> f32 {
+        -v
+    }
+);
 
 fn to_one_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(to_one, v)
 }
 
-fn to_one(v: f32) -> f32 {
-    match v.partial_cmp(&0.0) {
-        Some(Ordering::Equal) | None => 0.0,
-        Some(Ordering::Greater) => 1.0,
-        Some(Ordering::Less) => -1.0,
+with_source!(
+    TO_ONE_SOURCE,
+    fn to_one(v: f32) -> f32 {
+        match v.partial_cmp(&0.0) {
+            Some(Ordering::Equal) | None => 0.0,
+            Some(Ordering::Greater) => 1.0,
+            Some(Ordering::Less) => -1.0,
+        }
     }
-}
+);
 
 fn tau_sigmoid_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(tau_sigmoid, v)
 }
 
-fn tau_sigmoid(v: f32) -> f32 {
-    (2.0 * PI).powf(v)
-}
+with_source!(
+    TAU_SIGMOID_SOURCE,
+    fn tau_sigmoid(v: f32) -> f32 {
+        (2.0 * PI).powf(v)
+    }
+);
 
 fn tang_hyper_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(tang_hyper, v)
 }
 
-fn tang_hyper(v: f32) -> f32 {
-    let e_pos = E.powf(v);
-    let e_neg = E.powf(-v);
-    (e_pos - e_neg) / (e_pos + e_neg)
-}
+with_source!(
+    TANG_HYPER_SOURCE,
+    fn tang_hyper(v: f32) -> f32 {
+        let e_pos = E.powf(v);
+        let e_neg = E.powf(-v);
+        (e_pos - e_neg) / (e_pos + e_neg)
+    }
+);
 
 fn relu_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(relu, v)
@@ -194,17 +730,24 @@ fn round_array(v: Vec<f32>) -> Vec<f32> {
     single_array_fun(round, v)
 }
 
-fn round(v: f32) -> f32 {
-    v.round()
-}
+with_source!(
+    ROUND_SOURCE,
+    fn round(v: f32) -> f32 {
+        v.round()
+    }
+);
 
-fn relu(v: f32) -> f32 {
-    v.max(0.0)
-}
+with_source!(
+    RELU_SOURCE,
+    fn relu(v: f32) -> f32 {
+        v.max(0.0)
+    }
+);
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct SingleArgFunction {
     pub name: &'static str,
+    pub kind: NodeKind,
     pub fun: fn(Vec<f32>) -> Vec<f32>,
 }
 
@@ -226,181 +769,336 @@ impl Deserializable for &SingleArgFunction {
     }
 }
 
-pub static ONE_ARG_FUNCTIONS: [SingleArgFunction; 16] = [
-    SingleArgFunction { name: "abs", fun: abs_array },
-    SingleArgFunction { name: "ceil", fun: ceil_array },
-    SingleArgFunction { name: "dec", fun: dec_array },
-    SingleArgFunction { name: "floor", fun: floor_array },
-    SingleArgFunction { name: "inc", fun: inc_array },
-    SingleArgFunction { name: "log", fun: log_array },
-    SingleArgFunction { name: "neg", fun: neg_array },
-    SingleArgFunction { name: "normalize", fun: to_one_array },
-    SingleArgFunction { name: "reciprocal", fun: reciprocal_array },
-    SingleArgFunction { name: "relu", fun: relu_array },
-    SingleArgFunction { name: "round", fun: round_array },
-    SingleArgFunction { name: "sine", fun: sine_array },
-    SingleArgFunction { name: "sqrt", fun: sqrt_array },
-    SingleArgFunction { name: "square", fun: square_array },
-    SingleArgFunction { name: "tau_sigmoid", fun: tau_sigmoid_array },
-    SingleArgFunction { name: "tang_hyper", fun: tang_hyper_array },
+pub static ONE_ARG_FUNCTIONS: [SingleArgFunction; 18] = [
+    SingleArgFunction { name: "abs", kind: NodeKind::Abs, fun: abs_array },
+    SingleArgFunction { name: "ceil", kind: NodeKind::Ceil, fun: ceil_array },
+    SingleArgFunction { name: "cosine", kind: NodeKind::Cosine, fun: cosine_array },
+    SingleArgFunction { name: "dec", kind: NodeKind::Dec, fun: dec_array },
+    SingleArgFunction { name: "exp", kind: NodeKind::Exp, fun: exp_array },
+    SingleArgFunction { name: "floor", kind: NodeKind::Floor, fun: floor_array },
+    SingleArgFunction { name: "inc", kind: NodeKind::Inc, fun: inc_array },
+    SingleArgFunction { name: "log", kind: NodeKind::Log, fun: log_array },
+    SingleArgFunction { name: "neg", kind: NodeKind::Neg, fun: neg_array },
+    SingleArgFunction { name: "normalize", kind: NodeKind::Normalize, fun: to_one_array },
+    SingleArgFunction { name: "reciprocal", kind: NodeKind::Reciprocal, fun: reciprocal_array },
+    SingleArgFunction { name: "relu", kind: NodeKind::Relu, fun: relu_array },
+    SingleArgFunction { name: "round", kind: NodeKind::Round, fun: round_array },
+    SingleArgFunction { name: "sine", kind: NodeKind::Sine, fun: sine_array },
+    SingleArgFunction { name: "sqrt", kind: NodeKind::Sqrt, fun: sqrt_array },
+    SingleArgFunction { name: "square", kind: NodeKind::Square, fun: square_array },
+    SingleArgFunction {
+        name: "tau_sigmoid",
+        kind: NodeKind::TauSigmoid,
+        fun: tau_sigmoid_array,
+    },
+    SingleArgFunction { name: "tang_hyper", kind: NodeKind::TangHyper, fun: tang_hyper_array },
 ];
 
-fn two_arrays_fun(f: fn(f32, f32) -> f32, mut v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
-    for (value1, value2) in v1.iter_mut().zip(v2) {
-        *value1 = f(*value1, *value2);
-    }
+fn two_arrays_fun(f: impl Fn(f32, f32) -> f32, mut v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    zip_values(f, &mut v1, v2);
+    v1
+}
+
+#[cfg(feature = "simd")]
+fn two_simd_arrays_fun(
+    simd: impl Fn(f32x8, f32x8) -> f32x8,
+    scalar: impl Fn(f32, f32) -> f32,
+    mut v1: Vec<f32>,
+    v2: &[f32],
+) -> Vec<f32> {
+    zip_values_simd(simd, scalar, &mut v1, v2);
     v1
 }
 
+#[cfg(not(feature = "simd"))]
 fn add_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(add, v1, v2)
 }
-fn add(v1: f32, v2: f32) -> f32 {
-    v1 + v2
+
+#[cfg(feature = "simd")]
+fn add_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    two_simd_arrays_fun(|v1, v2| v1 + v2, add, v1, v2)
 }
+with_source!(
+    ADD_SOURCE,
+    fn add(v1: f32, v2: f32) -> f32 {
+        v1 + v2
+    }
+);
 
+#[cfg(not(feature = "simd"))]
 fn sub_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(sub, v1, v2)
 }
-fn sub(v1: f32, v2: f32) -> f32 {
-    v1 - v2
+
+#[cfg(feature = "simd")]
+fn sub_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    two_simd_arrays_fun(|v1, v2| v1 - v2, sub, v1, v2)
 }
+with_source!(
+    SUB_SOURCE,
+    fn sub(v1: f32, v2: f32) -> f32 {
+        v1 - v2
+    }
+);
 
+#[cfg(not(feature = "simd"))]
 fn div_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(div, v1, v2)
 }
-fn div(v1: f32, v2: f32) -> f32 {
-    v1 / v2
-}
 
+#[cfg(feature = "simd")]
+fn div_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    let zero = f32x8::splat(0.0);
+    two_simd_arrays_fun(|v1, v2| v2.cmp_eq(zero).blend(zero, v1 / v2), div, v1, v2)
+}
+with_source!(
+    DIV_SOURCE,
+    /// Protected division: dividing by zero gives 0 instead of infinity or NaN
+    fn div(v1: f32, v2: f32) -> f32 {
+        if v2 == 0.0 {
+            0.0
+        } else {
+            v1 / v2
+        }
+    }
+);
+
+fn div_or_one_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    two_arrays_fun(div_or_one, v1, v2)
+}
+with_source!(
+    DIV_OR_ONE_SOURCE,
+    /// Protected division: dividing by zero gives 1
+    fn div_or_one(v1: f32, v2: f32) -> f32 {
+        if v2 == 0.0 {
+            1.0
+        } else {
+            v1 / v2
+        }
+    }
+);
+
+fn div_or_numerator_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    two_arrays_fun(div_or_numerator, v1, v2)
+}
+with_source!(
+    DIV_OR_NUMERATOR_SOURCE,
+    /// Protected division: dividing by zero gives the numerator
+    fn div_or_numerator(v1: f32, v2: f32) -> f32 {
+        if v2 == 0.0 {
+            v1
+        } else {
+            v1 / v2
+        }
+    }
+);
+
+#[cfg(not(feature = "simd"))]
 fn mul_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(mul, v1, v2)
 }
-fn mul(v1: f32, v2: f32) -> f32 {
-    v1 * v2
+
+#[cfg(feature = "simd")]
+fn mul_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    two_simd_arrays_fun(|v1, v2| v1 * v2, mul, v1, v2)
 }
+with_source!(
+    MUL_SOURCE,
+    fn mul(v1: f32, v2: f32) -> f32 {
+        v1 * v2
+    }
+);
 
 fn higher_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(higher, v1, v2)
 }
-fn higher(v1: f32, v2: f32) -> f32 {
-    v1.max(v2)
-}
+with_source!(
+    HIGHER_SOURCE,
+    fn higher(v1: f32, v2: f32) -> f32 {
+        v1.max(v2)
+    }
+);
 
 fn lower_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(lower, v1, v2)
 }
-fn lower(v1: f32, v2: f32) -> f32 {
-    v1.min(v2)
-}
+with_source!(
+    LOWER_SOURCE,
+    fn lower(v1: f32, v2: f32) -> f32 {
+        v1.min(v2)
+    }
+);
 
 fn equal_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(equal, v1, v2)
 }
 
-fn equal(v1: f32, v2: f32) -> f32 {
-    if (1.0 - v1 / v2).abs() < 0.01 {
-        1.0
-    } else {
-        0.0
+with_source!(
+    EQUAL_SOURCE,
+    fn equal(v1: f32, v2: f32) -> f32 {
+        if (1.0 - v1 / v2).abs() < 0.01 {
+            1.0
+        } else {
+            0.0
+        }
     }
-}
+);
 
 fn abs_higher_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(abs_higher, v1, v2)
 }
-fn abs_higher(v1: f32, v2: f32) -> f32 {
-    v1.abs().max(v2.abs())
-}
+with_source!(
+    ABS_HIGHER_SOURCE,
+    fn abs_higher(v1: f32, v2: f32) -> f32 {
+        v1.abs().max(v2.abs())
+    }
+);
 
 fn abs_lower_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(abs_lower, v1, v2)
 }
-fn abs_lower(v1: f32, v2: f32) -> f32 {
-    v1.abs().min(v2.abs())
-}
+with_source!(
+    ABS_LOWER_SOURCE,
+    fn abs_lower(v1: f32, v2: f32) -> f32 {
+        v1.abs().min(v2.abs())
+    }
+);
 
 fn mid_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(mid, v1, v2)
 }
-fn mid(v1: f32, v2: f32) -> f32 {
-    (v1 + v2) / 2.0
-}
+with_source!(
+    MID_SOURCE,
+    fn mid(v1: f32, v2: f32) -> f32 {
+        (v1 + v2) / 2.0
+    }
+);
 
 fn sum_of_squares_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(sum_of_squares, v1, v2)
 }
-fn sum_of_squares(v1: f32, v2: f32) -> f32 {
-    v1 * v1 + v2 * v2
-}
+with_source!(
+    SUM_OF_SQUARES_SOURCE,
+    fn sum_of_squares(v1: f32, v2: f32) -> f32 {
+        v1 * v1 + v2 * v2
+    }
+);
 
 fn first_is_higher_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(first_is_higher, v1, v2)
 }
-fn first_is_higher(v1: f32, v2: f32) -> f32 {
-    if v1 > v2 {
-        1.0
-    } else {
-        0.0
+with_source!(
+    FIRST_IS_HIGHER_SOURCE,
+    fn first_is_higher(v1: f32, v2: f32) -> f32 {
+        if v1 > v2 {
+            1.0
+        } else {
+            0.0
+        }
     }
-}
+);
+
+fn greater_than_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    two_arrays_fun(greater_than, v1, v2)
+}
+with_source!(
+    GREATER_THAN_SOURCE,
+    fn greater_than(v1: f32, v2: f32) -> f32 {
+        if v1 > v2 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+);
+
+fn less_than_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
+    two_arrays_fun(less_than, v1, v2)
+}
+with_source!(
+    LESS_THAN_SOURCE,
+    fn less_than(v1: f32, v2: f32) -> f32 {
+        if v1 < v2 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+);
 
 fn xor_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(xor, v1, v2)
 }
-fn xor(v1: f32, v2: f32) -> f32 {
-    if (v1 == 0.0 && v2 != 0.0) || (v1 != 0.0 && v2 == 0.0) {
-        1.0
-    } else {
-        0.0
+with_source!(
+    XOR_SOURCE,
+    fn xor(v1: f32, v2: f32) -> f32 {
+        if (v1 == 0.0 && v2 != 0.0) || (v1 != 0.0 && v2 == 0.0) {
+            1.0
+        } else {
+            0.0
+        }
     }
-}
+);
 
 fn or_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(or, v1, v2)
 }
-fn or(v1: f32, v2: f32) -> f32 {
-    if v1 != 0.0 || v2 != 0.0 {
-        1.0
-    } else {
-        0.0
+with_source!(
+    OR_SOURCE,
+    fn or(v1: f32, v2: f32) -> f32 {
+        if v1 != 0.0 || v2 != 0.0 {
+            1.0
+        } else {
+            0.0
+        }
     }
-}
+);
 
 fn round_equal_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(round_equal, v1, v2)
 }
 
-#[allow(clippy::float_cmp)]
-fn round_equal(v1: f32, v2: f32) -> f32 {
-    if v1.round() == v2.round() {
-        1.0
-    } else {
-        0.0
+with_source!(
+    ROUND_EQUAL_SOURCE,
+    #[allow(clippy::float_cmp)]
+    fn round_equal(v1: f32, v2: f32) -> f32 {
+        if v1.round() == v2.round() {
+            1.0
+        } else {
+            0.0
+        }
     }
-}
+);
 
 fn and_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(and, v1, v2)
 }
-fn and(v1: f32, v2: f32) -> f32 {
-    if v1 != 0.0 && v2 != 0.0 {
-        1.0
-    } else {
-        0.0
+with_source!(
+    AND_SOURCE,
+    fn and(v1: f32, v2: f32) -> f32 {
+        if v1 != 0.0 && v2 != 0.0 {
+            1.0
+        } else {
+            0.0
+        }
     }
-}
+);
 
 fn diff_array(v1: Vec<f32>, v2: &[f32]) -> Vec<f32> {
     two_arrays_fun(diff, v1, v2)
 }
-fn diff(v1: f32, v2: f32) -> f32 {
-    (1.0 - v1 / v2).abs()
-}
+with_source!(
+    DIFF_SOURCE,
+    fn diff(v1: f32, v2: f32) -> f32 {
+        (1.0 - v1 / v2).abs()
+    }
+);
 
 #[derive(Copy, Clone)]
 pub struct DoubleArgFunction {
     pub name: &'static str,
+    pub kind: NodeKind,
     pub fun: fn(Vec<f32>, &[f32]) -> Vec<f32>,
 }
 
@@ -434,29 +1132,217 @@ impl Deserializable for &DoubleArgFunction {
     }
 }
 
-pub static TWO_ARG_FUNCTIONS: [DoubleArgFunction; 17] = [
-    DoubleArgFunction { name: "abs_higher", fun: abs_higher_array },
-    DoubleArgFunction { name: "abs_lower", fun: abs_lower_array },
-    DoubleArgFunction { name: "add", fun: add_array },
-    DoubleArgFunction { name: "and", fun: and_array },
-    DoubleArgFunction { name: "diff", fun: diff_array },
-    DoubleArgFunction { name: "div", fun: div_array },
-    DoubleArgFunction { name: "equal", fun: equal_array },
-    DoubleArgFunction { name: "first_is_higher", fun: first_is_higher_array },
-    DoubleArgFunction { name: "higher", fun: higher_array },
-    DoubleArgFunction { name: "lower", fun: lower_array },
-    DoubleArgFunction { name: "mid", fun: mid_array },
-    DoubleArgFunction { name: "mul", fun: mul_array },
-    DoubleArgFunction { name: "or", fun: or_array },
-    DoubleArgFunction { name: "sub", fun: sub_array },
-    DoubleArgFunction { name: "sum_of_squares", fun: sum_of_squares_array },
-    DoubleArgFunction { name: "xor", fun: xor_array },
-    DoubleArgFunction { name: "round_equal_array", fun: round_equal_array },
+pub static TWO_ARG_FUNCTIONS: [DoubleArgFunction; 21] = [
+    DoubleArgFunction { name: "abs_higher", kind: NodeKind::AbsHigher, fun: abs_higher_array },
+    DoubleArgFunction { name: "abs_lower", kind: NodeKind::AbsLower, fun: abs_lower_array },
+    DoubleArgFunction { name: "add", kind: NodeKind::Add, fun: add_array },
+    DoubleArgFunction { name: "and", kind: NodeKind::And, fun: and_array },
+    DoubleArgFunction { name: "diff", kind: NodeKind::Diff, fun: diff_array },
+    DoubleArgFunction { name: "div", kind: NodeKind::Div, fun: div_array },
+    DoubleArgFunction { name: "div_or_one", kind: NodeKind::DivOrOne, fun: div_or_one_array },
+    DoubleArgFunction {
+        name: "div_or_numerator",
+        kind: NodeKind::DivOrNumerator,
+        fun: div_or_numerator_array,
+    },
+    DoubleArgFunction { name: "equal", kind: NodeKind::Equal, fun: equal_array },
+    DoubleArgFunction {
+        name: "first_is_higher",
+        kind: NodeKind::FirstIsHigher,
+        fun: first_is_higher_array,
+    },
+    DoubleArgFunction { name: "higher", kind: NodeKind::Higher, fun: higher_array },
+    DoubleArgFunction { name: "lower", kind: NodeKind::Lower, fun: lower_array },
+    DoubleArgFunction { name: "mid", kind: NodeKind::Mid, fun: mid_array },
+    DoubleArgFunction { name: "mul", kind: NodeKind::Mul, fun: mul_array },
+    DoubleArgFunction { name: "or", kind: NodeKind::Or, fun: or_array },
+    DoubleArgFunction { name: "sub", kind: NodeKind::Sub, fun: sub_array },
+    DoubleArgFunction {
+        name: "sum_of_squares",
+        kind: NodeKind::SumOfSquares,
+        fun: sum_of_squares_array,
+    },
+    DoubleArgFunction { name: "xor", kind: NodeKind::Xor, fun: xor_array },
+    DoubleArgFunction {
+        name: "round_equal_array",
+        kind: NodeKind::RoundEqual,
+        fun: round_equal_array,
+    },
+    DoubleArgFunction {
+        name: "greater_than",
+        kind: NodeKind::GreaterThan,
+        fun: greater_than_array,
+    },
+    DoubleArgFunction { name: "less_than", kind: NodeKind::LessThan, fun: less_than_array },
 ];
 
+/// Computes values of a three argument function, reusing the first argument for output
+pub type TripleArgFun = fn(Vec<f32>, &[f32], &[f32]) -> Vec<f32>;
+
+/// Function of three weighted subtrees, e.g. a conditional
+#[derive(Copy, Clone)]
+pub struct TripleArgFunction {
+    pub name: &'static str,
+    pub kind: NodeKind,
+    pub fun: TripleArgFun,
+}
+
+impl Debug for TripleArgFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl PartialEq for TripleArgFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Serializable for TripleArgFunction {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_str(self.name);
+    }
+}
+
+impl Deserializable for &TripleArgFunction {
+    fn deserialize(s: &mut Serializator) -> Result<&'static TripleArgFunction, String> {
+        let v = s.next_token()?;
+        for fun in &THREE_ARG_FUNCTIONS {
+            if fun.name.eq(v) {
+                return Ok(fun);
+            }
+        }
+        Err(format!("TripleArgFunction {} not found", v))
+    }
+}
+
+fn if_then_else_array(mut condition: Vec<f32>, then: &[f32], otherwise: &[f32]) -> Vec<f32> {
+    for ((c, t), o) in condition.iter_mut().zip(then).zip(otherwise) {
+        *c = if_then_else(*c, *t, *o);
+    }
+    condition
+}
+// A missing condition gives NaN, as does any other function of a missing value
+with_source!(
+    IF_THEN_ELSE_SOURCE,
+    fn if_then_else(condition: f32, then: f32, otherwise: f32) -> f32 {
+        if condition.is_nan() {
+            f32::NAN
+        } else if condition != 0.0 {
+            then
+        } else {
+            otherwise
+        }
+    }
+);
+
+pub static THREE_ARG_FUNCTIONS: [TripleArgFunction; 1] = [TripleArgFunction {
+    name: "if_then_else",
+    kind: NodeKind::IfThenElse,
+    fun: if_then_else_array,
+}];
+
 #[cfg(test)]
 mod test {
-    use crate::exec::functions::{equal, relu};
+    use crate::exec::functions::{
+        add, add_array, cosine, div, div_array, div_or_numerator, div_or_one, equal, exp,
+        greater_than, if_then_else, less_than, log, map_values, mul, mul_array, reciprocal,
+        relu, sqrt, sub, sub_array, zip_values, NodeKind, SafeDivMode,
+    };
+    use crate::serialization::serializator::test::test_serialization;
+    use std::f32::consts::{E, PI};
+
+    #[test]
+    fn test_batched_values() {
+        let values = (0..19).map(|v| v as f32).collect::<Vec<_>>();
+        let mut mapped = values.clone();
+        map_values(|v| v * 2.0, &mut mapped);
+        assert_eq!(mapped, values.iter().map(|v| v * 2.0).collect::<Vec<_>>());
+        let mut zipped = values.clone();
+        zip_values(div, &mut zipped, &mapped);
+        assert_eq!(zipped[0], 0.0);
+        assert!(zipped[1..].iter().all(|v| (v - 0.5).abs() < f32::EPSILON));
+    }
+
+    #[test]
+    fn test_arithmetic_arrays() {
+        // 19 values, so that some are left over after groups of 8, with `simd` feature
+        let mut values1 = (0..16).map(|v| v as f32 - 5.5).collect::<Vec<_>>();
+        values1.extend([f32::NAN, f32::INFINITY, 0.0]);
+        let mut values2 = values1.iter().rev().map(|v| v * 0.5).collect::<Vec<_>>();
+        values2[3] = 0.0;
+        let arrays: [(fn(Vec<f32>, &[f32]) -> Vec<f32>, fn(f32, f32) -> f32); 4] =
+            [(add_array, add), (sub_array, sub), (mul_array, mul), (div_array, div)];
+        for (array, scalar) in arrays {
+            let mut expected = values1.clone();
+            zip_values(scalar, &mut expected, &values2);
+            let actual = array(values1.clone(), &values2);
+            assert_eq!(
+                actual.iter().map(|v| v.to_bits()).collect::<Vec<_>>(),
+                expected.iter().map(|v| v.to_bits()).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_node_kinds() {
+        for kind in &NodeKind::ALL {
+            test_serialization(*kind);
+        }
+        assert_eq!(NodeKind::Div.name(), "div");
+        assert_eq!(NodeKind::Div.arity(), 2);
+        assert_eq!(NodeKind::Sine.arity(), 1);
+        assert_eq!(NodeKind::IfThenElse.arity(), 3);
+        assert!(NodeKind::OPT_IN.iter().all(|kind| NodeKind::ALL.contains(kind)));
+    }
+
+    #[test]
+    fn test_conditionals() {
+        assert!((greater_than(2.0, 1.0) - 1.0).abs() < f32::EPSILON);
+        assert!(greater_than(1.0, 1.0).abs() < f32::EPSILON);
+        assert!((less_than(1.0, 2.0) - 1.0).abs() < f32::EPSILON);
+        assert!(less_than(f32::NAN, 2.0).abs() < f32::EPSILON);
+        assert!((if_then_else(1.0, 2.0, 3.0) - 2.0).abs() < f32::EPSILON);
+        assert!((if_then_else(-0.5, 2.0, 3.0) - 2.0).abs() < f32::EPSILON);
+        assert!((if_then_else(0.0, 2.0, 3.0) - 3.0).abs() < f32::EPSILON);
+        assert!(if_then_else(f32::NAN, 2.0, 3.0).is_nan());
+    }
+
+    #[test]
+    fn test_log_sqrt_domain() {
+        assert!(log(0.0).abs() < f32::EPSILON);
+        assert!(log(-2.0).abs() < f32::EPSILON);
+        assert!((log(E) - 1.0).abs() < f32::EPSILON);
+        assert!(sqrt(-4.0).abs() < f32::EPSILON);
+        assert!((sqrt(4.0) - 2.0).abs() < f32::EPSILON);
+        assert!((exp(0.0) - 1.0).abs() < f32::EPSILON);
+        assert!((cosine(PI) + 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_div_by_zero() {
+        assert!((div(3.0, 2.0) - 1.5).abs() < f32::EPSILON);
+        assert!(div(3.0, 0.0).abs() < f32::EPSILON);
+        assert!(div(0.0, -0.0).abs() < f32::EPSILON);
+        assert!(reciprocal(0.0).abs() < f32::EPSILON);
+        assert!((div_or_one(3.0, 2.0) - 1.5).abs() < f32::EPSILON);
+        assert!((div_or_one(3.0, 0.0) - 1.0).abs() < f32::EPSILON);
+        assert!((div_or_numerator(3.0, 2.0) - 1.5).abs() < f32::EPSILON);
+        assert!((div_or_numerator(3.0, 0.0) - 3.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_safe_div_forbidden() {
+        let kinds = SafeDivMode::ReturnOne.forbidden(&[NodeKind::Mul]);
+        assert!(kinds.contains(&NodeKind::Mul));
+        assert!(kinds.contains(&NodeKind::Div));
+        assert!(!kinds.contains(&NodeKind::DivOrOne));
+        assert!(kinds.contains(&NodeKind::DivOrNumerator));
+        let kinds = SafeDivMode::ReturnNumerator.forbidden(&[NodeKind::Div]);
+        assert!(SafeDivMode::DIVISIONS.iter().all(|kind| kinds.contains(kind)));
+        assert_eq!(SafeDivMode::default().forbidden(&[]), SafeDivMode::DIVISIONS[1..].to_vec());
+    }
 
     #[test]
     fn test_equal() {
@@ -487,4 +1373,17 @@ mod test {
         assert!((relu(-15.0) - 0.0).abs() < f32::EPSILON);
         assert!((relu(15.0) - 15.0).abs() < f32::EPSILON);
     }
+
+    #[test]
+    fn test_scalar_source() {
+        for kind in &NodeKind::ALL {
+            let source = kind.scalar_source();
+            let mut words = source.code.split([' ', '(']);
+            assert_eq!(words.next(), Some("fn"));
+            assert_eq!(words.next(), Some(source.name));
+            let arguments = source.code.split(')').next().unwrap().matches("f32").count();
+            assert_eq!(arguments, kind.arity());
+        }
+        assert_eq!(NodeKind::Normalize.scalar_source().name, "to_one");
+    }
 }
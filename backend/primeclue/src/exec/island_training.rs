@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::error::PrimeclueErr;
+use crate::exec::classifier::Classifier;
+use crate::exec::training_group::TrainingGroup;
+
+/// Several [`TrainingGroup`]s ("islands") on the same data that evolve independently and,
+/// if set with [`IslandTraining::migrate_every`], periodically send copies of their best trees
+/// to the next island in a ring.
+#[derive(Debug)]
+pub struct IslandTraining {
+    islands: Vec<TrainingGroup>,
+    generation: u32,
+    // generations between migrations and number of trees migrated per class
+    migration: Option<(u32, usize)>,
+}
+
+impl IslandTraining {
+    /// Creates [`IslandTraining`] from `islands`, that must all have the same classes and
+    /// input shape of training data
+    pub fn new(islands: Vec<TrainingGroup>) -> Result<Self, PrimeclueErr> {
+        let first = match islands.first() {
+            Some(first) => first,
+            None => return PrimeclueErr::result("Island training needs an island".to_string()),
+        };
+        for (index, island) in islands.iter().enumerate().skip(1) {
+            let (data, first_data) = (island.training_data(), first.training_data());
+            if data.class_map() != first_data.class_map() {
+                return PrimeclueErr::result(format!(
+                    "Island {} has different classes than the first one",
+                    index
+                ));
+            } else if data.input_shape() != first_data.input_shape() {
+                return PrimeclueErr::result(format!(
+                    "Island {} has input shape {}, expected {}",
+                    index,
+                    data.input_shape(),
+                    first_data.input_shape()
+                ));
+            }
+        }
+        Ok(IslandTraining { islands, generation: 0, migration: None })
+    }
+
+    /// Makes every island send its best `count` trees of every class to the next island
+    /// after every `generations` generations
+    pub fn migrate_every(
+        mut self,
+        generations: u32,
+        count: usize,
+    ) -> Result<Self, PrimeclueErr> {
+        if generations == 0 || count == 0 {
+            return PrimeclueErr::result(
+                "Migration interval and count must be positive".to_string(),
+            );
+        }
+        self.migration = Some((generations, count));
+        Ok(self)
+    }
+
+    /// Performs training for one generation on every island, then migration if it's due
+    pub fn next_generation(&mut self) {
+        self.generation += 1;
+        for island in &mut self.islands {
+            island.next_generation();
+        }
+        if let Some((generations, count)) = self.migration {
+            if self.generation.is_multiple_of(generations) {
+                self.migrate(count);
+            }
+        }
+    }
+
+    fn migrate(&mut self, count: usize) {
+        let emigrants =
+            self.islands.iter().map(|island| island.emigrants(count)).collect::<Vec<_>>();
+        let len = self.islands.len();
+        for (index, trees) in emigrants.into_iter().enumerate() {
+            self.islands[(index + 1) % len].immigrate(trees);
+        }
+    }
+
+    #[must_use]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    #[must_use]
+    pub fn islands(&self) -> &[TrainingGroup] {
+        &self.islands
+    }
+
+    /// Island with the best [`TrainingGroup::verification_score`], if any island has it
+    #[must_use]
+    pub fn best_island(&self) -> Option<&TrainingGroup> {
+        let mut best: Option<(&TrainingGroup, f32)> = None;
+        for island in &self.islands {
+            if let Some(score) = island.verification_score() {
+                match best {
+                    Some((_, best_score)) if island.improvement(best_score, score) <= 0.0 => {}
+                    _ => best = Some((island, score)),
+                }
+            }
+        }
+        best.map(|(island, _)| island)
+    }
+
+    /// Get [`Classifier`] of the best island, see [`IslandTraining::best_island`]
+    pub fn classifier(&self) -> Result<Classifier, PrimeclueErr> {
+        match self.best_island() {
+            Some(island) => island.classifier(),
+            None => PrimeclueErr::result("No island has a classifier yet".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::test::create_simple_data;
+    use crate::exec::island_training::IslandTraining;
+    use crate::exec::score::Objective::Auc;
+    use crate::exec::training_group::TrainingGroup;
+
+    fn create_islands(count: usize) -> IslandTraining {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let islands = (0..count)
+            .map(|seed| {
+                TrainingGroup::with_seed(
+                    training_data.clone(),
+                    verification_data.clone(),
+                    Auc,
+                    3,
+                    &[],
+                    seed as u64,
+                )
+                .unwrap()
+            })
+            .collect();
+        IslandTraining::new(islands).unwrap()
+    }
+
+    #[test]
+    fn test_island_training() {
+        assert!(IslandTraining::new(vec![]).is_err());
+        let mut training = create_islands(3).migrate_every(2, 2).unwrap();
+        assert!(training.classifier().is_err());
+        for _ in 0..5 {
+            training.next_generation();
+        }
+        assert_eq!(training.generation(), 5);
+        assert!(training.islands().iter().all(|island| island.generation() == 5));
+        let best = training.best_island().unwrap().verification_score().unwrap();
+        for island in training.islands() {
+            assert!(island.verification_score().unwrap() <= best);
+        }
+        assert!(training.classifier().is_ok());
+    }
+
+    #[test]
+    fn test_migration_config() {
+        assert!(create_islands(2).migrate_every(0, 1).is_err());
+        assert!(create_islands(2).migrate_every(1, 0).is_err());
+    }
+}
@@ -17,11 +17,18 @@
    along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+pub mod benchmark;
 pub mod class_training;
 pub mod classifier;
+pub mod cross_validation;
+pub mod ensemble;
 pub mod functions;
+pub mod island_training;
 pub mod node;
+pub(crate) mod onnx;
+pub(crate) mod pareto;
 pub mod score;
 pub mod scored_tree;
+pub mod search;
 pub mod training_group;
 pub mod tree;
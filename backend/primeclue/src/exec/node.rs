@@ -18,16 +18,22 @@
 */
 
 use crate::contrand::GET_RNG;
-use crate::data::{Data, InputShape};
-use crate::exec::functions::{DoubleArgFunction, MathConst, SingleArgFunction};
-use crate::exec::functions::{MATH_CONSTANTS, ONE_ARG_FUNCTIONS, TWO_ARG_FUNCTIONS};
+use crate::data::data_set::FeatureType;
+use crate::data::{Data, Input, InputShape};
+use crate::exec::functions::{allowed_one_arg, allowed_three_arg, allowed_two_arg};
+use crate::exec::functions::{map_values, MATH_CONSTANTS};
+use crate::exec::functions::{DoubleArgFunction, MathConst, NodeKind, SingleArgFunction};
+use crate::exec::functions::{Terminal, TripleArgFunction};
+use crate::exec::onnx::OnnxGraph;
 use crate::math::std_dev;
 use crate::serialization::deserializable::Deserializable;
 use crate::serialization::serializator::Serializator;
 use crate::serialization::Serializable;
 use rand::{prelude::SliceRandom, Rng};
-use std::collections::HashSet;
-use std::{borrow::BorrowMut, ops::Deref, ops::Mul};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::time::Instant;
+use std::{borrow::BorrowMut, ops::Deref, ops::DerefMut, ops::Mul};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Weight(f32);
@@ -47,9 +53,27 @@ impl Deserializable for Weight {
     }
 }
 
+/// Range weights of new nodes are drawn from, unless [`GeneticConfig::constant_range`] sets
+/// another one for constants
+///
+/// [`GeneticConfig::constant_range`]: crate::exec::class_training::GeneticConfig::constant_range
+pub const DEFAULT_WEIGHT_RANGE: (f32, f32) = (-1.618, 1.618);
+
+/// Probability of a new function node being a three argument one, when any is allowed
+const THREE_ARG_PROB: f64 = 0.2;
+
+/// Probability of a new data leaf being one of user-defined terminals, when there are any
+const TERMINAL_PROB: f64 = 0.2;
+
 impl Weight {
     pub fn generate() -> Self {
-        Weight { 0: GET_RNG().gen_range(-1.618..1.618) }
+        Weight::generate_in(DEFAULT_WEIGHT_RANGE)
+    }
+
+    /// Weight drawn uniformly from `[range.0, range.1)`, `range.0` must be less than `range.1`
+    #[must_use]
+    pub fn generate_in(range: (f32, f32)) -> Self {
+        Weight { 0: GET_RNG().gen_range(range.0..range.1) }
     }
 
     #[must_use]
@@ -78,6 +102,19 @@ impl Weighted {
         Weighted { w: Weight(1.0), n: Box::new(n) }
     }
 
+    /// Multiplier of the value of [`Weighted::node`]
+    #[must_use]
+    pub fn weight(&self) -> f32 {
+        self.w.0
+    }
+
+    /// Node whose value is weighted, to be matched for walking a tree, e.g. from
+    /// [`crate::exec::tree::Tree::get_start_node`]
+    #[must_use]
+    pub fn node(&self) -> &Node {
+        &self.n
+    }
+
     pub fn change_weight(&mut self, rate: f32) {
         let new = self.w.0 * rate;
         if !new.is_nan() {
@@ -85,8 +122,91 @@ impl Weighted {
         }
     }
 
-    pub fn mutate(&mut self, input_shape: &InputShape, forbidden_cols: &[usize]) {
-        self.n.mutate(input_shape, forbidden_cols);
+    /// Mutates the node, see [`Node::mutate`]. A constant also gets a new weight drawn from
+    /// `constant_range`.
+    pub fn mutate(
+        &mut self,
+        input_shape: &InputShape,
+        forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        terminals: &'static [Terminal],
+        constant_range: (f32, f32),
+    ) {
+        self.n.mutate(input_shape, forbidden_cols, forbidden_nodes, terminals);
+        if let Node::MathConstant(_) = self.n.deref() {
+            self.w = Weight::generate_in(constant_range);
+        }
+    }
+
+    /// Draws weights of all constants in this node and its subtrees anew from `range`
+    pub fn generate_constant_weights(&mut self, range: (f32, f32)) {
+        match self.n.deref_mut() {
+            Node::MathConstant(_) => self.w = Weight::generate_in(range),
+            Node::DataValue(_, _) | Node::StdDev(_, _) | Node::Terminal(_) => {}
+            Node::SingleArgFunction(_, n) => n.generate_constant_weights(range),
+            Node::DoubleArgFunction(_, n1, n2) => {
+                n1.generate_constant_weights(range);
+                n2.generate_constant_weights(range);
+            }
+            Node::TripleArgFunction(_, n1, n2, n3) => {
+                n1.generate_constant_weights(range);
+                n2.generate_constant_weights(range);
+                n3.generate_constant_weights(range);
+            }
+        }
+    }
+
+    /// Makes every comparison of an integer or boolean column of `feature_types` to a constant
+    /// in this node and its subtrees a clean rule: the column gets weight 1 and the value it
+    /// is compared to, the constant divided by the column's former weight, is rounded to a
+    /// whole number for equality, or to halfway between two whole numbers for order, e.g.
+    /// `count > 2.5`. Order comparisons of negatively weighted columns get their arguments
+    /// swapped, so they keep their direction. Boolean constants stay within 0 and 1.
+    pub fn discretize_comparisons(&mut self, feature_types: &[FeatureType]) {
+        match self.n.deref_mut() {
+            Node::MathConstant(_) | Node::DataValue(_, _) | Node::StdDev(_, _) => {}
+            Node::Terminal(_) => {}
+            Node::SingleArgFunction(_, n) => n.discretize_comparisons(feature_types),
+            Node::DoubleArgFunction(f, n1, n2) => {
+                if f.kind.is_comparison() {
+                    let ordering = f.kind.is_ordering();
+                    discretize_comparison(n1, n2, feature_types, ordering);
+                    discretize_comparison(n2, n1, feature_types, ordering);
+                }
+                n1.discretize_comparisons(feature_types);
+                n2.discretize_comparisons(feature_types);
+            }
+            Node::TripleArgFunction(_, n1, n2, n3) => {
+                n1.discretize_comparisons(feature_types);
+                n2.discretize_comparisons(feature_types);
+                n3.discretize_comparisons(feature_types);
+            }
+        }
+    }
+
+    /// Node mixing this node and `other`: if both have the same number of arguments, either
+    /// of them is taken with its weight with equal probability, and its arguments are mixed
+    /// with arguments of the other one the same way. Otherwise either of them is taken whole.
+    #[must_use]
+    pub(crate) fn uniform_crossover(&self, other: &Weighted) -> Weighted {
+        let (chosen, rest) = if GET_RNG().gen_bool(0.5) { (self, other) } else { (other, self) };
+        let mut mixed = chosen.clone();
+        match (mixed.n.deref_mut(), rest.n.deref()) {
+            (Node::SingleArgFunction(_, n), Node::SingleArgFunction(_, o)) => {
+                *n = n.uniform_crossover(o);
+            }
+            (Node::DoubleArgFunction(_, n1, n2), Node::DoubleArgFunction(_, o1, o2)) => {
+                *n1 = n1.uniform_crossover(o1);
+                *n2 = n2.uniform_crossover(o2);
+            }
+            (Node::TripleArgFunction(_, n1, n2, n3), Node::TripleArgFunction(_, o1, o2, o3)) => {
+                *n1 = n1.uniform_crossover(o1);
+                *n2 = n2.uniform_crossover(o2);
+                *n3 = n3.uniform_crossover(o3);
+            }
+            _ => (),
+        }
+        mixed
     }
 
     pub fn copy_internals(&mut self, n: Weighted) {
@@ -99,31 +219,129 @@ impl Weighted {
         self.n.node_count()
     }
 
+    /// See [`Node::depth`]
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.n.depth()
+    }
+
     #[must_use]
     pub fn execute(&self, data: &Data<Vec<f32>>) -> Vec<f32> {
-        let mut v = match self.n.deref() {
-            Node::MathConstant(v) => vec![v.value(); data.get(0, 0).len()],
-            Node::DataValue(r, c) => data.get(*r, *c).clone(),
-            Node::StdDev(r, c) => std_dev(data.get(*r, *c)),
-            Node::SingleArgFunction(f, n) => (f.fun)(n.execute(data)),
-            Node::DoubleArgFunction(f, n1, n2) => (f.fun)(n1.execute(data), &n2.execute(data)),
+        let folded = self.fold(data, None).expect("execution without deadline can't time out");
+        folded.into_values(data.get(0, 0).len())
+    }
+
+    /// Same as [`Weighted::execute`], but gives `None` if `deadline` passes before the
+    /// execution of any node starts. A node that already started is not interrupted.
+    pub(crate) fn execute_until(
+        &self,
+        data: &Data<Vec<f32>>,
+        deadline: Instant,
+    ) -> Option<Vec<f32>> {
+        Some(self.fold(data, Some(deadline))?.into_values(data.get(0, 0).len()))
+    }
+
+    /// Executes this node, computing subtrees made of constants only once instead of once per
+    /// point. All functions are element-wise, so values are the same as without folding.
+    /// Gives `None` once `deadline` passes, see [`Weighted::execute_until`].
+    fn fold(&self, data: &Data<Vec<f32>>, deadline: Option<Instant>) -> Option<Folded> {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return None;
+        }
+        let len = data.get(0, 0).len();
+        let folded = match self.n.deref() {
+            Node::MathConstant(v) => Folded::Constant(v.value()),
+            Node::DataValue(r, c) => Folded::Values(data.get(*r, *c).clone()),
+            Node::StdDev(r, c) => Folded::Values(std_dev(data.get(*r, *c))),
+            Node::Terminal(t) => {
+                Folded::Values((0..len).map(|p| t.value(&Input::from_cells(data, p))).collect())
+            }
+            Node::SingleArgFunction(f, n) => match n.fold(data, deadline)? {
+                Folded::Constant(v) => Folded::Constant((f.fun)(vec![v])[0]),
+                Folded::Values(v) => Folded::Values((f.fun)(v)),
+            },
+            Node::DoubleArgFunction(f, n1, n2) => {
+                match (n1.fold(data, deadline)?, n2.fold(data, deadline)?) {
+                    (Folded::Constant(v1), Folded::Constant(v2)) => {
+                        Folded::Constant((f.fun)(vec![v1], &[v2])[0])
+                    }
+                    (v1, v2) => {
+                        Folded::Values((f.fun)(v1.into_values(len), &v2.into_values(len)))
+                    }
+                }
+            }
+            Node::TripleArgFunction(f, n1, n2, n3) => {
+                match (
+                    n1.fold(data, deadline)?,
+                    n2.fold(data, deadline)?,
+                    n3.fold(data, deadline)?,
+                ) {
+                    (Folded::Constant(v1), Folded::Constant(v2), Folded::Constant(v3)) => {
+                        Folded::Constant((f.fun)(vec![v1], &[v2], &[v3])[0])
+                    }
+                    (v1, v2, v3) => Folded::Values((f.fun)(
+                        v1.into_values(len),
+                        &v2.into_values(len),
+                        &v3.into_values(len),
+                    )),
+                }
+            }
         };
-        v.iter_mut().for_each(|v| {
-            *v = &self.w * *v;
-        });
-        v
+        Some(match folded {
+            Folded::Constant(v) => Folded::Constant(&self.w * v),
+            Folded::Values(mut v) => {
+                map_values(|v| &self.w * v, &mut v);
+                Folded::Values(v)
+            }
+        })
     }
 
+    /// Generates a random node and its subtrees, with leaves taken from data values of
+    /// `input_shape` not in `forbidden_cols`, constants and `terminals`
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         current_depth: usize,
         input_shape: &InputShape,
         branch_prob: f64,
         max_depth: usize,
         forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        terminals: &'static [Terminal],
+        data_prob: f64,
+    ) -> Weighted {
+        Weighted::generate(
+            current_depth,
+            input_shape,
+            branch_prob,
+            max_depth,
+            forbidden_cols,
+            forbidden_nodes,
+            terminals,
+            data_prob,
+            0.0,
+        )
+    }
+
+    /// Same as [`Weighted::new`], but every node below the root and above `max_depth` is
+    /// a leaf with probability `leaf_prob`, so that branches end at different depths.
+    /// With `leaf_prob` of 0 all leaves are at `max_depth`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn generate(
+        current_depth: usize,
+        input_shape: &InputShape,
+        branch_prob: f64,
+        max_depth: usize,
+        forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        terminals: &'static [Terminal],
         data_prob: f64,
+        leaf_prob: f64,
     ) -> Weighted {
-        if current_depth == max_depth {
-            Weighted::new_terminating_node(input_shape, forbidden_cols, data_prob)
+        // no random draw unless leaves may be early, so that trees generated with
+        // default settings stay the same
+        let early_leaf = leaf_prob > 0.0 && current_depth > 1 && GET_RNG().gen_bool(leaf_prob);
+        if current_depth == max_depth || early_leaf {
+            Weighted::new_terminating_node(input_shape, forbidden_cols, terminals, data_prob)
         } else {
             Weighted::new_function_node(
                 current_depth,
@@ -131,44 +349,78 @@ impl Weighted {
                 branch_prob,
                 max_depth,
                 forbidden_cols,
+                forbidden_nodes,
+                terminals,
                 data_prob,
+                leaf_prob,
             )
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn new_function_node(
         current_depth: usize,
         input_shape: &InputShape,
         branch_prob: f64,
         max_depth: usize,
         forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        terminals: &'static [Terminal],
         data_prob: f64,
+        leaf_prob: f64,
     ) -> Weighted {
+        let one_arg = allowed_one_arg(forbidden_nodes);
+        let two_arg = allowed_two_arg(forbidden_nodes);
+        let three_arg = allowed_three_arg(forbidden_nodes);
+        if one_arg.is_empty() && two_arg.is_empty() && three_arg.is_empty() {
+            return Weighted::new_terminating_node(
+                input_shape,
+                forbidden_cols,
+                terminals,
+                data_prob,
+            );
+        }
         let mut rng = GET_RNG();
         let current_depth = current_depth + 1;
-        let wn = Weighted::new(
-            current_depth,
-            input_shape,
-            branch_prob,
-            max_depth,
-            forbidden_cols,
-            data_prob,
-        );
-        if rng.gen_bool(branch_prob) {
-            let wn2 = Weighted::new(
+        let new_operand = || {
+            Weighted::generate(
                 current_depth,
                 input_shape,
                 branch_prob,
                 max_depth,
                 forbidden_cols,
+                forbidden_nodes,
+                terminals,
                 data_prob,
-            );
-            let n =
-                Node::DoubleArgFunction(TWO_ARG_FUNCTIONS.choose(&mut rng).unwrap(), wn, wn2);
+                leaf_prob,
+            )
+        };
+        // no random draw unless three argument functions are allowed, so that trees
+        // generated with default settings stay the same
+        let conditional = match (three_arg.is_empty(), one_arg.is_empty() && two_arg.is_empty())
+        {
+            (true, _) => false,
+            (false, true) => true,
+            (false, false) => rng.gen_bool(THREE_ARG_PROB),
+        };
+        if conditional {
+            let f = three_arg.choose(&mut rng).unwrap();
+            let n = Node::TripleArgFunction(f, new_operand(), new_operand(), new_operand());
+            let w = Weight::generate();
+            return Weighted { w, n: Box::new(n) };
+        }
+        let wn = new_operand();
+        let branch = match (one_arg.is_empty(), two_arg.is_empty()) {
+            (false, false) => rng.gen_bool(branch_prob),
+            (one_arg_forbidden, _) => one_arg_forbidden,
+        };
+        if branch {
+            let wn2 = new_operand();
+            let n = Node::DoubleArgFunction(two_arg.choose(&mut rng).unwrap(), wn, wn2);
             let w = Weight::generate();
             Weighted { w, n: Box::new(n) }
         } else {
-            let n = Node::SingleArgFunction(ONE_ARG_FUNCTIONS.choose(&mut rng).unwrap(), wn);
+            let n = Node::SingleArgFunction(one_arg.choose(&mut rng).unwrap(), wn);
             let w = Weight::generate();
             Weighted { w, n: Box::new(n) }
         }
@@ -177,9 +429,16 @@ impl Weighted {
     fn new_terminating_node(
         input_shape: &InputShape,
         forbidden_cols: &[usize],
+        terminals: &'static [Terminal],
         data_prob: f64,
     ) -> Weighted {
         if GET_RNG().gen_bool(data_prob) {
+            // no random draw unless there are terminals, so that trees generated with
+            // default settings stay the same
+            if !terminals.is_empty() && GET_RNG().gen_bool(TERMINAL_PROB) {
+                let n = Node::Terminal(terminals.choose(&mut GET_RNG()).unwrap());
+                return Weighted { w: Weight::generate(), n: Box::new(n) };
+            }
             Weighted::new_data_value_node(input_shape, forbidden_cols)
         } else {
             let n = Node::MathConstant(MATH_CONSTANTS.choose(&mut GET_RNG()).unwrap());
@@ -210,10 +469,15 @@ impl Weighted {
                     node_queue.push(n1.n.deref());
                     node_queue.push(n2.n.deref());
                 }
+                Node::TripleArgFunction(_, n1, n2, n3) => {
+                    node_queue.push(n1.n.deref());
+                    node_queue.push(n2.n.deref());
+                    node_queue.push(n3.n.deref());
+                }
                 Node::SingleArgFunction(_, n) => {
                     node_queue.push(n.n.deref());
                 }
-                Node::MathConstant(_) => {}
+                Node::MathConstant(_) | Node::Terminal(_) => {}
                 Node::DataValue(_, c) | Node::StdDev(_, c) => {
                     columns.insert(*c);
                 }
@@ -222,6 +486,378 @@ impl Weighted {
         columns
     }
 
+    /// Row and column of every data value and standard deviation node of this node and
+    /// its descendants
+    #[must_use]
+    pub fn get_used_cells(&self) -> BTreeSet<(usize, usize)> {
+        let mut cells = BTreeSet::new();
+        let mut node_queue = vec![self.n.deref()];
+        while let Some(next_node) = node_queue.pop() {
+            match next_node {
+                Node::DoubleArgFunction(_, n1, n2) => {
+                    node_queue.push(n1.n.deref());
+                    node_queue.push(n2.n.deref());
+                }
+                Node::TripleArgFunction(_, n1, n2, n3) => {
+                    node_queue.push(n1.n.deref());
+                    node_queue.push(n2.n.deref());
+                    node_queue.push(n3.n.deref());
+                }
+                Node::SingleArgFunction(_, n) => node_queue.push(n.n.deref()),
+                Node::MathConstant(_) | Node::Terminal(_) => {}
+                Node::DataValue(r, c) | Node::StdDev(r, c) => {
+                    cells.insert((*r, *c));
+                }
+            }
+        }
+        cells
+    }
+
+    /// Increments count in `usage` for column of every data value and standard deviation
+    /// node of this node and its descendants
+    pub fn add_column_usage(&self, usage: &mut HashMap<usize, usize>) {
+        match self.n.deref() {
+            Node::DataValue(_, c) | Node::StdDev(_, c) => *usage.entry(*c).or_insert(0) += 1,
+            Node::MathConstant(_) | Node::Terminal(_) => {}
+            Node::SingleArgFunction(_, n) => n.add_column_usage(usage),
+            Node::DoubleArgFunction(_, n1, n2) => {
+                n1.add_column_usage(usage);
+                n2.add_column_usage(usage);
+            }
+            Node::TripleArgFunction(_, n1, n2, n3) => {
+                n1.add_column_usage(usage);
+                n2.add_column_usage(usage);
+                n3.add_column_usage(usage);
+            }
+        }
+    }
+
+    /// Feeds `hasher` with kinds of nodes, their cells and constants but not weights, so that
+    /// nodes differing by weights only hash the same
+    pub(crate) fn hash_structure(&self, hasher: &mut impl Hasher) {
+        match self.n.deref() {
+            Node::DataValue(r, c) => (0_u8, r, c).hash(hasher),
+            Node::StdDev(r, c) => (1_u8, r, c).hash(hasher),
+            Node::MathConstant(c) => (2_u8, c.name()).hash(hasher),
+            Node::Terminal(t) => (6_u8, t.name()).hash(hasher),
+            Node::SingleArgFunction(f, n) => {
+                (3_u8, f.kind).hash(hasher);
+                n.hash_structure(hasher);
+            }
+            Node::DoubleArgFunction(f, n1, n2) => {
+                (4_u8, f.kind).hash(hasher);
+                n1.hash_structure(hasher);
+                n2.hash_structure(hasher);
+            }
+            Node::TripleArgFunction(f, n1, n2, n3) => {
+                (5_u8, f.kind).hash(hasher);
+                n1.hash_structure(hasher);
+                n2.hash_structure(hasher);
+                n3.hash_structure(hasher);
+            }
+        }
+    }
+
+    /// Whether this node or any of its descendants is a [`Node::Terminal`]
+    pub(crate) fn uses_terminals(&self) -> bool {
+        match self.n.deref() {
+            Node::Terminal(_) => true,
+            Node::MathConstant(_) | Node::DataValue(_, _) | Node::StdDev(_, _) => false,
+            Node::SingleArgFunction(_, n) => n.uses_terminals(),
+            Node::DoubleArgFunction(_, n1, n2) => n1.uses_terminals() || n2.uses_terminals(),
+            Node::TripleArgFunction(_, n1, n2, n3) => {
+                n1.uses_terminals() || n2.uses_terminals() || n3.uses_terminals()
+            }
+        }
+    }
+
+    /// Kinds of all function nodes of this node and its descendants
+    #[must_use]
+    pub fn get_used_node_kinds(&self) -> HashSet<NodeKind> {
+        let mut kinds = HashSet::new();
+        let mut node_queue = vec![self.n.deref()];
+        while let Some(next_node) = node_queue.pop() {
+            match next_node {
+                Node::DoubleArgFunction(f, n1, n2) => {
+                    kinds.insert(f.kind);
+                    node_queue.push(n1.n.deref());
+                    node_queue.push(n2.n.deref());
+                }
+                Node::TripleArgFunction(f, n1, n2, n3) => {
+                    kinds.insert(f.kind);
+                    node_queue.push(n1.n.deref());
+                    node_queue.push(n2.n.deref());
+                    node_queue.push(n3.n.deref());
+                }
+                Node::SingleArgFunction(f, n) => {
+                    kinds.insert(f.kind);
+                    node_queue.push(n.n.deref());
+                }
+                Node::MathConstant(_) | Node::DataValue(_, _) | Node::StdDev(_, _) => {}
+                Node::Terminal(_) => {}
+            }
+        }
+        kinds
+    }
+
+    /// Returns node computing the same values with subtrees made of constants only replaced
+    /// by their value and identities `x + 0`, `x - 0`, `x * 1`, `x / 1`, `higher(x, x)`,
+    /// `lower(x, x)` and `neg(neg(x))` removed. Rewrites that would change any value, e.g.
+    /// `x - x` to 0 for infinite or missing `x`, are not applied. Only the sign of zero
+    /// values may change, which none of functions distinguishes.
+    #[must_use]
+    pub fn simplified(&self) -> Weighted {
+        let node = match self.n.deref() {
+            Node::SingleArgFunction(f, n) => Node::SingleArgFunction(f, n.simplified()),
+            Node::DoubleArgFunction(f, n1, n2) => {
+                Node::DoubleArgFunction(f, n1.simplified(), n2.simplified())
+            }
+            Node::TripleArgFunction(f, n1, n2, n3) => {
+                Node::TripleArgFunction(f, n1.simplified(), n2.simplified(), n3.simplified())
+            }
+            node => node.clone(),
+        };
+        let simplified = Weighted { w: self.w, n: Box::new(node) };
+        if let Some(value) = simplified.constant_value() {
+            return Weighted::constant(value);
+        }
+        match simplified.identity_operand() {
+            Some(operand) if is_one(simplified.w.0) => operand.clone(),
+            Some(operand) if is_one(operand.w.0) => {
+                Weighted { w: simplified.w, n: operand.n.clone() }
+            }
+            _ => simplified,
+        }
+    }
+
+    /// Value of this node if it's made of constants only, computed the same way as by
+    /// [`Weighted::execute`]
+    fn constant_value(&self) -> Option<f32> {
+        let value = match self.n.deref() {
+            Node::MathConstant(c) => c.value(),
+            Node::DataValue(_, _) | Node::StdDev(_, _) | Node::Terminal(_) => return None,
+            Node::SingleArgFunction(f, n) => (f.fun)(vec![n.constant_value()?])[0],
+            Node::DoubleArgFunction(f, n1, n2) => {
+                (f.fun)(vec![n1.constant_value()?], &[n2.constant_value()?])[0]
+            }
+            Node::TripleArgFunction(f, n1, n2, n3) => {
+                let (v1, v2, v3) =
+                    (n1.constant_value()?, n2.constant_value()?, n3.constant_value()?);
+                (f.fun)(vec![v1], &[v2], &[v3])[0]
+            }
+        };
+        Some(&self.w * value)
+    }
+
+    /// Node of exactly `value`: one of [`MATH_CONSTANTS`] if any is equal to it, otherwise
+    /// constant 1 weighted by `value`
+    fn constant(value: f32) -> Weighted {
+        match MATH_CONSTANTS.iter().find(|c| c.value() == value) {
+            Some(constant) => Weighted::from(Node::MathConstant(constant)),
+            None => {
+                let one = MATH_CONSTANTS.iter().find(|c| is_one(c.value())).unwrap();
+                Weighted { w: Weight(value), n: Box::new(Node::MathConstant(one)) }
+            }
+        }
+    }
+
+    /// Argument this node's function returns unchanged, if it's an identity
+    fn identity_operand(&self) -> Option<&Weighted> {
+        match self.n.deref() {
+            Node::DoubleArgFunction(f, n1, n2) => {
+                let (c1, c2) = (n1.constant_value(), n2.constant_value());
+                match f.kind {
+                    NodeKind::Add | NodeKind::Sub if c2 == Some(0.0) => Some(n1),
+                    NodeKind::Add if c1 == Some(0.0) => Some(n2),
+                    NodeKind::Mul
+                    | NodeKind::Div
+                    | NodeKind::DivOrOne
+                    | NodeKind::DivOrNumerator
+                        if c2 == Some(1.0) =>
+                    {
+                        Some(n1)
+                    }
+                    NodeKind::Mul if c1 == Some(1.0) => Some(n2),
+                    NodeKind::Higher | NodeKind::Lower if n1 == n2 => Some(n1),
+                    _ => None,
+                }
+            }
+            Node::SingleArgFunction(f, n) if f.kind == NodeKind::Neg && is_one(n.w.0) => {
+                match n.n.deref() {
+                    Node::SingleArgFunction(f, n) if f.kind == NodeKind::Neg => Some(n),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders node as an infix formula, e.g. `(col0 * col2) + sine(col1)`. Data values
+    /// are named by column, prefixed by row if it's not the first one. Weights other
+    /// than 1 are written as multipliers, of constant 1 as plain numbers. Weighted operands
+    /// of infix operators are parenthesized, e.g. `(col0 / (2 * col1))`.
+    #[must_use]
+    pub fn to_expression(&self) -> String {
+        self.to_named_expression(&[])
+    }
+
+    /// Same as [`Weighted::to_expression`] but columns are named with `feature_names`,
+    /// if there's a name for the column, e.g. `(revenue * age)`
+    #[must_use]
+    pub fn to_named_expression(&self, feature_names: &[&str]) -> String {
+        let names = feature_names;
+        let expression = match self.n.deref() {
+            Node::MathConstant(c) if is_one(c.value()) => return self.w.0.to_string(),
+            Node::MathConstant(c) => c.name().to_string(),
+            Node::DataValue(r, c) => feature_label(*r, *c, names),
+            Node::StdDev(r, c) => format!("std_dev({})", feature_label(*r, *c, names)),
+            Node::Terminal(t) => t.name().to_string(),
+            Node::SingleArgFunction(f, n) => {
+                format!("{}({})", f.name, n.to_named_expression(names))
+            }
+            Node::DoubleArgFunction(f, n1, n2) => match infix_operator(f.name) {
+                Some(op) => {
+                    format!("({} {} {})", n1.named_operand(names), op, n2.named_operand(names))
+                }
+                None => format!(
+                    "{}({}, {})",
+                    f.name,
+                    n1.to_named_expression(names),
+                    n2.to_named_expression(names)
+                ),
+            },
+            Node::TripleArgFunction(_, n1, n2, n3) => format!(
+                "(if {} then {} else {})",
+                n1.to_named_expression(names),
+                n2.to_named_expression(names),
+                n3.to_named_expression(names)
+            ),
+        };
+        if (self.w.0 - 1.0).abs() < f32::EPSILON {
+            expression
+        } else {
+            format!("{} * {}", self.w.0, expression)
+        }
+    }
+
+    /// [`Weighted::to_named_expression`] in parentheses if it's a product with the weight
+    fn named_operand(&self, feature_names: &[&str]) -> String {
+        let expression = self.to_named_expression(feature_names);
+        match self.n.deref() {
+            _ if (self.w.0 - 1.0).abs() < f32::EPSILON => expression,
+            Node::MathConstant(c) if is_one(c.value()) => expression,
+            _ => format!("({})", expression),
+        }
+    }
+
+    /// Renders node as a Rust expression computing the same value for a single point, with
+    /// data values read from `features`, a row by row slice of input with `columns` columns.
+    /// Functions are called by their [`crate::exec::functions::NodeKind::scalar_source`]
+    /// names. Standard deviation of a single value is always NaN, so are terminals, which
+    /// can't be rendered and are refused by
+    /// [`crate::exec::classifier::Classifier::to_rust_source`].
+    #[must_use]
+    pub fn to_rust_expression(&self, columns: usize) -> String {
+        let expression = match self.n.deref() {
+            Node::MathConstant(c) => rust_f32(c.value()),
+            Node::DataValue(r, c) => format!("features[{}]", r * columns + c),
+            Node::StdDev(_, _) | Node::Terminal(_) => rust_f32(f32::NAN),
+            Node::SingleArgFunction(f, n) => {
+                format!("{}({})", f.kind.scalar_source().name, n.to_rust_expression(columns))
+            }
+            Node::DoubleArgFunction(f, n1, n2) => format!(
+                "{}({}, {})",
+                f.kind.scalar_source().name,
+                n1.to_rust_expression(columns),
+                n2.to_rust_expression(columns)
+            ),
+            Node::TripleArgFunction(f, n1, n2, n3) => format!(
+                "{}({}, {}, {})",
+                f.kind.scalar_source().name,
+                n1.to_rust_expression(columns),
+                n2.to_rust_expression(columns),
+                n3.to_rust_expression(columns)
+            ),
+        };
+        // multiplying by exactly 1 never changes the value
+        if is_one(self.w.0) {
+            expression
+        } else {
+            format!("{} * {}", rust_f32(self.w.0), expression)
+        }
+    }
+
+    /// Adds ONNX nodes computing this node for every point to `graph`, with data values read
+    /// from features of input with `columns` columns. Returns name of output of the node.
+    pub(crate) fn add_onnx_nodes(&self, columns: usize, graph: &mut OnnxGraph) -> String {
+        let output = match self.n.deref() {
+            Node::MathConstant(c) => graph.constant(c.value()),
+            Node::DataValue(r, c) => graph.feature(r * columns + c),
+            Node::StdDev(_, _) | Node::Terminal(_) => graph.constant(f32::NAN),
+            Node::SingleArgFunction(f, n) => {
+                let arg = n.add_onnx_nodes(columns, graph);
+                graph.function(f.kind, &[arg])
+            }
+            Node::DoubleArgFunction(f, n1, n2) => {
+                let args =
+                    [n1.add_onnx_nodes(columns, graph), n2.add_onnx_nodes(columns, graph)];
+                graph.function(f.kind, &args)
+            }
+            Node::TripleArgFunction(f, n1, n2, n3) => {
+                let args = [
+                    n1.add_onnx_nodes(columns, graph),
+                    n2.add_onnx_nodes(columns, graph),
+                    n3.add_onnx_nodes(columns, graph),
+                ];
+                graph.function(f.kind, &args)
+            }
+        };
+        if is_one(self.w.0) {
+            output
+        } else {
+            graph.weighted(self.w.0, &output)
+        }
+    }
+
+    /// Appends Graphviz DOT statements for this node and its operands to `lines`, naming nodes
+    /// `{prefix}{id}` with ids taken from `next_id`. Columns are labeled with `feature_names`
+    /// where available. Weights other than 1 are written as multipliers, of functions around
+    /// the function name in parentheses, e.g. `0.5 * (sine)`. Returns DOT name of this node.
+    pub fn add_dot_nodes(
+        &self,
+        prefix: &str,
+        next_id: &mut usize,
+        feature_names: &[&str],
+        lines: &mut Vec<String>,
+    ) -> String {
+        let name = format!("{}{}", prefix, next_id);
+        *next_id += 1;
+        let (label, operands) = match self.n.deref() {
+            Node::MathConstant(c) => (c.name().to_string(), vec![]),
+            Node::DataValue(r, c) => (feature_label(*r, *c, feature_names), vec![]),
+            Node::StdDev(r, c) => {
+                (format!("std_dev({})", feature_label(*r, *c, feature_names)), vec![])
+            }
+            Node::Terminal(t) => (t.name().to_string(), vec![]),
+            Node::SingleArgFunction(f, n) => (f.name.to_string(), vec![n]),
+            Node::DoubleArgFunction(f, n1, n2) => (f.name.to_string(), vec![n1, n2]),
+            Node::TripleArgFunction(f, n1, n2, n3) => (f.name.to_string(), vec![n1, n2, n3]),
+        };
+        let label = if (self.w.0 - 1.0).abs() < f32::EPSILON {
+            label
+        } else if operands.is_empty() {
+            format!("{} * {}", self.w.0, label)
+        } else {
+            format!("{} * ({})", self.w.0, label)
+        };
+        lines.push(format!("{} [label=\"{}\"];", name, escape_dot(&label)));
+        for operand in operands {
+            let operand_name = operand.add_dot_nodes(prefix, next_id, feature_names, lines);
+            lines.push(format!("{} -> {};", name, operand_name));
+        }
+        name
+    }
+
     pub fn take_node(self, id: usize) -> Weighted {
         let mut count = id;
         let mut node_queue = Vec::with_capacity(1024);
@@ -233,10 +869,18 @@ impl Weighted {
                     node_queue.push(n2);
                     next_node = n1;
                 }
+                Node::TripleArgFunction(_, n1, n2, n3) => {
+                    node_queue.push(n3);
+                    node_queue.push(n2);
+                    next_node = n1;
+                }
                 Node::SingleArgFunction(_, n) => {
                     next_node = n;
                 }
-                Node::DataValue(_, _) | Node::MathConstant(_) | Node::StdDev(_, _) => {
+                Node::DataValue(_, _)
+                | Node::MathConstant(_)
+                | Node::StdDev(_, _)
+                | Node::Terminal(_) => {
                     if !node_queue.is_empty() {
                         next_node = node_queue.remove(node_queue.len() - 1);
                     }
@@ -257,10 +901,18 @@ impl Weighted {
                     node_queue.push(n2.borrow_mut());
                     next_node = n1.borrow_mut();
                 }
+                Node::TripleArgFunction(_, ref mut n1, ref mut n2, ref mut n3) => {
+                    node_queue.push(n3.borrow_mut());
+                    node_queue.push(n2.borrow_mut());
+                    next_node = n1.borrow_mut();
+                }
                 Node::SingleArgFunction(_, ref mut n) => {
                     next_node = n.borrow_mut();
                 }
-                Node::DataValue(_, _) | Node::MathConstant(_) | Node::StdDev(_, _) => {
+                Node::DataValue(_, _)
+                | Node::MathConstant(_)
+                | Node::StdDev(_, _)
+                | Node::Terminal(_) => {
                     if !node_queue.is_empty() {
                         next_node = node_queue.remove(node_queue.len() - 1);
                     }
@@ -285,13 +937,23 @@ impl Deserializable for Weighted {
     }
 }
 
+/// A function or terminal of a tree:
+/// * `DataValue` - input value at row and column
+/// * `StdDev` - standard deviation across data points of input value at row and column
+/// * `MathConstant` - constant value, see [`MathConst::value`]
+/// * `Terminal` - user-defined value computed from the whole input, see [`Terminal`]
+/// * `SingleArgFunction`, `DoubleArgFunction`, `TripleArgFunction` - function of weighted
+///   subtrees, see [`SingleArgFunction::kind`], [`DoubleArgFunction::kind`] and
+///   [`TripleArgFunction::kind`]
 #[derive(Debug, PartialEq, Clone)]
 pub enum Node {
     DataValue(usize, usize),
     StdDev(usize, usize),
     MathConstant(&'static MathConst),
+    Terminal(&'static Terminal),
     SingleArgFunction(&'static SingleArgFunction, Weighted),
     DoubleArgFunction(&'static DoubleArgFunction, Weighted, Weighted),
+    TripleArgFunction(&'static TripleArgFunction, Weighted, Weighted, Weighted),
 }
 
 impl Serializable for Node {
@@ -302,12 +964,16 @@ impl Serializable for Node {
             Node::MathConstant(constant) => {
                 s.add_items(&[&"Constant".to_owned(), constant.to_owned()])
             }
+            Node::Terminal(terminal) => s.add_items(&[&"Terminal".to_owned(), *terminal]),
             Node::SingleArgFunction(fun, node) => {
                 s.add_items(&[&"OneArgNode".to_owned(), fun.to_owned(), node.deref()])
             }
             Node::DoubleArgFunction(fun, n1, n2) => {
                 s.add_items(&[&"TwoArgNode".to_owned(), fun.to_owned(), n1.deref(), n2.deref()])
             }
+            Node::TripleArgFunction(fun, n1, n2, n3) => {
+                s.add_items(&[&"ThreeArgNode".to_owned(), fun.to_owned(), n1, n2, n3])
+            }
         }
     }
 }
@@ -330,6 +996,7 @@ impl Deserializable for Node {
                 let c = Deserializable::deserialize(s)?;
                 Ok(Node::MathConstant(c))
             }
+            "Terminal" => Ok(Node::Terminal(Deserializable::deserialize(s)?)),
             "OneArgNode" => {
                 let fun = Deserializable::deserialize(s)?;
                 let node = Weighted::deserialize(s)?;
@@ -341,6 +1008,13 @@ impl Deserializable for Node {
                 let n2 = Weighted::deserialize(s)?;
                 Ok(Node::DoubleArgFunction(fun, n1, n2))
             }
+            "ThreeArgNode" => {
+                let fun = Deserializable::deserialize(s)?;
+                let n1 = Weighted::deserialize(s)?;
+                let n2 = Weighted::deserialize(s)?;
+                let n3 = Weighted::deserialize(s)?;
+                Ok(Node::TripleArgFunction(fun, n1, n2, n3))
+            }
             _ => Err(format!("Invalid node type {}", node_type)),
         }
     }
@@ -357,25 +1031,58 @@ impl Node {
         Node::DoubleArgFunction(fun, wn1, wn2)
     }
 
+    #[must_use]
+    pub fn three_arg_node(
+        fun: &'static TripleArgFunction,
+        wn1: Weighted,
+        wn2: Weighted,
+        wn3: Weighted,
+    ) -> Node {
+        Node::TripleArgFunction(fun, wn1, wn2, wn3)
+    }
+
     pub fn set_branch(&mut self, wn: &Weighted) {
         match self {
-            Node::SingleArgFunction(_, ref mut n) | Node::DoubleArgFunction(_, _, ref mut n) => {
-                *n = wn.clone()
-            }
+            Node::SingleArgFunction(_, ref mut n)
+            | Node::DoubleArgFunction(_, _, ref mut n)
+            | Node::TripleArgFunction(_, _, _, ref mut n) => *n = wn.clone(),
             _ => (),
         }
     }
 
-    pub fn mutate(&mut self, input_shape: &InputShape, forbidden_cols: &[usize]) {
+    /// Replaces function of this node by another allowed one of the same arity, a constant
+    /// by another constant, a terminal by another of `terminals` and a data value by one of
+    /// another cell
+    pub fn mutate(
+        &mut self,
+        input_shape: &InputShape,
+        forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        terminals: &'static [Terminal],
+    ) {
         let mut rng = GET_RNG();
         match self {
             Node::SingleArgFunction(ref mut f, _) => {
-                *f = ONE_ARG_FUNCTIONS.choose(&mut rng).unwrap()
+                if let Some(new) = allowed_one_arg(forbidden_nodes).choose(&mut rng) {
+                    *f = new
+                }
             }
             Node::DoubleArgFunction(ref mut f, _, _) => {
-                *f = TWO_ARG_FUNCTIONS.choose(&mut rng).unwrap()
+                if let Some(new) = allowed_two_arg(forbidden_nodes).choose(&mut rng) {
+                    *f = new
+                }
+            }
+            Node::TripleArgFunction(ref mut f, _, _, _) => {
+                if let Some(new) = allowed_three_arg(forbidden_nodes).choose(&mut rng) {
+                    *f = new
+                }
             }
             Node::MathConstant(ref mut c) => *c = MATH_CONSTANTS.choose(&mut rng).unwrap(),
+            Node::Terminal(ref mut t) => {
+                if let Some(new) = terminals.choose(&mut rng) {
+                    *t = new
+                }
+            }
             Node::DataValue(ref mut row, ref mut column)
             | Node::StdDev(ref mut row, ref mut column) => {
                 let (r, c) = input_shape.random_row_column(forbidden_cols);
@@ -389,16 +1096,237 @@ impl Node {
     pub fn node_count(&self) -> usize {
         1 + match self {
             Node::MathConstant(_) | Node::DataValue(_, _) | Node::StdDev(_, _) => 0,
+            Node::Terminal(_) => 0,
             Node::SingleArgFunction(_, n) => n.n.node_count(),
             Node::DoubleArgFunction(_, n1, n2) => n1.n.node_count() + n2.n.node_count(),
+            Node::TripleArgFunction(_, n1, n2, n3) => {
+                n1.n.node_count() + n2.n.node_count() + n3.n.node_count()
+            }
+        }
+    }
+
+    /// Number of nodes on the longest path from this node to a leaf, 1 for a leaf
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        1 + match self {
+            Node::MathConstant(_) | Node::DataValue(_, _) | Node::StdDev(_, _) => 0,
+            Node::Terminal(_) => 0,
+            Node::SingleArgFunction(_, n) => n.n.depth(),
+            Node::DoubleArgFunction(_, n1, n2) => n1.n.depth().max(n2.n.depth()),
+            Node::TripleArgFunction(_, n1, n2, n3) => {
+                n1.n.depth().max(n2.n.depth()).max(n3.n.depth())
+            }
+        }
+    }
+}
+
+/// Rounds `constant` compared to `data`, if `data` is a discrete column of `feature_types`
+fn discretize_comparison(
+    data: &mut Weighted,
+    constant: &mut Weighted,
+    feature_types: &[FeatureType],
+    ordering: bool,
+) {
+    let feature_type = match data.n.deref() {
+        Node::DataValue(_, column) => feature_types.get(*column).copied().unwrap_or_default(),
+        _ => return,
+    };
+    let value = match constant.n.deref() {
+        Node::MathConstant(c) => c.value(),
+        _ => return,
+    };
+    let weight = data.w.0;
+    if !feature_type.is_discrete() || value == 0.0 || weight == 0.0 {
+        return;
+    }
+    // value of the column itself the weighted column is compared to
+    let current = constant.w.0 * value / weight;
+    let mut discrete = if ordering { (current - 0.5).round() + 0.5 } else { current.round() };
+    if feature_type == FeatureType::Boolean {
+        discrete = if ordering { 0.5 } else { discrete.clamp(0.0, 1.0) };
+    }
+    data.w = Weight(1.0);
+    constant.w = Weight(discrete / value);
+    if ordering && weight < 0.0 {
+        // comparing a negatively weighted column reverses the order, e.g. `-x > -3.2` is
+        // `3.5 > x` for whole x
+        std::mem::swap(data, constant);
+    }
+}
+
+fn data_value_name(row: usize, column: usize) -> String {
+    if row == 0 {
+        format!("col{}", column)
+    } else {
+        format!("row{}col{}", row, column)
+    }
+}
+
+fn feature_label(row: usize, column: usize, feature_names: &[&str]) -> String {
+    match feature_names.get(column) {
+        Some(name) if row == 0 => (*name).to_string(),
+        Some(name) => format!("row{} {}", row, name),
+        None => data_value_name(row, column),
+    }
+}
+
+fn is_one(value: f32) -> bool {
+    value.to_bits() == 1.0_f32.to_bits()
+}
+
+/// Rust literal or constant evaluating to exactly `value`
+pub(crate) fn rust_f32(value: f32) -> String {
+    if value.is_nan() {
+        "f32::NAN".to_string()
+    } else if value == f32::INFINITY {
+        "f32::INFINITY".to_string()
+    } else if value == f32::NEG_INFINITY {
+        "f32::NEG_INFINITY".to_string()
+    } else {
+        format!("{:?}_f32", value)
+    }
+}
+
+pub(crate) fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn infix_operator(function_name: &str) -> Option<&'static str> {
+    match function_name {
+        "add" => Some("+"),
+        "sub" => Some("-"),
+        "mul" => Some("*"),
+        "div" => Some("/"),
+        "greater_than" => Some(">"),
+        "less_than" => Some("<"),
+        _ => None,
+    }
+}
+
+/// Result of [`Weighted::fold`]: the same value for every point or one value per point
+enum Folded {
+    Constant(f32),
+    Values(Vec<f32>),
+}
+
+impl Folded {
+    fn into_values(self, len: usize) -> Vec<f32> {
+        match self {
+            Folded::Constant(v) => vec![v; len],
+            Folded::Values(v) => v,
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::exec::node::Weight;
+    use crate::data::data_set::FeatureType;
+    use crate::data::Data;
+    use crate::data::Input;
+    use crate::exec::functions::{register_terminals, Terminal};
+    use crate::exec::functions::{NodeKind, THREE_ARG_FUNCTIONS};
+    use crate::exec::functions::{MATH_CONSTANTS, ONE_ARG_FUNCTIONS, TWO_ARG_FUNCTIONS};
+    use crate::exec::node::{Node, Weight, Weighted};
     use crate::serialization::serializator::test::test_serialization;
+    use crate::serialization::{Deserializable, Serializator};
+
+    fn walk(weighted: &Weighted, kinds: &mut Vec<String>) -> usize {
+        kinds.push(match weighted.node() {
+            Node::DataValue(row, column) => format!("value {} {}", row, column),
+            Node::StdDev(row, column) => format!("std_dev {} {}", row, column),
+            Node::MathConstant(c) => format!("constant {}", c.value()),
+            Node::Terminal(t) => format!("terminal {}", t.name()),
+            Node::SingleArgFunction(f, _) => format!("{:?}", f.kind),
+            Node::DoubleArgFunction(f, _, _) => format!("{:?}", f.kind),
+            Node::TripleArgFunction(f, _, _, _) => format!("{:?}", f.kind),
+        });
+        1 + match weighted.node() {
+            Node::SingleArgFunction(_, n) => walk(n, kinds),
+            Node::DoubleArgFunction(_, n1, n2) => walk(n1, kinds) + walk(n2, kinds),
+            Node::TripleArgFunction(_, n1, n2, n3) => {
+                walk(n1, kinds) + walk(n2, kinds) + walk(n3, kinds)
+            }
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn walk_nodes() {
+        let sine = ONE_ARG_FUNCTIONS.iter().find(|f| f.name == "sine").unwrap();
+        let add = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "add").unwrap();
+        let e = MATH_CONSTANTS.iter().find(|c| c.name() == "e").unwrap();
+        let mut node = Weighted::from(Node::DoubleArgFunction(
+            add,
+            Weighted::from(Node::SingleArgFunction(sine, Weighted::from(Node::DataValue(0, 1)))),
+            Weighted::from(Node::MathConstant(e)),
+        ));
+        node.w = Weight(2.0);
+        assert_eq!(node.weight(), 2.0);
+        let mut kinds = Vec::new();
+        assert_eq!(walk(&node, &mut kinds), node.node_count());
+        assert_eq!(
+            kinds,
+            vec![
+                "Add".to_string(),
+                "Sine".to_string(),
+                "value 0 1".to_string(),
+                format!("constant {}", e.value())
+            ]
+        );
+    }
+
+    #[test]
+    fn simplify() {
+        let two = |name, n1, n2| {
+            let f = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == name).unwrap();
+            Weighted::from(Node::DoubleArgFunction(f, n1, n2))
+        };
+        let one = |name, n| {
+            let f = ONE_ARG_FUNCTIONS.iter().find(|f| f.name == name).unwrap();
+            Weighted::from(Node::SingleArgFunction(f, n))
+        };
+        let constant = |name| {
+            let c = MATH_CONSTANTS.iter().find(|c| c.name() == name).unwrap();
+            Weighted::from(Node::MathConstant(c))
+        };
+        let value = |column| Weighted::from(Node::DataValue(0, column));
+
+        let node = two("mul", two("add", value(0), constant("0")), constant("1"));
+        assert_eq!(node.simplified(), value(0));
+        let node = two(
+            "sub",
+            one("neg", one("neg", value(1))),
+            two("sub", constant("2"), constant("2")),
+        );
+        assert_eq!(node.simplified(), value(1));
+        let node = two(
+            "div",
+            two("higher", value(0), value(0)),
+            two("lower", constant("pi"), constant("2pi")),
+        );
+        assert_eq!(node.simplified(), two("div", value(0), constant("pi")));
+        let node = two("add", value(0), one("sine", constant("1")));
+        assert_eq!(node.simplified().to_expression(), format!("(col0 + {})", 1.0_f32.sin()));
+
+        let mut weighted = two("add", constant("0"), value(1));
+        weighted.w = Weight(2.0);
+        assert_eq!(weighted.simplified().to_expression(), "2 * col1");
+        let mut inner = value(1);
+        inner.w = Weight(3.0);
+        let mut weighted = two("add", constant("0"), inner);
+        weighted.w = Weight(2.0);
+        assert_eq!(weighted.simplified(), weighted);
+        for node in [
+            two("sub", value(0), value(0)),
+            two("mul", value(0), constant("0")),
+            two("sub", constant("0"), value(0)),
+            two("div", constant("1"), value(0)),
+            two("higher", value(0), value(1)),
+            one("neg", value(0)),
+        ] {
+            assert_eq!(node.simplified(), node);
+        }
+    }
 
     #[test]
     fn serialize_weight() {
@@ -406,4 +1334,191 @@ mod test {
             test_serialization(Weight::generate());
         }
     }
+
+    #[test]
+    fn discretize_comparisons() {
+        let function = |name: &str| TWO_ARG_FUNCTIONS.iter().find(|f| f.name == name).unwrap();
+        let constant = MATH_CONSTANTS.iter().find(|c| c.value() > 0.0).unwrap();
+        let weighted =
+            |w, column| Weighted { w: Weight(w), n: Box::new(Node::DataValue(0, column)) };
+        let data = |column| weighted(2.0, column);
+        let value = |v: f32| Weighted {
+            w: Weight(v / constant.value()),
+            n: Box::new(Node::MathConstant(constant)),
+        };
+        let compare = |name, first, second| {
+            Weighted::from(Node::DoubleArgFunction(function(name), first, second))
+        };
+        let add = |first, second| compare("add", first, second);
+        let types = [FeatureType::Integer, FeatureType::Continuous, FeatureType::Boolean];
+        let cases = vec![
+            (compare("first_is_higher", data(0), value(2.3)), 1.0, 1.5),
+            (compare("equal", value(2.7), data(0)), 1.0, 1.0),
+            (compare("equal", value(5.2), data(0)), 3.0, 1.0),
+            (compare("round_equal_array", data(1), value(2.3)), 2.0, 2.3),
+            (compare("first_is_higher", value(3.2), data(2)), 0.5, 1.0),
+            (compare("equal", data(2), value(3.2)), 1.0, 1.0),
+            (add(data(0), value(2.3)), 2.0, 2.3),
+            // -x > -3.2 is 3.5 > x
+            (compare("greater_than", weighted(-1.0, 0), value(-3.2)), 3.5, 1.0),
+            (compare("less_than", value(4.4), weighted(-2.0, 0)), 1.0, -2.5),
+            (compare("equal", weighted(-1.0, 0), value(-3.2)), 1.0, 3.0),
+        ];
+        let effective = |w: &Weighted| match w.node() {
+            Node::MathConstant(c) => w.weight() * c.value(),
+            _ => w.weight(),
+        };
+        for (mut node, first, second) in cases {
+            node.discretize_comparisons(&types);
+            match node.node() {
+                Node::DoubleArgFunction(_, n1, n2) => {
+                    assert!((effective(n1) - first).abs() < 1e-5, "{:?}", node);
+                    assert!((effective(n2) - second).abs() < 1e-5, "{:?}", node);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        let mut nested = Weighted::from(Node::SingleArgFunction(
+            &ONE_ARG_FUNCTIONS[0],
+            compare("equal", data(0), value(5.2)),
+        ));
+        nested.discretize_comparisons(&types);
+        match nested.node() {
+            Node::SingleArgFunction(_, comparison) => match comparison.node() {
+                Node::DoubleArgFunction(_, n1, n2) => {
+                    assert_eq!(n1.weight(), 1.0);
+                    assert!((effective(n2) - 3.0).abs() < 1e-5);
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn execute_constant_subtree() {
+        let add = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "add").unwrap();
+        let sine = ONE_ARG_FUNCTIONS.iter().find(|f| f.name == "sine").unwrap();
+        let pi = MATH_CONSTANTS.iter().find(|c| c.name() == "pi").unwrap();
+        let mut constant = Weighted::from(Node::SingleArgFunction(
+            sine,
+            Weighted::from(Node::MathConstant(pi)),
+        ));
+        constant.w = Weight(2.0);
+        let node = Weighted::from(Node::DoubleArgFunction(
+            add,
+            constant,
+            Weighted::from(Node::DataValue(0, 0)),
+        ));
+        assert_eq!(node.depth(), 3);
+        assert_eq!(Weighted::from(Node::DataValue(0, 0)).depth(), 1);
+        let mut data = Data::new();
+        data.add_row(vec![vec![1.0, 2.0, 3.0]]).unwrap();
+        let expected = [1.0, 2.0, 3.0].iter().map(|v| 2.0 * pi.value().sin() + v);
+        for (value, expected) in node.execute(&data).into_iter().zip(expected) {
+            assert!((value - expected).abs() < f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn terminal() {
+        static TERMINALS: [Terminal; 1] =
+            [Terminal::new("row_sum", &|input: &Input| input.row(0).iter().sum())];
+        let node = Weighted { w: Weight(2.0), n: Box::new(Node::Terminal(&TERMINALS[0])) };
+        let mut data = Data::new();
+        data.add_row(vec![vec![1.0, 2.0], vec![10.0, 20.0]]).unwrap();
+        assert_eq!(node.execute(&data), vec![22.0, 44.0]);
+        assert_eq!(node.to_expression(), "2 * row_sum");
+        assert!(node.uses_terminals());
+        assert_eq!(node.node_count(), 1);
+
+        let mut s = Serializator::new();
+        s.add(&node);
+        let mut d = Serializator::from_bytes(s.to_bytes()).unwrap();
+        assert!(Weighted::deserialize(&mut d).is_err());
+        register_terminals(&TERMINALS);
+        test_serialization(node);
+    }
+
+    #[test]
+    fn to_expression() {
+        let mul = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "mul").unwrap();
+        let higher = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "higher").unwrap();
+        let sine = ONE_ARG_FUNCTIONS.iter().find(|f| f.name == "sine").unwrap();
+        let pi = MATH_CONSTANTS.iter().find(|c| c.name() == "pi").unwrap();
+        let product = Weighted::from(Node::DoubleArgFunction(
+            mul,
+            Weighted::from(Node::DataValue(0, 0)),
+            Weighted::from(Node::DataValue(0, 2)),
+        ));
+        let mut sine =
+            Weighted::from(Node::SingleArgFunction(sine, Weighted::from(Node::StdDev(1, 1))));
+        sine.w = Weight(0.5);
+        let node = Weighted::from(Node::DoubleArgFunction(
+            higher,
+            product,
+            Weighted::from(Node::DoubleArgFunction(
+                mul,
+                sine.clone(),
+                Weighted::from(Node::MathConstant(pi)),
+            )),
+        ));
+        assert_eq!(
+            node.to_expression(),
+            "higher((col0 * col2), ((0.5 * sine(std_dev(row1col1))) * pi))"
+        );
+        assert_eq!(
+            node.to_rust_expression(3),
+            "higher(mul(features[0], features[2]), mul(0.5_f32 * sine(f32::NAN), \
+             3.1415927_f32))"
+        );
+        let div = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "div").unwrap();
+        let mut divisor = Weighted::from(Node::DataValue(0, 1));
+        divisor.w = Weight(2.0);
+        let quotient = Weighted::from(Node::DoubleArgFunction(
+            div,
+            Weighted::from(Node::DataValue(0, 0)),
+            divisor,
+        ));
+        assert_eq!(quotient.to_expression(), "(col0 / (2 * col1))");
+        let mut lines = vec![];
+        sine.add_dot_nodes("n", &mut 0, &[], &mut lines);
+        assert_eq!(
+            lines,
+            ["n0 [label=\"0.5 * (sine)\"];", "n1 [label=\"std_dev(row1col1)\"];", "n0 -> n1;"]
+        );
+    }
+
+    #[test]
+    fn conditional() {
+        let greater = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "greater_than").unwrap();
+        let if_then_else =
+            THREE_ARG_FUNCTIONS.iter().find(|f| f.name == "if_then_else").unwrap();
+        let pi = MATH_CONSTANTS.iter().find(|c| c.name() == "pi").unwrap();
+        let value = |column| Weighted::from(Node::DataValue(0, column));
+        let condition = Weighted::from(Node::DoubleArgFunction(greater, value(0), value(1)));
+        let node = Weighted::from(Node::TripleArgFunction(
+            if_then_else,
+            condition,
+            value(0),
+            Weighted::from(Node::MathConstant(pi)),
+        ));
+        let mut data = Data::new();
+        data.add_row(vec![vec![1.0, 5.0, f32::NAN, 3.0], vec![2.0, 2.0, 2.0, f32::NAN]])
+            .unwrap();
+        let values = node.execute(&data);
+        assert_eq!(values[..2], [pi.value(), 5.0]);
+        assert!(values[2..].iter().all(|v| (v - pi.value()).abs() < f32::EPSILON));
+
+        assert_eq!(node.to_expression(), "(if (col0 > col1) then col0 else pi)");
+        assert_eq!(
+            node.to_rust_expression(2),
+            "if_then_else(greater_than(features[0], features[1]), features[0], 3.1415927_f32)"
+        );
+        assert_eq!(node.node_count(), 6);
+        assert_eq!(node.clone().take_node(4), value(0));
+        assert!(node.get_used_node_kinds().contains(&NodeKind::IfThenElse));
+        test_serialization(node);
+    }
 }
@@ -0,0 +1,540 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Writer of ONNX models, encoding the few protobuf messages it needs by hand. Models take
+//! float tensor `features` of shape `[N, rows * columns]`, with values of every [`Input`]
+//! row by row, and give int64 tensor `class` of shape `[N]`.
+//!
+//! [`Input`]: crate::data::Input
+
+use crate::exec::functions::NodeKind;
+use crate::exec::tree::MAX_OUTPUT;
+use std::f32::consts::{E, PI};
+
+const IR_VERSION: u64 = 7;
+const OPSET_VERSION: u64 = 13;
+const FEATURES: &str = "features";
+const CLASS: &str = "class";
+
+// TensorProto.DataType
+const FLOAT: u64 = 1;
+const INT64: u64 = 7;
+
+// AttributeProto.AttributeType
+const ATTRIBUTE_INT: u64 = 2;
+const ATTRIBUTE_INTS: u64 = 7;
+
+/// Protobuf message being encoded, fields are appended in the order they are added
+#[derive(Default)]
+struct Message {
+    bytes: Vec<u8>,
+}
+
+impl Message {
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.raw_varint(u64::from(field) << 3 | u64::from(wire_type));
+    }
+
+    fn raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    fn varint(&mut self, field: u32, value: u64) -> &mut Self {
+        self.key(field, 0);
+        self.raw_varint(value);
+        self
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) -> &mut Self {
+        self.key(field, 2);
+        self.raw_varint(bytes.len() as u64);
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    fn string(&mut self, field: u32, value: &str) -> &mut Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    fn message(&mut self, field: u32, message: &Message) -> &mut Self {
+        self.bytes(field, &message.bytes)
+    }
+}
+
+/// Attribute of a node, either a single or a list of integers
+enum Attribute<'a> {
+    Int(&'a str, i64),
+    Ints(&'a str, &'a [i64]),
+}
+
+impl Attribute<'_> {
+    fn to_message(&self) -> Message {
+        let mut message = Message::default();
+        match self {
+            Attribute::Int(name, value) => {
+                message.string(1, name).varint(3, *value as u64).varint(20, ATTRIBUTE_INT);
+            }
+            Attribute::Ints(name, values) => {
+                message.string(1, name);
+                for value in values.iter() {
+                    message.varint(8, *value as u64);
+                }
+                message.varint(20, ATTRIBUTE_INTS);
+            }
+        }
+        message
+    }
+}
+
+/// Graph of ONNX nodes computing outputs of trees. Every node has a single output, named
+/// uniquely by the graph, which is returned when the node is added.
+#[derive(Default)]
+pub(crate) struct OnnxGraph {
+    nodes: Vec<Message>,
+    initializers: Vec<Message>,
+    next_id: usize,
+    /// Name of features after [`OnnxGraph::normalize`], if it was added
+    normalized: Option<String>,
+}
+
+impl OnnxGraph {
+    fn next_name(&mut self) -> String {
+        self.next_id += 1;
+        format!("t{}", self.next_id)
+    }
+
+    fn tensor(&mut self, data_type: u64, dims: &[i64], raw_data: Vec<u8>) -> String {
+        let name = self.next_name();
+        let mut tensor = Message::default();
+        for dim in dims {
+            tensor.varint(1, *dim as u64);
+        }
+        tensor.varint(2, data_type).string(8, &name).bytes(9, &raw_data);
+        self.initializers.push(tensor);
+        name
+    }
+
+    /// Scalar float constant
+    pub(crate) fn constant(&mut self, value: f32) -> String {
+        self.tensor(FLOAT, &[], value.to_le_bytes().to_vec())
+    }
+
+    fn float_constant(&mut self, dims: &[i64], values: &[f32]) -> String {
+        self.tensor(FLOAT, dims, values.iter().flat_map(|v| v.to_le_bytes()).collect())
+    }
+
+    fn int64_constant(&mut self, dims: &[i64], values: &[i64]) -> String {
+        self.tensor(INT64, dims, values.iter().flat_map(|v| v.to_le_bytes()).collect())
+    }
+
+    fn node_with_attributes(
+        &mut self,
+        op_type: &str,
+        inputs: &[&str],
+        attributes: &[Attribute<'_>],
+    ) -> String {
+        let output = self.next_name();
+        self.add_node(op_type, inputs, attributes, output)
+    }
+
+    fn add_node(
+        &mut self,
+        op_type: &str,
+        inputs: &[&str],
+        attributes: &[Attribute<'_>],
+        output: String,
+    ) -> String {
+        let mut node = Message::default();
+        for input in inputs {
+            node.string(1, input);
+        }
+        node.string(2, &output).string(3, &output).string(4, op_type);
+        for attribute in attributes {
+            node.message(5, &attribute.to_message());
+        }
+        self.nodes.push(node);
+        output
+    }
+
+    fn node(&mut self, op_type: &str, inputs: &[&str]) -> String {
+        self.node_with_attributes(op_type, inputs, &[])
+    }
+
+    /// Subtracts `centers` from features and divides them by `scales`, one value per
+    /// feature, before [`OnnxGraph::feature`] gathers them. Has to be called before any
+    /// feature is used.
+    pub(crate) fn normalize(&mut self, centers: &[f32], scales: &[f32]) {
+        let centers = self.float_constant(&[centers.len() as i64], centers);
+        let scales = self.float_constant(&[scales.len() as i64], scales);
+        let centered = self.node("Sub", &[FEATURES, &centers]);
+        self.normalized = Some(self.node("Div", &[&centered, &scales]));
+    }
+
+    /// Values of feature `index` of every point
+    pub(crate) fn feature(&mut self, index: usize) -> String {
+        let index = self.int64_constant(&[], &[index as i64]);
+        let features = self.normalized.clone().unwrap_or_else(|| FEATURES.to_string());
+        self.node_with_attributes("Gather", &[&features, &index], &[Attribute::Int("axis", 1)])
+    }
+
+    /// Product of `value` and `weight`
+    pub(crate) fn weighted(&mut self, weight: f32, value: &str) -> String {
+        let weight = self.constant(weight);
+        self.node("Mul", &[&weight, value])
+    }
+
+    /// Function of `kind` of `args`, computing the same values as functions used
+    /// during training, including their handling of zeros and NaN
+    pub(crate) fn function(&mut self, kind: NodeKind, args: &[String]) -> String {
+        let v = args[0].as_str();
+        let w = args.get(1).map_or(v, String::as_str);
+        match kind {
+            NodeKind::Abs => self.node("Abs", &[v]),
+            NodeKind::Ceil => self.node("Ceil", &[v]),
+            NodeKind::Cosine => self.node("Cos", &[v]),
+            NodeKind::Dec => self.with_constant("Sub", v, 1.0),
+            NodeKind::Exp => self.node("Exp", &[v]),
+            NodeKind::Floor => self.node("Floor", &[v]),
+            NodeKind::Inc => self.with_constant("Add", v, 1.0),
+            NodeKind::Log => {
+                let non_positive = self.with_constant("LessOrEqual", v, 0.0);
+                let ln = self.node("Log", &[v]);
+                let log = self.with_constant("Div", &ln, E.ln());
+                self.zero_where(&non_positive, &log)
+            }
+            NodeKind::Neg => self.node("Neg", &[v]),
+            NodeKind::Normalize => {
+                let nan = self.node("IsNaN", &[v]);
+                let sign = self.node("Sign", &[v]);
+                self.zero_where(&nan, &sign)
+            }
+            NodeKind::Reciprocal => {
+                let one = self.constant(1.0);
+                self.protected_div(&one, v)
+            }
+            NodeKind::Relu => {
+                let zero = self.constant(0.0);
+                self.max(v, &zero)
+            }
+            NodeKind::Round => self.round(v),
+            NodeKind::Sine => self.node("Sin", &[v]),
+            NodeKind::Sqrt => {
+                let negative = self.with_constant("Less", v, 0.0);
+                let sqrt = self.node("Sqrt", &[v]);
+                self.zero_where(&negative, &sqrt)
+            }
+            NodeKind::Square => self.node("Mul", &[v, v]),
+            NodeKind::TauSigmoid => {
+                let tau = self.constant(2.0 * PI);
+                self.node("Pow", &[&tau, v])
+            }
+            NodeKind::TangHyper => {
+                let e = self.constant(E);
+                let e_pos = self.node("Pow", &[&e, v]);
+                let neg = self.node("Neg", &[v]);
+                let e_neg = self.node("Pow", &[&e, &neg]);
+                let numerator = self.node("Sub", &[&e_pos, &e_neg]);
+                let denominator = self.node("Add", &[&e_pos, &e_neg]);
+                self.node("Div", &[&numerator, &denominator])
+            }
+            NodeKind::AbsHigher => {
+                let (v, w) = (self.node("Abs", &[v]), self.node("Abs", &[w]));
+                self.max(&v, &w)
+            }
+            NodeKind::AbsLower => {
+                let (v, w) = (self.node("Abs", &[v]), self.node("Abs", &[w]));
+                self.min(&v, &w)
+            }
+            NodeKind::Add => self.node("Add", &[v, w]),
+            NodeKind::And => self.logical("And", v, w),
+            NodeKind::Diff => self.diff(v, w),
+            NodeKind::Div => self.protected_div(v, w),
+            NodeKind::DivOrOne => {
+                let one = self.constant(1.0);
+                self.divided_or(v, w, &one)
+            }
+            NodeKind::DivOrNumerator => self.divided_or(v, w, v),
+            NodeKind::Equal => {
+                let diff = self.diff(v, w);
+                let equal = self.with_constant("Less", &diff, 0.01);
+                self.cast_to_float(&equal)
+            }
+            NodeKind::FirstIsHigher => {
+                let higher = self.node("Greater", &[v, w]);
+                self.cast_to_float(&higher)
+            }
+            NodeKind::Higher => self.max(v, w),
+            NodeKind::Lower => self.min(v, w),
+            NodeKind::Mid => {
+                let sum = self.node("Add", &[v, w]);
+                self.with_constant("Div", &sum, 2.0)
+            }
+            NodeKind::Mul => self.node("Mul", &[v, w]),
+            NodeKind::Or => self.logical("Or", v, w),
+            NodeKind::Sub => self.node("Sub", &[v, w]),
+            NodeKind::SumOfSquares => {
+                let (v, w) = (self.node("Mul", &[v, v]), self.node("Mul", &[w, w]));
+                self.node("Add", &[&v, &w])
+            }
+            NodeKind::Xor => self.logical("Xor", v, w),
+            NodeKind::RoundEqual => {
+                let (v, w) = (self.round(v), self.round(w));
+                let equal = self.node("Equal", &[&v, &w]);
+                self.cast_to_float(&equal)
+            }
+            NodeKind::GreaterThan => {
+                let greater = self.node("Greater", &[v, w]);
+                self.cast_to_float(&greater)
+            }
+            NodeKind::LessThan => {
+                let less = self.node("Less", &[v, w]);
+                self.cast_to_float(&less)
+            }
+            NodeKind::IfThenElse => {
+                let zero = self.with_constant("Equal", v, 0.0);
+                let chosen = self.node("Where", &[&zero, &args[2], w]);
+                let nan = self.node("IsNaN", &[v]);
+                let missing = self.constant(f32::NAN);
+                self.node("Where", &[&nan, &missing, &chosen])
+            }
+        }
+    }
+
+    fn with_constant(&mut self, op_type: &str, v: &str, constant: f32) -> String {
+        let constant = self.constant(constant);
+        self.node(op_type, &[v, &constant])
+    }
+
+    fn zero_where(&mut self, condition: &str, v: &str) -> String {
+        let zero = self.constant(0.0);
+        self.node("Where", &[condition, &zero, v])
+    }
+
+    fn cast_to_float(&mut self, condition: &str) -> String {
+        self.node_with_attributes("Cast", &[condition], &[Attribute::Int("to", FLOAT as i64)])
+    }
+
+    fn protected_div(&mut self, v: &str, w: &str) -> String {
+        let zero = self.with_constant("Equal", w, 0.0);
+        let div = self.node("Div", &[v, w]);
+        self.zero_where(&zero, &div)
+    }
+
+    /// `v` divided by `w`, or `fallback` where `w` is zero
+    fn divided_or(&mut self, v: &str, w: &str, fallback: &str) -> String {
+        let zero = self.with_constant("Equal", w, 0.0);
+        let div = self.node("Div", &[v, w]);
+        self.node("Where", &[&zero, fallback, &div])
+    }
+
+    fn diff(&mut self, v: &str, w: &str) -> String {
+        let one = self.constant(1.0);
+        let div = self.node("Div", &[v, w]);
+        let sub = self.node("Sub", &[&one, &div]);
+        self.node("Abs", &[&sub])
+    }
+
+    /// Same as [`f32::max`], which gives the other value if one of them is NaN
+    fn max(&mut self, v: &str, w: &str) -> String {
+        let higher = self.node("Greater", &[w, v]);
+        self.select(&higher, v, w)
+    }
+
+    /// Same as [`f32::min`], which gives the other value if one of them is NaN
+    fn min(&mut self, v: &str, w: &str) -> String {
+        let lower = self.node("Less", &[w, v]);
+        self.select(&lower, v, w)
+    }
+
+    /// `w` if `condition` or `v` is NaN, `v` otherwise
+    fn select(&mut self, condition: &str, v: &str, w: &str) -> String {
+        let nan = self.node("IsNaN", &[v]);
+        let condition = self.node("Or", &[condition, &nan]);
+        self.node("Where", &[&condition, w, v])
+    }
+
+    /// Same as [`f32::round`], rounding half away from zero, unlike ONNX `Round`
+    fn round(&mut self, v: &str) -> String {
+        let abs = self.node("Abs", &[v]);
+        let floor = self.node("Floor", &[&abs]);
+        let fraction = self.node("Sub", &[&abs, &floor]);
+        let half = self.with_constant("GreaterOrEqual", &fraction, 0.5);
+        let ceil = self.with_constant("Add", &floor, 1.0);
+        let rounded = self.node("Where", &[&half, &ceil, &floor]);
+        let negative = self.with_constant("Less", v, 0.0);
+        let neg = self.node("Neg", &[&rounded]);
+        self.node("Where", &[&negative, &neg, &rounded])
+    }
+
+    /// 1 or 0 depending on logical `op_type` of both values being non-zero
+    fn logical(&mut self, op_type: &str, v: &str, w: &str) -> String {
+        let v = self.non_zero(v);
+        let w = self.non_zero(w);
+        let result = self.node(op_type, &[&v, &w]);
+        self.cast_to_float(&result)
+    }
+
+    fn non_zero(&mut self, v: &str) -> String {
+        let zero = self.with_constant("Equal", v, 0.0);
+        self.node("Not", &[&zero])
+    }
+
+    /// Encodes a model of `feature_count` features giving class of the last of `trees` that
+    /// guesses true: output of its node, bounded by [`MAX_OUTPUT`] the same way as in
+    /// prediction, is not NaN and reaches its threshold, or -1 if none does.
+    /// `trees` are names of outputs of trees, their thresholds and class ids.
+    pub(crate) fn into_model(
+        mut self,
+        feature_count: usize,
+        trees: &[(String, f32, u16)],
+    ) -> Vec<u8> {
+        let shape = self.node("Shape", &[FEATURES]);
+        let first = self.int64_constant(&[1], &[0]);
+        let point_count = self.node("Gather", &[&shape, &first]);
+        let axes = self.int64_constant(&[1], &[1]);
+        let mut ranks = Vec::with_capacity(trees.len());
+        for (rank, (output, threshold, _)) in trees.iter().enumerate() {
+            let (min, max) = (self.constant(-MAX_OUTPUT), self.constant(MAX_OUTPUT));
+            let bounded = self.node("Clip", &[output, &min, &max]);
+            // NaN reaches no threshold
+            let guess = self.with_constant("GreaterOrEqual", &bounded, *threshold);
+            let (zero, rank) = (self.constant(0.0), self.constant(rank as f32 + 1.0));
+            let rank = self.node("Where", &[&guess, &rank, &zero]);
+            let rank = self.node("Expand", &[&rank, &point_count]);
+            ranks.push(self.node("Unsqueeze", &[&rank, &axes]));
+        }
+        let ranks = ranks.iter().map(String::as_str).collect::<Vec<_>>();
+        let ranks = self.node_with_attributes("Concat", &ranks, &[Attribute::Int("axis", 1)]);
+        let argmax = [Attribute::Int("axis", 1), Attribute::Int("keepdims", 0)];
+        let winner = self.node_with_attributes("ArgMax", &[&ranks], &argmax);
+        let classes = trees.iter().map(|(_, _, class)| i64::from(*class)).collect::<Vec<_>>();
+        let classes = self.int64_constant(&[classes.len() as i64], &classes);
+        let class = self.node("Gather", &[&classes, &winner]);
+        let reduce = [Attribute::Ints("axes", &[1]), Attribute::Int("keepdims", 0)];
+        let best_rank = self.node_with_attributes("ReduceMax", &[&ranks], &reduce);
+        let none = self.with_constant("Equal", &best_rank, 0.0);
+        let no_class = self.int64_constant(&[], &[-1]);
+        self.add_node("Where", &[&none, &no_class, &class], &[], CLASS.to_string());
+
+        let mut graph = Message::default();
+        for node in &self.nodes {
+            graph.message(1, node);
+        }
+        graph.string(2, "primeclue");
+        for initializer in &self.initializers {
+            graph.message(5, initializer);
+        }
+        graph.message(11, &value_info(FEATURES, FLOAT, &[None, Some(feature_count as i64)]));
+        graph.message(12, &value_info(CLASS, INT64, &[None]));
+
+        let mut opset = Message::default();
+        opset.string(1, "").varint(2, OPSET_VERSION);
+        let mut model = Message::default();
+        model
+            .varint(1, IR_VERSION)
+            .string(2, "primeclue")
+            .string(3, env!("CARGO_PKG_VERSION"))
+            .message(7, &graph)
+            .message(8, &opset);
+        model.bytes
+    }
+}
+
+/// Description of a graph input or output, dimensions of unknown size are named `N`
+fn value_info(name: &str, data_type: u64, dims: &[Option<i64>]) -> Message {
+    let mut shape = Message::default();
+    for dim in dims {
+        let mut dimension = Message::default();
+        match dim {
+            Some(size) => dimension.varint(1, *size as u64),
+            None => dimension.string(2, "N"),
+        };
+        shape.message(1, &dimension);
+    }
+    let mut tensor = Message::default();
+    tensor.varint(1, data_type).message(2, &shape);
+    let mut type_proto = Message::default();
+    type_proto.message(1, &tensor);
+    let mut info = Message::default();
+    info.string(1, name).message(2, &type_proto);
+    info
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::exec::onnx::Message;
+
+    /// Value of a decoded protobuf field
+    #[derive(Debug, PartialEq)]
+    pub(crate) enum Field {
+        Varint(u64),
+        Bytes(Vec<u8>),
+    }
+
+    fn read_varint(bytes: &[u8], position: &mut usize) -> Option<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = *bytes.get(*position)?;
+            *position += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte < 0x80 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// Decodes fields of a protobuf message, `None` if it's malformed
+    pub(crate) fn decode(bytes: &[u8]) -> Option<Vec<(u32, Field)>> {
+        let mut fields = vec![];
+        let mut position = 0;
+        while position < bytes.len() {
+            let key = read_varint(bytes, &mut position)?;
+            let field = match key & 7 {
+                0 => Field::Varint(read_varint(bytes, &mut position)?),
+                2 => {
+                    let length = read_varint(bytes, &mut position)? as usize;
+                    let field = bytes.get(position..position + length)?.to_vec();
+                    position += length;
+                    Field::Bytes(field)
+                }
+                _ => return None,
+            };
+            fields.push(((key >> 3) as u32, field));
+        }
+        Some(fields)
+    }
+
+    #[test]
+    fn encode_message() {
+        let mut inner = Message::default();
+        inner.varint(1, 300);
+        let mut message = Message::default();
+        message.varint(1, 7).string(2, "ab").message(3, &inner);
+        assert_eq!(message.bytes, [0x08, 7, 0x12, 2, b'a', b'b', 0x1a, 3, 0x08, 0xac, 0x02]);
+        let fields = decode(&message.bytes).unwrap();
+        assert_eq!(fields[0], (1, Field::Varint(7)));
+        assert_eq!(fields[2], (3, Field::Bytes(inner.bytes)));
+        assert!(decode(&[0x12, 5, 1]).is_none());
+    }
+}
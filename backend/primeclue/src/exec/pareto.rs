@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::cmp::Ordering::Equal;
+
+/// True if `a` is at least as good as `b` in every value and better in some.
+/// Higher values are better, NaN is worse than anything.
+fn dominates(a: &[f32], b: &[f32]) -> bool {
+    let mut better = false;
+    for (a, b) in a.iter().zip(b) {
+        let (a, b) = (not_nan(*a), not_nan(*b));
+        if a < b {
+            return false;
+        }
+        better |= a > b;
+    }
+    better
+}
+
+fn not_nan(value: f32) -> f32 {
+    if value.is_nan() {
+        f32::NEG_INFINITY
+    } else {
+        value
+    }
+}
+
+/// Splits indices of `points` into fronts of non-dominated sorting: the first front holds
+/// points dominated by no other, every next one points dominated only by earlier fronts
+pub(crate) fn fronts(points: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let mut dominated_by = vec![0; points.len()];
+    let mut dominating = vec![vec![]; points.len()];
+    for i in 0..points.len() {
+        for j in 0..points.len() {
+            if dominates(&points[i], &points[j]) {
+                dominating[i].push(j);
+                dominated_by[j] += 1;
+            }
+        }
+    }
+    let mut fronts = vec![];
+    let mut front = (0..points.len()).filter(|i| dominated_by[*i] == 0).collect::<Vec<_>>();
+    while !front.is_empty() {
+        let mut next = vec![];
+        for i in &front {
+            for j in &dominating[*i] {
+                dominated_by[*j] -= 1;
+                if dominated_by[*j] == 0 {
+                    next.push(*j);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/// Indices of `points` ordered as NSGA-II selects them: by front, then within a front
+/// by descending crowding distance, so that extreme and isolated points go first
+pub(crate) fn nsga2_order(points: &[Vec<f32>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(points.len());
+    for mut front in fronts(points) {
+        let distances = crowding_distances(points, &front);
+        let mut by_distance = front.drain(..).zip(distances).collect::<Vec<_>>();
+        by_distance.sort_by(|(_, d1), (_, d2)| d2.partial_cmp(d1).unwrap_or(Equal));
+        order.extend(by_distance.into_iter().map(|(i, _)| i));
+    }
+    order
+}
+
+/// Sum over values of the distance between neighbours of every point of `front`, normalized
+/// by the value's range. Points with the lowest or highest value are infinitely distant.
+/// Missing values count as NaN.
+fn crowding_distances(points: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distances = vec![0.0_f32; front.len()];
+    let dimensions = front.iter().map(|i| points[*i].len()).max().unwrap_or(0);
+    for dimension in 0..dimensions {
+        let value = |position: usize| {
+            not_nan(points[front[position]].get(dimension).copied().unwrap_or(f32::NAN))
+        };
+        let mut sorted = (0..front.len()).collect::<Vec<_>>();
+        sorted.sort_by(|p1, p2| value(*p1).partial_cmp(&value(*p2)).unwrap_or(Equal));
+        let (first, last) = (sorted[0], sorted[sorted.len() - 1]);
+        distances[first] = f32::INFINITY;
+        distances[last] = f32::INFINITY;
+        let range = value(last) - value(first);
+        if !(range.is_finite() && range > 0.0) {
+            continue;
+        }
+        for window in sorted.windows(3) {
+            distances[window[1]] += (value(window[2]) - value(window[0])) / range;
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exec::pareto::{fronts, nsga2_order};
+
+    #[test]
+    fn non_dominated_sorting() {
+        let points = vec![
+            vec![1.0, 1.0],
+            vec![3.0, 1.0],
+            vec![2.0, 2.0],
+            vec![1.0, 3.0],
+            vec![0.0, 0.0],
+            vec![2.0, f32::NAN],
+            vec![1.9, 1.9],
+        ];
+        assert_eq!(fronts(&points), vec![vec![1, 2, 3], vec![5, 6], vec![0], vec![4]]);
+        let order = nsga2_order(&points);
+        // 2.0, 2.0 is between the extremes of the first front
+        assert_eq!(order[2], 2);
+        assert_eq!(&order[5..], &[0, 4]);
+        assert!(fronts(&[]).is_empty());
+    }
+}
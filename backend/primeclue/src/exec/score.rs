@@ -23,25 +23,138 @@ use crate::serialization::{Deserializable, Serializable, Serializator};
 use core::fmt;
 use std::cmp::Ordering;
 use std::cmp::Ordering::Equal;
+use std::fmt::{Debug, Formatter};
+use std::mem::discriminant;
+use std::sync::Arc;
 
 /// An enum used to gauge a classifier goodness. Training is performed to maximize this
 /// value.
 /// * `Cost` - use cost function with reward and penalty for correct / incorrect predictions respectively
+/// * `WeightedReward` - same as `Cost` with reward and penalty of every point multiplied by
+///   the pair of multipliers of its class: the n-th pair is for class n, classes beyond the
+///   pairs keep multipliers of 1. Tunes costs of every class without changing points
 /// * `AUC` - use area under curve
+/// * `MacroAuc` - use area under curve with ties counted as half-correct, see
+///   [`calculate_auc_with_ties`]. Averaged over classes it gives macro one-vs-rest AUC
 /// * `Accuracy` - use simple accuracy
-#[derive(Debug, PartialEq, Copy, Clone, serde::Deserialize, serde::Serialize)]
+/// * `F1` - use harmonic mean of precision and recall, averaged over classes
+/// * `BalancedAccuracy` - use mean of recall of the class and of other classes, which is
+///   not inflated by predicting the majority class
+/// * `Hamming` - multi-label, use fraction of points whose membership in the class is
+///   decided correctly regardless of point weights, i.e. one minus the class's part of
+///   Hamming loss. Classes are independent, so training every class on
+///   [`DataView::label_view`] with it minimizes Hamming loss of the combined classifier, see
+///   [`Classifier::score_multi_label`]
+/// * `MeanSquaredError` - regression only, use mean squared difference between output and
+///   target
+/// * `MeanAbsoluteError` - regression only, use mean absolute difference between output and
+///   target
+/// * `Custom` - use user provided function, see [`CustomObjective`]
+/// * `Pareto` - trade off all given classification objectives and the number of nodes.
+///   Groups of trees are selected by non-dominated sorting as in NSGA-II, favouring ones no
+///   other is better than in every objective, see [`TrainingGroup::pareto_front`]. The first
+///   objective is used wherever a single score is needed, e.g. to choose the best tree.
+///
+/// [`TrainingGroup::pareto_front`]: crate::exec::training_group::TrainingGroup::pareto_front
+/// [`DataView::label_view`]: crate::data::data_set::DataView::label_view
+/// [`Classifier::score_multi_label`]: crate::exec::classifier::Classifier::score_multi_label
+///
+/// Error objectives are minimized instead, see [`Objective::is_regression`].
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub enum Objective {
     Cost,
+    WeightedReward(Vec<(f32, f32)>),
     Auc,
+    MacroAuc,
     Accuracy,
+    F1,
+    BalancedAccuracy,
+    Hamming,
+    MeanSquaredError,
+    MeanAbsoluteError,
+    #[serde(skip)]
+    Custom(CustomObjective),
+    Pareto(Vec<Objective>),
+}
+
+/// Fitness function to maximize. Every class is trained separately against all other
+/// classes, so the function receives `(actual, predicted)` pairs where `Class::from(true)`
+/// stands for the class being trained and `Class::from(false)` for any other class.
+/// Class weights are not applied to custom objectives.
+///
+/// Custom objectives can't be deserialized, so classifiers trained with them can't be loaded.
+pub type CustomObjective = Arc<dyn Fn(&[(Class, Class)]) -> f32 + Send + Sync>;
+
+/// Maximum number of thresholds a custom objective is evaluated with for a single tree
+const CUSTOM_THRESHOLD_CANDIDATES: usize = 32;
+const CUSTOM_TOKEN: &str = "Custom";
+const PARETO_TOKEN: &str = "Pareto";
+const WEIGHTED_REWARD_TOKEN: &str = "WeightedReward";
+
+impl Debug for Objective {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Objective::Cost => write!(f, "Cost"),
+            Objective::WeightedReward(multipliers) => {
+                write!(f, "WeightedReward({:?})", multipliers)
+            }
+            Objective::Auc => write!(f, "Auc"),
+            Objective::MacroAuc => write!(f, "MacroAuc"),
+            Objective::Accuracy => write!(f, "Accuracy"),
+            Objective::F1 => write!(f, "F1"),
+            Objective::BalancedAccuracy => write!(f, "BalancedAccuracy"),
+            Objective::Hamming => write!(f, "Hamming"),
+            Objective::MeanSquaredError => write!(f, "MeanSquaredError"),
+            Objective::MeanAbsoluteError => write!(f, "MeanAbsoluteError"),
+            Objective::Custom(_) => write!(f, "Custom"),
+            Objective::Pareto(objectives) => write!(f, "Pareto({:?})", objectives),
+        }
+    }
+}
+
+impl PartialEq for Objective {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Objective::Custom(f1), Objective::Custom(f2)) => Arc::ptr_eq(f1, f2),
+            (Objective::Pareto(o1), Objective::Pareto(o2)) => o1 == o2,
+            (Objective::WeightedReward(m1), Objective::WeightedReward(m2)) => m1 == m2,
+            _ => discriminant(self) == discriminant(other),
+        }
+    }
 }
 
 impl Objective {
+    /// Returns true for objectives that measure error of predicted values against outcome
+    /// targets. Lower score is better for these.
+    #[must_use]
+    pub fn is_regression(&self) -> bool {
+        matches!(self, Objective::MeanSquaredError | Objective::MeanAbsoluteError)
+    }
+
+    /// Returns true for objectives whose scores are summed across classes instead of
+    /// averaged, i.e. `Cost` and `WeightedReward`
+    #[must_use]
+    pub(crate) fn is_cost(&self) -> bool {
+        matches!(self, Objective::Cost | Objective::WeightedReward(_))
+    }
+
     pub fn threshold(&self, outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
         match self {
             Objective::Cost => cost_threshold(outcomes, class),
-            Objective::Auc => auc_threshold(outcomes, class),
+            Objective::WeightedReward(multipliers) => {
+                weighted_cost_threshold(outcomes, class, multipliers)
+            }
+            Objective::Auc | Objective::MacroAuc => auc_threshold(outcomes, class),
             Objective::Accuracy => accuracy_threshold(outcomes, class),
+            Objective::F1 => f1_threshold(outcomes, class),
+            Objective::BalancedAccuracy => balanced_accuracy_threshold(outcomes, class),
+            Objective::Hamming => accuracy_threshold(&unweighted(outcomes), class),
+            Objective::MeanSquaredError | Objective::MeanAbsoluteError => Threshold::new(0.0),
+            Objective::Custom(fun) => custom_threshold(outcomes, class, fun),
+            Objective::Pareto(objectives) => match objectives.first() {
+                Some(primary) => primary.threshold(outcomes, class),
+                None => Threshold::new(0.0),
+            },
         }
     }
 
@@ -53,10 +166,42 @@ impl Objective {
     ) -> Score {
         let value = match self {
             Objective::Auc => calculate_auc(outcomes, class),
+            Objective::MacroAuc => calculate_auc_with_ties(outcomes, class),
             Objective::Accuracy => calculate_accuracy(threshold, outcomes, class),
             Objective::Cost => calculate_cost(threshold, outcomes, class),
+            Objective::WeightedReward(multipliers) => {
+                calculate_weighted_cost(threshold, outcomes, class, multipliers)
+            }
+            Objective::F1 => calculate_f1(threshold, outcomes, class),
+            Objective::BalancedAccuracy => {
+                calculate_balanced_accuracy(threshold, outcomes, class)
+            }
+            Objective::Hamming => calculate_accuracy(threshold, &unweighted(outcomes), class),
+            Objective::MeanSquaredError => calculate_mean_error(outcomes, |d| d * d),
+            Objective::MeanAbsoluteError => calculate_mean_error(outcomes, f32::abs),
+            Objective::Custom(fun) => calculate_custom(threshold, outcomes, class, fun),
+            Objective::Pareto(objectives) => match objectives.first() {
+                Some(primary) => primary.score(threshold, outcomes, class).value,
+                None => f32::NAN,
+            },
         };
-        Score { objective: *self, class, value, threshold }
+        Score { objective: self.clone(), class, value, threshold }
+    }
+
+    /// Values of every objective of [`Objective::Pareto`] with the same `threshold`,
+    /// a single value for other objectives
+    pub(crate) fn values(
+        &self,
+        threshold: Threshold,
+        outcomes: &[(f32, Outcome)],
+        class: Class,
+    ) -> Vec<f32> {
+        match self {
+            Objective::Pareto(objectives) => {
+                objectives.iter().map(|o| o.score(threshold, outcomes, class).value).collect()
+            }
+            _ => vec![self.score(threshold, outcomes, class).value],
+        }
     }
 }
 
@@ -64,10 +209,24 @@ impl Serializable for Objective {
     fn serialize(&self, s: &mut Serializator) {
         let var = match self {
             Objective::Cost => "Cost",
+            Objective::WeightedReward(_) => WEIGHTED_REWARD_TOKEN,
             Objective::Auc => "AUC",
+            Objective::MacroAuc => "MacroAUC",
             Objective::Accuracy => "Accuracy",
+            Objective::F1 => "F1",
+            Objective::BalancedAccuracy => "BalancedAccuracy",
+            Objective::Hamming => "Hamming",
+            Objective::MeanSquaredError => "MSE",
+            Objective::MeanAbsoluteError => "MAE",
+            Objective::Custom(_) => CUSTOM_TOKEN,
+            Objective::Pareto(_) => PARETO_TOKEN,
         };
         s.add_str(var);
+        match self {
+            Objective::Pareto(objectives) => s.add(objectives),
+            Objective::WeightedReward(multipliers) => s.add(multipliers),
+            _ => {}
+        }
     }
 }
 
@@ -77,18 +236,36 @@ impl Deserializable for Objective {
         match t.as_ref() {
             "Cost" => Ok(Objective::Cost),
             "AUC" => Ok(Objective::Auc),
+            "MacroAUC" => Ok(Objective::MacroAuc),
             "Accuracy" => Ok(Objective::Accuracy),
+            "F1" => Ok(Objective::F1),
+            "BalancedAccuracy" => Ok(Objective::BalancedAccuracy),
+            "Hamming" => Ok(Objective::Hamming),
+            "MSE" => Ok(Objective::MeanSquaredError),
+            "MAE" => Ok(Objective::MeanAbsoluteError),
+            CUSTOM_TOKEN => Err("Custom objective cannot be deserialized".to_string()),
+            PARETO_TOKEN => Ok(Objective::Pareto(Vec::deserialize(s)?)),
+            WEIGHTED_REWARD_TOKEN => Ok(Objective::WeightedReward(Vec::deserialize(s)?)),
             _ => Err(format!("Invalid token for ScoreType: {}", t)),
         }
     }
 }
 
 impl fmt::Display for Objective {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let s = match self {
             Objective::Cost => "Cost",
+            Objective::WeightedReward(_) => "Weighted reward",
             Objective::Auc => "AUC",
+            Objective::MacroAuc => "Macro AUC",
             Objective::Accuracy => "Accuracy",
+            Objective::F1 => "F1",
+            Objective::BalancedAccuracy => "Balanced accuracy",
+            Objective::Hamming => "Hamming",
+            Objective::MeanSquaredError => "Mean squared error",
+            Objective::MeanAbsoluteError => "Mean absolute error",
+            Objective::Custom(_) => "Custom",
+            Objective::Pareto(_) => "Pareto",
         };
         write!(f, "{}", s)
     }
@@ -129,7 +306,7 @@ impl Deserializable for Threshold {
     }
 }
 
-#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct Score {
     objective: Objective,
     class: Class,
@@ -149,8 +326,8 @@ impl Score {
     }
 
     #[must_use]
-    pub fn objective(&self) -> Objective {
-        self.objective
+    pub fn objective(&self) -> &Objective {
+        &self.objective
     }
 
     #[must_use]
@@ -170,7 +347,7 @@ impl Score {
 }
 
 impl fmt::Display for Score {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.value)
     }
 }
@@ -180,7 +357,12 @@ impl PartialOrd for Score {
         if self.objective == other.objective {
             let diff = (self.value / other.value - 1.0).abs() > 0.001;
             if diff {
-                self.value.partial_cmp(&other.value)
+                let ordering = self.value.partial_cmp(&other.value);
+                if self.objective.is_regression() {
+                    ordering.map(Ordering::reverse)
+                } else {
+                    ordering
+                }
             } else {
                 Some(Equal)
             }
@@ -215,50 +397,220 @@ impl Deserializable for Score {
     }
 }
 
+/// Weighted probability that a random outcome of `class` is ranked above a random outcome
+/// of other class. Each pair counts with the product of both outcomes' weights.
 #[must_use]
 pub fn calculate_auc(outcomes: &[(f32, Outcome)], class: Class) -> f32 {
-    let mut incorrect_count = 0_usize;
-    let mut correct_count = 0_usize;
-    let mut total_incorrect = 0_usize;
+    let mut incorrect_weight = 0.0_f64;
+    let mut correct_weight = 0.0_f64;
+    let mut total_incorrect = 0.0_f64;
     for (_, outcome) in outcomes {
+        let weight = f64::from(outcome.weight());
         if outcome.class() == class {
-            correct_count += 1;
-            total_incorrect += incorrect_count;
+            correct_weight += weight;
+            total_incorrect += incorrect_weight * weight;
         } else {
-            incorrect_count += 1;
+            incorrect_weight += weight;
+        }
+    }
+    (total_incorrect / (correct_weight * incorrect_weight)) as f32
+}
+
+/// Same as [`calculate_auc`] but ranking doesn't depend on order of equal guesses: a pair of
+/// outcomes with equal guesses counts as half-correct, which is the Mann-Whitney statistic
+/// with mid-ranks for ties. `outcomes` must be sorted by guess in ascending order.
+#[must_use]
+pub fn calculate_auc_with_ties(outcomes: &[(f32, Outcome)], class: Class) -> f32 {
+    let mut incorrect_weight = 0.0_f64;
+    let mut correct_weight = 0.0_f64;
+    let mut total_incorrect = 0.0_f64;
+    let mut start = 0;
+    while start < outcomes.len() {
+        let guess = outcomes[start].0;
+        // NaN is not equal to itself so it's a group of its own
+        let tied = outcomes[start..].iter().take_while(|(g, _)| *g == guess).count().max(1);
+        let mut tied_correct = 0.0;
+        let mut tied_incorrect = 0.0;
+        for (_, outcome) in &outcomes[start..start + tied] {
+            if outcome.class() == class {
+                tied_correct += f64::from(outcome.weight());
+            } else {
+                tied_incorrect += f64::from(outcome.weight());
+            }
         }
+        total_incorrect += tied_correct * (incorrect_weight + tied_incorrect / 2.0);
+        correct_weight += tied_correct;
+        incorrect_weight += tied_incorrect;
+        start += tied;
     }
-    total_incorrect as f32 / (correct_count * incorrect_count) as f32
+    (total_incorrect / (correct_weight * incorrect_weight)) as f32
 }
 
 #[must_use]
 fn calculate_accuracy(threshold: Threshold, outcomes: &[(f32, Outcome)], class: Class) -> f32 {
-    let mut correct = 0;
-    let mut total = 0;
+    let mut correct = 0.0;
+    let mut total = 0.0;
     for (guess, outcome) in outcomes {
         if let Some(guess_bool) = threshold.bool(*guess) {
-            total += 1;
+            total += outcome.weight();
             if (outcome.class() == class && guess_bool)
                 || (outcome.class() != class && !guess_bool)
             {
-                correct += 1;
+                correct += outcome.weight();
             }
         }
     }
-    correct as f32 / total as f32
+    correct / total
+}
+
+/// `outcomes` with weight of every point 1
+fn unweighted(outcomes: &[(f32, Outcome)]) -> Vec<(f32, Outcome)> {
+    outcomes.iter().map(|(guess, o)| (*guess, Outcome::new(o.class(), 1.0, -1.0))).collect()
 }
 
 #[must_use]
 fn calculate_cost(threshold: Threshold, outcomes: &[(f32, Outcome)], class: Class) -> f32 {
+    calculate_weighted_cost(threshold, outcomes, class, &[])
+}
+
+#[must_use]
+fn calculate_weighted_cost(
+    threshold: Threshold,
+    outcomes: &[(f32, Outcome)],
+    class: Class,
+    multipliers: &[(f32, f32)],
+) -> f32 {
     let mut cost = 0.0;
     for (guess, outcome) in outcomes {
         if let Some(b) = threshold.bool(*guess) {
-            cost += outcome.calculate_cost(b, class);
+            cost += weighted_cost(outcome, b, class, multipliers);
         }
     }
     cost
 }
 
+/// Multipliers of reward and penalty of points of `class` in `multipliers` of
+/// [`Objective::WeightedReward`]
+#[must_use]
+pub(crate) fn reward_multipliers(multipliers: &[(f32, f32)], class: Class) -> (f32, f32) {
+    multipliers.get(class.value() as usize).copied().unwrap_or((1.0, 1.0))
+}
+
+/// [`Outcome::calculate_cost`] scaled by weight of `outcome` and by its multiplier in
+/// `multipliers` of [`Objective::WeightedReward`]
+#[must_use]
+fn weighted_cost(
+    outcome: &Outcome,
+    guess: bool,
+    class: Class,
+    multipliers: &[(f32, f32)],
+) -> f32 {
+    let (reward, penalty) = reward_multipliers(multipliers, outcome.class());
+    let multiplier = if outcome.class() == class { reward } else { penalty };
+    outcome.calculate_cost(guess, class) * outcome.weight() * multiplier
+}
+
+#[must_use]
+fn calculate_f1(threshold: Threshold, outcomes: &[(f32, Outcome)], class: Class) -> f32 {
+    let mut true_positive = 0.0;
+    let mut false_positive = 0.0;
+    let mut false_negative = 0.0;
+    for (guess, outcome) in outcomes {
+        if let Some(guess_bool) = threshold.bool(*guess) {
+            match (outcome.class() == class, guess_bool) {
+                (true, true) => true_positive += outcome.weight(),
+                (false, true) => false_positive += outcome.weight(),
+                (true, false) => false_negative += outcome.weight(),
+                (false, false) => {}
+            }
+        }
+    }
+    f1(true_positive, false_positive, false_negative)
+}
+
+#[must_use]
+fn calculate_balanced_accuracy(
+    threshold: Threshold,
+    outcomes: &[(f32, Outcome)],
+    class: Class,
+) -> f32 {
+    let mut true_positive = 0.0;
+    let mut false_positive = 0.0;
+    let mut true_negative = 0.0;
+    let mut false_negative = 0.0;
+    for (guess, outcome) in outcomes {
+        if let Some(guess_bool) = threshold.bool(*guess) {
+            match (outcome.class() == class, guess_bool) {
+                (true, true) => true_positive += outcome.weight(),
+                (false, true) => false_positive += outcome.weight(),
+                (true, false) => false_negative += outcome.weight(),
+                (false, false) => true_negative += outcome.weight(),
+            }
+        }
+    }
+    balanced_accuracy(true_positive, false_negative, true_negative, false_positive)
+}
+
+/// Weighted mean of `error` applied to differences between guesses and targets. Outcomes
+/// without target or non-finite guesses result in NaN, i.e. invalid score.
+#[must_use]
+fn calculate_mean_error(outcomes: &[(f32, Outcome)], error: impl Fn(f32) -> f32) -> f32 {
+    let mut total = 0.0;
+    let mut total_weight = 0.0;
+    for (guess, outcome) in outcomes {
+        match outcome.target() {
+            Some(target) => {
+                total += error(guess - target) * outcome.weight();
+                total_weight += outcome.weight();
+            }
+            None => return f32::NAN,
+        }
+    }
+    total / total_weight
+}
+
+#[must_use]
+fn calculate_custom(
+    threshold: Threshold,
+    outcomes: &[(f32, Outcome)],
+    class: Class,
+    fun: &CustomObjective,
+) -> f32 {
+    let pairs = outcomes
+        .iter()
+        .filter_map(|(guess, outcome)| {
+            let guess_bool = threshold.bool(*guess)?;
+            Some((Class::from(outcome.class() == class), Class::from(guess_bool)))
+        })
+        .collect::<Vec<_>>();
+    fun(&pairs)
+}
+
+/// Mean of recall of the class and of other classes. A side without any outcomes is left
+/// out, NaN if there are no outcomes at all.
+pub(crate) fn balanced_accuracy(
+    true_positive: f32,
+    false_negative: f32,
+    true_negative: f32,
+    false_positive: f32,
+) -> f32 {
+    let recalls = [(true_positive, false_negative), (true_negative, false_positive)]
+        .iter()
+        .filter(|(correct, incorrect)| correct + incorrect > 0.0)
+        .map(|(correct, incorrect)| correct / (correct + incorrect))
+        .collect::<Vec<_>>();
+    recalls.iter().sum::<f32>() / recalls.len() as f32
+}
+
+pub(crate) fn f1(true_positive: f32, false_positive: f32, false_negative: f32) -> f32 {
+    let denominator = 2.0 * true_positive + false_positive + false_negative;
+    if denominator <= 0.0 {
+        0.0
+    } else {
+        2.0 * true_positive / denominator
+    }
+}
+
 #[must_use]
 pub fn auc_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
     let none_class_count =
@@ -274,10 +626,19 @@ pub fn auc_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
 
 #[must_use]
 fn cost_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
+    weighted_cost_threshold(outcomes, class, &[])
+}
+
+#[must_use]
+fn weighted_cost_threshold(
+    outcomes: &[(f32, Outcome)],
+    class: Class,
+    multipliers: &[(f32, f32)],
+) -> Threshold {
     let mut false_list = Vec::with_capacity(outcomes.len());
     let mut false_cost = 0.0;
     for (guess, outcome) in outcomes {
-        let false_reward = outcome.calculate_cost(false, class);
+        let false_reward = weighted_cost(outcome, false, class, multipliers);
         false_list.push((*guess, *outcome, false_cost));
         false_cost += false_reward;
     }
@@ -285,7 +646,7 @@ fn cost_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
     let mut true_cost = 0.0;
     let mut cost_list = Vec::with_capacity(false_list.len());
     for (guess, outcome, false_reward) in false_list {
-        let true_reward = outcome.calculate_cost(true, class);
+        let true_reward = weighted_cost(&outcome, true, class, multipliers);
         true_cost += true_reward;
         cost_list.push((guess, outcome, false_reward + true_cost));
     }
@@ -296,41 +657,124 @@ fn cost_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
 #[must_use]
 fn accuracy_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
     let mut incorrect_list = Vec::with_capacity(outcomes.len());
-    let mut incorrect_count = 0;
+    let mut incorrect_count = 0.0;
     for (guess, outcome) in outcomes {
         incorrect_list.push((*guess, *outcome, incorrect_count));
         if outcome.class() != class {
-            incorrect_count += 1;
+            incorrect_count += outcome.weight();
         }
     }
-    let mut correct_count = 0;
+    let mut correct_count = 0.0;
     incorrect_list.reverse();
     let mut accuracy_list = Vec::with_capacity(incorrect_list.len());
     for (guess, outcome, incorrect_count) in incorrect_list {
         if outcome.class() == class {
-            correct_count += 1;
+            correct_count += outcome.weight();
         }
         accuracy_list.push((guess, outcome, incorrect_count + correct_count));
     }
-    accuracy_list.sort_by(|(_, _, count1), (_, _, count2)| count1.cmp(count2));
+    accuracy_list.sort_by(|(_, _, count1), (_, _, count2)| count1.partial_cmp(count2).unwrap());
     Threshold::new(accuracy_list.last().unwrap().0)
 }
 
+#[must_use]
+fn f1_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
+    let total_positive: f32 =
+        outcomes.iter().filter(|(_, o)| o.class() == class).map(|(_, o)| o.weight()).sum();
+    let mut best = (0.0, Threshold::new(outcomes[0].0));
+    let mut true_positive = total_positive;
+    let mut false_positive: f32 =
+        outcomes.iter().filter(|(_, o)| o.class() != class).map(|(_, o)| o.weight()).sum();
+    for (i, (guess, outcome)) in outcomes.iter().enumerate() {
+        // guesses equal to previous one are classified the same way
+        if i == 0 || outcomes[i - 1].0 < *guess {
+            let score = f1(true_positive, false_positive, total_positive - true_positive);
+            if score > best.0 {
+                best = (score, Threshold::new(*guess));
+            }
+        }
+        if outcome.class() == class {
+            true_positive -= outcome.weight();
+        } else {
+            false_positive -= outcome.weight();
+        }
+    }
+    best.1
+}
+
+#[must_use]
+fn balanced_accuracy_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
+    let total_positive: f32 =
+        outcomes.iter().filter(|(_, o)| o.class() == class).map(|(_, o)| o.weight()).sum();
+    let total_negative: f32 =
+        outcomes.iter().filter(|(_, o)| o.class() != class).map(|(_, o)| o.weight()).sum();
+    let mut best = (0.0, Threshold::new(outcomes[0].0));
+    let mut true_positive = total_positive;
+    let mut false_positive = total_negative;
+    for (i, (guess, outcome)) in outcomes.iter().enumerate() {
+        // guesses equal to previous one are classified the same way
+        if i == 0 || outcomes[i - 1].0 < *guess {
+            let score = balanced_accuracy(
+                true_positive,
+                total_positive - true_positive,
+                total_negative - false_positive,
+                false_positive,
+            );
+            if score > best.0 {
+                best = (score, Threshold::new(*guess));
+            }
+        }
+        if outcome.class() == class {
+            true_positive -= outcome.weight();
+        } else {
+            false_positive -= outcome.weight();
+        }
+    }
+    best.1
+}
+
+/// Evaluates custom objective with thresholds at evenly spaced positions of sorted outcomes
+#[must_use]
+fn custom_threshold(
+    outcomes: &[(f32, Outcome)],
+    class: Class,
+    fun: &CustomObjective,
+) -> Threshold {
+    let step = (outcomes.len() / CUSTOM_THRESHOLD_CANDIDATES).max(1);
+    let mut best = (f32::NEG_INFINITY, Threshold::new(outcomes[0].0));
+    for (guess, _) in outcomes.iter().step_by(step) {
+        let threshold = Threshold::new(*guess);
+        let score = calculate_custom(threshold, outcomes, class, fun);
+        if score > best.0 {
+            best = (score, threshold);
+        }
+    }
+    best.1
+}
+
 #[cfg(test)]
 mod test {
     use crate::data::outcome::Class;
     use crate::data::Outcome;
-    use crate::exec::score::Objective::{Accuracy, Auc, Cost};
+    use crate::exec::score::Objective::{
+        Accuracy, Auc, Cost, Custom, MacroAuc, MeanAbsoluteError, MeanSquaredError, F1,
+    };
     use crate::exec::score::{
-        accuracy_threshold, auc_threshold, calculate_accuracy, calculate_auc, calculate_cost,
-        cost_threshold, Score, Threshold,
+        accuracy_threshold, auc_threshold, balanced_accuracy_threshold, calculate_accuracy,
+        calculate_auc, calculate_auc_with_ties, calculate_balanced_accuracy, calculate_cost,
+        calculate_f1, calculate_weighted_cost, cost_threshold, f1_threshold,
+        weighted_cost_threshold, CustomObjective, Objective, Score, Threshold,
     };
     use crate::serialization::serializator::test::test_serialization;
+    use crate::serialization::{Deserializable, Serializator};
     use std::cmp::Ordering::Equal;
+    use std::sync::Arc;
 
     #[test]
     fn serialize_tree_score() {
         test_serialization(Accuracy);
+        test_serialization(Objective::Pareto(vec![Accuracy, F1]));
+        test_serialization(Objective::WeightedReward(vec![(2.0, 1.0), (1.0, 0.5)]));
         test_serialization(Score {
             objective: Cost,
             class: Class::from(true),
@@ -410,6 +854,29 @@ mod test {
         assert!((auc - 0.68).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn test_auc_with_ties() {
+        let p = Class::new(1);
+        let n = Class::new(0);
+        let outcomes = vec![
+            (0.1, Outcome::new(n, 1.0, -1.0)),
+            (0.2, Outcome::new(p, 1.0, -1.0)),
+            (0.2, Outcome::new(n, 1.0, -1.0)),
+            (0.3, Outcome::new(p, 1.0, -1.0)),
+        ];
+        // pairs: (0.2p, 0.1n) = 1, (0.2p, 0.2n) = 0.5, (0.3p, 0.1n) = 1, (0.3p, 0.2n) = 1
+        let auc = calculate_auc_with_ties(&outcomes, p);
+        assert!((auc - 3.5 / 4.0).abs() < f32::EPSILON);
+        let mut reordered = outcomes.clone();
+        reordered.swap(1, 2);
+        assert!((calculate_auc_with_ties(&reordered, p) - auc).abs() < f32::EPSILON);
+        // without ties both AUCs are the same
+        let outcomes = get_biased_outcomes();
+        let difference = calculate_auc_with_ties(&outcomes, p) - calculate_auc(&outcomes, p);
+        assert!(difference.abs() < 0.000_1);
+        test_serialization(MacroAuc);
+    }
+
     #[test]
     fn test_auc_threshold() {
         // Classification uses >= comparison
@@ -470,6 +937,259 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_f1_threshold() {
+        let class = Class::new(1);
+        for _ in 0..1_000 {
+            let outcomes = get_biased_outcomes();
+            let slow_threshold = naive_f1_threshold(&outcomes, class);
+            let fast_threshold = f1_threshold(&outcomes, class);
+            let slow_score = calculate_f1(slow_threshold, &outcomes, class);
+            let fast_score = calculate_f1(fast_threshold, &outcomes, class);
+            assert!((slow_score - fast_score).abs() < f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_f1() {
+        let p = Class::new(1);
+        let n = Class::new(0);
+        let outcomes = vec![
+            (0.1, Outcome::new(n, 1.0, -1.0)),
+            (0.2, Outcome::new(p, 1.0, -1.0)),
+            (0.3, Outcome::new(n, 1.0, -1.0)),
+            (0.4, Outcome::new(p, 1.0, -1.0)),
+            (0.5, Outcome::new(p, 1.0, -1.0)),
+        ];
+        // tp = 2, fp = 1, fn = 1
+        let f1 = calculate_f1(Threshold::new(0.3), &outcomes, p);
+        assert!((f1 - 4.0 / 6.0).abs() < f32::EPSILON);
+        let score = F1.score(F1.threshold(&outcomes, p), &outcomes, p);
+        assert!((score.value() - 6.0 / 7.0).abs() < f32::EPSILON);
+        test_serialization(F1);
+    }
+
+    #[test]
+    fn test_balanced_accuracy_threshold() {
+        let class = Class::new(1);
+        for _ in 0..1_000 {
+            let outcomes = get_biased_outcomes();
+            let slow_threshold = naive_balanced_accuracy_threshold(&outcomes, class);
+            let fast_threshold = balanced_accuracy_threshold(&outcomes, class);
+            let slow_score = calculate_balanced_accuracy(slow_threshold, &outcomes, class);
+            let fast_score = calculate_balanced_accuracy(fast_threshold, &outcomes, class);
+            assert!((slow_score - fast_score).abs() < f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_balanced_accuracy() {
+        let p = Class::new(1);
+        let n = Class::new(0);
+        let mut outcomes = vec![
+            (0.1, Outcome::new(n, 1.0, -1.0)),
+            (0.2, Outcome::new(n, 1.0, -1.0)),
+            (0.3, Outcome::new(p, 1.0, -1.0)),
+            (0.4, Outcome::new(n, 1.0, -1.0)),
+            (0.5, Outcome::new(p, 1.0, -1.0)),
+        ];
+        // recall of positive = 2 / 2, recall of negative = 2 / 3
+        let score = calculate_balanced_accuracy(Threshold::new(0.3), &outcomes, p);
+        assert!((score - 5.0 / 6.0).abs() < f32::EPSILON);
+        // always guessing the majority gets only half
+        let score = calculate_balanced_accuracy(Threshold::new(1.0), &outcomes, p);
+        assert!((score - 0.5).abs() < f32::EPSILON);
+        let objective = Objective::BalancedAccuracy;
+        let score = objective.score(objective.threshold(&outcomes, p), &outcomes, p);
+        assert!((score.value() - 5.0 / 6.0).abs() < f32::EPSILON);
+        outcomes.retain(|(_, o)| o.class() == n);
+        let score = calculate_balanced_accuracy(Threshold::new(0.3), &outcomes, p);
+        assert!((score - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert!(calculate_balanced_accuracy(Threshold::new(0.3), &[], p).is_nan());
+        test_serialization(objective);
+    }
+
+    #[test]
+    fn test_hamming() {
+        let p = Class::new(1);
+        let n = Class::new(0);
+        let mut heavy = Outcome::new(n, 1.0, -1.0);
+        heavy.set_weight(10.0);
+        let outcomes = vec![
+            (0.1, Outcome::new(n, 1.0, -1.0)),
+            (0.2, Outcome::new(p, 1.0, -1.0)),
+            (0.3, Outcome::new(p, 1.0, -1.0)),
+            (0.4, heavy),
+        ];
+        // weights are ignored, so the heavy point is given up for two positive ones
+        let objective = Objective::Hamming;
+        let threshold = objective.threshold(&outcomes, p);
+        assert!((threshold.value() - 0.2).abs() < f32::EPSILON);
+        let score = objective.score(threshold, &outcomes, p);
+        assert!((score.value() - 0.75).abs() < f32::EPSILON);
+        assert!((Accuracy.score(threshold, &outcomes, p).value() - 3.0 / 13.0).abs() < 1e-6);
+        test_serialization(objective);
+    }
+
+    #[test]
+    fn test_mean_error() {
+        let class = Class::default();
+        let outcomes = vec![
+            (1.0, Outcome::regression(2.0)),
+            (3.0, Outcome::regression(3.0)),
+            (-1.0, Outcome::regression(-4.0)),
+        ];
+        let threshold = MeanSquaredError.threshold(&outcomes, class);
+        let mse = MeanSquaredError.score(threshold, &outcomes, class);
+        assert!((mse.value() - 10.0 / 3.0).abs() < f32::EPSILON);
+        let mae = MeanAbsoluteError.score(threshold, &outcomes, class);
+        assert!((mae.value() - 4.0 / 3.0).abs() < f32::EPSILON);
+
+        let outcomes =
+            vec![(1.0, Outcome::regression(2.0)), (3.0, Outcome::new(class, 1.0, -1.0))];
+        assert!(MeanSquaredError.score(threshold, &outcomes, class).value().is_nan());
+        test_serialization(MeanSquaredError);
+        test_serialization(MeanAbsoluteError);
+    }
+
+    #[test]
+    fn test_weighted_reward() {
+        let p = Class::new(1);
+        let n = Class::new(0);
+        let mut rare = Outcome::new(p, 1.0, -1.0);
+        rare.set_weight(3.0);
+        let outcomes = vec![
+            (0.1, Outcome::new(n, 1.0, -1.0)),
+            (0.2, rare),
+            (0.3, Outcome::new(n, 1.0, -1.0)),
+            (0.4, Outcome::new(p, 1.0, -1.0)),
+        ];
+        let threshold = Threshold::new(0.3);
+        let plain = calculate_cost(threshold, &outcomes, p);
+        assert_eq!(calculate_weighted_cost(threshold, &outcomes, p, &[]), plain);
+        let ones = [(1.0, 1.0), (1.0, 1.0)];
+        assert_eq!(calculate_weighted_cost(threshold, &outcomes, p, &ones), plain);
+        // penalty of 0.3 (class 0) times 4, reward of 0.4 (class 1) times 2
+        let multipliers = vec![(1.0, 4.0), (2.0, 1.0)];
+        let cost = calculate_weighted_cost(threshold, &outcomes, p, &multipliers);
+        assert!((cost + 2.0).abs() < f32::EPSILON);
+        let objective = Objective::WeightedReward(multipliers.clone());
+        assert!((objective.score(threshold, &outcomes, p).value() + 2.0).abs() < f32::EPSILON);
+        assert!(objective.is_cost());
+        assert_ne!(objective, Objective::WeightedReward(vec![(1.0, 4.0)]));
+
+        let plain = cost_threshold(&outcomes, p);
+        assert_eq!(weighted_cost_threshold(&outcomes, p, &[]).value(), plain.value());
+        // costly false positives of class 0 move the threshold above them
+        let t = weighted_cost_threshold(&outcomes, p, &[(1.0, 10.0)]);
+        assert!((t.value() - 0.4).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_weighted_scores() {
+        let p = Class::new(1);
+        let n = Class::new(0);
+        let mut rare = Outcome::new(p, 1.0, -1.0);
+        rare.set_weight(3.0);
+        let outcomes = vec![
+            (0.1, Outcome::new(n, 1.0, -1.0)),
+            (0.2, rare),
+            (0.3, Outcome::new(n, 1.0, -1.0)),
+            (0.4, Outcome::new(p, 1.0, -1.0)),
+        ];
+        let threshold = Threshold::new(0.3);
+        // correct: 0.1 and 0.4, incorrect: weighted 0.2 and 0.3
+        let accuracy = calculate_accuracy(threshold, &outcomes, p);
+        assert!((accuracy - 2.0 / 6.0).abs() < f32::EPSILON);
+        let cost = calculate_cost(threshold, &outcomes, p);
+        assert!(cost.abs() < f32::EPSILON);
+        // tp = 1, fp = 1, fn = 3
+        let f1 = calculate_f1(threshold, &outcomes, p);
+        assert!((f1 - 2.0 / 6.0).abs() < f32::EPSILON);
+        // 3 of 4 positive-negative pairs ordered correctly, rare pairs count triple
+        let auc = calculate_auc(&outcomes, p);
+        assert!((auc - 5.0 / 8.0).abs() < f32::EPSILON);
+        // moving threshold to include rare outcome is now the best choice
+        let t = accuracy_threshold(&outcomes, p);
+        assert!((t.value() - 0.2).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_custom() {
+        let p = Class::new(1);
+        let n = Class::new(0);
+        // true positives are worth 3, false positives cost 1
+        let utility: CustomObjective = Arc::new(|pairs: &[(Class, Class)]| {
+            pairs
+                .iter()
+                .map(|(actual, predicted)| match (actual.value(), predicted.value()) {
+                    (1, 1) => 3.0,
+                    (0, 1) => -1.0,
+                    _ => 0.0,
+                })
+                .sum()
+        });
+        let outcomes = vec![
+            (0.1, Outcome::new(n, 1.0, -1.0)),
+            (0.2, Outcome::new(p, 1.0, -1.0)),
+            (0.3, Outcome::new(n, 1.0, -1.0)),
+            (0.4, Outcome::new(n, 1.0, -1.0)),
+            (0.5, Outcome::new(p, 1.0, -1.0)),
+        ];
+        let custom = Custom(utility.clone());
+        let value = custom.score(Threshold::new(0.3), &outcomes, p).value();
+        assert!((value - 1.0).abs() < f32::EPSILON);
+        let threshold = custom.threshold(&outcomes, p);
+        assert!((threshold.value() - 0.2).abs() < f32::EPSILON);
+        assert!((custom.score(threshold, &outcomes, p).value() - 4.0).abs() < f32::EPSILON);
+
+        assert_eq!(custom, Custom(utility));
+        assert_ne!(custom, Custom(Arc::new(|_: &[(Class, Class)]| 0.0)));
+        let mut s = Serializator::new();
+        s.add(&custom);
+        assert!(Objective::deserialize(&mut s).is_err());
+    }
+
+    #[test]
+    fn cmp_error_score() {
+        let class = Class::new(0);
+        let threshold = Threshold::new(0.0);
+        let lower = Score { objective: MeanSquaredError, class, value: 1.0, threshold };
+        let higher = Score { objective: MeanSquaredError, class, value: 2.0, threshold };
+        assert!(lower > higher);
+    }
+
+    fn naive_f1_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
+        let mut max_score = 0.0;
+        let mut threshold = Threshold::new(0.0);
+        for (g, _) in outcomes {
+            let t = Threshold::new(*g);
+            let score = calculate_f1(t, outcomes, class);
+            if score > max_score {
+                threshold = t;
+                max_score = score;
+            }
+        }
+        threshold
+    }
+
+    fn naive_balanced_accuracy_threshold(
+        outcomes: &[(f32, Outcome)],
+        class: Class,
+    ) -> Threshold {
+        let mut max_score = 0.0;
+        let mut threshold = Threshold::new(0.0);
+        for (g, _) in outcomes {
+            let t = Threshold::new(*g);
+            let score = calculate_balanced_accuracy(t, outcomes, class);
+            if score > max_score {
+                threshold = t;
+                max_score = score;
+            }
+        }
+        threshold
+    }
+
     fn naive_cost_threshold(outcomes: &[(f32, Outcome)], class: Class) -> Threshold {
         let mut max_score = 0.0;
         let mut threshold = Threshold::new(0.0);
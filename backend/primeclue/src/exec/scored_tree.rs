@@ -49,8 +49,8 @@ impl ScoredTree {
         self.tree.input_shape()
     }
 
-    pub fn score(&self) -> Score {
-        self.score
+    pub fn score(&self) -> &Score {
+        &self.score
     }
 
     pub fn set_score(&mut self, score: Score) {
@@ -61,6 +61,11 @@ impl ScoredTree {
         &self.tree
     }
 
+    #[must_use]
+    pub fn into_tree(self) -> Tree {
+        self.tree
+    }
+
     pub fn node_count(&self) -> usize {
         self.tree.node_count()
     }
@@ -73,11 +78,23 @@ impl ScoredTree {
         self.score.threshold().bool(value)
     }
 
+    /// Total order of trees used by selection: better score first, then fewer nodes (as in
+    /// [`PartialOrd`] of [`ScoredTree`]), then lower structure hash, so that trees of equal
+    /// score and size, e.g. all trees of an early generation, are still ordered the same way
+    /// in every run. Trees differing by weights only are equal. Greater is better.
+    pub(crate) fn cmp_fitness(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.tree.structure_hash().cmp(&self.tree.structure_hash()))
+    }
+
+    /// The best of `trees`: of the best score, with the fewest nodes, then of the lowest
+    /// structure hash. The first of trees differing by weights only.
     pub fn best_tree(trees: &[ScoredTree]) -> Option<&ScoredTree> {
         trees.iter().fold(None, |last, next| match last {
             None => Some(next),
             Some(last) => {
-                if next.partial_cmp(last).unwrap() == Greater {
+                if next.cmp_fitness(last) == Greater {
                     Some(next)
                 } else {
                     Some(last)
@@ -194,7 +211,7 @@ mod test {
         let long = create_long_tree();
         let class = Class::new(0);
         let s1 = Score::new(Auc, class, 1.0, Threshold::new(0.0));
-        let s2 = s1;
+        let s2 = s1.clone();
         let short_tree = ScoredTree::new(short, s1);
         let long_tree = ScoredTree::new(long, s2);
 
@@ -207,7 +224,7 @@ mod test {
         let t2 = t1.clone();
         let class = Class::new(0);
         let s1 = Score::new(Auc, class, 1.0, Threshold::new(0.0));
-        let s2 = s1;
+        let s2 = s1.clone();
         let st1 = ScoredTree::new(t1, s1);
         let st2 = ScoredTree::new(t2, s2);
         assert_eq!(st1.partial_cmp(&st2), Some(Ordering::Equal))
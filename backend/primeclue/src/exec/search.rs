@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::contrand::GET_RNG;
+use crate::data::data_set::DataView;
+use crate::error::PrimeclueErr;
+use crate::exec::class_training::{GeneticConfig, SelectionMethod};
+use crate::exec::score::Objective;
+use crate::exec::training_group::{StopCondition, TrainingGroup};
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+/// Ranges [`random_search`] samples training configurations from, uniformly
+/// * `size` - size of a training group, see [`TrainingGroup::new`]
+/// * `mutation_rate`, `crossover_rate` - see [`GeneticConfig`]
+/// * `tournament_size` - size of [`SelectionMethod::Tournament`] used by trials
+/// * `max_nodes` - choices of [`GeneticConfig::max_nodes`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSpace {
+    pub size: RangeInclusive<usize>,
+    pub mutation_rate: RangeInclusive<f64>,
+    pub crossover_rate: RangeInclusive<f64>,
+    pub tournament_size: RangeInclusive<usize>,
+    pub max_nodes: Vec<Option<usize>>,
+}
+
+impl Default for SearchSpace {
+    fn default() -> Self {
+        SearchSpace {
+            size: 10..=100,
+            mutation_rate: 0.0..=1.0,
+            crossover_rate: 0.0..=1.0,
+            tournament_size: 1..=7,
+            max_nodes: vec![None],
+        }
+    }
+}
+
+impl SearchSpace {
+    fn validate(&self) -> Result<(), PrimeclueErr> {
+        if self.size.is_empty() || *self.size.start() == 0 {
+            PrimeclueErr::result(format!("Invalid size range {:?}", self.size))
+        } else if self.mutation_rate.is_empty() {
+            PrimeclueErr::result(format!("Invalid mutation rate range {:?}", self.mutation_rate))
+        } else if self.crossover_rate.is_empty() {
+            PrimeclueErr::result(format!(
+                "Invalid crossover rate range {:?}",
+                self.crossover_rate
+            ))
+        } else if self.tournament_size.is_empty() {
+            PrimeclueErr::result(format!(
+                "Invalid tournament size range {:?}",
+                self.tournament_size
+            ))
+        } else if self.max_nodes.is_empty() {
+            PrimeclueErr::result("No max nodes choices".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn sample(&self) -> TrialConfig {
+        let mut rng = GET_RNG();
+        TrialConfig {
+            size: rng.gen_range(self.size.clone()),
+            genetic: GeneticConfig {
+                mutation_rate: rng.gen_range(self.mutation_rate.clone()),
+                crossover_rate: rng.gen_range(self.crossover_rate.clone()),
+                selection: SelectionMethod::Tournament {
+                    size: rng.gen_range(self.tournament_size.clone()),
+                },
+                max_nodes: *self.max_nodes.choose(&mut rng).unwrap(),
+                ..GeneticConfig::default()
+            },
+        }
+    }
+}
+
+/// Training configuration sampled by [`random_search`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TrialConfig {
+    pub size: usize,
+    pub genetic: GeneticConfig,
+}
+
+/// Result of training with a single configuration
+/// * `score` - [`TrainingGroup::verification_score`] after training, `None` if no
+///   trees were found for some class
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Trial {
+    pub config: TrialConfig,
+    pub score: Option<f32>,
+}
+
+/// Results of [`random_search`]: all trials in order they were run and index of the best one
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    trials: Vec<Trial>,
+    best: Option<usize>,
+}
+
+impl SearchResult {
+    #[must_use]
+    pub fn trials(&self) -> &[Trial] {
+        &self.trials
+    }
+
+    /// Trial with the best verification score: the highest, or the lowest for regression
+    /// objectives. `None` if no trial has a score.
+    #[must_use]
+    pub fn best(&self) -> Option<&Trial> {
+        self.best.map(|best| &self.trials[best])
+    }
+}
+
+/// Trains a [`TrainingGroup`] for each of `trials` configurations sampled from `space`,
+/// until any of `budget` conditions is met (see [`TrainingGroup::next_generation_until`]),
+/// and compares their verification scores.
+pub fn random_search(
+    training_data: &DataView,
+    verification_data: &DataView,
+    objective: Objective,
+    trials: usize,
+    budget: &[StopCondition],
+    space: &SearchSpace,
+) -> Result<SearchResult, PrimeclueErr> {
+    space.validate()?;
+    let mut result = SearchResult { trials: Vec::with_capacity(trials), best: None };
+    for _ in 0..trials {
+        let config = space.sample();
+        let mut training = TrainingGroup::new(
+            training_data.clone(),
+            verification_data.clone(),
+            objective.clone(),
+            config.size,
+            &[],
+        )?
+        .with_genetic_config(config.genetic)?;
+        training.next_generation_until(budget);
+        let score = training.verification_score();
+        let best_score = result.best().and_then(|best| best.score);
+        if let Some(score) = score {
+            if best_score.is_none_or(|best| training.improvement(best, score) > 0.0) {
+                result.best = Some(result.trials.len());
+            }
+        }
+        result.trials.push(Trial { config, score });
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::test::create_simple_data;
+    use crate::exec::class_training::SelectionMethod;
+    use crate::exec::score::Objective::Auc;
+    use crate::exec::search::{random_search, SearchSpace};
+    use crate::exec::training_group::StopCondition::FixedGenerations;
+
+    #[test]
+    fn search() {
+        let (training_data, verification_data, _) = create_simple_data(300).into_3_views_split();
+        let space = SearchSpace {
+            size: 2..=5,
+            tournament_size: 1..=2,
+            max_nodes: vec![None, Some(20)],
+            ..SearchSpace::default()
+        };
+        let result = random_search(
+            &training_data,
+            &verification_data,
+            Auc,
+            4,
+            &[FixedGenerations(3)],
+            &space,
+        )
+        .unwrap();
+        assert_eq!(result.trials().len(), 4);
+        for trial in result.trials() {
+            assert!(space.size.contains(&trial.config.size));
+            match trial.config.genetic.selection {
+                SelectionMethod::Tournament { size } => {
+                    assert!(space.tournament_size.contains(&size))
+                }
+                other => panic!("Unexpected selection {:?}", other),
+            }
+            assert!(space.max_nodes.contains(&trial.config.genetic.max_nodes));
+        }
+        let best = result.trials().iter().filter_map(|t| t.score).fold(f32::MIN, f32::max);
+        assert_eq!(result.best().and_then(|b| b.score), Some(best));
+
+        let invalid = SearchSpace { size: 0..=3, ..SearchSpace::default() };
+        let result = random_search(
+            &training_data,
+            &verification_data,
+            Auc,
+            1,
+            &[FixedGenerations(1)],
+            &invalid,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -18,16 +18,46 @@
 */
 
 use crate::data::data_set::DataView;
+use crate::data::disk_data_view::DiskDataView;
 use crate::data::outcome::Class;
+use crate::data::Point;
 use crate::error::PrimeclueErr;
-use crate::exec::class_training::ClassTraining;
+use crate::exec::class_training::{ClassTraining, GeneticConfig};
 use crate::exec::classifier::Classifier;
+use crate::exec::functions::NodeKind;
+use crate::exec::node::{Node, Weighted};
+use crate::exec::pareto::fronts;
 use crate::exec::score::{Objective, Score};
 use crate::exec::scored_tree::ScoredTree;
+use crate::exec::tree::Tree;
+use crate::serialization::{Deserializable, Serializable, Serializator};
+use rand::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
 use rayon::{ThreadPool, ThreadPoolBuilder};
 use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{Debug, Error, Formatter};
+use std::fs;
+#[cfg(feature = "serde")]
+use std::io::Write;
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+pub type ProgressCallback = Box<dyn Fn(&Stats) + Send>;
+pub type NewBestCallback = Box<dyn Fn(&Classifier, &Stats) + Send>;
+
+const STATE_MAGIC: &str = "PrimeclueTrainingState";
+const STATE_FORMAT_VERSION: usize = 22;
+/// Approximate number of bytes taken by a single tree node
+const NODE_BYTES: usize = size_of::<Weighted>() + size_of::<Node>();
+/// Number of threads used by [`TrainingGroup`] unless set with [`TrainingGroup::with_threads`]
+pub const DEFAULT_THREADS: usize = 64;
+/// Improvement of verification score needed to replace [`TrainingGroup::best_classifier`]
+/// unless set with [`TrainingGroup::with_min_improvement`]
+pub const DEFAULT_MIN_IMPROVEMENT: f32 = 1e-6;
 
-#[derive(Debug)]
 pub struct TrainingGroup {
     generation: u32,
     training_data: DataView,
@@ -35,6 +65,57 @@ pub struct TrainingGroup {
     classes: Vec<ClassTraining>,
     objective: Objective,
     thread_pool: ThreadPool,
+    progress_callback: Option<ProgressCallback>,
+    new_best_callback: Option<NewBestCallback>,
+    class_weights: HashMap<Class, f32>,
+    verification_weights: Option<HashMap<Class, f32>>,
+    history: Vec<Stats>,
+    best: Option<(f32, Classifier)>,
+    best_generation: Option<usize>,
+    min_improvement: f32,
+    max_population_memory: Option<usize>,
+    verification_interval: usize,
+    fitness_subsample: Option<FitnessSubsample>,
+    model_selection: ModelSelection,
+    autosave: Option<Autosave>,
+    #[cfg(feature = "serde")]
+    jsonl_logger: Option<JsonlLogger>,
+    generation_duration: Duration,
+    total_evaluations: u64,
+}
+
+/// Settings of [`TrainingGroup::set_autosave`]
+struct Autosave {
+    path: PathBuf,
+    every: usize,
+    error: Option<PrimeclueErr>,
+}
+
+/// Settings of [`TrainingGroup::set_jsonl_logger`]
+#[cfg(feature = "serde")]
+struct JsonlLogger {
+    writer: Box<dyn Write + Send>,
+    error: Option<PrimeclueErr>,
+}
+
+/// Settings of [`TrainingGroup::with_fitness_subsample`]
+struct FitnessSubsample {
+    fraction: f64,
+    stratified: bool,
+    rng: XorShiftRng,
+}
+
+impl Debug for TrainingGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_struct("TrainingGroup")
+            .field("generation", &self.generation)
+            .field("training_data", &self.training_data)
+            .field("verification_data", &self.verification_data)
+            .field("classes", &self.classes)
+            .field("objective", &self.objective)
+            .field("thread_pool", &self.thread_pool)
+            .finish_non_exhaustive()
+    }
 }
 
 impl TrainingGroup {
@@ -43,10 +124,18 @@ impl TrainingGroup {
     ///
     /// # Arguments
     /// * `training_data` - [`DataView`] that represents training data view
-    /// * `verification_data` - [`DataView`] that represents verification data view
+    /// * `verification_data` - [`DataView`] that represents verification data view. Trees are
+    ///   never trained on it. Every generation best trees of groups are scored on it to choose
+    ///   which groups survive and which tree of every class makes
+    ///   [`TrainingGroup::classifier`], see [`TrainingGroup::with_model_selection`]. It also
+    ///   gives [`TrainingGroup::verification_score`], used by
+    ///   [`TrainingGroup::best_classifier`] and [`StopCondition::NoImprovement`].
     /// * `objective` - [`Objective`] that represents the measure to optimize for
     /// * `size` - size of a training group. Determines amount of RAM needed
     /// * `forbidden_cols` - indexes of data columns that should not be used as input
+    ///
+    /// Regression objectives (see [`Objective::is_regression`]) require every outcome to have
+    /// a target value and all of them to belong to a single class.
     pub fn new(
         training_data: DataView,
         verification_data: DataView,
@@ -54,46 +143,933 @@ impl TrainingGroup {
         size: usize,
         forbidden_cols: &[usize],
     ) -> Result<Self, PrimeclueErr> {
-        validate(&training_data, &verification_data)?;
+        TrainingGroup::create(
+            training_data,
+            verification_data,
+            objective,
+            size,
+            forbidden_cols,
+            None,
+        )
+    }
+
+    /// Same as [`TrainingGroup::new`] but training is reproducible: for the same `seed` and
+    /// data, calling [`next_generation`] the same number of times always produces
+    /// the same [`Classifier`].
+    pub fn with_seed(
+        training_data: DataView,
+        verification_data: DataView,
+        objective: Objective,
+        size: usize,
+        forbidden_cols: &[usize],
+        seed: u64,
+    ) -> Result<Self, PrimeclueErr> {
+        TrainingGroup::create(
+            training_data,
+            verification_data,
+            objective,
+            size,
+            forbidden_cols,
+            Some(seed),
+        )
+    }
+
+    /// Starts [`TrainingGroupBuilder`], an alternative to [`TrainingGroup::new`] and the
+    /// `with_` methods
+    #[must_use]
+    pub fn builder() -> TrainingGroupBuilder {
+        TrainingGroupBuilder::default()
+    }
+
+    fn create(
+        training_data: DataView,
+        verification_data: DataView,
+        objective: Objective,
+        size: usize,
+        forbidden_cols: &[usize],
+        seed: Option<u64>,
+    ) -> Result<Self, PrimeclueErr> {
+        validate(&training_data, &verification_data, &objective)?;
+        let mut rng = seed.map(XorShiftRng::seed_from_u64);
         let classes = (0..training_data.class_count())
             .map(|class| {
-                ClassTraining::new(
-                    size,
-                    forbidden_cols.to_vec(),
-                    objective,
-                    Class::new(class as u16),
-                )
+                let class = Class::new(class as u16);
+                let forbidden_cols = forbidden_cols.to_vec();
+                match rng.as_mut() {
+                    Some(rng) => ClassTraining::with_seed(
+                        size,
+                        forbidden_cols,
+                        objective.clone(),
+                        class,
+                        rng.next_u64(),
+                    ),
+                    None => ClassTraining::new(size, forbidden_cols, objective.clone(), class),
+                }
             })
             .collect();
-        let num_threads = 64;
-        let thread_pool = ThreadPoolBuilder::new()
-            .num_threads(num_threads)
-            .build()
-            .map_err(|e| format!("Unable to build thread pool: {:?}", e))?;
         Ok(TrainingGroup {
             objective,
             generation: 0,
             training_data,
             verification_data,
             classes,
-            thread_pool,
+            thread_pool: build_thread_pool(DEFAULT_THREADS)?,
+            progress_callback: None,
+            new_best_callback: None,
+            class_weights: HashMap::new(),
+            verification_weights: None,
+            history: Vec::new(),
+            best: None,
+            best_generation: None,
+            min_improvement: DEFAULT_MIN_IMPROVEMENT,
+            max_population_memory: None,
+            verification_interval: 1,
+            fitness_subsample: None,
+            model_selection: ModelSelection::default(),
+            autosave: None,
+            #[cfg(feature = "serde")]
+            jsonl_logger: None,
+            generation_duration: Duration::default(),
+            total_evaluations: 0,
+        })
+    }
+
+    /// Saves the whole evolved population, generation counter, class weights (including
+    /// verification ones, see [`TrainingGroup::set_verification_weights`]), memory limit (see
+    /// [`TrainingGroup::with_max_population_memory`]) and the best classifier so far with its
+    /// generation and [`Stats::total_evaluations`], so that training can be resumed with
+    /// [`TrainingGroup::load_state`]. Data views, progress and new best callbacks are not
+    /// saved. Training with a seed is reseeded with a seed drawn from its random number
+    /// generator, which is saved, so the resumed training continues exactly like this one
+    /// does after saving.
+    pub fn save_state(&mut self, path: &Path) -> Result<(), PrimeclueErr> {
+        let custom = |objective: &Objective| matches!(objective, Objective::Custom(_));
+        let pareto_custom = match &self.objective {
+            Objective::Pareto(objectives) => objectives.iter().any(custom),
+            _ => false,
+        };
+        if custom(&self.objective) || pareto_custom {
+            return PrimeclueErr::result("Custom objective cannot be saved".to_string());
+        }
+        let mut ser = Serializator::new();
+        ser.add_str(STATE_MAGIC);
+        ser.add(&STATE_FORMAT_VERSION);
+        ser.add_items(&[
+            &(self.generation as usize),
+            &self.objective,
+            &self.class_weights,
+            &self.verification_weights,
+            &self.history,
+            &self.best,
+            &self.best_generation,
+            &self.total_evaluations,
+            &self.max_population_memory,
+            &self.classes.len(),
+        ]);
+        self.classes.iter_mut().for_each(|class| class.save_state(&mut ser));
+        fs::write(path, ser.to_bytes())
+            .map_err(|e| PrimeclueErr::from(format!("Unable to write {:?}: {}", path, e)))
+    }
+
+    /// Resumes training saved with [`TrainingGroup::save_state`] with given data views.
+    /// Fitness subsample (see [`TrainingGroup::with_fitness_subsample`]), model selection
+    /// (see [`TrainingGroup::with_model_selection`]) and minimum improvement (see
+    /// [`TrainingGroup::with_min_improvement`]) are not saved.
+    /// Views must match each other the same way as in [`TrainingGroup::new`] and have the
+    /// same classes and input shape as the saved trees.
+    pub fn load_state(
+        path: &Path,
+        mut training_data: DataView,
+        mut verification_data: DataView,
+    ) -> Result<Self, PrimeclueErr> {
+        let mut ser = Serializator::load(path)?;
+        let magic = String::deserialize(&mut ser)?;
+        if magic != STATE_MAGIC {
+            return PrimeclueErr::result(format!(
+                "{:?} is not a Primeclue training state",
+                path
+            ));
+        }
+        let version = usize::deserialize(&mut ser)?;
+        if version != STATE_FORMAT_VERSION {
+            return PrimeclueErr::result(format!(
+                "Unsupported training state version {} in {:?}, expected {}",
+                version, path, STATE_FORMAT_VERSION
+            ));
+        }
+        let generation = usize::deserialize(&mut ser)? as u32;
+        let objective = Objective::deserialize(&mut ser)?;
+        let class_weights: HashMap<Class, f32> = HashMap::deserialize(&mut ser)?;
+        let verification_weights: Option<HashMap<Class, f32>> = Option::deserialize(&mut ser)?;
+        let history = Vec::deserialize(&mut ser)?;
+        let best = Option::deserialize(&mut ser)?;
+        let best_generation = Option::deserialize(&mut ser)?;
+        let total_evaluations = u64::deserialize(&mut ser)?;
+        let max_population_memory = Option::deserialize(&mut ser)?;
+        let len = usize::deserialize(&mut ser)?;
+        let classes = (0..len)
+            .map(|_| ClassTraining::load_state(&mut ser))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PrimeclueErr::from(format!("Unable to load training state: {}", e)))?;
+        validate(&training_data, &verification_data, &objective)?;
+        if classes.len() != training_data.class_count() {
+            return PrimeclueErr::result(format!(
+                "Saved training has {} classes, data has {}",
+                classes.len(),
+                training_data.class_count()
+            ));
+        }
+        for class in &classes {
+            match class.input_shape() {
+                Some(shape) if shape != training_data.input_shape() => {
+                    return PrimeclueErr::result(format!(
+                        "Saved trees have input shape {}, data has {}",
+                        shape,
+                        training_data.input_shape()
+                    ))
+                }
+                _ => {}
+            }
+        }
+        training_data.apply_class_weights(&class_weights);
+        verification_data
+            .apply_class_weights(verification_weights.as_ref().unwrap_or(&class_weights));
+        Ok(TrainingGroup {
+            generation,
+            training_data,
+            verification_data,
+            classes,
+            objective,
+            thread_pool: build_thread_pool(DEFAULT_THREADS)?,
+            progress_callback: None,
+            new_best_callback: None,
+            class_weights,
+            verification_weights,
+            history,
+            best,
+            best_generation,
+            min_improvement: DEFAULT_MIN_IMPROVEMENT,
+            max_population_memory,
+            verification_interval: 1,
+            fitness_subsample: None,
+            model_selection: ModelSelection::default(),
+            autosave: None,
+            #[cfg(feature = "serde")]
+            jsonl_logger: None,
+            generation_duration: Duration::default(),
+            total_evaluations,
         })
     }
 
+    /// Scales every data point's contribution to the score of any [`Objective`] by the weight
+    /// of its class, e.g. to make misclassification of a rare class more costly. Classes not
+    /// present in `weights` keep weight 1.0. Should be called before the first generation.
+    /// Verification data is weighted the same way unless weights were set for it with
+    /// [`TrainingGroup::set_verification_weights`].
+    pub fn with_class_weights(
+        mut self,
+        weights: HashMap<Class, f32>,
+    ) -> Result<Self, PrimeclueErr> {
+        validate_class_weights(&weights)?;
+        self.training_data.apply_class_weights(&weights);
+        if self.verification_weights.is_none() {
+            self.verification_data.apply_class_weights(&weights);
+        }
+        self.class_weights = weights;
+        Ok(self)
+    }
+
+    /// Same as [`TrainingGroup::with_class_weights`], but for verification data only,
+    /// independently of training data, e.g. to select trees for a distribution of classes
+    /// expected after deployment rather than the one of historical training data. Applies to
+    /// every verification score, so to [`TrainingGroup::best_classifier`], stop conditions and
+    /// selection of trees (see [`ModelSelection::ByVerification`]) as well. Should be called
+    /// before the first generation.
+    pub fn set_verification_weights(
+        &mut self,
+        weights: HashMap<Class, f32>,
+    ) -> Result<(), PrimeclueErr> {
+        validate_class_weights(&weights)?;
+        self.verification_data.apply_class_weights(&weights);
+        self.verification_weights = Some(weights);
+        Ok(())
+    }
+
+    /// Replaces training data, e.g. with a bigger view including newly labeled points, while
+    /// keeping evolved trees. `training_data` must be compatible with verification data the
+    /// same way as in [`TrainingGroup::new`]. Class weights, if set, are applied to it.
+    ///
+    /// Trees kept so far are scored again on the new data in the next generation and best
+    /// trees are selected anew. Until then [`TrainingGroup::classifier`] is made of the best
+    /// trees so far scored on the new data.
+    pub fn replace_training_data(
+        &mut self,
+        mut training_data: DataView,
+    ) -> Result<(), PrimeclueErr> {
+        validate(&training_data, &self.verification_data, &self.objective)?;
+        training_data.apply_class_weights(&self.class_weights);
+        self.training_data = training_data;
+        self.reset_scores();
+        Ok(())
+    }
+
+    /// Appends `point` to training data, for learning from points labeled one at a time, with
+    /// [`TrainingGroup::step`] in between. Its input must have the shape of training data and
+    /// its class must be one of its classes, see [`DataView::add_point`]. Its weight is
+    /// multiplied by the class weight, if there is one.
+    ///
+    /// Trees kept so far are scored again in the next generation, the same way as after
+    /// [`TrainingGroup::replace_training_data`].
+    pub fn observe(&mut self, point: Point) -> Result<(), PrimeclueErr> {
+        let (input, outcome) = point.data();
+        let point = match self.class_weights.get(&outcome.class()) {
+            Some(weight) => {
+                let mut outcome = *outcome;
+                outcome.set_class_weight(*weight);
+                Point::new(input.clone(), outcome)
+            }
+            None => point,
+        };
+        self.training_data.add_point(point)?;
+        self.reset_scores();
+        Ok(())
+    }
+
+    /// Makes every tree scored again in the next generation, see [`ClassTraining::reset_scores`]
+    fn reset_scores(&mut self) {
+        let selection_data = match self.model_selection {
+            ModelSelection::ByVerification if self.verified() => &self.verification_data,
+            _ => &self.training_data,
+        };
+        let training_data = &self.training_data;
+        self.classes
+            .iter_mut()
+            .for_each(|class| class.reset_scores(training_data, selection_data));
+    }
+
+    /// Performs training for a single generation lasting no longer than about `budget`, see
+    /// [`TrainingGroup::next_generation_deadline`], so that learning can be interleaved with
+    /// arrival of new points given to [`TrainingGroup::observe`]. Returns false if the
+    /// generation was cut short.
+    pub fn step(&mut self, budget: Duration) -> bool {
+        self.next_generation_deadline(Instant::now() + budget)
+    }
+
+    /// Trains on points of `data` too large to keep in memory: reads them in consecutive
+    /// chunks of `chunk_size` points (see [`DiskDataView::chunks`]), makes each chunk training
+    /// data with [`TrainingGroup::replace_training_data`] and performs `generations`
+    /// generations on it, so only one chunk is in memory at a time. Trees are scored on the
+    /// current chunk only, so chunks should be large enough to represent all classes.
+    /// Verification data stays in memory. The last chunk is left as training data.
+    pub fn train_on_chunks(
+        &mut self,
+        data: &DiskDataView,
+        chunk_size: usize,
+        generations: usize,
+    ) -> Result<(), PrimeclueErr> {
+        if data.is_empty() {
+            return Err(PrimeclueErr::EmptyDataSet("Data training set"));
+        }
+        for chunk in data.chunks(chunk_size) {
+            self.replace_training_data(chunk?)?;
+            for _ in 0..generations {
+                self.next_generation();
+            }
+        }
+        Ok(())
+    }
+
+    /// Size given to [`TrainingGroup::new`] or [`TrainingGroup::resize_population`]: number of
+    /// groups of trees of every class kept after a generation, each of them keeping up to as
+    /// many trees. Number of trees actually kept is given by [`Stats::population_size`].
+    #[must_use]
+    pub fn population_size(&self) -> usize {
+        self.classes.first().map_or(0, ClassTraining::size)
+    }
+
+    /// Changes population size between generations, e.g. to start small for speed and grow
+    /// later for refinement. Growing adds random groups of trees, shrinking keeps the groups
+    /// and trees with the best training scores. The best classifier so far is kept either way.
+    pub fn resize_population(&mut self, new_size: usize) -> Result<(), PrimeclueErr> {
+        if new_size == 0 {
+            return PrimeclueErr::result("Population size must be positive".to_string());
+        }
+        let input_shape = self.training_data.input_shape();
+        self.classes.iter_mut().for_each(|class| class.resize(new_size, input_shape));
+        Ok(())
+    }
+
+    /// Scores trees on a random subsample of `fraction` of training data drawn anew every
+    /// generation (see [`DataView::subsample`]) instead of on all of it, which makes
+    /// generations on big data faster at the cost of noisier scores. Best trees are still
+    /// selected on full verification data. Subsamples are given by `seed`, so seeded training
+    /// stays reproducible. [`Stats::training_score`] is the score on the last subsample.
+    pub fn with_fitness_subsample(
+        mut self,
+        fraction: f64,
+        seed: u64,
+        stratified: bool,
+    ) -> Result<Self, PrimeclueErr> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return PrimeclueErr::result(format!(
+                "Invalid subsample fraction {}, must be in range (0, 1]",
+                fraction
+            ));
+        }
+        let rng = XorShiftRng::seed_from_u64(seed);
+        self.fitness_subsample = Some(FitnessSubsample { fraction, stratified, rng });
+        Ok(self)
+    }
+
+    /// Sets data that groups are selected on and best trees of classes are chosen by,
+    /// [`ModelSelection::ByVerification`] by default. [`TrainingGroup::verification_score`]
+    /// and so [`TrainingGroup::best_classifier`] and stop conditions use verification data
+    /// either way. With [`TrainingGroup::set_verification_interval`] above 1, only verified
+    /// generations select by verification data.
+    pub fn with_model_selection(mut self, model_selection: ModelSelection) -> Self {
+        self.model_selection = model_selection;
+        self
+    }
+
+    /// Sets how much better verification score of the current best trees has to be than of
+    /// [`TrainingGroup::best_classifier`] to replace it, so that noise-level fluctuations
+    /// don't replace it with a nearly identical classifier. [`DEFAULT_MIN_IMPROVEMENT`] is
+    /// used otherwise, 0 replaces it on any improvement.
+    pub fn with_min_improvement(mut self, min_improvement: f32) -> Result<Self, PrimeclueErr> {
+        if !min_improvement.is_finite() || min_improvement < 0.0 {
+            return PrimeclueErr::result(format!(
+                "Invalid minimum improvement {}, must be non-negative",
+                min_improvement
+            ));
+        }
+        self.min_improvement = min_improvement;
+        Ok(self)
+    }
+
+    /// Sets parameters of genetic operators used by [`next_generation`] for all classes.
+    /// [`GeneticConfig::default`] is used otherwise.
+    pub fn with_genetic_config(mut self, config: GeneticConfig) -> Result<Self, PrimeclueErr> {
+        self.set_config(config)?;
+        Ok(self)
+    }
+
+    /// Same as [`TrainingGroup::with_genetic_config`], but can be called between generations,
+    /// e.g. to lower mutation rate once training has explored enough. The next generation is
+    /// the first to use `config`, trees kept so far are not changed. Generations done so far
+    /// still count for `mutation_schedule` of `config`. An invalid `config` is rejected and
+    /// the current one is kept.
+    pub fn set_config(&mut self, config: GeneticConfig) -> Result<(), PrimeclueErr> {
+        config.validate()?;
+        self.classes.iter_mut().for_each(|class| class.set_genetic_config(config));
+        Ok(())
+    }
+
+    /// Forbids function nodes of given `kinds`, e.g. [`NodeKind::Div`] for numerical
+    /// stability, so that none of them appears in any tree. Kinds of [`NodeKind::OPT_IN`]
+    /// stay forbidden too. Should be called before the first generation. At least one kind
+    /// must stay allowed.
+    pub fn with_forbidden_node_kinds(self, kinds: &[NodeKind]) -> Result<Self, PrimeclueErr> {
+        let mut forbidden = kinds.to_vec();
+        forbidden.extend(NodeKind::OPT_IN.iter().filter(|kind| !kinds.contains(kind)));
+        self.set_forbidden_node_kinds(forbidden)
+    }
+
+    /// Same as [`TrainingGroup::with_forbidden_node_kinds`] with all kinds except `kinds`,
+    /// which may include kinds of [`NodeKind::OPT_IN`], e.g. [`NodeKind::IfThenElse`] for
+    /// models made of decision rules
+    pub fn with_allowed_node_kinds(self, kinds: &[NodeKind]) -> Result<Self, PrimeclueErr> {
+        let forbidden = NodeKind::ALL
+            .iter()
+            .copied()
+            .filter(|kind| !kinds.contains(kind))
+            .collect::<Vec<_>>();
+        self.set_forbidden_node_kinds(forbidden)
+    }
+
+    fn set_forbidden_node_kinds(mut self, kinds: Vec<NodeKind>) -> Result<Self, PrimeclueErr> {
+        if NodeKind::ALL.iter().all(|kind| kinds.contains(kind)) {
+            return PrimeclueErr::result("At least one node kind must be allowed".to_string());
+        }
+        self.classes.iter_mut().for_each(|class| class.set_forbidden_node_kinds(kinds.clone()));
+        Ok(self)
+    }
+
+    /// Sets the number of threads evolving classes and their groups in parallel,
+    /// [`DEFAULT_THREADS`] by default. Seeded training gives the same results regardless of it.
+    pub fn with_threads(mut self, threads: usize) -> Result<Self, PrimeclueErr> {
+        if threads == 0 {
+            return PrimeclueErr::result("Number of threads must be positive".to_string());
+        }
+        self.thread_pool = build_thread_pool(threads)?;
+        Ok(self)
+    }
+
+    /// Limits memory estimated by [`TrainingGroup::estimated_memory_bytes`] to `bytes`.
+    /// What data doesn't take is shared equally by trees of all classes, recalculated before
+    /// every generation in case data changed. When a class exceeds its share after
+    /// a generation, its groups with the largest trees are removed. Fails if data alone
+    /// leaves no room for a single node per class.
+    pub fn with_max_population_memory(mut self, bytes: usize) -> Result<Self, PrimeclueErr> {
+        if self.node_limit(bytes) == 0 {
+            return PrimeclueErr::result(format!(
+                "Memory limit of {} bytes is too low, data takes {} bytes",
+                bytes,
+                self.data_bytes()
+            ));
+        }
+        self.max_population_memory = Some(bytes);
+        self.apply_memory_limit();
+        Ok(self)
+    }
+
+    /// Number of nodes each class can have within `bytes` left after data
+    fn node_limit(&self, bytes: usize) -> usize {
+        bytes.saturating_sub(self.data_bytes()) / NODE_BYTES / self.classes.len()
+    }
+
+    fn apply_memory_limit(&mut self) {
+        if let Some(bytes) = self.max_population_memory {
+            let node_limit = self.node_limit(bytes);
+            self.classes.iter_mut().for_each(|class| class.set_node_limit(node_limit));
+        }
+    }
+
+    /// Approximate number of bytes taken by trees of all classes and by cells of training and
+    /// verification data that trees are executed on
+    #[must_use]
+    pub fn estimated_memory_bytes(&self) -> usize {
+        let nodes = self.classes.iter().map(ClassTraining::total_node_count).sum::<usize>();
+        nodes * NODE_BYTES + self.data_bytes()
+    }
+
+    /// Bytes taken by cells of training and verification data
+    fn data_bytes(&self) -> usize {
+        let data_bytes = |data: &DataView| {
+            let shape = data.input_shape();
+            shape.rows() * shape.columns() * data.outcomes().len() * size_of::<f32>()
+        };
+        data_bytes(&self.training_data) + data_bytes(&self.verification_data)
+    }
+
+    /// Sets a callback invoked after every generation with current [`Stats`], once they are
+    /// available, i.e. when every class has its best tree. No internal state is borrowed
+    /// mutably while it runs, so it can e.g. send [`Stats`] through a channel.
+    pub fn set_progress_callback(&mut self, callback: impl Fn(&Stats) + Send + 'static) {
+        self.progress_callback = Some(Box::new(callback));
+    }
+
+    /// Sets a callback invoked after a generation that replaced
+    /// [`TrainingGroup::best_classifier`], with the new best classifier and [`Stats`] of that
+    /// generation, e.g. to print [`Classifier::to_expression_string`] whenever it improves.
+    /// Generations without a better verification score don't invoke it.
+    pub fn set_new_best_callback(
+        &mut self,
+        callback: impl Fn(&Classifier, &Stats) + Send + 'static,
+    ) {
+        self.new_best_callback = Some(Box::new(callback));
+    }
+
+    /// Scores trees on verification data only every `interval` generations, i.e. in
+    /// generations whose number is a multiple of `interval`, instead of every generation.
+    /// This changes model selection: no ranking on verification data is kept between
+    /// verified generations, so generations in between select groups and best trees on
+    /// training data, as with [`ModelSelection::ByTraining`]. Groups overfitting training
+    /// data may survive them and [`TrainingGroup::classifier`] taken after them is chosen
+    /// by training scores. They don't update [`TrainingGroup::best_classifier`] and have no
+    /// verification score in [`Stats`], see [`Stats::verified`].
+    /// [`StopCondition::NoImprovement`] counts patience in generations that were verified.
+    /// Not saved with [`TrainingGroup::save_state`].
+    pub fn set_verification_interval(&mut self, interval: usize) -> Result<(), PrimeclueErr> {
+        if interval == 0 {
+            return PrimeclueErr::result("Verification interval must be at least 1".to_string());
+        }
+        self.verification_interval = interval;
+        Ok(())
+    }
+
+    /// Whether the last generation scored trees on verification data, see
+    /// [`TrainingGroup::set_verification_interval`]
+    fn verified(&self) -> bool {
+        (self.generation as usize).is_multiple_of(self.verification_interval)
+    }
+
+    /// Saves [`TrainingGroup::best_classifier`] to `path` with [`Classifier::save`] after
+    /// every `every` generations, so a crashed training leaves the best model so far. The file
+    /// is written next to `path` first and then renamed, so `path` never holds a partially
+    /// written classifier. Nothing is saved until there is the best classifier. Failed saves
+    /// don't stop training, see [`TrainingGroup::autosave_error`]. Not saved with
+    /// [`TrainingGroup::save_state`].
+    pub fn set_autosave(&mut self, path: &Path, every: usize) -> Result<(), PrimeclueErr> {
+        if every == 0 {
+            return PrimeclueErr::result("Autosave interval must be at least 1".to_string());
+        }
+        self.autosave = Some(Autosave { path: path.to_path_buf(), every, error: None });
+        Ok(())
+    }
+
+    /// Error of the latest save set with [`TrainingGroup::set_autosave`], `None` if it
+    /// succeeded or there was none yet
+    #[must_use]
+    pub fn autosave_error(&self) -> Option<&PrimeclueErr> {
+        self.autosave.as_ref().and_then(|autosave| autosave.error.as_ref())
+    }
+
+    /// Writes [`Stats`] of every generation that has them (see
+    /// [`TrainingGroup::set_progress_callback`]) to `writer` as a single line JSON object with
+    /// fields named as in [`Stats`], in JSON Lines format. Writing stops at the first error,
+    /// see [`TrainingGroup::jsonl_logger_error`]. Not saved with [`TrainingGroup::save_state`].
+    #[cfg(feature = "serde")]
+    pub fn set_jsonl_logger(&mut self, writer: impl Write + Send + 'static) {
+        self.jsonl_logger = Some(JsonlLogger { writer: Box::new(writer), error: None });
+    }
+
+    /// Error that stopped the logger set with [`TrainingGroup::set_jsonl_logger`]
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub fn jsonl_logger_error(&self) -> Option<&PrimeclueErr> {
+        self.jsonl_logger.as_ref().and_then(|logger| logger.error.as_ref())
+    }
+
+    #[cfg(feature = "serde")]
+    fn log_jsonl(&mut self, stats: &Stats) {
+        let logger = match &mut self.jsonl_logger {
+            Some(logger) if logger.error.is_none() => logger,
+            _ => return,
+        };
+        logger.error = serde_json::to_writer(&mut logger.writer, stats)
+            .map_err(|e| PrimeclueErr::from(format!("Unable to log stats: {}", e)))
+            .and_then(|_| Ok(writeln!(logger.writer)?))
+            .err();
+    }
+
+    fn autosave(&mut self) {
+        let (autosave, best) = match (&mut self.autosave, &self.best) {
+            (Some(autosave), Some((_, best))) => (autosave, best),
+            _ => return,
+        };
+        if !(self.generation as usize).is_multiple_of(autosave.every) {
+            return;
+        }
+        let mut temp = autosave.path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        autosave.error = best
+            .save(&temp)
+            .and_then(|_| {
+                fs::rename(&temp, &autosave.path).map_err(|e| {
+                    PrimeclueErr::from(format!(
+                        "Unable to rename {:?} to {:?}: {}",
+                        temp, autosave.path, e
+                    ))
+                })
+            })
+            .err();
+    }
+
     /// Performs training for one generation
     pub fn next_generation(&mut self) {
+        self.run_generation(None);
+    }
+
+    /// Same as [`TrainingGroup::next_generation`], but stops breeding and scoring trees once
+    /// `deadline` passes, leaving the rest for the next generation. Trees scored so far take
+    /// part in selection, so [`TrainingGroup::classifier`], [`TrainingGroup::best_classifier`]
+    /// and [`Stats`] reflect them. Returns false if the generation was cut short.
+    pub fn next_generation_deadline(&mut self, deadline: Instant) -> bool {
+        self.run_generation(Some(deadline))
+    }
+
+    fn run_generation(&mut self, deadline: Option<Instant>) -> bool {
+        let start = Instant::now();
         self.generation += 1;
-        let training_data = &self.training_data;
-        let verification_data = &self.verification_data;
+        self.apply_memory_limit();
+        let subsample = self.fitness_subsample.as_mut().map(|subsample| {
+            let seed = subsample.rng.next_u64();
+            self.training_data.subsample(subsample.fraction, seed, subsample.stratified)
+        });
+        let training_data = subsample.as_ref().unwrap_or(&self.training_data);
+        let points = training_data.len() as u64;
+        let selection_data = match self.model_selection {
+            ModelSelection::ByVerification if self.verified() => &self.verification_data,
+            _ => &self.training_data,
+        };
         let mut classes = std::mem::take(&mut self.classes);
+        let complete = AtomicBool::new(true);
         self.thread_pool.scope(|s| {
             for class in &mut classes {
+                let complete = &complete;
                 s.spawn(move |_| {
-                    class.next_generation(training_data, verification_data);
+                    if !class.next_generation_deadline(training_data, selection_data, deadline) {
+                        complete.store(false, Ordering::Relaxed);
+                    }
                 })
             }
         });
         self.classes = classes;
+        self.generation_duration = start.elapsed();
+        let trees = self.classes.iter().map(ClassTraining::evaluations).sum::<usize>();
+        self.total_evaluations += trees as u64 * points;
+        if let Some(stats) = self.stats() {
+            self.history.push(stats);
+            let improved = self.update_best(stats.verification_score);
+            if let Some(callback) = &self.progress_callback {
+                callback(&stats);
+            }
+            if let (true, Some(callback), Some((_, best))) =
+                (improved, &self.new_best_callback, &self.best)
+            {
+                callback(best, &stats);
+            }
+            #[cfg(feature = "serde")]
+            self.log_jsonl(&stats);
+        }
+        self.autosave();
+        complete.into_inner()
+    }
+
+    /// Keeps classifier of the current best trees if their verification `score` is better
+    /// than of any classifier kept before by more than [`TrainingGroup::with_min_improvement`].
+    /// Returns whether it was kept.
+    fn update_best(&mut self, score: Option<f32>) -> bool {
+        let score = match score {
+            Some(score) if score.is_finite() => score,
+            _ => return false,
+        };
+        let improved = |best| self.improvement(best, score) > self.min_improvement;
+        if self.best.as_ref().is_none_or(|(best, _)| improved(*best)) {
+            if let Ok(classifier) = self.classifier() {
+                self.best = Some((score, classifier));
+                self.best_generation = Some(self.generation as usize);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Classifier with the best verification score seen after any generation so far: the
+    /// highest score, or the lowest one for regression objectives (see
+    /// [`TrainingGroup::verification_score`]). Unlike [`TrainingGroup::classifier`] it is not
+    /// affected by later generations with worse scores, nor with scores better by no more than
+    /// [`TrainingGroup::with_min_improvement`]. Of classifiers with equal scores the earliest
+    /// one is kept. `None` until every class has its best tree.
+    #[must_use]
+    pub fn best_classifier(&self) -> Option<Classifier> {
+        self.best.as_ref().map(|(_, classifier)| classifier.clone())
+    }
+
+    /// Number of the generation after which [`TrainingGroup::best_classifier`] was found, as
+    /// in [`Stats::generation`]. `None` until there is a best classifier.
+    #[must_use]
+    pub fn best_classifier_generation(&self) -> Option<usize> {
+        self.best_generation
+    }
+
+    /// [`Stats`] of every generation so far that had them available, i.e. since every class
+    /// got its best tree, in order
+    #[must_use]
+    pub fn history(&self) -> &[Stats] {
+        &self.history
+    }
+
+    /// Performs training until any of `conditions` is met and tells which kind of condition
+    /// it was. Conditions are checked before every generation, so with no conditions
+    /// no training is done.
+    pub fn next_generation_until(&mut self, conditions: &[StopCondition]) -> StopReason {
+        let start = Instant::now();
+        let mut generations = 0;
+        // best verification score and generations since it was reached, per condition
+        let mut progress = vec![(None, 0); conditions.len()];
+        loop {
+            for (condition, (_, since_best)) in conditions.iter().zip(&progress) {
+                match condition {
+                    StopCondition::FixedGenerations(count) if generations >= *count => {
+                        return StopReason::LimitReached
+                    }
+                    StopCondition::TimeLimit(limit) if start.elapsed() >= *limit => {
+                        return StopReason::LimitReached
+                    }
+                    StopCondition::MaxEvaluations(max) if self.total_evaluations >= *max => {
+                        return StopReason::LimitReached
+                    }
+                    StopCondition::NoImprovement { patience, .. } if since_best >= patience => {
+                        return StopReason::Converged
+                    }
+                    StopCondition::OverfitGap(max)
+                        if self.verified()
+                            && self.overfit_gap().is_some_and(|gap| gap > *max) =>
+                    {
+                        return StopReason::Overfitting
+                    }
+                    _ => {}
+                }
+            }
+            if conditions.is_empty() {
+                return StopReason::LimitReached;
+            }
+            self.next_generation();
+            generations += 1;
+            if !self.verified() {
+                continue;
+            }
+            let score = self.verification_score();
+            for (condition, (best, since_best)) in conditions.iter().zip(progress.iter_mut()) {
+                if let StopCondition::NoImprovement { min_delta, .. } = condition {
+                    match (score, *best) {
+                        (Some(score), Some(b)) if self.improvement(b, score) > *min_delta => {
+                            *best = Some(score);
+                            *since_best = 0;
+                        }
+                        (Some(score), None) => *best = Some(score),
+                        (_, Some(_)) => *since_best += 1,
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Same as [`TrainingGroup::next_generation_until`], but returns everything a batch user
+    /// usually needs once training stops, see [`TrainResult`]. Fails if training stopped
+    /// before every class got its best tree, so that there is no classifier.
+    pub fn train_until(
+        &mut self,
+        conditions: &[StopCondition],
+    ) -> Result<TrainResult, PrimeclueErr> {
+        let reason = self.next_generation_until(conditions);
+        let classifier = match self.best_classifier() {
+            Some(classifier) => classifier,
+            None => self.classifier()?,
+        };
+        let stats = self.stats().ok_or_else(|| {
+            PrimeclueErr::from(
+                "Training stopped before every class got its best tree".to_string(),
+            )
+        })?;
+        Ok(TrainResult { classifier, stats, reason, history: self.history.clone() })
+    }
+
+    /// Performs training until [`Stats::training_score`] reaches `target`, i.e. is at least
+    /// `target`, or at most `target` for regression objectives, for comparing how fast
+    /// configurations get to the same quality. Returns number of generations it took, 0 if
+    /// `target` was already reached, or `None` if it wasn't within `max_generations`.
+    pub fn generations_to_score(
+        &mut self,
+        target: f32,
+        max_generations: usize,
+    ) -> Option<usize> {
+        let mut generations = 0;
+        loop {
+            let score = self.stats().map(|stats| stats.training_score);
+            if score.is_some_and(|score| self.improvement(target, score) >= 0.0) {
+                return Some(generations);
+            }
+            if generations == max_generations {
+                return None;
+            }
+            self.next_generation();
+            generations += 1;
+        }
+    }
+
+    /// Adds trees of `classifier`, e.g. trained before on similar data, to the population so
+    /// that evolution starts from them. Tree of every class grows a new group of its mutated
+    /// copies, next to randomly generated ones. `classifier` must have a tree for every class
+    /// of training data and trees must have the input shape of training data.
+    pub fn seed_with(&mut self, classifier: &Classifier) -> Result<(), PrimeclueErr> {
+        if classifier.input_shape() != self.training_data.input_shape() {
+            return Err(PrimeclueErr::ShapeMismatch {
+                expected: *self.training_data.input_shape(),
+                actual: *classifier.input_shape(),
+            });
+        }
+        let trees = self
+            .classes
+            .iter()
+            .map(|class| {
+                let tree =
+                    classifier.trees().iter().find(|t| t.score().class() == *class.class());
+                match tree {
+                    Some(tree) => Ok(vec![tree.tree().clone()]),
+                    None => PrimeclueErr::result(format!("No tree for class {}", class.class())),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.immigrate(trees);
+        Ok(())
+    }
+
+    /// Best trees of up to `count` groups of every class, in order of classes
+    pub(crate) fn emigrants(&self, count: usize) -> Vec<Vec<Tree>> {
+        self.classes.iter().map(|class| class.emigrants(count)).collect()
+    }
+
+    /// Adds trees given by [`TrainingGroup::emigrants`] of another group with the same classes
+    pub(crate) fn immigrate(&mut self, trees: Vec<Vec<Tree>>) {
+        for (class, trees) in self.classes.iter_mut().zip(trees) {
+            class.immigrate(trees);
+        }
+    }
+
+    /// How much `current` score is better than `previous` one
+    pub(crate) fn improvement(&self, previous: f32, current: f32) -> f32 {
+        if self.objective.is_regression() {
+            previous - current
+        } else {
+            current - previous
+        }
+    }
+
+    /// Score of the current best trees on verification data, combined across classes
+    /// the same way as [`Stats::training_score`].
+    pub fn verification_score(&self) -> Option<f32> {
+        let mut score = 0.0;
+        for class in &self.classes {
+            score += class.best_tree()?.execute_for_score(&self.verification_data)?.value();
+        }
+        if !self.objective.is_cost() {
+            score /= self.classes.len() as f32
+        }
+        Some(score)
+    }
+
+    /// How much training score of the current best trees is better than their
+    /// [`TrainingGroup::verification_score`]: training minus verification score, or the other
+    /// way round for regression objectives, so a growing positive gap means overfitting.
+    /// Same as [`Stats::overfit_gap`].
+    #[must_use]
+    pub fn overfit_gap(&self) -> Option<f32> {
+        self.stats()?.overfit_gap
+    }
+
+    /// Tells if the best verification score of the last `window` generations of
+    /// [`TrainingGroup::history`] is better than the best one of all generations before them
+    /// by no more than `min_delta`, e.g. to stop training on data without signal instead of
+    /// waiting for a time limit. Generations that weren't verified are left out of both.
+    /// `false` until there is a verification score before the window.
+    #[must_use]
+    pub fn is_stagnant(&self, window: usize, min_delta: f32) -> bool {
+        let split = match self.history.len().checked_sub(window) {
+            Some(split) => split,
+            None => return false,
+        };
+        let best = |stats: &[Stats]| {
+            stats.iter().filter_map(|s| s.verification_score).fold(
+                None,
+                |best, score| match best {
+                    Some(best) if self.improvement(best, score) <= 0.0 => Some(best),
+                    _ => Some(score),
+                },
+            )
+        };
+        match (best(&self.history[..split]), best(&self.history[split..])) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(before), Some(recent)) => self.improvement(before, recent) <= min_delta,
+        }
+    }
+
+    pub(crate) fn training_data(&self) -> &DataView {
+        &self.training_data
     }
 
     pub fn generation(&self) -> u32 {
@@ -103,15 +1079,51 @@ impl TrainingGroup {
     pub fn stats(&self) -> Option<Stats> {
         let mut node_count = 0;
         let mut training_score = 0.0;
+        let mut average_training_score = 0.0;
+        let mut population_trees = 0;
+        let mut population_nodes = 0;
+        let mut max_node_count = 0;
+        let mut structures = 0;
+        let mut evaluations = 0;
         for class in &self.classes {
             let best_tree = class.best_tree()?;
             node_count += best_tree.node_count();
             training_score += class.training_score()?;
+            average_training_score += class.average_training_score()?;
+            let (trees, nodes) = class.population_size();
+            population_trees += trees;
+            population_nodes += nodes;
+            max_node_count = max_node_count.max(class.max_node_count());
+            structures += class.structure_count();
+            evaluations += class.evaluations();
         }
-        if self.objective != Objective::Cost {
-            training_score /= self.classes.len() as f32
+        let average_node_count = population_nodes as f32 / population_trees.max(1) as f32;
+        if !self.objective.is_cost() {
+            training_score /= self.classes.len() as f32;
+            average_training_score /= self.classes.len() as f32;
         }
-        Some(Stats { generation: self.generation, node_count, training_score })
+        let verified = self.verified();
+        let verification_score = if verified { self.verification_score() } else { None };
+        Some(Stats {
+            generation: self.generation,
+            node_count,
+            training_score,
+            average_training_score,
+            verification_score,
+            overfit_gap: verification_score.map(|score| self.improvement(score, training_score)),
+            average_node_count,
+            max_node_count,
+            diversity: structures as f32 / population_trees.max(1) as f32,
+            generation_duration: self.generation_duration,
+            evaluations_per_second: if self.generation_duration.is_zero() {
+                0.0
+            } else {
+                evaluations as f32 / self.generation_duration.as_secs_f32()
+            },
+            population_size: population_trees,
+            verified,
+            total_evaluations: self.total_evaluations,
+        })
     }
 
     pub fn get_tree(&self, class: &Class) -> Option<&ScoredTree> {
@@ -119,6 +1131,76 @@ impl TrainingGroup {
         class_training.best_tree()
     }
 
+    /// Classifiers trading off objectives of [`Objective::Pareto`] and node count on
+    /// verification data: none of them is as good as another in all of these and better in
+    /// some. Candidates take trees at the same position of every class's front of trees,
+    /// ordered by node count. Classifiers are ordered by ascending node count. Empty for
+    /// other objectives or if some class has no trees yet. Custom objectives can't be
+    /// measured for a classifier, so they don't differentiate candidates.
+    #[must_use]
+    pub fn pareto_front(&self) -> Vec<Classifier> {
+        let objectives = match &self.objective {
+            Objective::Pareto(objectives) => objectives,
+            _ => return vec![],
+        };
+        let class_fronts = self
+            .classes
+            .iter()
+            .map(|class| class.pareto_front(&self.verification_data))
+            .collect::<Vec<_>>();
+        if class_fronts.iter().any(Vec::is_empty) {
+            return vec![];
+        }
+        let count = class_fronts.iter().map(Vec::len).max().unwrap_or(0);
+        let candidates = (0..count)
+            .filter_map(|i| {
+                let trees = class_fronts.iter().map(|f| f[i.min(f.len() - 1)].clone()).collect();
+                self.new_classifier(trees).ok()
+            })
+            .collect::<Vec<_>>();
+        let values = candidates
+            .iter()
+            .map(|classifier| {
+                let mut values = match classifier.score(&self.verification_data) {
+                    Ok(score) => objectives.iter().map(|o| score.value(o)).collect(),
+                    Err(_) => vec![f32::NAN; objectives.len()],
+                };
+                values.push(-(classifier.node_count() as f32));
+                values
+            })
+            .collect::<Vec<_>>();
+        let first = fronts(&values).into_iter().next().unwrap_or_default();
+        let mut front = first.into_iter().map(|i| candidates[i].clone()).collect::<Vec<_>>();
+        front.sort_by_key(Classifier::node_count);
+        front
+    }
+
+    /// Up to `k` candidate classifiers made of trees of the current population, e.g. to
+    /// compare them on held-out data. The n-th candidate takes the n-th best tree of every
+    /// class by verification score, or the last one if the class has fewer trees. Trees of
+    /// the same structure as a better tree of their class are left out, so candidates differ
+    /// by more than weights. Trees no longer in population are not used, so the first
+    /// candidate may differ from [`TrainingGroup::classifier`]. Empty if some class has no
+    /// trees yet.
+    #[must_use]
+    pub fn top_classifiers(&self, k: usize) -> Vec<Classifier> {
+        let class_trees = self
+            .classes
+            .iter()
+            .map(|class| class.top_trees(&self.verification_data, k))
+            .collect::<Vec<_>>();
+        if class_trees.iter().any(Vec::is_empty) {
+            return vec![];
+        }
+        let count = class_trees.iter().map(Vec::len).max().unwrap_or(0);
+        (0..count)
+            .filter_map(|i| {
+                let trees = class_trees.iter().map(|t| t[i.min(t.len() - 1)].clone()).collect();
+                self.new_classifier(trees).ok()
+            })
+            .collect()
+    }
+
     /// Get [`Classifier`] after training. [`Classifier`] can later be used for
     /// classification on unseen data.
     pub fn classifier(&self) -> Result<Classifier, PrimeclueErr> {
@@ -133,54 +1215,426 @@ impl TrainingGroup {
                 ));
             }
         }
-        let classes = self.training_data.class_map().clone();
-        Classifier::new(classes, trees).map_err(|e| {
+        self.new_classifier(trees).map_err(|e| {
             PrimeclueErr::from(format!("Unable to create a classifier: {}", e.to_string()))
         })
     }
+
+    /// Classifier of `trees` with classes and feature names of training data
+    fn new_classifier(&self, trees: Vec<ScoredTree>) -> Result<Classifier, PrimeclueErr> {
+        let mut classifier = Classifier::new(self.training_data.class_map().clone(), trees)?;
+        classifier.set_feature_names(self.training_data.feature_names().to_vec())?;
+        Ok(classifier)
+    }
+}
+
+fn build_thread_pool(num_threads: usize) -> Result<ThreadPool, PrimeclueErr> {
+    let thread_pool = ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map_err(|e| format!("Unable to build thread pool: {:?}", e))?;
+    Ok(thread_pool)
+}
+
+fn validate_class_weights(weights: &HashMap<Class, f32>) -> Result<(), PrimeclueErr> {
+    for (class, weight) in weights {
+        if !weight.is_finite() || *weight <= 0.0 {
+            return PrimeclueErr::result(format!(
+                "Invalid weight {} for class {}, must be positive",
+                weight, class
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate(
+    training_data: &DataView,
+    verification_data: &DataView,
+    objective: &Objective,
+) -> Result<(), PrimeclueErr> {
+    if training_data.is_empty() {
+        return Err(PrimeclueErr::EmptyDataSet("Data training set"));
+    } else if verification_data.is_empty() {
+        return Err(PrimeclueErr::EmptyDataSet("Data verification set"));
+    }
+    if let Objective::Pareto(objectives) = objective {
+        if objectives.is_empty() {
+            return PrimeclueErr::result(
+                "Pareto objective needs at least one objective".to_string(),
+            );
+        }
+        if let Some(invalid) =
+            objectives.iter().find(|o| o.is_regression() || matches!(o, Objective::Pareto(_)))
+        {
+            return PrimeclueErr::result(format!("{} can't be a Pareto objective", invalid));
+        }
+    }
+    let objectives = match objective {
+        Objective::Pareto(objectives) => objectives.as_slice(),
+        _ => std::slice::from_ref(objective),
+    };
+    for objective in objectives {
+        if let Objective::WeightedReward(multipliers) = objective {
+            validate_reward_multipliers(multipliers, training_data)?;
+        }
+    }
+    if objective.is_regression() {
+        validate_regression(training_data)?;
+        validate_regression(verification_data)?;
+    } else if training_data.class_count() < 2 {
+        return PrimeclueErr::result(format!(
+            "Training data has points of {} class, at least 2 classes are required",
+            training_data.class_count()
+        ));
+    }
+    if verification_data.class_count() != training_data.class_count() {
+        Err(PrimeclueErr::ClassCountMismatch {
+            expected: training_data.class_count(),
+            actual: verification_data.class_count(),
+        })
+    } else if verification_data.input_shape() != training_data.input_shape() {
+        Err(PrimeclueErr::ShapeMismatch {
+            expected: *training_data.input_shape(),
+            actual: *verification_data.input_shape(),
+        })
+    } else {
+        Ok(())
+    }
 }
 
-fn validate(training_data: &DataView, verification_data: &DataView) -> Result<(), PrimeclueErr> {
-    if training_data.cells().is_empty() {
-        PrimeclueErr::result("Data training set is empty".to_string())
-    } else if verification_data.cells().is_empty() {
-        PrimeclueErr::result("Data verification set is empty".to_string())
-    } else if verification_data.class_count() != training_data.class_count() {
+fn validate_reward_multipliers(
+    multipliers: &[(f32, f32)],
+    data: &DataView,
+) -> Result<(), PrimeclueErr> {
+    let valid = |m: f32| m.is_finite() && m >= 0.0;
+    if multipliers.len() > data.class_map().len() {
         PrimeclueErr::result(format!(
-            "Training and verification data differ in class count: {} vs {}",
-            training_data.class_count(),
-            verification_data.class_count()
+            "Got reward multipliers of {} classes, data has {}",
+            multipliers.len(),
+            data.class_map().len()
         ))
-    } else if verification_data.input_shape() != training_data.input_shape() {
+    } else if let Some(invalid) = multipliers.iter().find(|(r, p)| !valid(*r) || !valid(*p)) {
         PrimeclueErr::result(format!(
-            "Training and verification data differ in data size: {:?} vs {:?}",
-            training_data.input_shape(),
-            verification_data.input_shape()
+            "Reward multipliers must be finite and not negative, got {:?}",
+            invalid
         ))
     } else {
         Ok(())
     }
 }
 
-#[derive(Serialize, Debug, Copy, Clone)]
-pub struct Stats {
-    pub generation: u32,
-    pub training_score: f32,
-    pub node_count: usize,
+fn validate_regression(data: &DataView) -> Result<(), PrimeclueErr> {
+    if data.class_count() != 1 {
+        PrimeclueErr::result(format!(
+            "Regression requires a single class, got {}",
+            data.class_count()
+        ))
+    } else if data.outcomes().iter().any(|o| o.target().is_none()) {
+        PrimeclueErr::result("Regression requires target value for every outcome".to_string())
+    } else {
+        Ok(())
+    }
 }
 
-#[derive(Serialize, Debug)]
-pub struct ClassScore {
-    class: String,
+/// Builder of [`TrainingGroup`] with optional settings chained, see
+/// [`TrainingGroup::builder`]. Objective and population size must be set, every other
+/// setting left out is the same as in [`TrainingGroup::new`].
+#[derive(Debug, Clone, Default)]
+pub struct TrainingGroupBuilder {
+    objective: Option<Objective>,
+    size: Option<usize>,
+    forbidden_cols: Vec<usize>,
+    seed: Option<u64>,
+    config: Option<GeneticConfig>,
+    class_weights: Option<HashMap<Class, f32>>,
+    forbidden_nodes: Option<Vec<NodeKind>>,
+}
+
+impl TrainingGroupBuilder {
+    /// Same as `objective` of [`TrainingGroup::new`]
+    #[must_use]
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = Some(objective);
+        self
+    }
+
+    /// Same as `size` of [`TrainingGroup::new`]
+    #[must_use]
+    pub fn population_size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Same as `forbidden_cols` of [`TrainingGroup::new`]
+    #[must_use]
+    pub fn forbidden_columns(mut self, columns: &[usize]) -> Self {
+        self.forbidden_cols = columns.to_vec();
+        self
+    }
+
+    /// Makes training reproducible, see [`TrainingGroup::with_seed`]
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// See [`TrainingGroup::with_genetic_config`]
+    #[must_use]
+    pub fn genetic_config(mut self, config: GeneticConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// See [`TrainingGroup::with_class_weights`]
+    #[must_use]
+    pub fn class_weights(mut self, weights: HashMap<Class, f32>) -> Self {
+        self.class_weights = Some(weights);
+        self
+    }
+
+    /// See [`TrainingGroup::with_forbidden_node_kinds`]
+    #[must_use]
+    pub fn forbidden_nodes(mut self, kinds: &[NodeKind]) -> Self {
+        self.forbidden_nodes = Some(kinds.to_vec());
+        self
+    }
+
+    /// Creates [`TrainingGroup`] on given data views. Fails if objective or population size
+    /// was not set or for the same reasons as [`TrainingGroup::new`] and the `with_` methods
+    /// of settings that were set.
+    pub fn build(
+        self,
+        training_data: DataView,
+        verification_data: DataView,
+    ) -> Result<TrainingGroup, PrimeclueErr> {
+        let objective = self
+            .objective
+            .ok_or_else(|| PrimeclueErr::from("Objective is not set".to_string()))?;
+        let size = self
+            .size
+            .ok_or_else(|| PrimeclueErr::from("Population size is not set".to_string()))?;
+        let mut group = TrainingGroup::create(
+            training_data,
+            verification_data,
+            objective,
+            size,
+            &self.forbidden_cols,
+            self.seed,
+        )?;
+        if let Some(weights) = self.class_weights {
+            group = group.with_class_weights(weights)?;
+        }
+        if let Some(config) = self.config {
+            group = group.with_genetic_config(config)?;
+        }
+        if let Some(kinds) = self.forbidden_nodes {
+            group = group.with_forbidden_node_kinds(&kinds)?;
+        }
+        Ok(group)
+    }
+}
+
+/// A condition to end training in [`TrainingGroup::next_generation_until`]
+/// * `FixedGenerations` - stop after given number of generations
+/// * `NoImprovement` - stop when verification score did not improve by more than `min_delta`
+///   for `patience` generations
+/// * `TimeLimit` - stop after given time has passed
+/// * `OverfitGap` - stop when [`TrainingGroup::overfit_gap`] exceeds given value
+/// * `MaxEvaluations` - stop when [`Stats::total_evaluations`] reaches given number, i.e. a
+///   budget of work that, unlike time limit, doesn't depend on the machine
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StopCondition {
+    FixedGenerations(u32),
+    NoImprovement { patience: u32, min_delta: f32 },
+    TimeLimit(Duration),
+    OverfitGap(f32),
+    MaxEvaluations(u64),
+}
+
+/// Outcome of [`TrainingGroup::train_until`]
+#[derive(Debug, Clone)]
+pub struct TrainResult {
+    /// Same as [`TrainingGroup::best_classifier`], or [`TrainingGroup::classifier`] if no
+    /// generation was verified
+    pub classifier: Classifier,
+    /// [`Stats`] of the last generation
+    pub stats: Stats,
+    pub reason: StopReason,
+    /// Same as [`TrainingGroup::history`]
+    pub history: Vec<Stats>,
+}
+
+/// Tells why [`TrainingGroup::next_generation_until`] stopped
+/// * `Converged` - verification score stopped improving
+/// * `LimitReached` - generation, time or evaluation limit was reached
+/// * `Overfitting` - training score got too far ahead of verification score
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    Converged,
+    LimitReached,
+    Overfitting,
+}
+
+/// Data that [`TrainingGroup`] selects trees on, see [`TrainingGroup::with_model_selection`]
+/// * `ByVerification` - groups survive and best trees are chosen by scores on verification
+///   data, so that trees overfitting training data are left out
+/// * `ByTraining` - scores on full training data are used for it as well, verification data
+///   is only measured on
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ModelSelection {
+    #[default]
+    ByVerification,
+    ByTraining,
+}
+
+#[derive(Serialize, Debug, Copy, Clone)]
+pub struct Stats {
+    pub generation: u32,
+    /// Training score of the best trees
+    pub training_score: f32,
+    /// Training score of all trees kept in population, averaged per class
+    pub average_training_score: f32,
+    /// Same as [`TrainingGroup::verification_score`]
+    pub verification_score: Option<f32>,
+    /// Same as [`TrainingGroup::overfit_gap`]
+    pub overfit_gap: Option<f32>,
+    pub node_count: usize,
+    /// Average number of nodes of all trees kept in population, not only the best ones
+    pub average_node_count: f32,
+    /// Number of nodes of the largest tree kept in population
+    pub max_node_count: usize,
+    /// Number of distinct structures of trees kept in population per tree, from 0 to 1.
+    /// Trees differing by weights only have the same structure. See
+    /// [`DiversityMode`](crate::exec::class_training::DiversityMode)
+    pub diversity: f32,
+    /// Wall time of the last generation, including selection and scoring on verification data
+    pub generation_duration: Duration,
+    /// Number of trees executed on training data during the last generation per second of
+    /// [`Stats::generation_duration`]
+    pub evaluations_per_second: f32,
+    /// Number of trees kept in population, of all classes
+    pub population_size: usize,
+    /// Whether the generation scored trees on verification data, see
+    /// [`TrainingGroup::set_verification_interval`]. Verification score and overfit gap are
+    /// `None` otherwise
+    pub verified: bool,
+    /// Number of executions of a tree on a single point of training data, or of the fitness
+    /// subsample of it, during all generations so far, see [`StopCondition::MaxEvaluations`]
+    pub total_evaluations: u64,
+}
+
+impl Serializable for Stats {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_items(&[
+            &(self.generation as usize),
+            &self.training_score,
+            &self.average_training_score,
+            &self.verification_score,
+            &self.overfit_gap,
+            &self.node_count,
+            &self.average_node_count,
+            &self.max_node_count,
+            &self.diversity,
+            &self.generation_duration.as_secs_f64(),
+            &self.evaluations_per_second,
+            &self.population_size,
+            &self.verified,
+            &self.total_evaluations,
+        ]);
+    }
+}
+
+impl Deserializable for Stats {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        Ok(Stats {
+            generation: usize::deserialize(s)? as u32,
+            training_score: f32::deserialize(s)?,
+            average_training_score: f32::deserialize(s)?,
+            verification_score: Option::deserialize(s)?,
+            overfit_gap: Option::deserialize(s)?,
+            node_count: usize::deserialize(s)?,
+            average_node_count: f32::deserialize(s)?,
+            max_node_count: usize::deserialize(s)?,
+            diversity: f32::deserialize(s)?,
+            generation_duration: Duration::try_from_secs_f64(f64::deserialize(s)?)
+                .map_err(|e| e.to_string())?,
+            evaluations_per_second: f32::deserialize(s)?,
+            population_size: usize::deserialize(s)?,
+            verified: bool::deserialize(s)?,
+            total_evaluations: u64::deserialize(s)?,
+        })
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ClassScore {
+    class: String,
     score: Score,
 }
 
 #[cfg(test)]
 mod test {
+    use crate::contrand::seed_thread_rng;
     use crate::data::data_set::test::create_simple_data;
+    use crate::data::data_set::{DataSet, DataView};
+    use crate::data::disk_data_view::DiskDataView;
     use crate::data::outcome::Class;
-    use crate::exec::score::Objective::Auc;
-    use crate::exec::training_group::TrainingGroup;
+    use crate::data::{Input, InputShape, Outcome, Point};
+    use crate::error::PrimeclueErr;
+    use crate::exec::class_training::{
+        ClassTraining, CrossoverMethod, DiversityMode, GeneticConfig, InitMethod,
+        MutationSchedule, ReplacementMode, SelectionMethod,
+    };
+    use crate::exec::classifier::Classifier;
+    use crate::exec::functions::{NodeKind, SafeDivMode, Terminal, TWO_ARG_FUNCTIONS};
+    use crate::exec::node::{Node, Weighted};
+    use crate::exec::score::Objective::{Accuracy, Auc, MeanSquaredError, F1};
+    use crate::exec::score::{Objective, Score, Threshold};
+    use crate::exec::scored_tree::ScoredTree;
+    use crate::exec::training_group::{
+        ModelSelection, Stats, StopCondition, StopReason, TrainingGroup, TrainingGroupBuilder,
+        DEFAULT_MIN_IMPROVEMENT, DEFAULT_THREADS, NODE_BYTES,
+    };
+    use crate::exec::tree::test::create_short_tree;
+    use crate::exec::tree::Tree;
+    use crate::serialization::{Deserializable, Serializator};
+    use std::collections::HashMap;
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use std::{env, fs, process};
+
+    /// The same 100 simple points on every call.
+    fn simple_views() -> (DataView, DataView) {
+        let _seed = seed_thread_rng(3);
+        create_simple_data(100).into_2_views_split()
+    }
+
+    /// Trains the same seeded group of 20 on [`simple_views`] with `config`.
+    fn train_group(config: GeneticConfig, generations: usize) -> TrainingGroup {
+        let (training_data, verification_data) = simple_views();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 20, &[], 3)
+                .unwrap()
+                .with_genetic_config(config)
+                .unwrap();
+        for _ in 0..generations {
+            group.next_generation();
+        }
+        group
+    }
+
+    fn train_history(config: GeneticConfig, generations: usize) -> Vec<(f32, f32, usize)> {
+        population(&train_group(config, generations))
+    }
+
+    fn population(group: &TrainingGroup) -> Vec<(f32, f32, usize)> {
+        let stats =
+            |s: &Stats| (s.average_training_score, s.average_node_count, s.max_node_count);
+        group.history().iter().map(stats).collect()
+    }
 
     #[test]
     fn test_generation() {
@@ -208,4 +1662,1506 @@ mod test {
         }
         panic!("get_tree(class) failed")
     }
+
+    #[test]
+    fn test_f1_training() {
+        let (training_data, verification_data) =
+            create_simple_data(500).shuffle().into_2_views_split();
+        let mut training_group =
+            TrainingGroup::new(training_data, verification_data, F1, 10, &[]).unwrap();
+        for _ in 0..1_000 {
+            training_group.next_generation();
+            if let Some(stats) = training_group.stats() {
+                assert!(stats.training_score > 0.0 && stats.training_score <= 1.0);
+                return;
+            }
+        }
+        panic!("F1 training failed")
+    }
+
+    #[test]
+    fn test_class_weights() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let group =
+            TrainingGroup::new(training_data, verification_data, Accuracy, 3, &[]).unwrap();
+        let mut invalid = HashMap::new();
+        invalid.insert(Class::new(1), -1.0);
+        assert!(group.with_class_weights(invalid).is_err());
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let group =
+            TrainingGroup::new(training_data, verification_data, Accuracy, 3, &[]).unwrap();
+        let mut weights = HashMap::new();
+        weights.insert(Class::new(1), 10.0);
+        let mut group = group.with_class_weights(weights).unwrap();
+        for data in &[&group.training_data, &group.verification_data] {
+            for outcome in data.outcomes() {
+                let expected = if outcome.class() == Class::new(1) { 10.0 } else { 1.0 };
+                assert!((outcome.weight() - expected).abs() < f32::EPSILON);
+            }
+        }
+        group.next_generation();
+        assert_eq!(group.generation(), 1);
+    }
+
+    #[test]
+    fn test_verification_weights() {
+        let weight = |data: &DataView, class| {
+            data.outcomes().iter().find(|o| o.class() == Class::new(class)).unwrap().weight()
+        };
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::new(training_data, verification_data, Accuracy, 3, &[]).unwrap();
+        assert!(group.set_verification_weights(HashMap::from([(Class::new(0), 0.0)])).is_err());
+        group.set_verification_weights(HashMap::from([(Class::new(0), 3.0)])).unwrap();
+        let mut group =
+            group.with_class_weights(HashMap::from([(Class::new(1), 10.0)])).unwrap();
+        assert_eq!(weight(&group.training_data, 0), 1.0);
+        assert_eq!(weight(&group.training_data, 1), 10.0);
+        assert_eq!(weight(&group.verification_data, 0), 3.0);
+        assert_eq!(weight(&group.verification_data, 1), 1.0);
+        for _ in 0..3 {
+            group.next_generation();
+        }
+        let path = env::temp_dir().join(format!("primeclue_weights_{}.ssd", process::id()));
+        group.save_state(&path).unwrap();
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let loaded = TrainingGroup::load_state(&path, training_data, verification_data).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(weight(&loaded.training_data, 1), 10.0);
+        assert_eq!(weight(&loaded.verification_data, 0), 3.0);
+        assert_eq!(weight(&loaded.verification_data, 1), 1.0);
+    }
+
+    #[test]
+    fn test_point_weights_kept_with_class_weights() {
+        let path = env::temp_dir().join(format!("primeclue_weighted_{}.csv", process::id()));
+        let content = (0..60)
+            .map(|i| format!("{},{},{}\n", i, (i % 4) as f32 * 0.5, i % 2 == 0))
+            .collect::<String>();
+        fs::write(&path, content).unwrap();
+        let views = || DataSet::from_csv(&path, 2, Some(1), false).unwrap().into_2_views_split();
+        let weights = |data: &DataView| data.outcomes().iter().map(Outcome::weight).collect();
+        let (training_data, verification_data) = views();
+        let point_weights: Vec<f32> = weights(&training_data);
+        let class = training_data.outcomes()[0].class();
+        let expected = training_data
+            .outcomes()
+            .iter()
+            .map(|o| if o.class() == class { o.weight() * 4.0 } else { o.weight() })
+            .collect::<Vec<_>>();
+        assert!(point_weights.contains(&0.0) && point_weights.iter().any(|w| *w > 1.0));
+        let group =
+            TrainingGroup::with_seed(training_data, verification_data, Accuracy, 3, &[], 5)
+                .unwrap();
+        let mut group = group.with_class_weights(HashMap::from([(class, 4.0)])).unwrap();
+        assert_eq!(weights(&group.training_data), expected);
+        group.next_generation();
+
+        let state = env::temp_dir().join(format!("primeclue_weighted_{}.ssd", process::id()));
+        group.save_state(&state).unwrap();
+        let (training_data, verification_data) = views();
+        let loaded =
+            TrainingGroup::load_state(&state, training_data, verification_data).unwrap();
+        fs::remove_file(&state).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(weights(&loaded.training_data), expected);
+    }
+
+    #[test]
+    fn test_genetic_config() {
+        let (training_data, verification_data) = simple_views();
+        let mut implicit =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 20, &[], 3).unwrap();
+        for _ in 0..5 {
+            implicit.next_generation();
+        }
+        let defaults = train_history(GeneticConfig::default(), 5);
+        assert_eq!(population(&implicit), defaults);
+        let config = GeneticConfig {
+            mutation_rate: 0.1,
+            mutation_schedule: MutationSchedule::LinearDecay {
+                from: 0.9,
+                to: 0.1,
+                generations: 5,
+            },
+            crossover_rate: 0.9,
+            selection: SelectionMethod::Tournament { size: 4 },
+            max_nodes: None,
+            diversity: DiversityMode::None,
+            elitism: 0,
+            constant_range: (-300.0, 300.0),
+            terminals: &[],
+            safe_div: SafeDivMode::ReturnZero,
+            replacement: ReplacementMode::Generational,
+            init_method: InitMethod::Full,
+            crossover: CrossoverMethod::Uniform,
+        };
+        assert_ne!(defaults, train_history(config, 5));
+
+        let (training_data, verification_data) = simple_views();
+        let group =
+            TrainingGroup::new(training_data.clone(), verification_data.clone(), Auc, 5, &[])
+                .unwrap();
+        let invalid = GeneticConfig { mutation_rate: 1.5, ..GeneticConfig::default() };
+        assert!(group.with_genetic_config(invalid).is_err());
+        let group = TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        let invalid = GeneticConfig { constant_range: (1.0, 1.0), ..GeneticConfig::default() };
+        assert!(group.with_genetic_config(invalid).is_err());
+    }
+
+    #[test]
+    fn test_replacement_mode() {
+        let train = |replacement| {
+            let config = GeneticConfig { replacement, ..GeneticConfig::default() };
+            let group = train_group(config, 5);
+            assert_eq!(group.stats().unwrap().generation, 5);
+            let mut s = Serializator::new();
+            s.add(&group.classifier().unwrap());
+            (s.to_bytes(), group.classes[0].evaluations())
+        };
+        let steady_state = ReplacementMode::SteadyState { count: 2 };
+        let (generational, generational_evaluations) = train(ReplacementMode::Generational);
+        let (steady, steady_evaluations) = train(steady_state);
+        assert_eq!(generational, train(ReplacementMode::Generational).0);
+        assert_eq!(steady, train(steady_state).0);
+        assert!(steady_evaluations < generational_evaluations);
+
+        let (training_data, verification_data) = simple_views();
+        let group = TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        let replacement = ReplacementMode::SteadyState { count: 0 };
+        let invalid = GeneticConfig { replacement, ..GeneticConfig::default() };
+        assert!(group.with_genetic_config(invalid).is_err());
+    }
+
+    #[test]
+    fn test_init_method() {
+        let train = |init_method| {
+            let config = GeneticConfig { init_method, ..GeneticConfig::default() };
+            let mut s = Serializator::new();
+            s.add(&train_group(config, 1).classifier().unwrap());
+            s.to_bytes()
+        };
+        let full = train(InitMethod::Full);
+        assert_eq!(full, train(GeneticConfig::default().init_method));
+        let ramped = train(InitMethod::RampedHalfAndHalf);
+        assert_eq!(ramped, train(InitMethod::RampedHalfAndHalf));
+        assert_ne!(ramped, full);
+        assert_ne!(train(InitMethod::Grow), full);
+    }
+
+    #[test]
+    fn test_builder() {
+        let data = create_simple_data(100);
+        let config = GeneticConfig { mutation_rate: 0.1, ..GeneticConfig::default() };
+        let weights = [(Class::new(1), 2.0)].into_iter().collect::<HashMap<_, _>>();
+        let (training_data, verification_data) = data.clone().into_2_views_split();
+        let mut built = TrainingGroup::builder()
+            .objective(Accuracy)
+            .population_size(5)
+            .forbidden_columns(&[1])
+            .seed(4)
+            .genetic_config(config)
+            .class_weights(weights.clone())
+            .forbidden_nodes(&[NodeKind::Div])
+            .build(training_data, verification_data)
+            .unwrap();
+        let (training_data, verification_data) = data.clone().into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Accuracy, 5, &[1], 4)
+                .unwrap()
+                .with_class_weights(weights)
+                .unwrap()
+                .with_genetic_config(config)
+                .unwrap()
+                .with_forbidden_node_kinds(&[NodeKind::Div])
+                .unwrap();
+        for _ in 0..4 {
+            built.next_generation();
+            group.next_generation();
+        }
+        assert_eq!(population(&built), population(&group));
+        assert_eq!(built.classifier().ok(), group.classifier().ok());
+
+        let views = || data.clone().into_2_views_split();
+        let builder = TrainingGroupBuilder::default().objective(Auc);
+        let (training_data, verification_data) = views();
+        assert!(builder.clone().build(training_data, verification_data).is_err());
+        let (training_data, verification_data) = views();
+        let missing_objective = TrainingGroup::builder().population_size(5);
+        assert!(missing_objective.build(training_data, verification_data).is_err());
+        let (training_data, verification_data) = views();
+        let invalid = builder
+            .population_size(5)
+            .genetic_config(GeneticConfig { crossover_rate: 2.0, ..GeneticConfig::default() });
+        assert!(invalid.build(training_data, verification_data).is_err());
+    }
+
+    #[test]
+    fn test_set_config() {
+        let train = |changed| {
+            let mut group = train_group(GeneticConfig::default(), 2);
+            group.set_config(changed).unwrap();
+            group.next_generation();
+            group.next_generation();
+            population(&group)
+        };
+        let unchanged = train_history(GeneticConfig::default(), 4);
+        assert_eq!(unchanged, train(GeneticConfig::default()));
+        let config = GeneticConfig {
+            mutation_rate: 0.0,
+            crossover_rate: 1.0,
+            ..GeneticConfig::default()
+        };
+        let changed = train(config);
+        assert_eq!(changed, train(config));
+        assert_eq!(changed[..2], unchanged[..2]);
+        assert_ne!(changed[2..], unchanged[2..]);
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        let invalid = GeneticConfig { crossover_rate: 2.0, ..GeneticConfig::default() };
+        assert!(group.set_config(invalid).is_err());
+    }
+
+    #[test]
+    fn test_weighted_reward() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let multipliers = vec![(1.0, 3.0), (2.0, 1.0)];
+        let objective = Objective::WeightedReward(multipliers.clone());
+        let data = (training_data.clone(), verification_data.clone());
+        let mut group =
+            TrainingGroup::with_seed(data.0, data.1, objective.clone(), 5, &[], 5).unwrap();
+        for _ in 0..3 {
+            group.next_generation();
+        }
+        assert!(group.verification_score().is_some());
+        let score = group.classifier().unwrap().score(&verification_data).unwrap();
+        assert_eq!(score.weighted_cost(&[]), score.cost);
+        assert_eq!(score.value(&objective), score.weighted_cost(&multipliers));
+
+        let invalid = [vec![(1.0, -1.0)], vec![(f32::NAN, 1.0)], vec![(1.0, 1.0); 3]];
+        for multipliers in invalid {
+            let data = (training_data.clone(), verification_data.clone());
+            let objective = Objective::WeightedReward(multipliers);
+            assert!(TrainingGroup::new(data.0, data.1, objective, 5, &[]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_crossover_method() {
+        let train = |crossover| {
+            let config =
+                GeneticConfig { crossover, crossover_rate: 1.0, ..GeneticConfig::default() };
+            train_history(config, 4)
+        };
+        let join = train(CrossoverMethod::Join);
+        for crossover in &[CrossoverMethod::SubtreeSwap, CrossoverMethod::Uniform] {
+            let trained = train(*crossover);
+            assert_eq!(trained, train(*crossover));
+            assert_ne!(trained, join);
+        }
+    }
+
+    #[test]
+    fn test_mutation_schedule() {
+        let constant = GeneticConfig { mutation_rate: 0.3, ..GeneticConfig::default() };
+        assert_eq!(constant.mutation_rate_at(100, 100), 0.3);
+        let decay = GeneticConfig {
+            mutation_schedule: MutationSchedule::LinearDecay {
+                from: 0.9,
+                to: 0.1,
+                generations: 4,
+            },
+            ..constant
+        };
+        assert!((decay.mutation_rate_at(0, 0) - 0.9).abs() < 1e-9);
+        assert!((decay.mutation_rate_at(2, 0) - 0.5).abs() < 1e-9);
+        assert!((decay.mutation_rate_at(10, 0) - 0.1).abs() < 1e-9);
+        let adaptive = GeneticConfig {
+            mutation_schedule: MutationSchedule::AdaptiveOnStagnation { window: 3, raised: 0.8 },
+            ..constant
+        };
+        assert_eq!(adaptive.mutation_rate_at(10, 2), 0.3);
+        assert_eq!(adaptive.mutation_rate_at(10, 3), 0.8);
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let group = || {
+            TrainingGroup::new(training_data.clone(), verification_data.clone(), Auc, 5, &[])
+                .unwrap()
+        };
+        let mut training = group().with_genetic_config(adaptive).unwrap();
+        for _ in 0..5 {
+            training.next_generation();
+        }
+        let invalid = [
+            MutationSchedule::LinearDecay { from: 1.5, to: 0.1, generations: 4 },
+            MutationSchedule::LinearDecay { from: 0.9, to: 0.1, generations: 0 },
+            MutationSchedule::AdaptiveOnStagnation { window: 0, raised: 0.8 },
+            MutationSchedule::AdaptiveOnStagnation { window: 3, raised: -0.1 },
+        ];
+        for mutation_schedule in invalid {
+            let config = GeneticConfig { mutation_schedule, ..GeneticConfig::default() };
+            assert!(group().with_genetic_config(config).is_err());
+        }
+    }
+
+    #[test]
+    fn test_throughput_stats() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        let start = Instant::now();
+        group.next_generation();
+        let elapsed = start.elapsed();
+        let stats = group.stats().unwrap();
+        assert!(stats.generation_duration > Duration::default());
+        assert!(stats.generation_duration <= elapsed);
+        assert!(stats.evaluations_per_second > 0.0);
+        assert!(stats.population_size > 0);
+        let mut s = Serializator::new();
+        s.add(&stats);
+        let mut s = Serializator::from_bytes(s.to_bytes()).unwrap();
+        let loaded = Stats::deserialize(&mut s).unwrap();
+        let difference =
+            loaded.generation_duration.as_secs_f64() - stats.generation_duration.as_secs_f64();
+        assert!(difference.abs() < 1e-6);
+        assert_eq!(loaded.population_size, stats.population_size);
+    }
+
+    #[test]
+    fn test_selection_method() {
+        let trees = [0.1, 0.5, 0.7, 0.9]
+            .iter()
+            .map(|value| {
+                let score = Score::new(Auc, Class::new(0), *value, Threshold::new(0.0));
+                ScoredTree::new(create_short_tree(), score)
+            })
+            .collect::<Vec<_>>();
+        let candidates = trees.iter().collect::<Vec<_>>();
+        let counts = |selection: SelectionMethod| {
+            let _seed = seed_thread_rng(5);
+            let mut counts = vec![0; trees.len()];
+            for _ in 0..10_000 {
+                let parent = selection.select(&candidates).unwrap();
+                counts[trees.iter().position(|t| t == parent).unwrap()] += 1;
+            }
+            counts
+        };
+        assert_eq!(counts(SelectionMethod::Tournament { size: 100 }), vec![0, 0, 0, 10_000]);
+        let uniform = counts(SelectionMethod::Tournament { size: 1 });
+        assert!(uniform.iter().all(|c| (2_200..2_800).contains(c)));
+        // fitness 0, 0.4, 0.6, 0.8
+        let roulette = counts(SelectionMethod::Roulette);
+        assert_eq!(roulette[0], 0);
+        assert!((2_000..2_500).contains(&roulette[1]));
+        assert!((4_200..4_700).contains(&roulette[3]));
+        // weights 1, 2, 3, 4
+        let rank = counts(SelectionMethod::Rank);
+        assert!((800..1_200).contains(&rank[0]));
+        assert!((3_800..4_200).contains(&rank[3]));
+        assert_eq!(rank, counts(SelectionMethod::Rank));
+        assert!(SelectionMethod::Rank.select(&[]).is_none());
+        assert!(SelectionMethod::Roulette.select(&candidates[..1]).is_some());
+
+        let train = |selection| {
+            let config = GeneticConfig { selection, ..GeneticConfig::default() };
+            train_history(config, 5)
+        };
+        for selection in [SelectionMethod::Roulette, SelectionMethod::Rank] {
+            assert!(!train(selection).is_empty());
+            assert_eq!(train(selection), train(selection));
+        }
+        let (training_data, verification_data) = simple_views();
+        let invalid = GeneticConfig {
+            selection: SelectionMethod::Tournament { size: 0 },
+            ..GeneticConfig::default()
+        };
+        let group = TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        assert!(group.with_genetic_config(invalid).is_err());
+    }
+
+    #[test]
+    fn test_equal_fitness_selection() {
+        let col = || Weighted::from(Node::DataValue(0, 0));
+        let shape = InputShape::new(1, 1);
+        let trees = TWO_ARG_FUNCTIONS
+            .iter()
+            .take(4)
+            .map(|f| Tree::from_two(f, col(), col(), shape))
+            .chain([create_short_tree()])
+            .map(|tree| {
+                let score = Score::new(Auc, Class::new(0), 0.5, Threshold::new(0.0));
+                ScoredTree::new(tree, score)
+            })
+            .collect::<Vec<_>>();
+        let parents = |selection: SelectionMethod, reversed: bool| {
+            let mut candidates = trees.iter().collect::<Vec<_>>();
+            if reversed {
+                candidates.reverse();
+            }
+            let _seed = seed_thread_rng(3);
+            (0..100)
+                .map(|_| selection.select(&candidates).unwrap().tree().structure_hash())
+                .collect::<Vec<_>>()
+        };
+        let best = parents(SelectionMethod::Tournament { size: 100 }, false);
+        assert!(best.iter().all(|hash| *hash == create_short_tree().structure_hash()));
+        assert_eq!(best, parents(SelectionMethod::Tournament { size: 100 }, true));
+        assert_eq!(parents(SelectionMethod::Rank, false), parents(SelectionMethod::Rank, true));
+        assert_eq!(ScoredTree::best_tree(&trees).unwrap(), &trees[4]);
+        let mut same_size = trees[..4].to_vec();
+        let best = ScoredTree::best_tree(&same_size).unwrap().clone();
+        same_size.reverse();
+        assert_eq!(ScoredTree::best_tree(&same_size), Some(&best));
+        let lowest = trees[..4].iter().map(|t| t.tree().structure_hash()).min();
+        assert_eq!(Some(best.tree().structure_hash()), lowest);
+    }
+
+    #[test]
+    fn test_diversity() {
+        let train = |diversity| {
+            let config = GeneticConfig { diversity, ..GeneticConfig::default() };
+            train_group(config, 10)
+                .history()
+                .iter()
+                .map(|stats| stats.diversity)
+                .collect::<Vec<_>>()
+        };
+        let plain = train(DiversityMode::None);
+        let crowding = train(DiversityMode::Crowding);
+        for diversity in plain.iter().chain(&crowding) {
+            assert!(*diversity > 0.0 && *diversity <= 1.0);
+        }
+        let average = |values: &[f32]| values.iter().sum::<f32>() / values.len() as f32;
+        assert!(average(&crowding) > average(&plain));
+    }
+
+    #[test]
+    fn test_generations_to_score() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 4).unwrap();
+        assert_eq!(group.generations_to_score(2.0, 3), None);
+        assert_eq!(group.generation(), 3);
+        let score = group.stats().unwrap().training_score;
+        assert_eq!(group.generations_to_score(score, 3), Some(0));
+        assert_eq!(group.generation(), 3);
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 4).unwrap();
+        assert_eq!(group.generations_to_score(0.0, 10), Some(1));
+    }
+
+    #[test]
+    fn test_observe() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let len = training_data.len();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 4)
+                .unwrap()
+                .with_class_weights(HashMap::from([(Class::new(1), 2.0)]))
+                .unwrap();
+        assert!(group.step(Duration::from_secs(60)));
+        let point = |values, class| {
+            Point::new(Input::from_vector(vec![values]).unwrap(), Outcome::new(class, 1.0, -1.0))
+        };
+        group.observe(point(vec![5.0, 1.0], Class::new(0))).unwrap();
+        group.observe(point(vec![1.0, 5.0], Class::new(1))).unwrap();
+        assert_eq!(group.training_data().len(), len + 2);
+        let weights = group.training_data().outcomes()[len..].iter().map(Outcome::weight);
+        assert_eq!(weights.collect::<Vec<_>>(), vec![1.0, 2.0]);
+        assert!(group.observe(point(vec![1.0], Class::new(0))).is_err());
+        assert!(group.observe(point(vec![1.0, 5.0], Class::new(2))).is_err());
+        assert_eq!(group.training_data().len(), len + 2);
+        assert!(group.classifier().is_ok());
+        for _ in 0..3 {
+            assert!(group.step(Duration::from_secs(60)));
+        }
+        assert!(group.classifier().is_ok());
+
+        // population survives steps cut short right after every observed point
+        let groups = |group: &TrainingGroup| {
+            group.classes.iter().map(ClassTraining::group_count).collect::<Vec<_>>()
+        };
+        assert_eq!(groups(&group), vec![5, 5]);
+        for i in 0..5 {
+            group.observe(point(vec![i as f32, 1.0], Class::new(i % 2))).unwrap();
+            assert!(!group.step(Duration::ZERO));
+            assert_eq!(groups(&group), vec![5, 5]);
+            assert!(group.classifier().is_ok());
+        }
+        assert!(group.step(Duration::from_secs(60)));
+        assert!(group.classifier().is_ok());
+    }
+
+    #[test]
+    fn test_node_kinds() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let forbidden = [NodeKind::Div, NodeKind::Sine, NodeKind::Mul];
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 4)
+                .unwrap()
+                .with_forbidden_node_kinds(&forbidden)
+                .unwrap();
+        for _ in 0..5 {
+            group.next_generation();
+        }
+        let used = group.classifier().unwrap().used_node_kinds();
+        assert!(forbidden.iter().all(|kind| !used.contains(kind)));
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let allowed = [NodeKind::Add, NodeKind::Neg];
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 4)
+                .unwrap()
+                .with_allowed_node_kinds(&allowed)
+                .unwrap();
+        for _ in 0..5 {
+            group.next_generation();
+        }
+        let used = group.classifier().unwrap().used_node_kinds();
+        assert!(used.iter().all(|kind| allowed.contains(kind)));
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 4).unwrap();
+        for _ in 0..5 {
+            group.next_generation();
+        }
+        let used = group.classifier().unwrap().used_node_kinds();
+        assert!(NodeKind::OPT_IN.iter().all(|kind| !used.contains(kind)));
+        assert!(
+            !used.contains(&NodeKind::DivOrOne) && !used.contains(&NodeKind::DivOrNumerator)
+        );
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let allowed = [NodeKind::IfThenElse, NodeKind::GreaterThan, NodeKind::LessThan];
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 4)
+                .unwrap()
+                .with_allowed_node_kinds(&allowed)
+                .unwrap();
+        for _ in 0..5 {
+            group.next_generation();
+        }
+        let classifier = group.classifier().unwrap();
+        let used = classifier.used_node_kinds();
+        assert!(!used.is_empty() && used.iter().all(|kind| allowed.contains(kind)));
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        assert!(TrainingGroup::new(training_data, verification_data, Auc, 5, &[])
+            .unwrap()
+            .with_allowed_node_kinds(&[])
+            .is_err());
+    }
+
+    #[test]
+    fn test_safe_div() {
+        for (safe_div, kind) in &[
+            (SafeDivMode::ReturnOne, NodeKind::DivOrOne),
+            (SafeDivMode::ReturnNumerator, NodeKind::DivOrNumerator),
+        ] {
+            let (training_data, verification_data) =
+                create_simple_data(100).into_2_views_split();
+            let config = GeneticConfig { safe_div: *safe_div, ..GeneticConfig::default() };
+            let mut group =
+                TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 4)
+                    .unwrap()
+                    .with_genetic_config(config)
+                    .unwrap()
+                    .with_allowed_node_kinds(&[NodeKind::Div, NodeKind::Add])
+                    .unwrap();
+            for _ in 0..5 {
+                group.next_generation();
+            }
+            let classifier = group.classifier().unwrap();
+            let used = classifier.used_node_kinds();
+            assert!(used.iter().all(|k| k == kind || *k == NodeKind::Add));
+            let mut s = Serializator::new();
+            s.add(&classifier);
+            let loaded = Classifier::deserialize(&mut s).unwrap();
+            assert_eq!(loaded.used_node_kinds(), used);
+        }
+    }
+
+    #[test]
+    fn test_max_nodes() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let config = GeneticConfig { max_nodes: Some(5), ..GeneticConfig::default() };
+        let mut group = TrainingGroup::new(training_data, verification_data, Auc, 5, &[])
+            .unwrap()
+            .with_genetic_config(config)
+            .unwrap();
+        for _ in 0..10 {
+            group.next_generation();
+            for class in &group.classes {
+                assert!(class.best_tree().is_none_or(|t| t.node_count() <= 5));
+            }
+            if let Some(stats) = group.stats() {
+                assert!(stats.average_node_count >= 1.0 && stats.average_node_count <= 5.0);
+                assert!(stats.max_node_count <= 5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_population_memory() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let group =
+            TrainingGroup::new(training_data.clone(), verification_data.clone(), Auc, 5, &[])
+                .unwrap();
+        assert!(group.with_max_population_memory(1).is_err());
+        let mut unlimited =
+            TrainingGroup::new(training_data.clone(), verification_data.clone(), Auc, 5, &[])
+                .unwrap();
+        let empty = unlimited.estimated_memory_bytes();
+        assert!(empty > 0);
+        unlimited.next_generation();
+        assert!(unlimited.estimated_memory_bytes() > empty);
+
+        let limit = empty + 50_000;
+        let mut limited = TrainingGroup::new(training_data, verification_data, Auc, 5, &[])
+            .unwrap()
+            .with_max_population_memory(limit)
+            .unwrap();
+        for _ in 0..5 {
+            limited.next_generation();
+            assert!(limited.estimated_memory_bytes() <= limit);
+        }
+    }
+
+    #[test]
+    fn test_resize_population() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::new(training_data, verification_data, Auc, 10, &[]).unwrap();
+        assert!(group.resize_population(0).is_err());
+        for _ in 0..3 {
+            group.next_generation();
+        }
+        let score = group.stats().unwrap().training_score;
+        group.resize_population(3).unwrap();
+        assert_eq!(group.population_size(), 3);
+        for class in &group.classes {
+            assert!(class.population_size().0 <= 9);
+        }
+        assert_eq!(group.stats().unwrap().training_score, score);
+
+        group.resize_population(20).unwrap();
+        assert_eq!(group.population_size(), 20);
+        group.next_generation();
+        for class in &group.classes {
+            assert!(class.population_size().0 > 9);
+        }
+        assert!(group.stats().unwrap().training_score >= score);
+    }
+
+    #[test]
+    fn test_elitism() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let data_bytes =
+            TrainingGroup::new(training_data.clone(), verification_data.clone(), Auc, 5, &[])
+                .unwrap()
+                .estimated_memory_bytes();
+        // node limit of 1 per class is exceeded by any group
+        let train = |elitism| {
+            let config = GeneticConfig { elitism, ..GeneticConfig::default() };
+            let mut group = TrainingGroup::new(
+                training_data.clone(),
+                verification_data.clone(),
+                Auc,
+                5,
+                &[],
+            )
+            .unwrap()
+            .with_genetic_config(config)
+            .unwrap()
+            .with_max_population_memory(data_bytes + NODE_BYTES * 2)
+            .unwrap();
+            group.next_generation();
+            group.classes.iter().map(|c| c.population_size().0).collect::<Vec<_>>()
+        };
+        assert_eq!(train(0), vec![0, 0]);
+        for trees in train(2) {
+            // elite groups have up to 5 trees each
+            assert!(trees > 0 && trees <= 10);
+        }
+    }
+
+    #[test]
+    fn test_pareto_training() {
+        let (training_data, verification_data) = create_simple_data(200).into_2_views_split();
+        let pareto = Objective::Pareto(vec![Accuracy, F1]);
+        let mut group = TrainingGroup::new(
+            training_data.clone(),
+            verification_data.clone(),
+            pareto,
+            10,
+            &[],
+        )
+        .unwrap();
+        assert!(group.pareto_front().is_empty());
+        for _ in 0..10 {
+            group.next_generation();
+        }
+        let front = group.pareto_front();
+        assert!(!front.is_empty());
+        let values = front
+            .iter()
+            .map(|c| {
+                let score = c.score(&verification_data).unwrap();
+                (c.node_count(), score.accuracy, score.f1)
+            })
+            .collect::<Vec<_>>();
+        for (i, (nodes, accuracy, f1)) in values.iter().enumerate() {
+            for (other_nodes, other_accuracy, other_f1) in &values[i + 1..] {
+                assert!(nodes <= other_nodes);
+                // a larger classifier must be better in some objective
+                assert!(nodes == other_nodes || other_accuracy > accuracy || other_f1 > f1);
+            }
+        }
+
+        let mut group =
+            TrainingGroup::new(training_data.clone(), verification_data.clone(), Auc, 5, &[])
+                .unwrap();
+        group.next_generation();
+        assert!(group.pareto_front().is_empty());
+        for invalid in [vec![], vec![Accuracy, MeanSquaredError]] {
+            let objective = Objective::Pareto(invalid);
+            let training_data = training_data.clone();
+            let group =
+                TrainingGroup::new(training_data, verification_data.clone(), objective, 5, &[]);
+            assert!(group.is_err());
+        }
+    }
+
+    #[test]
+    fn test_generation_deadline() {
+        let (training_data, verification_data) = create_simple_data(200).into_2_views_split();
+        let mut group =
+            TrainingGroup::new(training_data, verification_data, Accuracy, 10, &[]).unwrap();
+        assert!(!group.next_generation_deadline(Instant::now()));
+        assert_eq!(group.generation(), 1);
+        // groups not scored before the deadline are kept for the next generation
+        assert!(group.classes.iter().all(|class| class.group_count() == 10));
+        let later = Instant::now() + Duration::from_secs(600);
+        while group.best_classifier().is_none() {
+            assert!(group.next_generation_deadline(later));
+        }
+        // trees scored in earlier generations are kept when a generation is cut short
+        assert!(!group.next_generation_deadline(Instant::now()));
+        assert!(group.classifier().is_ok());
+        assert!(group.best_classifier().is_some());
+        assert!(group.classes.iter().all(|class| class.group_count() == 10));
+    }
+
+    #[test]
+    fn test_custom_training() {
+        let (training_data, verification_data) = create_simple_data(200).into_2_views_split();
+        let correct = Objective::Custom(Arc::new(|pairs: &[(Class, Class)]| {
+            pairs.iter().filter(|(actual, predicted)| actual == predicted).count() as f32
+        }));
+        let mut group =
+            TrainingGroup::new(training_data, verification_data, correct, 5, &[]).unwrap();
+        for _ in 0..20 {
+            group.next_generation();
+            if let Some(stats) = group.stats() {
+                assert!(stats.training_score > 0.0);
+                assert!(group.classifier().is_ok());
+                return;
+            }
+        }
+        panic!("Custom objective training failed")
+    }
+
+    #[test]
+    fn test_replace_training_data() {
+        let data = create_simple_data(200).shuffle();
+        let (training_data, verification_data) = data.clone().into_2_views_split();
+        let mut group =
+            TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        for _ in 0..3 {
+            group.next_generation();
+        }
+        let incompatible = create_regression_data(100).into_view();
+        assert!(group.replace_training_data(incompatible).is_err());
+
+        let trained = group.classifier().is_ok();
+        group.replace_training_data(data.into_view()).unwrap();
+        assert_eq!(group.classifier().is_ok(), trained);
+        for _ in 0..20 {
+            group.next_generation();
+            if group.classifier().is_ok() {
+                assert!(group.generation() > 3);
+                return;
+            }
+        }
+        panic!("Training after replacing data failed")
+    }
+
+    #[test]
+    fn test_train_on_chunks() {
+        let path = env::temp_dir().join(format!("primeclue_chunks_{}.pcdv", process::id()));
+        let (training_data, verification_data) = create_simple_data(300).into_2_views_split();
+        let disk = DiskDataView::create(&path, &create_simple_data(200).shuffle()).unwrap();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Accuracy, 5, &[], 8)
+                .unwrap();
+        group.train_on_chunks(&disk, 80, 3).unwrap();
+        assert_eq!(group.generation(), 9);
+        assert_eq!(group.training_data().len(), 40);
+        assert!(group.classifier().is_ok());
+        assert!(group.best_classifier().is_some());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_terminals() {
+        static TERMINALS: [Terminal; 2] = [
+            Terminal::new("a_minus_b", &|input: &Input| input.get(0, 0) - input.get(0, 1)),
+            Terminal::new("b_squared", &|input: &Input| input.get(0, 1).powi(2)),
+        ];
+        let path = env::temp_dir().join(format!("primeclue_terminals_{}.ssd", process::id()));
+        let data = create_simple_data(200).shuffle();
+        let (training_data, verification_data) = data.clone().into_2_views_split();
+        let config = GeneticConfig { terminals: &TERMINALS, ..GeneticConfig::default() };
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 10, &[], 3)
+                .unwrap()
+                .with_genetic_config(config)
+                .unwrap();
+        let uses_terminals = |classifier: &Classifier| {
+            classifier.sorted_trees().iter().any(|t| t.get_start_node().uses_terminals())
+        };
+        // best trees of random data don't always use terminals after a few generations
+        for _ in 0..50 {
+            group.next_generation();
+            if group.classifier().is_ok_and(|c| uses_terminals(&c)) {
+                break;
+            }
+        }
+        let classifier = group.classifier().unwrap();
+        assert!(uses_terminals(&classifier));
+        assert!(classifier.to_onnx(&path).is_err());
+        assert!(classifier.to_rust_source("classify").is_err());
+        group.save_state(&path).unwrap();
+
+        let (training_data, verification_data) = data.into_2_views_split();
+        let mut loaded =
+            TrainingGroup::load_state(&path, training_data, verification_data).unwrap();
+        assert_eq!(loaded.classifier().unwrap(), classifier);
+        loaded.next_generation();
+        fs::remove_file(&path).unwrap();
+
+        let same = [TERMINALS[0], TERMINALS[0]];
+        let duplicate = GeneticConfig { terminals: Box::leak(Box::new(same)), ..config };
+        assert!(duplicate.validate().is_err());
+        for name in ["", "a minus b", "tab\tb"] {
+            let invalid = [Terminal::new(name, &|input: &Input| input.get(0, 0))];
+            let invalid = GeneticConfig { terminals: Box::leak(Box::new(invalid)), ..config };
+            assert!(invalid.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_save_load_state() {
+        let path = env::temp_dir().join(format!("primeclue_state_{}.ssd", process::id()));
+        let data = create_simple_data(200).shuffle();
+        let (training_data, verification_data) = data.clone().into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 11).unwrap();
+        for _ in 0..3 {
+            group.next_generation();
+        }
+        group.save_state(&path).unwrap();
+
+        let (training_data, verification_data) = data.into_2_views_split();
+        let mut loaded =
+            TrainingGroup::load_state(&path, training_data, verification_data).unwrap();
+        assert_eq!(loaded.generation(), 3);
+        assert!(loaded.best_classifier().is_some());
+        assert_eq!(loaded.best_classifier(), group.best_classifier());
+        assert_eq!(loaded.best_classifier_generation(), group.best_classifier_generation());
+        let evaluations = group.stats().unwrap().total_evaluations;
+        assert!(evaluations > 0);
+        assert_eq!(loaded.stats().unwrap().total_evaluations, evaluations);
+        for _ in 0..3 {
+            group.next_generation();
+            loaded.next_generation();
+            let (stats, loaded_stats) = (group.stats().unwrap(), loaded.stats().unwrap());
+            assert_eq!(stats.generation, loaded_stats.generation);
+            assert!((stats.training_score - loaded_stats.training_score).abs() < f32::EPSILON);
+            assert_eq!(stats.node_count, loaded_stats.node_count);
+            assert_eq!(stats.total_evaluations, loaded_stats.total_evaluations);
+        }
+        assert_eq!(loaded.stats().unwrap().generation, 6);
+        assert_eq!(loaded.history().len(), group.history().len());
+
+        let regression = create_regression_data(100);
+        let (training_data, verification_data) = regression.into_2_views_split();
+        assert!(TrainingGroup::load_state(&path, training_data, verification_data).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_autosave() {
+        let path = env::temp_dir().join(format!("primeclue_autosave_{}.ssd", process::id()));
+        let (training_data, verification_data) =
+            create_simple_data(200).shuffle().into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 7).unwrap();
+        while group.best_classifier().is_none() || group.generation() % 3 != 1 {
+            group.next_generation();
+        }
+        assert!(group.set_autosave(&path, 0).is_err());
+        group.set_autosave(&path, 3).unwrap();
+        group.next_generation();
+        assert!(!path.exists());
+        group.next_generation();
+        assert!(group.autosave_error().is_none());
+        assert_eq!(Classifier::load(&path).unwrap(), group.best_classifier().unwrap());
+        fs::remove_file(&path).unwrap();
+
+        let missing = env::temp_dir().join("primeclue_missing_dir").join("autosave.ssd");
+        group.set_autosave(&missing, 1).unwrap();
+        group.next_generation();
+        assert!(group.autosave_error().is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jsonl_logger() {
+        use std::io;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Shared(Arc<Mutex<Vec<u8>>>);
+
+        impl io::Write for Shared {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        let log = Shared::default();
+        group.set_jsonl_logger(log.clone());
+        for _ in 0..3 {
+            group.next_generation();
+        }
+        assert!(group.jsonl_logger_error().is_none());
+        let log = String::from_utf8(log.0.lock().unwrap().clone()).unwrap();
+        let lines = log.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), group.history().len());
+        for (line, stats) in lines.into_iter().zip(group.history()) {
+            let json: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(json["generation"], stats.generation);
+            assert_eq!(json["population_size"], stats.population_size);
+            assert!(json["training_score"].is_number());
+            assert!(json["diversity"].is_number());
+            assert!(json["generation_duration"].is_object());
+        }
+
+        group.set_jsonl_logger(io::sink());
+        group.next_generation();
+        assert!(group.jsonl_logger_error().is_none());
+    }
+
+    fn create_regression_data(count: usize) -> DataSet {
+        let mut classes = HashMap::new();
+        classes.insert(Class::default(), "value".to_string());
+        let mut data = DataSet::new(classes);
+        for i in 0..count {
+            let x = i as f32 / 10.0;
+            let point = Point::new(
+                Input::from_vector(vec![vec![x]]).unwrap(),
+                Outcome::regression(2.0 * x + 1.0),
+            );
+            data.add_data_point(point).unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn test_regression_training() {
+        let (training_data, verification_data) =
+            create_regression_data(200).shuffle().into_2_views_split();
+        let mut training_group =
+            TrainingGroup::new(training_data, verification_data, MeanSquaredError, 10, &[])
+                .unwrap();
+        for _ in 0..1_000 {
+            training_group.next_generation();
+            if let Some(stats) = training_group.stats() {
+                assert!(stats.training_score >= 0.0);
+                let classifier = training_group.classifier().unwrap();
+                let data = create_regression_data(200).into_view();
+                let score = classifier.score(&data).unwrap();
+                assert!(score.mean_squared_error.unwrap() >= 0.0);
+                assert!(score.mean_absolute_error.unwrap() >= 0.0);
+                return;
+            }
+        }
+        panic!("Regression training failed")
+    }
+
+    #[test]
+    fn test_regression_requires_targets() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        assert!(TrainingGroup::new(training_data, verification_data, MeanSquaredError, 10, &[])
+            .is_err());
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let data = create_simple_data(100);
+        let (training_data, verification_data) = data.clone().into_2_views_split();
+        let empty = data.clone().filter(|_| false).into_view();
+        match TrainingGroup::new(empty.clone(), verification_data.clone(), Accuracy, 10, &[]) {
+            Err(PrimeclueErr::EmptyDataSet(name)) => assert_eq!(name, "Data training set"),
+            other => panic!("Expected empty training set, got {:?}", other.err()),
+        }
+        match TrainingGroup::new(training_data.clone(), empty, Accuracy, 10, &[]) {
+            Err(PrimeclueErr::EmptyDataSet(name)) => assert_eq!(name, "Data verification set"),
+            other => panic!("Expected empty verification set, got {:?}", other.err()),
+        }
+        let one_class = data.filter(|p| p.data().1.class() == Class::new(0));
+        let (one_class_training, one_class_verification) = one_class.into_2_views_split();
+        let err = TrainingGroup::new(one_class_training, verification_data, Accuracy, 10, &[])
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Training data has points of 1 class, at least 2 classes are required"
+        );
+        match TrainingGroup::new(training_data, one_class_verification, Accuracy, 10, &[]) {
+            Err(PrimeclueErr::ClassCountMismatch { expected: 2, actual: 1 }) => {}
+            other => panic!("Expected class count mismatch, got {:?}", other.err()),
+        }
+    }
+
+    fn train_with_seed(data: DataSet, seed: u64, threads: usize) -> Vec<u8> {
+        let (training_data, verification_data) = data.into_2_views_split();
+        let mut training_group =
+            TrainingGroup::with_seed(training_data, verification_data, Accuracy, 5, &[], seed)
+                .unwrap()
+                .with_threads(threads)
+                .unwrap();
+        for _ in 0..10 {
+            training_group.next_generation();
+        }
+        let mut s = Serializator::new();
+        s.add(&training_group.classifier().unwrap());
+        s.to_bytes()
+    }
+
+    #[test]
+    fn test_seeded_training() {
+        let data = create_simple_data(200);
+        let first = train_with_seed(data.clone(), 7, DEFAULT_THREADS);
+        assert_eq!(first, train_with_seed(data.clone(), 7, DEFAULT_THREADS));
+        assert_ne!(first, train_with_seed(data, 8, DEFAULT_THREADS));
+    }
+
+    #[test]
+    fn test_fitness_subsample() {
+        let (training_data, verification_data) = create_simple_data(400).into_2_views_split();
+        let train = |seed| {
+            let mut group = TrainingGroup::with_seed(
+                training_data.clone(),
+                verification_data.clone(),
+                Auc,
+                5,
+                &[],
+                3,
+            )
+            .unwrap()
+            .with_fitness_subsample(0.25, seed, true)
+            .unwrap();
+            for _ in 0..10 {
+                group.next_generation();
+            }
+            let mut s = Serializator::new();
+            s.add(&group.classifier().unwrap());
+            s.to_bytes()
+        };
+        assert_eq!(train(11), train(11));
+        assert_ne!(train(11), train(12));
+        for fraction in [0.0, -0.5, 1.5, f64::NAN] {
+            let group = TrainingGroup::new(
+                training_data.clone(),
+                verification_data.clone(),
+                Auc,
+                5,
+                &[],
+            )
+            .unwrap();
+            assert!(group.with_fitness_subsample(fraction, 1, false).is_err());
+        }
+    }
+
+    #[test]
+    fn test_model_selection() {
+        let (training_data, verification_data) = create_simple_data(400).into_2_views_split();
+        let other_verification = create_simple_data(100).shuffle().into_view();
+        let train = |verification_data: &DataView, model_selection| {
+            let mut group = TrainingGroup::with_seed(
+                training_data.clone(),
+                verification_data.clone(),
+                Auc,
+                5,
+                &[],
+                3,
+            )
+            .unwrap()
+            .with_model_selection(model_selection);
+            for _ in 0..5 {
+                group.next_generation();
+            }
+            group.classifier().unwrap()
+        };
+        // verification data is not used for training at all
+        assert_eq!(
+            train(&verification_data, ModelSelection::ByTraining),
+            train(&other_verification, ModelSelection::ByTraining)
+        );
+        assert_eq!(
+            train(&verification_data, ModelSelection::ByVerification),
+            train(&verification_data, ModelSelection::default())
+        );
+    }
+
+    #[test]
+    fn test_seed_with() {
+        let (training_data, verification_data) = create_simple_data(200).into_2_views_split();
+        let mut group =
+            TrainingGroup::new(training_data.clone(), verification_data.clone(), Auc, 5, &[])
+                .unwrap();
+        for _ in 0..10 {
+            group.next_generation();
+        }
+        let classifier = group.classifier().unwrap();
+        let mut seeded =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 1).unwrap();
+        let memory = seeded.estimated_memory_bytes();
+        seeded.seed_with(&classifier).unwrap();
+        assert!(seeded.estimated_memory_bytes() > memory);
+        seeded.next_generation();
+        assert!(seeded.classifier().is_ok());
+
+        let mut data = DataSet::new(create_simple_data(0).class_map().clone());
+        for point in create_simple_data(100).iter() {
+            let (input, outcome) = point.data();
+            let input = Input::from_vector(vec![input.row(0), input.row(0)]).unwrap();
+            data.add_data_point(Point::new(input, *outcome)).unwrap();
+        }
+        let (training_data, verification_data) = data.into_2_views_split();
+        let mut other =
+            TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        assert!(other.seed_with(&classifier).is_err());
+    }
+
+    #[test]
+    fn test_threads() {
+        let data = create_simple_data(200);
+        let single = train_with_seed(data.clone(), 7, 1);
+        assert_eq!(single, train_with_seed(data, 7, 16));
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let training_group =
+            TrainingGroup::new(training_data, verification_data, Auc, 3, &[]).unwrap();
+        assert!(training_group.with_threads(0).is_err());
+    }
+
+    #[test]
+    fn test_train_until() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut training_group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 3, &[], 2).unwrap();
+        let timeout = StopCondition::TimeLimit(Duration::from_secs(0));
+        assert!(training_group.train_until(&[timeout]).is_err());
+
+        let limit = StopCondition::FixedGenerations(5);
+        let result = training_group.train_until(&[limit]).unwrap();
+        assert_eq!(result.reason, StopReason::LimitReached);
+        assert_eq!(result.stats.generation, 5);
+        assert_eq!(result.history.len(), training_group.history().len());
+        assert_eq!(result.history.last().map(|s| s.generation), Some(5));
+        assert_eq!(Some(result.classifier), training_group.best_classifier());
+    }
+
+    #[test]
+    fn test_stop_conditions() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut training_group =
+            TrainingGroup::new(training_data, verification_data, Auc, 3, &[]).unwrap();
+        let limit = StopCondition::FixedGenerations(3);
+        assert_eq!(training_group.next_generation_until(&[limit]), StopReason::LimitReached);
+        assert_eq!(training_group.generation(), 3);
+
+        let timeout = StopCondition::TimeLimit(Duration::from_secs(0));
+        assert_eq!(training_group.next_generation_until(&[timeout]), StopReason::LimitReached);
+        assert_eq!(training_group.generation(), 3);
+
+        let plateau = StopCondition::NoImprovement { patience: 2, min_delta: f32::MAX };
+        let limit = StopCondition::FixedGenerations(1_000);
+        assert_eq!(
+            training_group.next_generation_until(&[plateau, limit]),
+            StopReason::Converged
+        );
+        assert!(training_group.generation() < 1_003);
+
+        let gap = training_group.overfit_gap().unwrap();
+        let stats = training_group.stats().unwrap();
+        assert_eq!(Some(gap), stats.overfit_gap);
+        assert!((gap - (stats.training_score - stats.verification_score.unwrap())).abs() < 1e-6);
+        let generation = training_group.generation();
+        let overfit = StopCondition::OverfitGap(gap - 1.0);
+        assert_eq!(training_group.next_generation_until(&[overfit]), StopReason::Overfitting);
+        assert_eq!(training_group.generation(), generation);
+        let overfit = StopCondition::OverfitGap(f32::MAX);
+        let limit = StopCondition::FixedGenerations(2);
+        assert_eq!(
+            training_group.next_generation_until(&[overfit, limit]),
+            StopReason::LimitReached
+        );
+    }
+
+    #[test]
+    fn test_max_evaluations() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 3, &[], 4).unwrap();
+        let budget = StopCondition::MaxEvaluations(0);
+        assert_eq!(group.next_generation_until(&[budget]), StopReason::LimitReached);
+        assert_eq!(group.generation(), 0);
+
+        group.next_generation();
+        let evaluations = group.stats().unwrap().total_evaluations;
+        assert!(evaluations > 0);
+        let budget = StopCondition::MaxEvaluations(evaluations + 1);
+        assert_eq!(group.next_generation_until(&[budget]), StopReason::LimitReached);
+        assert_eq!(group.generation(), 2);
+        assert!(group.stats().unwrap().total_evaluations > evaluations);
+
+        let evaluations = group.stats().unwrap().total_evaluations;
+        let budget = StopCondition::MaxEvaluations(evaluations * 3);
+        let limit = StopCondition::FixedGenerations(1_000);
+        assert_eq!(group.next_generation_until(&[budget, limit]), StopReason::LimitReached);
+        assert!(group.generation() > 2 && group.generation() < 1_002);
+        assert!(group.stats().unwrap().total_evaluations >= evaluations * 3);
+        let totals = group.history().iter().map(|stats| stats.total_evaluations);
+        assert!(totals.clone().zip(totals.skip(1)).all(|(total, next)| total < next));
+    }
+
+    #[test]
+    fn test_history() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 3).unwrap();
+        for _ in 0..10 {
+            group.next_generation();
+        }
+        let history = group.history();
+        assert!(!history.is_empty());
+        assert_eq!(history.last().unwrap().generation, 10);
+        for (stats, next) in history.iter().zip(&history[1..]) {
+            assert_eq!(stats.generation + 1, next.generation);
+        }
+        for stats in history {
+            assert!(stats.average_training_score.is_finite());
+            assert!(stats.verification_score.is_some());
+        }
+    }
+
+    #[test]
+    fn test_is_stagnant() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 3).unwrap();
+        group.next_generation();
+        let stats = group.stats().unwrap();
+        let scores = [Some(0.5), Some(0.7), None, Some(0.6), Some(0.72), None];
+        group.history =
+            scores.iter().map(|score| Stats { verification_score: *score, ..stats }).collect();
+        assert!(!group.is_stagnant(6, 0.0));
+        assert!(!group.is_stagnant(7, 0.0));
+        assert!(group.is_stagnant(0, 0.0));
+        assert!(group.is_stagnant(1, 0.0));
+        assert!(!group.is_stagnant(2, 0.0));
+        assert!(group.is_stagnant(2, 0.05));
+        assert!(!group.is_stagnant(5, 0.1));
+        assert!(group.is_stagnant(4, 0.1));
+        assert!(!group.is_stagnant(4, 0.0));
+        group.objective = MeanSquaredError;
+        assert!(group.is_stagnant(4, 0.0));
+    }
+
+    #[test]
+    fn test_top_classifiers() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 10, &[], 5).unwrap();
+        assert!(group.top_classifiers(3).is_empty());
+        for _ in 0..5 {
+            group.next_generation();
+        }
+        assert!(group.top_classifiers(0).is_empty());
+        let top = group.top_classifiers(3);
+        assert!(!top.is_empty() && top.len() <= 3);
+        for (i, classifier) in top.iter().enumerate() {
+            assert!(top[..i].iter().all(|other| other != classifier));
+        }
+        assert_eq!(group.top_classifiers(1)[..], top[..1]);
+    }
+
+    #[test]
+    fn test_best_classifier() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 5).unwrap();
+        assert!(group.best_classifier().is_none());
+        assert!(group.best_classifier_generation().is_none());
+        let mut best: Option<(f32, Classifier)> = None;
+        let mut best_generation = None;
+        for _ in 0..15 {
+            group.next_generation();
+            let score = group.verification_score().unwrap();
+            if best.as_ref().is_none_or(|(best, _)| score - *best > DEFAULT_MIN_IMPROVEMENT) {
+                best = Some((score, group.classifier().unwrap()));
+                best_generation = Some(group.generation() as usize);
+            }
+        }
+        assert_eq!(group.best_classifier(), best.map(|(_, classifier)| classifier));
+        assert_eq!(group.best_classifier_generation(), best_generation);
+    }
+
+    #[test]
+    fn test_verification_interval() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 5).unwrap();
+        assert!(group.set_verification_interval(0).is_err());
+        group.set_verification_interval(3).unwrap();
+        for _ in 0..2 {
+            group.next_generation();
+            assert!(group.best_classifier().is_none());
+        }
+        for _ in 0..5 {
+            group.next_generation();
+        }
+        assert!(group.best_classifier().is_some());
+        for stats in group.history() {
+            let verified = stats.generation % 3 == 0;
+            assert_eq!(stats.verified, verified);
+            assert_eq!(stats.verification_score.is_some(), verified);
+            assert_eq!(stats.overfit_gap.is_some(), verified);
+        }
+        assert!(group.history().iter().any(|stats| stats.verified));
+    }
+
+    #[test]
+    fn test_min_improvement() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 5, &[], 5)
+                .unwrap()
+                .with_min_improvement(0.05)
+                .unwrap();
+        let mut best: Option<(f32, Classifier)> = None;
+        for _ in 0..15 {
+            group.next_generation();
+            let score = group.verification_score().unwrap();
+            if best.as_ref().is_none_or(|(best, _)| score - *best > 0.05) {
+                best = Some((score, group.classifier().unwrap()));
+            }
+        }
+        assert_eq!(group.best_classifier(), best.map(|(_, classifier)| classifier));
+
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let group = TrainingGroup::new(training_data, verification_data, Auc, 5, &[]).unwrap();
+        assert!(group.with_min_improvement(-0.1).is_err());
+    }
+
+    #[test]
+    fn test_training_with_missing_values() {
+        let mut data = DataSet::new(create_simple_data(0).class_map().clone());
+        for point in create_simple_data(100).iter() {
+            let (input, outcome) = point.data();
+            let mask = vec![vec![false, (input.get(0, 0) as usize).is_multiple_of(3)]];
+            let input = Input::from_vector_with_mask(vec![input.row(0)], &mask).unwrap();
+            data.add_data_point(Point::new(input, *outcome)).unwrap();
+        }
+        let (training_data, verification_data) = data.into_2_views_split();
+        let (standardized, normalization) = training_data.standardized();
+        let mut input = Input::from_vector(vec![vec![1.0, 2.0]]).unwrap();
+        normalization.apply(&mut input).unwrap();
+        assert!(input.row(0).iter().all(|v| v.is_finite()));
+        let verification_data = verification_data.normalized(&normalization).unwrap();
+        let mut group =
+            TrainingGroup::with_seed(standardized.clone(), verification_data, Auc, 3, &[], 2)
+                .unwrap();
+        let mut classifier = (0..20)
+            .find_map(|_| {
+                group.next_generation();
+                group.classifier().ok()
+            })
+            .unwrap();
+        let standardized_score = classifier.score(&standardized).unwrap();
+        classifier.set_normalization(Some(normalization)).unwrap();
+        let score = classifier.score(&training_data).unwrap();
+        assert!(score.auc.is_finite());
+        assert_eq!(score.auc.to_bits(), standardized_score.auc.to_bits());
+    }
+
+    #[test]
+    fn test_progress_callback() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut training_group =
+            TrainingGroup::new(training_data, verification_data, Auc, 3, &[]).unwrap();
+        let (sender, receiver) = channel();
+        training_group.set_progress_callback(move |stats| sender.send(*stats).unwrap());
+        for _ in 0..1_000 {
+            training_group.next_generation();
+            if let Ok(stats) = receiver.try_recv() {
+                assert_eq!(stats.generation, training_group.generation());
+                return;
+            }
+        }
+        panic!("Progress callback was never called")
+    }
+
+    #[test]
+    fn test_new_best_callback() {
+        let (training_data, verification_data) = create_simple_data(100).into_2_views_split();
+        let mut training_group =
+            TrainingGroup::with_seed(training_data, verification_data, Auc, 3, &[], 7).unwrap();
+        let (sender, receiver) = channel();
+        training_group.set_new_best_callback(move |classifier, stats| {
+            sender.send((classifier.to_expression_string(), *stats)).unwrap()
+        });
+        let mut last_best = None;
+        for _ in 0..30 {
+            training_group.next_generation();
+            let best = training_group.best_classifier_generation();
+            match receiver.try_recv() {
+                Ok((expression, stats)) => {
+                    assert_ne!(best, last_best);
+                    assert_eq!(best, Some(stats.generation as usize));
+                    let classifier = training_group.best_classifier().unwrap();
+                    assert_eq!(expression, classifier.to_expression_string());
+                }
+                Err(_) => assert_eq!(best, last_best),
+            }
+            last_best = best;
+        }
+        assert!(last_best.is_some());
+    }
 }
@@ -18,17 +18,40 @@
 */
 
 use crate::contrand::GET_RNG;
-use crate::data::data_set::DataView;
+use crate::data::data_set::{DataView, FeatureType};
 use crate::data::outcome::{sort_guesses, Class};
-use crate::data::{Input, InputShape};
-use crate::exec::functions::DoubleArgFunction;
+use crate::data::{Data, Input, InputShape};
+use crate::exec::functions::{DoubleArgFunction, NodeKind, Terminal};
 use crate::exec::node::{Node, Weighted};
 use crate::exec::score::{Objective, Score};
 use crate::math::valid;
 use crate::serialization::{Deserializable, Serializable, Serializator};
 use rand::Rng;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashSet;
+use std::hash::Hasher;
+use std::time::Instant;
 
+/// Bound of absolute value of every output of [`Tree`]. Outputs beyond it, including
+/// infinities, are clamped to it, NaN (e.g. of missing values) stays NaN. A tree that reaches
+/// the bound on data it is scored with gets no score, see [`Tree::execute_for_score`].
+pub const MAX_OUTPUT: f32 = 1e30;
+
+/// Formula of nodes evolved to separate points of a class from the others.
+///
+/// Trees are evaluated in single precision: input values are stored as `f32` (about 7
+/// significant digits) and every node computes in `f32`, there is no `f64` path. This is
+/// enough for most data, but keep in mind that:
+/// * the step between neighbouring `f32` values grows with magnitude, e.g. it's about 3e-5
+///   for inputs around 300 and about 0.008 for their products around 90 000, so differences
+///   of large, nearly equal values lose most of their digits
+/// * outputs are compared to thresholds as they are, so points whose outputs differ by less
+///   than that step fall on the same side of a threshold
+/// * outputs beyond [`MAX_OUTPUT`] are clamped and a tree reaching it gets no score, so
+///   trees multiplying large values many times over are lost
+/// * a tree whose outputs differ by no more than 0.001 across all points gets no score,
+///   so data of very small magnitude should be scaled first, e.g. with
+///   [`DataView::standardized`]
 #[derive(Debug, PartialEq, Clone)]
 pub struct Tree {
     node: Weighted,
@@ -67,15 +90,76 @@ impl Tree {
         input_shape: &InputShape,
         max_depth: usize,
         forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        branch_prob: f64,
+        data_prob: f64,
+    ) -> Tree {
+        let (shape, cols, nodes) = (input_shape, forbidden_cols, forbidden_nodes);
+        Tree::with_terminals(shape, max_depth, cols, nodes, &[], branch_prob, data_prob)
+    }
+
+    /// Same as [`Tree::new`] with user-defined `terminals` as leaves as well
+    pub fn with_terminals(
+        input_shape: &InputShape,
+        max_depth: usize,
+        forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        terminals: &'static [Terminal],
         branch_prob: f64,
         data_prob: f64,
     ) -> Tree {
-        let node =
-            Weighted::new(1, input_shape, branch_prob, max_depth, forbidden_cols, data_prob);
+        let (shape, cols, nodes) = (input_shape, forbidden_cols, forbidden_nodes);
+        Tree::generate(shape, max_depth, cols, nodes, terminals, branch_prob, data_prob, 0.0)
+    }
+
+    /// Same as [`Tree::with_terminals`], with nodes below the root being leaves before
+    /// `max_depth` with probability `leaf_prob`, see [`Weighted::generate`]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn generate(
+        input_shape: &InputShape,
+        max_depth: usize,
+        forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        terminals: &'static [Terminal],
+        branch_prob: f64,
+        data_prob: f64,
+        leaf_prob: f64,
+    ) -> Tree {
+        let node = Weighted::generate(
+            1,
+            input_shape,
+            branch_prob,
+            max_depth,
+            forbidden_cols,
+            forbidden_nodes,
+            terminals,
+            data_prob,
+            leaf_prob,
+        );
         let node_count = node.node_count();
         Tree { node, input_shape: *input_shape, node_count }
     }
 
+    /// Copy of this tree with a random node replaced by a random node of `other`, together
+    /// with nodes below them, see [`crate::exec::class_training::CrossoverMethod::SubtreeSwap`]
+    #[must_use]
+    pub(crate) fn subtree_swap(&self, other: &Tree) -> Tree {
+        let id = GET_RNG().gen_range(0..other.node_count());
+        let branch = other.node.clone().take_node(id);
+        let mut tree = self.clone();
+        *tree.select_random_node() = branch;
+        tree.node_count = tree.node.node_count();
+        tree
+    }
+
+    /// Tree mixing nodes of this tree and `other`, see [`Weighted::uniform_crossover`]
+    #[must_use]
+    pub(crate) fn uniform_crossover(&self, other: &Tree) -> Tree {
+        let node = self.node.uniform_crossover(&other.node);
+        let node_count = node.node_count();
+        Tree { node, input_shape: self.input_shape, node_count }
+    }
+
     pub fn change_weights(&mut self) {
         let mut rng = GET_RNG();
         let count = rng.gen_range(0..(self.node_count() as f32).sqrt() as i32);
@@ -85,10 +169,28 @@ impl Tree {
         }
     }
 
-    pub fn mutate(&mut self, forbidden_cols: &[usize]) {
+    /// Mutates a random node, a mutated constant gets a new weight from `constant_range`
+    pub fn mutate(
+        &mut self,
+        forbidden_cols: &[usize],
+        forbidden_nodes: &[NodeKind],
+        terminals: &'static [Terminal],
+        constant_range: (f32, f32),
+    ) {
         let input_shape = self.input_shape;
         let node = self.select_random_node();
-        node.mutate(&input_shape, forbidden_cols);
+        node.mutate(&input_shape, forbidden_cols, forbidden_nodes, terminals, constant_range);
+    }
+
+    /// Draws weights of all constants anew from `range`, see
+    /// [`Weighted::generate_constant_weights`]
+    pub fn generate_constant_weights(&mut self, range: (f32, f32)) {
+        self.node.generate_constant_weights(range);
+    }
+
+    /// Rounds constants compared to discrete columns, see [`Weighted::discretize_comparisons`]
+    pub fn discretize_comparisons(&mut self, feature_types: &[FeatureType]) {
+        self.node.discretize_comparisons(feature_types);
     }
 
     pub fn select_random_node(&mut self) -> &mut Weighted {
@@ -96,6 +198,14 @@ impl Tree {
         self.select_node_mut(node_id)
     }
 
+    /// Same tree with nodes simplified, see [`Weighted::simplified`]
+    #[must_use]
+    pub fn simplified(&self) -> Tree {
+        let node = self.node.simplified();
+        let node_count = node.node_count();
+        Tree { node, input_shape: self.input_shape, node_count }
+    }
+
     pub fn get_start_node(&self) -> &Weighted {
         &self.node
     }
@@ -108,6 +218,18 @@ impl Tree {
         self.node.get_used_columns()
     }
 
+    #[must_use]
+    pub fn get_used_node_kinds(&self) -> HashSet<NodeKind> {
+        self.node.get_used_node_kinds()
+    }
+
+    /// Hash of tree's structure, the same for trees differing by weights only
+    pub(crate) fn structure_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.node.hash_structure(&mut hasher);
+        hasher.finish()
+    }
+
     #[must_use]
     pub fn serializator(&self) -> Serializator {
         let mut s = Serializator::new();
@@ -125,18 +247,27 @@ impl Tree {
         self.node_count
     }
 
+    /// Number of nodes on the longest path from the root to a leaf, 1 for a single node
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.node.depth()
+    }
+
+    /// Scores tree on `data` with `objective`. Returns `None` if there are less than two
+    /// points, if outputs are not finite or all the same, or if any of them reaches
+    /// [`MAX_OUTPUT`], so that a tree overflowing on some points can't win with the rest.
     #[must_use]
     pub fn execute_for_score(
         &self,
         data: &DataView,
         class: Class,
-        objective: Objective,
+        objective: &Objective,
     ) -> Option<Score> {
         if data.cells().get(0, 0).len() < 2 {
             None
         } else {
             let guesses = self.execute(data);
-            if !valid(&guesses) {
+            if !scorable(&guesses) {
                 None
             } else {
                 let outcomes = sort_guesses(guesses, data.outcomes());
@@ -147,35 +278,115 @@ impl Tree {
         }
     }
 
+    /// Values of all objectives of [`Objective::Pareto`] (see [`Objective::values`]) on
+    /// `data` followed by negated node count, so that higher values are better
+    pub(crate) fn execute_for_pareto(
+        &self,
+        data: &DataView,
+        class: Class,
+        objective: &Objective,
+    ) -> Option<Vec<f32>> {
+        if data.cells().get(0, 0).len() < 2 {
+            return None;
+        }
+        let guesses = self.execute(data);
+        if !scorable(&guesses) {
+            return None;
+        }
+        let outcomes = sort_guesses(guesses, data.outcomes());
+        let threshold = objective.threshold(&outcomes, class);
+        let mut values = objective.values(threshold, &outcomes, class);
+        values.push(-(self.node_count as f32));
+        Some(values)
+    }
+
+    /// Outputs for every point of `data`, bounded by [`MAX_OUTPUT`]
     pub(crate) fn execute(&self, data: &DataView) -> Vec<f32> {
-        self.node.execute(data.cells())
+        self.execute_cells(data.cells())
     }
 
+    /// Same as [`Tree::execute`] for column oriented `cells`
+    pub(crate) fn execute_cells(&self, cells: &Data<Vec<f32>>) -> Vec<f32> {
+        self.node.execute(cells).into_iter().map(|v| v.clamp(-MAX_OUTPUT, MAX_OUTPUT)).collect()
+    }
+
+    /// Output for `input`, bounded by [`MAX_OUTPUT`]
     pub fn execute_input(&self, input: &Input) -> f32 {
-        self.node.execute(&input.to_view())[0]
+        self.execute_cells(&input.to_view())[0]
     }
+
+    /// Same as [`Tree::execute_input`], but gives `None` if `deadline` passes before all nodes
+    /// were executed, see [`Weighted::execute_until`]
+    pub(crate) fn execute_input_until(&self, input: &Input, deadline: Instant) -> Option<f32> {
+        let outputs = self.node.execute_until(&input.to_view(), deadline)?;
+        Some(outputs[0].clamp(-MAX_OUTPUT, MAX_OUTPUT))
+    }
+}
+
+/// Whether `outputs` are valid and none of them was bounded
+fn scorable(outputs: &[f32]) -> bool {
+    valid(outputs) && outputs.iter().all(|v| v.abs() < MAX_OUTPUT)
 }
 
 #[cfg(test)]
 pub(crate) mod test {
     use crate::contrand::GET_RNG;
-    use crate::data::InputShape;
+    use crate::data::data_set::DataSet;
+    use crate::data::outcome::Class;
+    use crate::data::{Input, InputShape, Outcome, Point};
     use crate::exec::functions::{MATH_CONSTANTS, ONE_ARG_FUNCTIONS, TWO_ARG_FUNCTIONS};
     use crate::exec::node::{Node, Weighted};
-    use crate::exec::tree::Tree;
+    use crate::exec::score::Objective;
+    use crate::exec::tree::{Tree, MAX_OUTPUT};
     use crate::serialization::serializator::test::test_serialization;
     use rand::Rng;
+    use std::collections::HashMap;
 
     #[test]
     fn serialize_tree() {
         let input_shape = InputShape::new(10, 20);
         for _ in 0..10_000 {
             let max_depth = GET_RNG().gen_range(2..15);
-            let tree = Tree::new(&input_shape, max_depth, &Vec::new(), 0.5, 0.5);
+            let tree = Tree::new(&input_shape, max_depth, &Vec::new(), &[], 0.5, 0.5);
             test_serialization(tree);
         }
     }
 
+    #[test]
+    fn generate_with_leaf_prob() {
+        let input_shape = InputShape::new(2, 3);
+        for _ in 0..1_000 {
+            let full = Tree::generate(&input_shape, 5, &[], &[], &[], 0.5, 0.5, 0.0);
+            assert_eq!(full.depth(), 5);
+            let shallow = Tree::generate(&input_shape, 5, &[], &[], &[], 0.5, 0.5, 1.0);
+            assert_eq!(shallow.depth(), 2);
+            let grown = Tree::generate(&input_shape, 5, &[], &[], &[], 0.5, 0.5, 0.3);
+            assert!((2..=5).contains(&grown.depth()));
+        }
+    }
+
+    #[test]
+    fn crossover() {
+        let sample = sample_tree();
+        let short = create_short_tree();
+        for _ in 0..100 {
+            let swapped = sample.subtree_swap(&short);
+            assert_eq!(swapped.node_count(), swapped.get_start_node().node_count());
+            assert!(swapped.node_count() <= sample.node_count());
+            let swapped = short.subtree_swap(&sample);
+            assert!(swapped.node_count() <= sample.node_count());
+            assert!(swapped.depth() <= sample.depth());
+
+            assert_eq!(sample.uniform_crossover(&sample), sample);
+            let mixed = sample.uniform_crossover(&short);
+            assert!(mixed == sample || mixed == short);
+            let long = create_long_tree();
+            let mixed = sample.uniform_crossover(&long);
+            assert_eq!(mixed.node_count(), mixed.get_start_node().node_count());
+            assert!(mixed.depth() <= sample.depth().max(long.depth()));
+        }
+    }
+
     fn sample_tree() -> Tree {
         let n1 = Node::DataValue(0, 0);
         let w1 = Weighted::from(n1);
@@ -198,6 +409,48 @@ pub(crate) mod test {
         assert_eq!(tree.node_count(), 6);
     }
 
+    #[test]
+    fn generate_constant_weights() {
+        let mut tree = sample_tree();
+        tree.generate_constant_weights((100.0, 200.0));
+        for id in 0..tree.node_count() {
+            let node = tree.select_node_mut(id);
+            match node.node() {
+                Node::MathConstant(_) => assert!((100.0..200.0).contains(&node.weight())),
+                _ => assert_eq!(node.weight(), 1.0),
+            }
+        }
+    }
+
+    #[test]
+    fn bounded_output() {
+        let mul = TWO_ARG_FUNCTIONS.iter().find(|f| f.name == "mul").unwrap();
+        let w1 = Weighted::from(Node::DataValue(0, 0));
+        let w2 = Weighted::from(Node::DataValue(0, 0));
+        let tree = Tree::from_two(mul, w1, w2, InputShape::new(1, 1));
+        let input = |x: f32| Input::from_vector(vec![vec![x]]).unwrap();
+        assert_eq!(tree.execute_input(&input(1e20)), MAX_OUTPUT);
+        assert_eq!(tree.execute_input(&input(3.0)), 9.0);
+        let missing = Input::from_vector_with_mask(vec![vec![0.0]], &[vec![true]]).unwrap();
+        assert!(tree.execute_input(&missing).is_nan());
+
+        let mut classes = HashMap::new();
+        classes.insert(Class::new(0), "low".to_string());
+        classes.insert(Class::new(1), "high".to_string());
+        let mut data = DataSet::new(classes);
+        for (x, class) in &[(1.0, 0), (2.0, 0), (3.0, 1), (4.0, 1)] {
+            let outcome = Outcome::new(Class::new(*class), 1.0, -1.0);
+            data.add_data_point(Point::new(input(*x), outcome)).unwrap();
+        }
+        let objective = Objective::Auc;
+        assert!(tree
+            .execute_for_score(&data.clone().into_view(), Class::new(1), &objective)
+            .is_some());
+        data.add_data_point(Point::new(input(1e20), Outcome::new(Class::new(1), 1.0, -1.0)))
+            .unwrap();
+        assert!(tree.execute_for_score(&data.into_view(), Class::new(1), &objective).is_none());
+    }
+
     pub(crate) fn create_short_tree() -> Tree {
         let n1 = Node::DataValue(0, 0);
         let w1 = Weighted::from(n1);
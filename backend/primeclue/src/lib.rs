@@ -43,4 +43,5 @@ pub mod error;
 pub mod exec;
 pub mod math;
 pub mod serialization;
+pub mod testing;
 pub mod user;
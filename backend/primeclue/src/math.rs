@@ -16,6 +16,18 @@ pub fn median(values: &mut [f32]) -> f32 {
     }
 }
 
+/// Value below which `q` of `values` lie, from 0 to 1, interpolated linearly between the
+/// closest two values. Sorts `values`. Returns `None` if `values` are empty or contain NaN.
+pub fn quantile(values: &mut [f32], q: f32) -> Option<f32> {
+    if values.is_empty() || values.iter().any(|v| v.is_nan()) {
+        return None;
+    }
+    values.sort_by(f32::total_cmp);
+    let position = q.clamp(0.0, 1.0) * (values.len() - 1) as f32;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    Some(values[lower] + (values[upper] - values[lower]) * (position - lower as f32))
+}
+
 pub fn valid(values: &[f32]) -> bool {
     let mut change = false;
     for v in values {
@@ -31,7 +43,7 @@ pub fn valid(values: &[f32]) -> bool {
 
 #[cfg(test)]
 mod test {
-    use crate::math::{median, valid};
+    use crate::math::{median, quantile, valid};
 
     #[test]
     fn median_test() {
@@ -49,6 +61,20 @@ mod test {
         assert!((median(&mut v) - 2.0).abs() < f32::EPSILON);
     }
 
+    #[test]
+    fn quantile_test() {
+        let mut v = vec![4.0, 1.0, 3.0, 2.0, 5.0];
+        assert!((quantile(&mut v, 0.5).unwrap() - 3.0).abs() < f32::EPSILON);
+        assert!((quantile(&mut v, 0.25).unwrap() - 2.0).abs() < f32::EPSILON);
+        assert!((quantile(&mut v, 0.1).unwrap() - 1.4).abs() < 1e-6);
+        assert!((quantile(&mut v, 1.0).unwrap() - 5.0).abs() < f32::EPSILON);
+        let mut v = vec![1.0, 2.0, 3.0, 4.0];
+        assert!((quantile(&mut v, 0.5).unwrap() - median(&mut v)).abs() < f32::EPSILON);
+        assert!((quantile(&mut [7.0], 0.75).unwrap() - 7.0).abs() < f32::EPSILON);
+        assert_eq!(quantile(&mut [], 0.5), None);
+        assert_eq!(quantile(&mut [1.0, f32::NAN, 2.0], 0.5), None);
+    }
+
     #[test]
     fn test_valid() {
         assert!(!valid(&[]));
@@ -18,7 +18,7 @@
 */
 
 use crate::serialization::serializator::{Serializator, PRIMECLUE_SPACE_SUBSTITUTE};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 
 pub trait Deserializable {
@@ -34,6 +34,13 @@ impl Deserializable for usize {
     }
 }
 
+impl Deserializable for u64 {
+    fn deserialize(s: &mut Serializator) -> Result<u64, String> {
+        let v = s.next_token()?;
+        v.parse().map_err(|e| format!("Unable to parse '{}': {:?}", v, e))
+    }
+}
+
 impl Deserializable for u16 {
     fn deserialize(s: &mut Serializator) -> Result<u16, String> {
         let v = s.next_token()?;
@@ -50,6 +57,13 @@ impl Deserializable for String {
     }
 }
 
+impl Deserializable for f64 {
+    fn deserialize(s: &mut Serializator) -> Result<f64, String> {
+        let v = s.next_token()?;
+        v.parse().map_err(|e| format!("Unable to parse '{}': {:?}", v, e))
+    }
+}
+
 impl Deserializable for f32 {
     fn deserialize(s: &mut Serializator) -> Result<f32, String> {
         let v = s.next_token()?;
@@ -118,3 +132,16 @@ impl<
         Ok(map)
     }
 }
+
+impl<K: Deserializable + Ord, V: Deserializable> Deserializable for BTreeMap<K, V> {
+    fn deserialize(s: &mut Serializator) -> Result<Self, String> {
+        let len = usize::deserialize(s)?;
+        let mut map = BTreeMap::new();
+        for _ in 0..len {
+            let k = K::deserialize(s)?;
+            let v = V::deserialize(s)?;
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+}
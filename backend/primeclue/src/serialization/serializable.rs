@@ -18,7 +18,7 @@
 */
 
 use crate::serialization::serializator::Serializator;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::BuildHasher;
 
 pub trait Serializable {
@@ -31,6 +31,12 @@ impl Serializable for usize {
     }
 }
 
+impl Serializable for u64 {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add_string(format!("{}", self));
+    }
+}
+
 impl Serializable for u16 {
     fn serialize(&self, s: &mut Serializator) {
         s.add_string(format!("{}", self));
@@ -87,6 +93,27 @@ impl<T: Serializable> Serializable for Vec<T> {
 }
 
 impl<K: Serializable, V: Serializable, S: BuildHasher> Serializable for HashMap<K, V, S> {
+    fn serialize(&self, s: &mut Serializator) {
+        s.add(&self.len());
+        // sorted by serialized key, so that equal maps are always serialized the same
+        let mut entries = self
+            .iter()
+            .map(|(k, v)| {
+                let mut key = Serializator::new();
+                key.add(k);
+                (key.as_serialized(), k, v)
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|(k1, _, _), (k2, _, _)| k1.cmp(k2));
+        for (_, k, v) in entries {
+            s.add(k);
+            s.add(v);
+        }
+    }
+}
+
+/// Same format as [`HashMap`], in order of keys
+impl<K: Serializable, V: Serializable> Serializable for BTreeMap<K, V> {
     fn serialize(&self, s: &mut Serializator) {
         s.add(&self.len());
         for (k, v) in self {
@@ -48,6 +48,12 @@ impl Serializator {
         }
     }
 
+    /// Returns next token without consuming it
+    #[must_use]
+    pub fn peek_token(&self) -> Option<&str> {
+        self.strings.get(self.next_token).map(String::as_str)
+    }
+
     pub fn add_items(&mut self, a: &[&dyn Serializable]) {
         a.iter().for_each(|i| self.add(*i));
     }
@@ -167,7 +173,7 @@ pub mod test {
     }
 
     use crate::serialization::{Deserializable, Serializable, Serializator};
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::fmt::Debug;
 
     pub fn test_serialization<T: Serializable + Deserializable + PartialEq + Debug>(v: T) {
@@ -210,6 +216,17 @@ pub mod test {
         test_serialization(m);
     }
 
+    #[test]
+    fn test_sorted_map() {
+        let m = BTreeMap::from([(10u16, "10".to_string()), (2, "2".to_string())]);
+        test_serialization(m.clone());
+        let mut s = Serializator::new();
+        s.add(&m);
+        let mut d = Serializator::from_bytes(s.to_bytes()).unwrap();
+        let h: HashMap<u16, String> = HashMap::deserialize(&mut d).unwrap();
+        assert_eq!(h, m.into_iter().collect());
+    }
+
     #[test]
     fn test_tuple() {
         let mut m = HashMap::new();
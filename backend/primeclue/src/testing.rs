@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: AGPL-3.0
+/*
+   Primeclue: Machine Learning and Data Mining
+   Copyright (C) 2020 Łukasz Wojtów
+
+   This program is free software: you can redistribute it and/or modify
+   it under the terms of the GNU Affero General Public License as
+   published by the Free Software Foundation, either version 3 of the
+   License, or (at your option) any later version.
+
+   This program is distributed in the hope that it will be useful,
+   but WITHOUT ANY WARRANTY; without even the implied warranty of
+   MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+   GNU Affero General Public License for more details.
+
+   You should have received a copy of the GNU Affero General Public License
+   along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! Data generators for tests of code using Primeclue
+
+use crate::data::data_set::{DataSet, DataView};
+use crate::data::outcome::Class;
+use crate::data::{Input, Outcome, Point};
+use crate::error::PrimeclueErr;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use std::collections::HashMap;
+
+/// Largest distance of a point's feature from its class center
+const SPREAD: f32 = 50.0;
+
+/// Number of classes of data given by [`make_problem`]
+pub const PROBLEM_CLASSES: u16 = 4;
+
+/// Number of features of data given by [`make_problem`]
+pub const PROBLEM_FEATURES: usize = 3;
+
+/// Number of points of every view given by [`make_problem`]
+pub const PROBLEM_VIEW_POINTS: usize = 300;
+
+/// Width of the range of values of every feature of a single class in [`make_problem`]
+const CLASS_WIDTH: f32 = 100.0;
+
+/// Knobs of how hard the problem given by [`make_problem`] is:
+/// * `label_noise` - fraction of points, from 0 to 1, whose class is replaced by another
+///   class drawn at random, in all views
+/// * `feature_overlap` - how much ranges of feature values of different classes overlap,
+///   from 0, where classes are separated by any feature, to 1, where all classes have the
+///   same range
+/// * `test_shift` - how far test values are moved past the range of training and
+///   verification values, as a fraction of that range. At 0 test data comes from the same
+///   range, at 1 none of its values were seen before, as in the `test_training` example.
+///   May be negative to move them below it
+///
+/// The default is the easiest problem, without noise, overlap or shift.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Difficulty {
+    pub label_noise: f64,
+    pub feature_overlap: f32,
+    pub test_shift: f32,
+}
+
+/// Generates training, verification and testing views of [`PROBLEM_VIEW_POINTS`] points of
+/// [`PROBLEM_CLASSES`] classes named `"0"`, `"1"` and so on, e.g. to measure how training
+/// copes with harder problems. Every point has a single row of [`PROBLEM_FEATURES`] values,
+/// each drawn uniformly from the range of its class, which [`Difficulty`] makes more or less
+/// overlapping. Classes of points are drawn at random. The same `difficulty` and `seed`
+/// always give the same views, so scores on them can be compared.
+pub fn make_problem(
+    difficulty: Difficulty,
+    seed: u64,
+) -> Result<(DataView, DataView, DataView), PrimeclueErr> {
+    let Difficulty { label_noise, feature_overlap, test_shift } = difficulty;
+    if !(0.0..=1.0).contains(&label_noise) {
+        return PrimeclueErr::result(format!(
+            "Label noise must be between 0 and 1, got {}",
+            label_noise
+        ));
+    } else if !(0.0..=1.0).contains(&feature_overlap) {
+        return PrimeclueErr::result(format!(
+            "Feature overlap must be between 0 and 1, got {}",
+            feature_overlap
+        ));
+    } else if !test_shift.is_finite() {
+        return PrimeclueErr::result(format!("Test shift must be finite, got {}", test_shift));
+    }
+    let mut rng = XorShiftRng::seed_from_u64(seed);
+    let class_map =
+        (0..PROBLEM_CLASSES).map(|c| (Class::new(c), c.to_string())).collect::<HashMap<_, _>>();
+    let step = CLASS_WIDTH * (1.0 - feature_overlap);
+    let range = step * f32::from(PROBLEM_CLASSES - 1) + CLASS_WIDTH;
+    let mut view = |shift: f32| -> Result<DataView, PrimeclueErr> {
+        let mut data = DataSet::new(class_map.clone());
+        for _ in 0..PROBLEM_VIEW_POINTS {
+            let class = rng.gen_range(0..PROBLEM_CLASSES);
+            let start = step * f32::from(class) + shift * range;
+            let values = (0..PROBLEM_FEATURES)
+                .map(|_| start + rng.gen_range(0.0..CLASS_WIDTH))
+                .collect::<Vec<_>>();
+            let label = if rng.gen_bool(label_noise) {
+                (class + rng.gen_range(1..PROBLEM_CLASSES)) % PROBLEM_CLASSES
+            } else {
+                class
+            };
+            let input = Input::from_vector(vec![values])?;
+            data.add_data_point(Point::new(input, Outcome::new(Class::new(label), 1.0, -1.0)))?;
+        }
+        Ok(data.into_view())
+    };
+    Ok((view(0.0)?, view(0.0)?, view(test_shift)?))
+}
+
+/// Generates a [`DataSet`] of `classes` classes named `"0"`, `"1"` and so on, with
+/// `points_per_class` points each whose input is a single row of `features` values.
+/// Every class has a random center and its points are spread uniformly around it, so that
+/// classes overlap partially. Points are shuffled. The same `seed` always gives the same data.
+pub fn synthetic_classification(
+    seed: u64,
+    classes: u16,
+    points_per_class: usize,
+    features: usize,
+) -> Result<DataSet, PrimeclueErr> {
+    if classes < 2 {
+        return PrimeclueErr::result(format!(
+            "At least 2 classes are required, got {}",
+            classes
+        ));
+    } else if points_per_class == 0 || features == 0 {
+        return PrimeclueErr::result(format!(
+            "Points per class and features must be positive, got {} and {}",
+            points_per_class, features
+        ));
+    }
+    let mut rng = XorShiftRng::seed_from_u64(seed);
+    let class_map =
+        (0..classes).map(|c| (Class::new(c), c.to_string())).collect::<HashMap<_, _>>();
+    let mut points = Vec::with_capacity(classes as usize * points_per_class);
+    for class in 0..classes {
+        let center = (0..features).map(|_| rng.gen_range(0.0..100.0)).collect::<Vec<f32>>();
+        for _ in 0..points_per_class {
+            let values = center.iter().map(|c| c + rng.gen_range(-SPREAD..SPREAD)).collect();
+            let input = Input::from_vector(vec![values])?;
+            points.push(Point::new(input, Outcome::new(Class::new(class), 1.0, -1.0)));
+        }
+    }
+    points.shuffle(&mut rng);
+    let mut data = DataSet::new(class_map);
+    for point in points {
+        data.add_data_point(point)?;
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::data::data_set::DataView;
+    use crate::data::outcome::Class;
+    use crate::data::InputShape;
+    use crate::testing::{make_problem, synthetic_classification, Difficulty};
+    use crate::testing::{CLASS_WIDTH, PROBLEM_CLASSES, PROBLEM_FEATURES, PROBLEM_VIEW_POINTS};
+
+    #[test]
+    fn synthetic() {
+        let data = synthetic_classification(7, 3, 20, 4).unwrap();
+        assert_eq!(data.len(), 60);
+        assert_eq!(data.input_shape(), &InputShape::new(1, 4));
+        let view = data.clone().into_view();
+        assert_eq!(view.class_count(), 3);
+        assert!(view.class_counts().values().all(|count| *count == 20));
+        assert_eq!(data, synthetic_classification(7, 3, 20, 4).unwrap());
+        assert_ne!(data, synthetic_classification(8, 3, 20, 4).unwrap());
+
+        assert!(synthetic_classification(7, 1, 20, 4).is_err());
+        assert!(synthetic_classification(7, 3, 0, 4).is_err());
+        assert!(synthetic_classification(7, 3, 20, 0).is_err());
+    }
+
+    #[test]
+    fn problem() {
+        let values = |view: &DataView| {
+            (0..PROBLEM_FEATURES)
+                .flat_map(|column| view.cells().get(0, column).clone())
+                .collect::<Vec<_>>()
+        };
+        let range = |view: &DataView| {
+            values(view)
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(min, max), v| (min.min(*v), max.max(*v)))
+        };
+        let easy = make_problem(Difficulty::default(), 7).unwrap();
+        for view in [&easy.0, &easy.1, &easy.2] {
+            assert_eq!(view.len(), PROBLEM_VIEW_POINTS);
+            assert_eq!(view.input_shape(), &InputShape::new(1, PROBLEM_FEATURES));
+            assert_eq!(view.class_map().len(), PROBLEM_CLASSES as usize);
+            // without overlap and noise every value tells the class
+            for (point, outcome) in view.outcomes().iter().enumerate() {
+                let class = (view.cells().get(0, 0)[point] / CLASS_WIDTH) as u16;
+                assert_eq!(Class::new(class), outcome.class());
+            }
+        }
+        let (min, max) = range(&easy.0);
+        assert!(min >= 0.0 && max <= 4.0 * CLASS_WIDTH);
+
+        let difficulty = Difficulty { label_noise: 0.2, feature_overlap: 1.0, test_shift: 1.0 };
+        let (training, verification, testing) = make_problem(difficulty, 7).unwrap();
+        let (_, max) = range(&training);
+        assert!(max <= CLASS_WIDTH);
+        let (min, _) = range(&testing);
+        assert!(min >= CLASS_WIDTH);
+        assert_eq!(values(&verification), values(&make_problem(difficulty, 7).unwrap().1));
+        assert_ne!(values(&training), values(&make_problem(difficulty, 8).unwrap().0));
+
+        let invalid = [
+            Difficulty { label_noise: 1.5, ..Difficulty::default() },
+            Difficulty { feature_overlap: -0.1, ..Difficulty::default() },
+            Difficulty { test_shift: f32::NAN, ..Difficulty::default() },
+        ];
+        for difficulty in &invalid {
+            assert!(make_problem(*difficulty, 7).is_err());
+        }
+    }
+}